@@ -0,0 +1,71 @@
+//! Tokenizer and parser throughput on documents where the bulk scanning
+//! of `src/scan.rs` matters most and least. Run it with and without the
+//! `simd` feature to compare:
+//!
+//! ```text
+//! cargo bench --bench scan
+//! cargo bench --bench scan --features simd
+//! ```
+
+use rust_json_parser::{JsonParser, Tokenizer};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// About this many bytes per document.
+const SIZE: usize = 4 * 1024 * 1024;
+
+fn main() {
+    let documents = [
+        ("long strings", long_strings()),
+        ("indented", indented()),
+        ("short tokens", short_tokens()),
+    ];
+    println!("simd feature: {}", rust_json_parser::features::SIMD);
+    for (name, text) in &documents {
+        let tokenize = throughput(text, || {
+            black_box(Tokenizer::new(text).tokenize().unwrap());
+        });
+        let parse = throughput(text, || {
+            black_box(JsonParser::new(text).unwrap().parse().unwrap());
+        });
+        println!("{name:>14}: tokenize {tokenize:7.1} MB/s, parse {parse:7.1} MB/s");
+    }
+}
+
+/// Megabytes of `text` per second `run` gets through, best of several
+/// rounds.
+fn throughput(text: &str, mut run: impl FnMut()) -> f64 {
+    run();
+    let mut best = Duration::MAX;
+    for _ in 0..30 {
+        let start = Instant::now();
+        run();
+        best = best.min(start.elapsed());
+    }
+    text.len() as f64 / 1e6 / best.as_secs_f64()
+}
+
+/// An array of 1 KiB strings: nearly everything is a string run.
+fn long_strings() -> String {
+    let item = format!("\"{}\"", "lorem ipsum dolor sit amet ".repeat(38));
+    repeated(&item)
+}
+
+/// Objects pretty-printed deep in a tree: long runs of indentation.
+fn indented() -> String {
+    let item = format!(
+        "\n{pad}{{\n{pad}  \"id\": 1,\n{pad}  \"name\": \"a\"\n{pad}}}",
+        pad = " ".repeat(48)
+    );
+    repeated(&item)
+}
+
+/// Numbers, keywords and short keys: few runs long enough to skip.
+fn short_tokens() -> String {
+    repeated(r#"{"a":1,"b":true,"c":null,"d":[2,3]}"#)
+}
+
+fn repeated(item: &str) -> String {
+    let count = SIZE / item.len();
+    format!("[{}]", vec![item; count].join(","))
+}
@@ -0,0 +1,397 @@
+use crate::{JsonArray, JsonObject, JsonPath, JsonValue, PathSegment};
+use std::fmt;
+
+/// A typed lookup failed: the value was missing or had the wrong type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonAccessError {
+    /// Where the lookup failed, from the value it started at.
+    pub path: JsonPath,
+    /// The JSON type the caller asked for.
+    pub expected: &'static str,
+    /// The JSON type actually present, or `None` when nothing was there.
+    pub found: Option<&'static str>,
+}
+
+impl fmt::Display for JsonAccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = if self.path.is_root() {
+            "the document root".to_string()
+        } else {
+            format!("'{}'", self.path)
+        };
+        match self.found {
+            Some(found) => write!(f, "Expected {} at {location}, found {found}", self.expected),
+            None => write!(f, "Missing {} at {location}", self.expected),
+        }
+    }
+}
+
+impl std::error::Error for JsonAccessError {}
+
+/// A borrowed value together with the path it was reached by, so failed
+/// lookups can report where they happened.
+///
+/// Created with [`JsonValue::access`]; each step extends the path.
+#[derive(Debug, Clone)]
+pub struct JsonAccess<'a> {
+    value: &'a JsonValue,
+    path: JsonPath,
+}
+
+impl<'a> JsonAccess<'a> {
+    pub fn value(&self) -> &'a JsonValue {
+        self.value
+    }
+
+    pub fn path(&self) -> &JsonPath {
+        &self.path
+    }
+
+    /// Steps into the object member `key`.
+    pub fn field(&self, key: &str) -> Result<JsonAccess<'a>, JsonAccessError> {
+        let obj = self.as_object()?;
+        let mut path = self.path.clone();
+        path.push_key(key);
+        match obj.get(key) {
+            Some(value) => Ok(JsonAccess { value, path }),
+            None => Err(JsonAccessError {
+                path,
+                expected: "value",
+                found: None,
+            }),
+        }
+    }
+
+    /// Steps into the array element at `index`.
+    pub fn index(&self, index: usize) -> Result<JsonAccess<'a>, JsonAccessError> {
+        let arr = self.as_array()?;
+        let mut path = self.path.clone();
+        path.push_index(index);
+        match arr.get(index) {
+            Some(value) => Ok(JsonAccess { value, path }),
+            None => Err(JsonAccessError {
+                path,
+                expected: "value",
+                found: None,
+            }),
+        }
+    }
+
+    pub fn as_str(&self) -> Result<&'a str, JsonAccessError> {
+        self.value.as_str().ok_or_else(|| self.mismatch("string"))
+    }
+
+    pub fn as_f64(&self) -> Result<f64, JsonAccessError> {
+        self.value.as_f64().ok_or_else(|| self.mismatch("number"))
+    }
+
+    pub fn as_bool(&self) -> Result<bool, JsonAccessError> {
+        self.value.as_bool().ok_or_else(|| self.mismatch("boolean"))
+    }
+
+    pub fn as_array(&self) -> Result<&'a JsonArray, JsonAccessError> {
+        self.value.as_array().ok_or_else(|| self.mismatch("array"))
+    }
+
+    pub fn as_object(&self) -> Result<&'a JsonObject, JsonAccessError> {
+        self.value
+            .as_object()
+            .ok_or_else(|| self.mismatch("object"))
+    }
+
+    pub fn get_str(&self, key: &str) -> Result<&'a str, JsonAccessError> {
+        self.typed_field(key, "string", JsonAccess::as_str)
+    }
+
+    pub fn get_f64(&self, key: &str) -> Result<f64, JsonAccessError> {
+        self.typed_field(key, "number", JsonAccess::as_f64)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<bool, JsonAccessError> {
+        self.typed_field(key, "boolean", JsonAccess::as_bool)
+    }
+
+    pub fn get_array(&self, key: &str) -> Result<&'a JsonArray, JsonAccessError> {
+        self.typed_field(key, "array", JsonAccess::as_array)
+    }
+
+    pub fn get_object(&self, key: &str) -> Result<&'a JsonObject, JsonAccessError> {
+        self.typed_field(key, "object", JsonAccess::as_object)
+    }
+
+    /// Looks up `key` and converts it, naming `expected` if the key is missing.
+    fn typed_field<T>(
+        &self,
+        key: &str,
+        expected: &'static str,
+        convert: fn(&JsonAccess<'a>) -> Result<T, JsonAccessError>,
+    ) -> Result<T, JsonAccessError> {
+        match self.field(key) {
+            Ok(field) => convert(&field),
+            Err(err) if err.found.is_none() => Err(JsonAccessError { expected, ..err }),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn mismatch(&self, expected: &'static str) -> JsonAccessError {
+        JsonAccessError {
+            path: self.path.clone(),
+            expected,
+            found: Some(self.value.type_name()),
+        }
+    }
+}
+
+impl JsonValue {
+    /// Starts a chain of typed lookups rooted at this value.
+    ///
+    /// ```
+    /// # use rust_json_parser::JsonParser;
+    /// let config = JsonParser::new(r#"{"db": {"port": "5432"}}"#)?.parse()?;
+    /// let err = config.access().field("db")?.get_f64("port").unwrap_err();
+    /// assert_eq!(err.to_string(), "Expected number at '/db/port', found string");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn access(&self) -> JsonAccess<'_> {
+        JsonAccess {
+            value: self,
+            path: JsonPath::new(),
+        }
+    }
+
+    /// The string member `key`, or an error saying what was there instead.
+    pub fn get_str(&self, key: &str) -> Result<&str, JsonAccessError> {
+        self.access().get_str(key)
+    }
+
+    pub fn get_f64(&self, key: &str) -> Result<f64, JsonAccessError> {
+        self.access().get_f64(key)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<bool, JsonAccessError> {
+        self.access().get_bool(key)
+    }
+
+    pub fn get_array(&self, key: &str) -> Result<&JsonArray, JsonAccessError> {
+        self.access().get_array(key)
+    }
+
+    pub fn get_object(&self, key: &str) -> Result<&JsonObject, JsonAccessError> {
+        self.access().get_object(key)
+    }
+}
+
+/// Typed lookups by key, with paths relative to this object.
+impl JsonObject {
+    pub fn get_str(&self, key: &str) -> Result<&str, JsonAccessError> {
+        member(
+            self.get(key),
+            PathSegment::Key(key.into()),
+            "string",
+            JsonValue::as_str,
+        )
+    }
+
+    pub fn get_f64(&self, key: &str) -> Result<f64, JsonAccessError> {
+        member(
+            self.get(key),
+            PathSegment::Key(key.into()),
+            "number",
+            JsonValue::as_f64,
+        )
+    }
+
+    pub fn get_bool(&self, key: &str) -> Result<bool, JsonAccessError> {
+        member(
+            self.get(key),
+            PathSegment::Key(key.into()),
+            "boolean",
+            JsonValue::as_bool,
+        )
+    }
+
+    pub fn get_array(&self, key: &str) -> Result<&JsonArray, JsonAccessError> {
+        member(
+            self.get(key),
+            PathSegment::Key(key.into()),
+            "array",
+            JsonValue::as_array,
+        )
+    }
+
+    pub fn get_object(&self, key: &str) -> Result<&JsonObject, JsonAccessError> {
+        member(
+            self.get(key),
+            PathSegment::Key(key.into()),
+            "object",
+            JsonValue::as_object,
+        )
+    }
+}
+
+/// Typed lookups by index, with paths relative to this array.
+impl JsonArray {
+    pub fn get_str(&self, index: usize) -> Result<&str, JsonAccessError> {
+        member(
+            self.get(index),
+            PathSegment::Index(index),
+            "string",
+            JsonValue::as_str,
+        )
+    }
+
+    pub fn get_f64(&self, index: usize) -> Result<f64, JsonAccessError> {
+        member(
+            self.get(index),
+            PathSegment::Index(index),
+            "number",
+            JsonValue::as_f64,
+        )
+    }
+
+    pub fn get_bool(&self, index: usize) -> Result<bool, JsonAccessError> {
+        member(
+            self.get(index),
+            PathSegment::Index(index),
+            "boolean",
+            JsonValue::as_bool,
+        )
+    }
+
+    pub fn get_array(&self, index: usize) -> Result<&JsonArray, JsonAccessError> {
+        member(
+            self.get(index),
+            PathSegment::Index(index),
+            "array",
+            JsonValue::as_array,
+        )
+    }
+
+    pub fn get_object(&self, index: usize) -> Result<&JsonObject, JsonAccessError> {
+        member(
+            self.get(index),
+            PathSegment::Index(index),
+            "object",
+            JsonValue::as_object,
+        )
+    }
+}
+
+/// Converts a looked-up child, describing what went wrong at `segment`.
+fn member<'a, T>(
+    value: Option<&'a JsonValue>,
+    segment: PathSegment,
+    expected: &'static str,
+    convert: fn(&'a JsonValue) -> Option<T>,
+) -> Result<T, JsonAccessError> {
+    let error = |found| JsonAccessError {
+        path: JsonPath::from(vec![segment]),
+        expected,
+        found,
+    };
+    match value {
+        Some(value) => convert(value).ok_or_else(|| error(Some(value.type_name()))),
+        None => Err(error(None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn sample() -> JsonValue {
+        parse_json(r#"{"name": "svc", "port": 80, "tls": true, "hosts": ["a", 1], "db": {}}"#)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_typed_getters_succeed() {
+        let value = sample();
+        assert_eq!(value.get_str("name"), Ok("svc"));
+        assert_eq!(value.get_f64("port"), Ok(80.0));
+        assert_eq!(value.get_bool("tls"), Ok(true));
+        assert_eq!(value.get_array("hosts").map(|a| a.len()), Ok(2));
+        assert!(value.get_object("db").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let value = sample();
+        let cases = vec![
+            (
+                value.get_str("port").unwrap_err(),
+                "Expected string at '/port', found number",
+            ),
+            (
+                value.get_bool("missing").unwrap_err(),
+                "Missing boolean at '/missing'",
+            ),
+            (
+                value
+                    .access()
+                    .field("hosts")
+                    .unwrap()
+                    .index(1)
+                    .unwrap()
+                    .as_str()
+                    .unwrap_err(),
+                "Expected string at '/hosts/1', found number",
+            ),
+            (
+                value.access().field("hosts").unwrap().index(5).unwrap_err(),
+                "Missing value at '/hosts/5'",
+            ),
+            (
+                JsonValue::Null.get_str("x").unwrap_err(),
+                "Expected object at the document root, found null",
+            ),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_error_fields() {
+        let value = sample();
+        let err = value
+            .access()
+            .field("db")
+            .unwrap()
+            .get_f64("pool")
+            .unwrap_err();
+        assert_eq!(err.path.to_pointer(), "/db/pool");
+        assert_eq!(err.expected, "number");
+        assert_eq!(err.found, None);
+
+        let err = value
+            .access()
+            .field("name")
+            .unwrap()
+            .field("x")
+            .unwrap_err();
+        assert_eq!(err.path.to_pointer(), "/name");
+        assert_eq!(err.found, Some("string"));
+    }
+
+    #[test]
+    fn test_object_and_array_getters() {
+        let value = sample();
+        let obj = value.as_object().unwrap();
+        assert_eq!(obj.get_str("name"), Ok("svc"));
+        assert_eq!(
+            obj.get_f64("name").unwrap_err().to_string(),
+            "Expected number at '/name', found string"
+        );
+
+        let hosts = obj.get_array("hosts").unwrap();
+        assert_eq!(hosts.get_str(0), Ok("a"));
+        assert_eq!(hosts.get_f64(1), Ok(1.0));
+        assert_eq!(
+            hosts.get_object(2).unwrap_err().to_string(),
+            "Missing object at '/2'"
+        );
+    }
+}
@@ -0,0 +1,384 @@
+use crate::path::{parse_index, pointer_tokens};
+use crate::tape::{Kind, Tape, parse_to_tape};
+use crate::{JsonArray, JsonObject, JsonValue, Result, unescape_json_string};
+use std::slice;
+
+/// The bump arena [`JsonValueRef`]s are allocated in.
+pub use bumpalo::Bump as Arena;
+
+/// A JSON value whose containers and decoded strings live in an [`Arena`],
+/// for when building and dropping a [`JsonValue`] tree node by node costs
+/// too much.
+///
+/// [`JsonValueRef::parse_in`] builds every array, object and decoded string
+/// of a document in the arena, and borrows strings without escapes straight
+/// from the text. Dropping the arena frees the whole document in a few
+/// large deallocations; resetting it reuses the memory for the next one.
+/// Building and copying out use no recursion, so any depth of nesting is
+/// fine.
+///
+/// Objects keep their members in document order, repeated keys included;
+/// [`get`](JsonValueRef::get) finds the last, as
+/// [`JsonParser::parse`](crate::JsonParser::parse) keeps by default.
+///
+/// ```
+/// use rust_json_parser::{Arena, JsonValueRef};
+///
+/// let mut arena = Arena::new();
+/// for request in [r#"{"user": "ada", "tags": ["a", "b"]}"#, r#"{"user": "bob"}"#] {
+///     let doc = JsonValueRef::parse_in(request, &arena)?;
+///     assert!(doc.get("user").and_then(|u| u.as_str()).is_some());
+///     arena.reset();
+/// }
+/// # Ok::<(), rust_json_parser::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonValueRef<'a> {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(&'a str),
+    Array(&'a [JsonValueRef<'a>]),
+    Object(&'a [(&'a str, JsonValueRef<'a>)]),
+}
+
+impl<'a> JsonValueRef<'a> {
+    /// Parses `json` into `arena`. Takes the grammar of
+    /// [`parse_to_tape`](crate::parse_to_tape), and reports the same errors.
+    pub fn parse_in(json: &'a str, arena: &'a Arena) -> Result<Self> {
+        let tape = parse_to_tape(json)?;
+        Ok(Builder { tape: &tape, arena }.build())
+    }
+
+    /// Named as [`JsonValue::type_name`] does.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValueRef::Null => "null",
+            JsonValueRef::Boolean(_) => "boolean",
+            JsonValueRef::Number(_) => "number",
+            JsonValueRef::String(_) => "string",
+            JsonValueRef::Array(_) => "array",
+            JsonValueRef::Object(_) => "object",
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValueRef::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValueRef::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValueRef::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self {
+            JsonValueRef::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&'a [JsonValueRef<'a>]> {
+        match self {
+            JsonValueRef::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&'a [(&'a str, JsonValueRef<'a>)]> {
+        match self {
+            JsonValueRef::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// The last member named `key`.
+    pub fn get(&self, key: &str) -> Option<&'a JsonValueRef<'a>> {
+        self.as_object()?
+            .iter()
+            .rev()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value)
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&'a JsonValueRef<'a>> {
+        self.as_array()?.get(index)
+    }
+
+    /// Looks up a value by RFC 6901 JSON Pointer, as
+    /// [`JsonValue::pointer`] does.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValueRef<'a>> {
+        pointer_tokens(pointer)?
+            .iter()
+            .try_fold(self, |node, token| match node {
+                JsonValueRef::Object(_) => node.get(token),
+                JsonValueRef::Array(items) => items.get(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Copies the value out of the arena into an owned [`JsonValue`].
+    pub fn to_value(&self) -> JsonValue {
+        // The containers being copied, each with the key its parent holds
+        // it under and the children left to copy
+        let mut open: Vec<(Option<&'a str>, JsonValue, Children<'a>)> = Vec::new();
+        let mut node = (None, *self);
+        loop {
+            let (key, value) = node;
+            let mut done = match value {
+                JsonValueRef::Null => Some(JsonValue::Null),
+                JsonValueRef::Boolean(b) => Some(JsonValue::Boolean(b)),
+                JsonValueRef::Number(n) => Some(JsonValue::Number(n)),
+                JsonValueRef::String(s) => Some(JsonValue::String(s.into())),
+                JsonValueRef::Array(items) => {
+                    let array = JsonValue::Array(JsonArray::new());
+                    open.push((key, array, Children::Items(items.iter())));
+                    None
+                }
+                JsonValueRef::Object(members) => {
+                    let object = JsonValue::Object(JsonObject::new());
+                    open.push((key, object, Children::Members(members.iter())));
+                    None
+                }
+            }
+            .map(|value| (key, value));
+            // Add what was copied to its parent, closing every container
+            // that completes, until one has a child left to copy
+            node = loop {
+                let Some((_, parent, children)) = open.last_mut() else {
+                    let (_, value) = done.expect("the outermost value was copied");
+                    return value;
+                };
+                match (parent, done.take()) {
+                    (JsonValue::Array(array), Some((_, value))) => array.push(value),
+                    (JsonValue::Object(object), Some((Some(key), value))) => {
+                        object.insert(key.to_string(), value);
+                    }
+                    _ => {}
+                }
+                match children.next() {
+                    Some(child) => break child,
+                    None => {
+                        let (key, value, _) = open.pop().expect("checked above");
+                        done = Some((key, value));
+                    }
+                }
+            };
+        }
+    }
+}
+
+/// The children of a container [`JsonValueRef::to_value`] is copying.
+enum Children<'a> {
+    Items(slice::Iter<'a, JsonValueRef<'a>>),
+    Members(slice::Iter<'a, (&'a str, JsonValueRef<'a>)>),
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = (Option<&'a str>, JsonValueRef<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Children::Items(items) => items.next().map(|item| (None, *item)),
+            Children::Members(members) => members.next().map(|(key, value)| (Some(*key), *value)),
+        }
+    }
+}
+
+/// Copies a tape into the arena, sizing each slice from the tape.
+struct Builder<'t, 'a> {
+    tape: &'t Tape<'a>,
+    arena: &'a Arena,
+}
+
+/// A container [`Builder::build`] is filling: its slice, how many slots
+/// are filled and the tape index of the next child, or of its key.
+struct Frame<'a> {
+    slots: Slots<'a>,
+    filled: usize,
+    next: usize,
+}
+
+enum Slots<'a> {
+    Items(&'a mut [JsonValueRef<'a>]),
+    Members(&'a mut [(&'a str, JsonValueRef<'a>)]),
+}
+
+impl<'a> Builder<'_, 'a> {
+    /// Builds the document, filling containers from an explicit stack.
+    fn build(&self) -> JsonValueRef<'a> {
+        let mut open: Vec<Frame<'a>> = Vec::new();
+        let mut index = 0;
+        loop {
+            let entry = self.tape.entries[index];
+            let mut value = match entry.kind {
+                Kind::Array if entry.len > 0 => {
+                    let items = self
+                        .arena
+                        .alloc_slice_fill_copy(entry.len, JsonValueRef::Null);
+                    open.push(Frame {
+                        slots: Slots::Items(items),
+                        filled: 0,
+                        next: index + 1,
+                    });
+                    index += 1;
+                    continue;
+                }
+                Kind::Object if entry.len > 0 => {
+                    let members = self
+                        .arena
+                        .alloc_slice_fill_copy(entry.len, ("", JsonValueRef::Null));
+                    open.push(Frame {
+                        slots: Slots::Members(members),
+                        filled: 0,
+                        next: index + 1,
+                    });
+                    // A key is a string, so its value is the entry after it
+                    index += 2;
+                    continue;
+                }
+                _ => self.value(index),
+            };
+            // Store the value built, closing every container it completes,
+            // until one has a child left to build
+            index = loop {
+                let Some(frame) = open.last_mut() else {
+                    return value;
+                };
+                let len = match &mut frame.slots {
+                    Slots::Items(items) => {
+                        items[frame.filled] = value;
+                        frame.next = self.tape.entries[frame.next].next;
+                        items.len()
+                    }
+                    Slots::Members(members) => {
+                        members[frame.filled] = (self.string(frame.next), value);
+                        frame.next = self.tape.entries[frame.next + 1].next;
+                        members.len()
+                    }
+                };
+                frame.filled += 1;
+                if frame.filled < len {
+                    break match frame.slots {
+                        Slots::Items(_) => frame.next,
+                        Slots::Members(_) => frame.next + 1,
+                    };
+                }
+                value = match open.pop().expect("checked above").slots {
+                    Slots::Items(items) => JsonValueRef::Array(items),
+                    Slots::Members(members) => JsonValueRef::Object(members),
+                };
+            };
+        }
+    }
+
+    /// Builds a scalar or an empty container.
+    fn value(&self, index: usize) -> JsonValueRef<'a> {
+        let entry = self.tape.entries[index];
+        match entry.kind {
+            Kind::Null => JsonValueRef::Null,
+            Kind::Boolean(b) => JsonValueRef::Boolean(b),
+            Kind::Number => JsonValueRef::Number(
+                self.raw(index)
+                    .parse()
+                    .expect("numbers are checked when the tape is built"),
+            ),
+            Kind::String { .. } => JsonValueRef::String(self.string(index)),
+            Kind::Array => JsonValueRef::Array(&[]),
+            Kind::Object => JsonValueRef::Object(&[]),
+        }
+    }
+
+    fn raw(&self, index: usize) -> &'a str {
+        let entry = self.tape.entries[index];
+        &self.tape.text[entry.start..entry.end]
+    }
+
+    fn string(&self, index: usize) -> &'a str {
+        let raw = self.raw(index);
+        match self.tape.entries[index].kind {
+            Kind::String { escaped: true } => self.arena.alloc_str(
+                &unescape_json_string(raw).expect("strings are checked when the tape is built"),
+            ),
+            _ => &raw[1..raw.len() - 1],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_parse_in_matches_parser() {
+        let arena = Arena::new();
+        let inputs = [
+            "null",
+            "-1.5e2",
+            r#""escé\n""#,
+            "[]",
+            r#"{"a": [1, {"b": [true, false]}], "c": {}, "a": "again"}"#,
+        ];
+        for input in inputs {
+            let value = JsonValueRef::parse_in(input, &arena).unwrap();
+            assert_eq!(value.to_value(), parse_json(input).unwrap(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_navigation() {
+        let arena = Arena::new();
+        let json = r#"{"a": [10, {"b": "plain"}], "k\n": null, "a": [20]}"#;
+        let doc = JsonValueRef::parse_in(json, &arena).unwrap();
+        assert_eq!(doc.as_object().map(<[_]>::len), Some(3));
+        assert_eq!(
+            doc.get("a"),
+            Some(&JsonValueRef::Array(&[JsonValueRef::Number(20.0)]))
+        );
+        assert!(doc.get("k\n").is_some_and(JsonValueRef::is_null));
+        let b = doc.as_object().unwrap()[0].1.pointer("/1/b").unwrap();
+        assert_eq!(b.as_str(), Some("plain"));
+        // Unescaped strings are borrowed from the text.
+        assert!(
+            json.as_bytes()
+                .as_ptr_range()
+                .contains(&b.as_str().unwrap().as_ptr())
+        );
+        assert_eq!(
+            doc.pointer("/a/0").and_then(JsonValueRef::as_f64),
+            Some(20.0)
+        );
+        assert!(doc.pointer("/a/1").is_none());
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let arena = Arena::new();
+        let deep = "[{\"a\": ".repeat(100_000) + "[]" + &"}]".repeat(100_000);
+        let value = JsonValueRef::parse_in(&deep, &arena).unwrap();
+        let copied = value.to_value();
+        let mut node = &copied;
+        for _ in 0..100_000 {
+            node = node.get_index(0).unwrap().get("a").unwrap();
+        }
+        assert_eq!(node, &JsonValue::Array(JsonArray::new()));
+    }
+
+    #[test]
+    fn test_errors() {
+        let arena = Arena::new();
+        let err = JsonValueRef::parse_in("[1, }", &arena).unwrap_err();
+        assert_eq!(err, parse_to_tape("[1, }").unwrap_err());
+    }
+}
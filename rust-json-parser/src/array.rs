@@ -0,0 +1,524 @@
+use crate::node::{self, Node};
+use crate::path::pointer_tokens;
+use crate::{JsonError, JsonValue, Result};
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// The elements of a JSON array.
+///
+/// Dereferences to a slice, so indexing, iteration, `first`, `sort_by` and
+/// the other slice methods work directly; growing and shrinking goes through
+/// the methods below.
+///
+/// The elements live behind a single pointer, allocated with the first of
+/// them, so an array takes one word inside a [`JsonValue`] and an empty one
+/// allocates nothing. With the `shared` feature clones share that
+/// allocation until one of them is changed.
+#[derive(Clone, Default)]
+pub struct JsonArray {
+    items: Option<Node<Vec<JsonValue>>>,
+}
+
+impl JsonArray {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity).into()
+    }
+
+    /// Number of elements the array can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.items.as_ref().map_or(0, |items| items.capacity())
+    }
+
+    /// Whether `self` and `other` are clones that still share their
+    /// elements, as they do with the `shared` feature until one is changed.
+    /// Shared elements compare equal without being walked.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.items, &other.items) {
+            (Some(a), Some(b)) => node::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[JsonValue] {
+        self.items.as_deref().map_or(&[], Vec::as_slice)
+    }
+
+    pub fn push(&mut self, value: JsonValue) {
+        self.items_mut().push(value);
+    }
+
+    pub fn pop(&mut self) -> Option<JsonValue> {
+        self.existing_mut()?.pop()
+    }
+
+    /// Inserts `value` at `index`, shifting later elements up.
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: JsonValue) {
+        self.items_mut().insert(index, value);
+    }
+
+    /// Removes the element at `index`, or returns `None` if it is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Option<JsonValue> {
+        (index < self.len()).then(|| self.items_mut().remove(index))
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len()
+            && let Some(items) = self.existing_mut()
+        {
+            items.truncate(len);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        match self.items.as_mut().and_then(node::get_mut) {
+            Some(items) => items.clear(),
+            // Shared elements are left to the clones that hold them.
+            None => self.items = None,
+        }
+    }
+
+    /// Keeps only the elements for which `keep` returns `true`.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&mut JsonValue) -> bool,
+    {
+        if let Some(items) = self.existing_mut() {
+            items.retain_mut(|item| keep(item));
+        }
+    }
+
+    /// Sorts elements by the value each holds at the JSON Pointer `pointer`,
+    /// using [`JsonValue::total_cmp`]. Elements where the pointer does not
+    /// resolve go last. The sort is stable.
+    pub fn sort_by_key_path(&mut self, pointer: &str) {
+        self.sort_by(|a, b| match (a.pointer(pointer), b.pointer(pointer)) {
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        });
+    }
+
+    /// Turns rows into columns: for each JSON Pointer, the value every
+    /// element holds there, in element order. Columns come in the order of
+    /// `pointers`, one per pointer even when a pointer is repeated.
+    ///
+    /// Elements are visited once however many pointers there are. Where a
+    /// pointer does not resolve the column holds `None`, so every column
+    /// has one entry per element and a missing value is told apart from a
+    /// `null`. Fails with [`JsonError::InvalidPointer`] for the first
+    /// malformed pointer, before any element is read.
+    ///
+    /// ```
+    /// use rust_json_parser::{JsonParser, JsonValue};
+    ///
+    /// let rows = JsonParser::new(r#"[{"id": 1, "user": {"name": "a"}}, {"id": null}]"#)?.parse()?;
+    /// let columns = rows.as_array().unwrap().to_columns(&["/id", "/user/name"])?;
+    /// assert_eq!(columns[0], [Some(JsonValue::Number(1.0)), Some(JsonValue::Null)]);
+    /// assert_eq!(columns[1][1], None);
+    /// # Ok::<(), rust_json_parser::JsonError>(())
+    /// ```
+    pub fn to_columns(&self, pointers: &[&str]) -> Result<Vec<Vec<Option<JsonValue>>>> {
+        let tokens = pointers
+            .iter()
+            .map(|pointer| {
+                pointer_tokens(pointer).ok_or_else(|| JsonError::InvalidPointer {
+                    pointer: pointer.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut columns: Vec<Vec<Option<JsonValue>>> = pointers
+            .iter()
+            .map(|_| Vec::with_capacity(self.len()))
+            .collect();
+        for row in self.iter() {
+            for (column, tokens) in columns.iter_mut().zip(&tokens) {
+                column.push(row.resolve(tokens).cloned());
+            }
+        }
+        Ok(columns)
+    }
+
+    /// Removes consecutive equal elements, like [`Vec::dedup`]. Sort first to
+    /// remove every duplicate.
+    pub fn dedup(&mut self) {
+        if let Some(items) = self.existing_mut() {
+            items.dedup();
+        }
+    }
+
+    /// Removes consecutive elements for which `same` returns `true`, keeping
+    /// the first of each run.
+    pub fn dedup_by<F>(&mut self, mut same: F)
+    where
+        F: FnMut(&JsonValue, &JsonValue) -> bool,
+    {
+        if let Some(items) = self.existing_mut() {
+            items.dedup_by(|later, earlier| same(earlier, later));
+        }
+    }
+
+    /// The elements, allocating room for them if there is none yet.
+    fn items_mut(&mut self) -> &mut Vec<JsonValue> {
+        node::make_mut(self.items.get_or_insert_with(Node::default))
+    }
+
+    /// The elements, if any room for them was allocated.
+    fn existing_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        self.items.as_mut().map(node::make_mut)
+    }
+
+    /// Moves the elements out, leaving the array empty.
+    fn take(&mut self) -> Vec<JsonValue> {
+        self.items.take().map_or_else(Vec::new, node::into_inner)
+    }
+
+    /// Empties the array into `stack` and returns the emptied allocation,
+    /// unless another clone shares it.
+    pub(crate) fn recycle_into(
+        &mut self,
+        stack: &mut Vec<JsonValue>,
+    ) -> Option<Node<Vec<JsonValue>>> {
+        let mut items = self.items.take()?;
+        if let Some(elements) = node::get_mut(&mut items) {
+            stack.append(elements);
+            return Some(items);
+        }
+        stack.extend(node::into_unique(items).unwrap_or_default());
+        None
+    }
+
+    /// An empty array filling an allocation [`JsonArray::recycle_into`]
+    /// returned.
+    pub(crate) fn recycled(items: Node<Vec<JsonValue>>) -> Self {
+        Self { items: Some(items) }
+    }
+
+    /// Moves the elements out if no clone shares them, leaving the array
+    /// empty either way.
+    fn take_unique(&mut self) -> Vec<JsonValue> {
+        self.items
+            .take()
+            .and_then(node::into_unique)
+            .unwrap_or_default()
+    }
+}
+
+impl JsonValue {
+    /// A total order over JSON values, for sorting.
+    ///
+    /// Values of different types order as null < boolean < number < string <
+    /// array < object. Numbers use [`f64::total_cmp`], strings compare by code
+    /// point, and containers compare element by element (members in document
+    /// order, key before value).
+    pub fn total_cmp(&self, other: &JsonValue) -> Ordering {
+        fn rank(value: &JsonValue) -> u8 {
+            match value {
+                JsonValue::Null => 0,
+                JsonValue::Boolean(_) => 1,
+                JsonValue::Number(_) => 2,
+                JsonValue::String(_) => 3,
+                JsonValue::Array(_) => 4,
+                JsonValue::Object(_) => 5,
+            }
+        }
+        match (self, other) {
+            (JsonValue::Boolean(a), JsonValue::Boolean(b)) => a.cmp(b),
+            (JsonValue::Number(a), JsonValue::Number(b)) => a.total_cmp(b),
+            (JsonValue::String(a), JsonValue::String(b)) => a.cmp(b),
+            (JsonValue::Array(a), JsonValue::Array(b)) => a
+                .iter()
+                .zip(b.iter())
+                .map(|(x, y)| x.total_cmp(y))
+                .find(|ord| ord.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (JsonValue::Object(a), JsonValue::Object(b)) => a
+                .iter()
+                .zip(b.iter())
+                .map(|((ka, va), (kb, vb))| ka.cmp(kb).then_with(|| va.total_cmp(vb)))
+                .find(|ord| ord.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl Deref for JsonArray {
+    type Target = [JsonValue];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl DerefMut for JsonArray {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.existing_mut().map_or(&mut [], Vec::as_mut_slice)
+    }
+}
+
+impl PartialEq for JsonArray {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || self.as_slice() == other.as_slice()
+    }
+}
+
+impl fmt::Debug for JsonArray {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonArray {
+    fn from(items: Vec<JsonValue>) -> Self {
+        Self {
+            items: (items.capacity() > 0).then(|| Node::new(items)),
+        }
+    }
+}
+
+impl From<JsonArray> for Vec<JsonValue> {
+    fn from(mut array: JsonArray) -> Self {
+        array.take()
+    }
+}
+
+impl FromIterator<JsonValue> for JsonArray {
+    fn from_iter<I: IntoIterator<Item = JsonValue>>(iter: I) -> Self {
+        iter.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl Extend<JsonValue> for JsonArray {
+    fn extend<I: IntoIterator<Item = JsonValue>>(&mut self, iter: I) {
+        self.items_mut().extend(iter);
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonArray {
+    type Item = &'a JsonValue;
+    type IntoIter = std::slice::Iter<'a, JsonValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut JsonArray {
+    type Item = &'a mut JsonValue;
+    type IntoIter = std::slice::IterMut<'a, JsonValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl IntoIterator for JsonArray {
+    type Item = JsonValue;
+    type IntoIter = std::vec::IntoIter<JsonValue>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.take().into_iter()
+    }
+}
+
+impl Drop for JsonArray {
+    fn drop(&mut self) {
+        let items = self.take_unique();
+        if items.iter().any(has_children) {
+            drop_iteratively(items);
+        }
+    }
+}
+
+/// Whether dropping `value` would recurse into further values.
+pub(crate) fn has_children(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Array(array) => !array.is_empty(),
+        JsonValue::Object(object) => !object.is_empty(),
+        _ => false,
+    }
+}
+
+/// Frees a tree with an explicit stack, like serde_json does, so a document
+/// nested thousands of levels deep cannot overflow the call stack. Each
+/// container is emptied before it goes out of scope, except for contents
+/// another clone still shares.
+pub(crate) fn drop_iteratively(mut stack: Vec<JsonValue>) {
+    while let Some(value) = stack.pop() {
+        match value {
+            JsonValue::Array(mut array) => stack.extend(array.take_unique()),
+            JsonValue::Object(mut object) => stack.extend(object.drain_values()),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(values: &[f64]) -> JsonArray {
+        values.iter().map(|n| JsonValue::Number(*n)).collect()
+    }
+
+    #[test]
+    fn test_push_pop_and_slice_access() {
+        let mut arr = JsonArray::new();
+        arr.push(JsonValue::Number(1.0));
+        arr.push(JsonValue::Null);
+        assert_eq!(arr.len(), 2);
+        assert_eq!(arr[0], JsonValue::Number(1.0));
+        assert_eq!(arr.last(), Some(&JsonValue::Null));
+        assert_eq!(arr.pop(), Some(JsonValue::Null));
+        assert_eq!(arr, numbers(&[1.0]));
+    }
+
+    #[test]
+    fn test_insert_remove_and_retain() {
+        let mut arr = numbers(&[1.0, 3.0, 4.0]);
+        arr.insert(1, JsonValue::Number(2.0));
+        assert_eq!(arr, numbers(&[1.0, 2.0, 3.0, 4.0]));
+        assert_eq!(arr.remove(9), None);
+        assert_eq!(arr.remove(0), Some(JsonValue::Number(1.0)));
+        arr.retain(|v| v.as_f64() != Some(3.0));
+        assert_eq!(arr, numbers(&[2.0, 4.0]));
+    }
+
+    #[test]
+    fn test_conversions_and_iteration() {
+        let arr = JsonArray::from(vec![JsonValue::Boolean(true)]);
+        assert_eq!(format!("{arr:?}"), "[Boolean(true)]");
+        assert_eq!((&arr).into_iter().count(), 1);
+        let items: Vec<JsonValue> = arr.into();
+        assert_eq!(items, vec![JsonValue::Boolean(true)]);
+    }
+
+    fn names(rows: &JsonArray) -> Vec<Option<&str>> {
+        rows.iter()
+            .map(|row| row.get("name").and_then(JsonValue::as_str))
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_by_key_path() {
+        let input = r#"[{"name": "b", "stats": [2]}, {"name": "a", "stats": [3]}, null, {"name": "c", "stats": [1]}]"#;
+        let mut value = crate::JsonParser::new(input).unwrap().parse().unwrap();
+        let rows = value.as_array_mut().unwrap();
+        rows.sort_by_key_path("/stats/0");
+        assert_eq!(names(rows), vec![Some("c"), Some("b"), Some("a"), None]);
+        rows.sort_by_key_path("/name");
+        assert_eq!(names(rows), vec![Some("a"), Some("b"), Some("c"), None]);
+    }
+
+    #[test]
+    fn test_to_columns() {
+        let input = r#"[
+            {"id": 1, "tags": ["x"], "user": {"name": "a"}},
+            {"id": 2, "user": null},
+            7,
+            {"id": 3, "tags": [], "user": {"name": "c"}}
+        ]"#;
+        let value = crate::JsonParser::new(input).unwrap().parse().unwrap();
+        let columns = value
+            .as_array()
+            .unwrap()
+            .to_columns(&["/id", "/user/name", "/tags/0", "/user", "/id"])
+            .unwrap();
+        let column = |i: usize| -> Vec<Option<String>> {
+            columns[i]
+                .iter()
+                .map(|v| v.as_ref().map(JsonValue::to_string))
+                .collect()
+        };
+        let some = |text: &str| Some(text.to_string());
+        assert_eq!(columns.len(), 5);
+        assert_eq!(column(0), [some("1"), some("2"), None, some("3")]);
+        assert_eq!(column(1), [some(r#""a""#), None, None, some(r#""c""#)]);
+        assert_eq!(column(2), [some(r#""x""#), None, None, None]);
+        assert_eq!(column(3)[1], some("null"));
+        assert_eq!(column(4), column(0));
+        assert_eq!(JsonArray::new().to_columns(&["/id"]).unwrap(), [Vec::new()]);
+    }
+
+    #[test]
+    fn test_to_columns_rejects_malformed_pointers() {
+        let rows: JsonArray = vec![JsonValue::Null].into();
+        assert_eq!(
+            rows.to_columns(&["/id", "bad", "worse"]).unwrap_err(),
+            JsonError::InvalidPointer {
+                pointer: "bad".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_total_cmp_orders_across_types() {
+        let mut arr: JsonArray = vec![
+            JsonValue::String("a".into()),
+            JsonValue::Number(-1.0),
+            JsonValue::new_array(),
+            JsonValue::Boolean(true),
+            JsonValue::Null,
+            JsonValue::Number(-2.0),
+        ]
+        .into();
+        arr.sort_by(JsonValue::total_cmp);
+        assert_eq!(
+            arr,
+            vec![
+                JsonValue::Null,
+                JsonValue::Boolean(true),
+                JsonValue::Number(-2.0),
+                JsonValue::Number(-1.0),
+                JsonValue::String("a".into()),
+                JsonValue::new_array(),
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut arr = numbers(&[1.0, 1.0, 2.0, 1.0, 3.0, 3.0]);
+        arr.dedup();
+        assert_eq!(arr, numbers(&[1.0, 2.0, 1.0, 3.0]));
+
+        arr.sort_by(JsonValue::total_cmp);
+        arr.dedup();
+        assert_eq!(arr, numbers(&[1.0, 2.0, 3.0]));
+
+        arr.dedup_by(|kept, next| next.as_f64() == kept.as_f64().map(|n| n + 1.0));
+        assert_eq!(arr, numbers(&[1.0, 3.0]));
+    }
+
+    #[test]
+    fn test_deep_nesting_drops_iteratively() {
+        let mut value = JsonValue::Null;
+        for i in 0..100_000 {
+            value = if i % 2 == 0 {
+                JsonValue::Array(vec![value, JsonValue::Boolean(true)].into())
+            } else {
+                let mut object = crate::JsonObject::new();
+                object.insert("next".to_string(), value);
+                JsonValue::Object(object)
+            };
+        }
+        drop(value);
+
+        // Values moved out before the drop are left intact
+        let nested = JsonValue::Array(vec![JsonValue::Array(numbers(&[1.0]))].into());
+        let JsonValue::Array(outer) = nested else {
+            unreachable!()
+        };
+        let items: Vec<JsonValue> = outer.into();
+        assert_eq!(items, vec![JsonValue::Array(numbers(&[1.0]))]);
+    }
+}
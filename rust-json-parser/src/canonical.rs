@@ -0,0 +1,170 @@
+//! The JSON Canonicalization Scheme (RFC 8785), which gives every value one
+//! exact byte sequence so that signatures over JSON can be verified.
+
+use crate::value::escape_json_string;
+use crate::{JsonError, JsonPath, JsonValue, Result};
+
+impl JsonValue {
+    /// Serializes in JCS canonical form: no whitespace, object members sorted
+    /// by the UTF-16 code units of their keys, strings with only the escapes
+    /// JSON requires, and numbers formatted as ECMAScript does.
+    ///
+    /// Fails with [`JsonError::NonFiniteNumber`] for NaN and infinities,
+    /// which have no canonical form.
+    pub fn to_canonical_string(&self) -> Result<String> {
+        let mut out = String::new();
+        write_canonical(self, &mut out, &mut JsonPath::new())?;
+        Ok(out)
+    }
+}
+
+fn write_canonical(value: &JsonValue, out: &mut String, path: &mut JsonPath) -> Result<()> {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Boolean(b) => out.push_str(if *b { "true" } else { "false" }),
+        JsonValue::Number(n) => {
+            if !n.is_finite() {
+                return Err(JsonError::NonFiniteNumber {
+                    pointer: path.to_pointer(),
+                });
+            }
+            out.push_str(&es_number(*n));
+        }
+        JsonValue::String(s) => out.push_str(&escape_json_string(s)),
+        JsonValue::Array(arr) => {
+            out.push('[');
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                path.push_index(i);
+                write_canonical(item, out, path)?;
+                path.pop();
+            }
+            out.push(']');
+        }
+        JsonValue::Object(obj) => {
+            let mut members: Vec<_> = obj.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&escape_json_string(key));
+                out.push(':');
+                path.push_key(key.as_str());
+                write_canonical(item, out, path)?;
+                path.pop();
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// Formats a finite number as ECMAScript's `Number.prototype.toString`,
+/// which RFC 8785 adopts: the shortest round-tripping digits, in positional
+/// notation from 1e-6 up to 1e21 and exponential notation outside it.
+pub(crate) fn es_number(n: f64) -> String {
+    if n == 0.0 {
+        // Covers -0 as well
+        return "0".to_string();
+    }
+    // `{:e}` yields the shortest round-tripping digits, e.g. "1.2345e3"
+    let formatted = format!("{:e}", n.abs());
+    let (mantissa, exponent) = formatted
+        .split_once('e')
+        .expect("LowerExp output always has an exponent");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().expect("LowerExp exponent is an integer");
+    let k = digits.len() as i32;
+    // Position of the decimal point relative to the start of `digits`
+    let point = exponent + 1;
+
+    let body = if k <= point && point <= 21 {
+        format!("{digits}{}", "0".repeat((point - k) as usize))
+    } else if 0 < point && point <= 21 {
+        let (int, frac) = digits.split_at(point as usize);
+        format!("{int}.{frac}")
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{digits}", "0".repeat(-point as usize))
+    } else {
+        let sign = if point - 1 < 0 { '-' } else { '+' };
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{rest}")
+        };
+        format!("{first}{fraction}e{sign}{}", (point - 1).abs())
+    };
+    if n < 0.0 { format!("-{body}") } else { body }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_number_formatting() {
+        // Expected strings from RFC 8785 appendix B and ECMAScript
+        let cases = vec![
+            (0.0, "0"),
+            (-0.0, "0"),
+            (1.0, "1"),
+            (-1.5, "-1.5"),
+            (100.0, "100"),
+            (1e21, "1e+21"),
+            (1e20, "100000000000000000000"),
+            (123456789012345680000.0, "123456789012345680000"),
+            (0.000001, "0.000001"),
+            (0.0000001, "1e-7"),
+            (1.5e-7, "1.5e-7"),
+            (9007199254740992.0, "9007199254740992"),
+            (4.5, "4.5"),
+            (0.002, "0.002"),
+            (333333333.3333333, "333333333.3333333"),
+            (1e23, "1e+23"),
+            (5e-324, "5e-324"),
+            (1.7976931348623157e308, "1.7976931348623157e+308"),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(es_number(n), expected, "Number: {n:e}");
+        }
+    }
+
+    #[test]
+    fn test_canonical_layout() {
+        let value =
+            parse_json(r#"{"b": [1, {"z": null, "a": true}], "a": "€\n", "": 0.10}"#).unwrap();
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            "{\"\":0.1,\"a\":\"\u{20ac}\\n\",\"b\":[1,{\"a\":true,\"z\":null}]}"
+        );
+    }
+
+    #[test]
+    fn test_keys_sort_by_utf16_code_units() {
+        // U+1F600 encodes as 0xD83D 0xDE00, which sorts before U+FB33
+        let value = parse_json("{\"\u{fb33}\": 1, \"\u{1f600}\": 2, \"a\": 3}").unwrap();
+        assert_eq!(
+            value.to_canonical_string().unwrap(),
+            "{\"a\":3,\"\u{1f600}\":2,\"\u{fb33}\":1}"
+        );
+    }
+
+    #[test]
+    fn test_non_finite_numbers_are_rejected() {
+        let mut value = parse_json(r#"{"a": [0, 1]}"#).unwrap();
+        *value.pointer_mut("/a/1").unwrap() = JsonValue::Number(f64::NAN);
+        assert_eq!(
+            value.to_canonical_string(),
+            Err(JsonError::NonFiniteNumber {
+                pointer: "/a/1".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,406 @@
+//! `Content-Length` framed JSON messages, as used by LSP and JSON-RPC transports.
+//!
+//! Each message is a block of `Name: value` header lines terminated by an
+//! empty line, followed by exactly `Content-Length` bytes of UTF-8 JSON:
+//!
+//! ```text
+//! Content-Length: 17\r\n
+//! \r\n
+//! {"method":"exit"}
+//! ```
+//!
+//! With the `async` feature, the same framing is available over Tokio streams.
+//!
+//! Messages come from peers, so they are read as untrusted input: header
+//! lines are capped at [`MAX_HEADER_LINE_BYTES`], a body larger than the
+//! size limit is refused before any of it is read, and nesting is capped.
+//! The crate has no push parser to feed a body to as it arrives, so each
+//! body is read in full, up to that limit, and then parsed.
+
+use crate::{JsonError, JsonParser, JsonValue, ParserOptions};
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+
+/// Longest header line accepted, line break included.
+pub const MAX_HEADER_LINE_BYTES: usize = 8 * 1024;
+
+/// Body size allowed when the options given set no
+/// [`Limits::max_input_bytes`](crate::Limits::max_input_bytes): 16 MiB, as
+/// in [`Limits::untrusted`](crate::Limits::untrusted).
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Errors produced while reading or writing framed messages.
+#[derive(Debug)]
+pub enum CodecError {
+    Io(io::Error),
+    /// The body was not valid JSON.
+    Json(JsonError),
+    /// The header block ended without a `Content-Length` header.
+    MissingContentLength,
+    /// A header line was malformed or had an unusable value.
+    InvalidHeader(String),
+    /// The body was not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Io(err) => write!(f, "I/O error: {err}"),
+            CodecError::Json(err) => write!(f, "Invalid message body: {err}"),
+            CodecError::MissingContentLength => write!(f, "Missing Content-Length header"),
+            CodecError::InvalidHeader(line) => write!(f, "Invalid header: {line}"),
+            CodecError::InvalidUtf8 => write!(f, "Message body is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CodecError::Io(err) => Some(err),
+            CodecError::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CodecError {
+    fn from(err: io::Error) -> Self {
+        CodecError::Io(err)
+    }
+}
+
+impl From<JsonError> for CodecError {
+    fn from(err: JsonError) -> Self {
+        CodecError::Json(err)
+    }
+}
+
+/// Reads one framed message, parsing its body with
+/// [`ParserOptions::untrusted`].
+///
+/// Returns `Ok(None)` when the stream ends cleanly before a new message starts.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<JsonValue>, CodecError> {
+    read_message_with(reader, &ParserOptions::untrusted())
+}
+
+/// Reads one framed message, parsing its body with `options`. A
+/// `Content-Length` over the options' input size limit, or
+/// [`DEFAULT_MAX_MESSAGE_BYTES`] if they set none, fails with
+/// [`JsonError::InputTooLarge`]; nesting is capped at depth 128 if they
+/// set no depth limit.
+pub fn read_message_with<R: BufRead>(
+    reader: &mut R,
+    options: &ParserOptions,
+) -> Result<Option<JsonValue>, CodecError> {
+    let mut headers = HeaderParser::default();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let limit = MAX_HEADER_LINE_BYTES as u64;
+        if reader.by_ref().take(limit).read_line(&mut line)? == 0 {
+            return headers.end_of_stream();
+        }
+        if headers.feed(&line)? {
+            break;
+        }
+    }
+
+    let length = headers.body_length(options)?;
+    let mut body = Vec::new();
+    reader.take(length as u64).read_to_end(&mut body)?;
+    decode_body(body, length, options).map(Some)
+}
+
+/// Writes `value` as one framed message, in compact form.
+pub fn write_message<W: Write>(writer: &mut W, value: &JsonValue) -> io::Result<()> {
+    writer.write_all(&encode_message(value))?;
+    writer.flush()
+}
+
+/// Async counterparts of [`read_message`] and [`write_message`] over Tokio streams.
+#[cfg(feature = "async")]
+pub mod async_io {
+    use super::{CodecError, HeaderParser, MAX_HEADER_LINE_BYTES, decode_body, encode_message};
+    use crate::{JsonValue, ParserOptions};
+    use std::io;
+    use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+    /// Reads one framed message with [`ParserOptions::untrusted`]; `Ok(None)`
+    /// on a clean end of stream.
+    pub async fn read_message<R>(reader: &mut R) -> Result<Option<JsonValue>, CodecError>
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        read_message_with(reader, &ParserOptions::untrusted()).await
+    }
+
+    /// Reads one framed message, parsing its body with `options` under the
+    /// same caps as [`super::read_message_with`].
+    pub async fn read_message_with<R>(
+        reader: &mut R,
+        options: &ParserOptions,
+    ) -> Result<Option<JsonValue>, CodecError>
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        let mut headers = HeaderParser::default();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let limit = MAX_HEADER_LINE_BYTES as u64;
+            if (&mut *reader).take(limit).read_line(&mut line).await? == 0 {
+                return headers.end_of_stream();
+            }
+            if headers.feed(&line)? {
+                break;
+            }
+        }
+
+        let length = headers.body_length(options)?;
+        let mut body = Vec::new();
+        (&mut *reader)
+            .take(length as u64)
+            .read_to_end(&mut body)
+            .await?;
+        decode_body(body, length, options).map(Some)
+    }
+
+    /// Writes `value` as one framed message, in compact form.
+    pub async fn write_message<W>(writer: &mut W, value: &JsonValue) -> io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        writer.write_all(&encode_message(value)).await?;
+        writer.flush().await
+    }
+}
+
+/// Accumulates header lines until the blank separator line.
+#[derive(Default)]
+struct HeaderParser {
+    content_length: Option<usize>,
+    started: bool,
+}
+
+impl HeaderParser {
+    /// Consumes one header line; returns `true` once the header block is complete.
+    fn feed(&mut self, line: &str) -> Result<bool, CodecError> {
+        if !line.ends_with('\n') && line.len() >= MAX_HEADER_LINE_BYTES {
+            return Err(CodecError::InvalidHeader(format!(
+                "line longer than {MAX_HEADER_LINE_BYTES} bytes"
+            )));
+        }
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            // Tolerate stray blank lines between messages
+            return Ok(self.started);
+        }
+        self.started = true;
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| CodecError::InvalidHeader(line.to_string()))?;
+        if name.trim().eq_ignore_ascii_case("Content-Length") {
+            let length = value
+                .trim()
+                .parse()
+                .map_err(|_| CodecError::InvalidHeader(line.to_string()))?;
+            self.content_length = Some(length);
+        }
+        Ok(false)
+    }
+
+    fn end_of_stream(&self) -> Result<Option<JsonValue>, CodecError> {
+        if self.started {
+            Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// The body length the headers gave, if `options` allow a body that
+    /// large.
+    fn body_length(&self, options: &ParserOptions) -> Result<usize, CodecError> {
+        let length = self
+            .content_length
+            .ok_or(CodecError::MissingContentLength)?;
+        let limit = options
+            .limits
+            .max_input_bytes
+            .unwrap_or(DEFAULT_MAX_MESSAGE_BYTES);
+        if length > limit {
+            return Err(JsonError::InputTooLarge {
+                size: length,
+                limit,
+            }
+            .into());
+        }
+        Ok(length)
+    }
+}
+
+/// Parses a body that should be `length` bytes long.
+fn decode_body(
+    body: Vec<u8>,
+    length: usize,
+    options: &ParserOptions,
+) -> Result<JsonValue, CodecError> {
+    if body.len() < length {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    let text = String::from_utf8(body).map_err(|_| CodecError::InvalidUtf8)?;
+    let options = options.clone().limits(options.limits.capped());
+    Ok(JsonParser::with_options(&text, options)?.parse()?)
+}
+
+fn encode_message(value: &JsonValue) -> Vec<u8> {
+    let body = value.to_string();
+    let mut frame = format!("Content-Length: {}\r\n\r\n", body.len()).into_bytes();
+    frame.extend_from_slice(body.as_bytes());
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse_json;
+    use crate::{JsonArray, Limits};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_round_trip_multiple_messages() {
+        let first = parse_json(r#"{"jsonrpc": "2.0", "id": 1, "method": "ping"}"#).unwrap();
+        let second = parse_json(r#"{"text": "héllo"}"#).unwrap();
+
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &first).unwrap();
+        write_message(&mut buffer, &second).unwrap();
+
+        let mut reader = Cursor::new(buffer);
+        assert_eq!(read_message(&mut reader).unwrap(), Some(first));
+        assert_eq!(read_message(&mut reader).unwrap(), Some(second));
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_content_length_counts_bytes() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &JsonValue::String("é".into())).unwrap();
+        assert!(buffer.starts_with(b"Content-Length: 4\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_extra_headers_and_case_are_accepted() {
+        let input = "content-length: 2\r\nContent-Type: application/vscode-jsonrpc\r\n\r\n[]";
+        let value = read_message(&mut Cursor::new(input)).unwrap();
+        assert_eq!(value, Some(JsonValue::Array(JsonArray::new())));
+    }
+
+    #[test]
+    fn test_missing_content_length() {
+        let input = "Content-Type: json\r\n\r\n{}";
+        let result = read_message(&mut Cursor::new(input));
+        assert!(matches!(result, Err(CodecError::MissingContentLength)));
+    }
+
+    #[test]
+    fn test_malformed_headers() {
+        for input in ["Content-Length: abc\r\n\r\n", "no colon here\r\n\r\n"] {
+            let result = read_message(&mut Cursor::new(input));
+            assert!(
+                matches!(result, Err(CodecError::InvalidHeader(_))),
+                "Should reject: {input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_truncated_message() {
+        for input in ["Content-Length: 10\r\n\r\n{}", "Content-Length: 2\r\n"] {
+            match read_message(&mut Cursor::new(input)) {
+                Err(CodecError::Io(err)) => assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof),
+                other => panic!("Expected UnexpectedEof for {input:?}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_oversized_messages_are_refused_before_reading() {
+        let input = "Content-Length: 99999999999999\r\n\r\n{}";
+        let result = read_message(&mut Cursor::new(input));
+        assert!(matches!(
+            result,
+            Err(CodecError::Json(JsonError::InputTooLarge {
+                size: 99999999999999,
+                ..
+            }))
+        ));
+
+        let options = ParserOptions::new().limits(Limits::new().max_input_bytes(4));
+        let input = "Content-Length: 5\r\n\r\n[1,2]";
+        let result = read_message_with(&mut Cursor::new(input), &options);
+        assert!(matches!(
+            result,
+            Err(CodecError::Json(JsonError::InputTooLarge { limit: 4, .. }))
+        ));
+
+        let long = format!("X-Padding: {}\r\n", "a".repeat(MAX_HEADER_LINE_BYTES));
+        let result = read_message(&mut Cursor::new(long));
+        assert!(matches!(result, Err(CodecError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_deep_bodies_are_refused() {
+        let body = "[".repeat(100_000) + &"]".repeat(100_000);
+        let input = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+        for options in [ParserOptions::untrusted(), ParserOptions::new()] {
+            let result = read_message_with(&mut Cursor::new(&input), &options);
+            assert!(matches!(
+                result,
+                Err(CodecError::Json(JsonError::DepthLimitExceeded {
+                    limit: 128,
+                    ..
+                }))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_body() {
+        let result = read_message(&mut Cursor::new("Content-Length: 3\r\n\r\n{x}"));
+        assert!(matches!(result, Err(CodecError::Json(_))));
+
+        let mut bytes = b"Content-Length: 2\r\n\r\n".to_vec();
+        bytes.extend_from_slice(&[0xff, 0xfe]);
+        let result = read_message(&mut Cursor::new(bytes));
+        assert!(matches!(result, Err(CodecError::InvalidUtf8)));
+    }
+
+    #[cfg(feature = "async")]
+    mod async_codec {
+        use super::*;
+
+        #[tokio::test]
+        async fn test_async_round_trip() {
+            let value = parse_json(r#"{"id": 7, "result": [1, 2]}"#).unwrap();
+            let mut buffer = Vec::new();
+            async_io::write_message(&mut buffer, &value).await.unwrap();
+
+            let mut reader = tokio::io::BufReader::new(buffer.as_slice());
+            assert_eq!(
+                async_io::read_message(&mut reader).await.unwrap(),
+                Some(value)
+            );
+            assert!(async_io::read_message(&mut reader).await.unwrap().is_none());
+        }
+
+        #[tokio::test]
+        async fn test_async_missing_content_length() {
+            let mut reader = tokio::io::BufReader::new(&b"X-Other: 1\r\n\r\n"[..]);
+            let result = async_io::read_message(&mut reader).await;
+            assert!(matches!(result, Err(CodecError::MissingContentLength)));
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::JsonPath;
+use std::collections::HashMap;
+
+/// Comments from a JSONC document, each attached to the node that follows
+/// it.
+///
+/// Collect them with [`Document::comments`](crate::Document::comments) and
+/// write them back with [`JsonValue::serialize_with_comments`](crate::JsonValue::serialize_with_comments),
+/// so a parse, edit and serialize cycle on the value tree keeps them.
+/// Comments are stored with their delimiters, as `// note` or `/* note */`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comments {
+    by_path: HashMap<JsonPath, Vec<String>>,
+}
+
+impl Comments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The comments before the node at `path`, in document order.
+    pub fn get(&self, path: &JsonPath) -> &[String] {
+        self.by_path.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Attaches `comment` to the node at `path`, after any it already has.
+    /// It must be a complete `//` or `/* */` comment.
+    pub fn push(&mut self, path: JsonPath, comment: impl Into<String>) {
+        self.by_path.entry(path).or_default().push(comment.into());
+    }
+
+    /// Number of nodes with at least one comment.
+    pub fn len(&self) -> usize {
+        self.by_path.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_path.is_empty()
+    }
+}
+
+/// The comments in `gap`, text between tokens that holds only whitespace
+/// and comments.
+pub(crate) fn scan(gap: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = gap;
+    while let Some(start) = rest.find('/') {
+        rest = &rest[start..];
+        let end = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        } else {
+            rest.find("*/").map_or(rest.len(), |end| end + 2)
+        };
+        found.push(rest[..end].trim_end());
+        rest = &rest[end..];
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_finds_both_kinds() {
+        let gap = "\n  // one\r\n  /* two\n three */ // four\n  ";
+        assert_eq!(scan(gap), vec!["// one", "/* two\n three */", "// four"]);
+        assert!(scan(" \n\t").is_empty());
+    }
+
+    #[test]
+    fn test_push_and_get() {
+        let mut comments = Comments::new();
+        let mut path = JsonPath::new();
+        path.push_key("a");
+        comments.push(path.clone(), "// first");
+        comments.push(path.clone(), "/* second */");
+        assert_eq!(comments.get(&path), ["// first", "/* second */"]);
+        assert!(comments.get(&JsonPath::new()).is_empty());
+        assert_eq!(comments.len(), 1);
+    }
+}
@@ -0,0 +1,239 @@
+//! Runs the parser over a conformance suite laid out like
+//! [JSONTestSuite](https://github.com/nst/JSONTestSuite): a directory of
+//! `*.json` files whose name prefix says what a parser must do with them.
+//!
+//! - `y_` files are valid JSON and must be accepted.
+//! - `n_` files are invalid and must be rejected.
+//! - `i_` files may go either way; the outcome is recorded, not judged.
+
+use crate::{JsonError, JsonParser, ParserOptions};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// What the suite requires for a case, from its file name prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Expected {
+    /// `y_`: must parse.
+    Accept,
+    /// `n_`: must fail.
+    Reject,
+    /// `i_`: either is allowed.
+    Either,
+}
+
+impl Expected {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.get(..2)? {
+            "y_" => Some(Expected::Accept),
+            "n_" => Some(Expected::Reject),
+            "i_" => Some(Expected::Either),
+            _ => None,
+        }
+    }
+}
+
+/// What the parser did with a case.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Accepted,
+    Rejected(JsonError),
+    /// The file is not UTF-8, which JSON text must be, so it was rejected
+    /// before parsing.
+    NotUtf8,
+}
+
+impl Outcome {
+    pub fn is_accepted(&self) -> bool {
+        matches!(self, Outcome::Accepted)
+    }
+}
+
+/// One file of the suite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseResult {
+    /// File name, such as `n_array_extra_comma.json`.
+    pub name: String,
+    pub path: PathBuf,
+    pub expected: Expected,
+    pub outcome: Outcome,
+}
+
+impl CaseResult {
+    /// `true` when the outcome is the one the suite requires; always for
+    /// `i_` cases.
+    pub fn passed(&self) -> bool {
+        match self.expected {
+            Expected::Accept => self.outcome.is_accepted(),
+            Expected::Reject => !self.outcome.is_accepted(),
+            Expected::Either => true,
+        }
+    }
+}
+
+/// Every case of a suite run, sorted by file name.
+///
+/// Displays a summary line per prefix followed by each failing case.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConformanceReport {
+    pub cases: Vec<CaseResult>,
+}
+
+impl ConformanceReport {
+    /// `true` when every `y_` case was accepted and every `n_` case
+    /// rejected.
+    pub fn is_conformant(&self) -> bool {
+        self.cases.iter().all(CaseResult::passed)
+    }
+
+    /// Cases whose outcome is not the one required.
+    pub fn failures(&self) -> impl Iterator<Item = &CaseResult> {
+        self.cases.iter().filter(|case| !case.passed())
+    }
+
+    /// Cases with the given expectation, and how many of them were
+    /// accepted.
+    pub fn count(&self, expected: Expected) -> (usize, usize) {
+        self.cases
+            .iter()
+            .filter(|case| case.expected == expected)
+            .fold((0, 0), |(total, accepted), case| {
+                (
+                    total + 1,
+                    accepted + usize::from(case.outcome.is_accepted()),
+                )
+            })
+    }
+}
+
+impl fmt::Display for ConformanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (y, y_accepted) = self.count(Expected::Accept);
+        let (n, n_accepted) = self.count(Expected::Reject);
+        let (i, i_accepted) = self.count(Expected::Either);
+        writeln!(f, "y_: {y_accepted}/{y} accepted")?;
+        writeln!(f, "n_: {}/{n} rejected", n - n_accepted)?;
+        write!(f, "i_: {i_accepted} accepted, {} rejected", i - i_accepted)?;
+        for case in self.failures() {
+            match &case.outcome {
+                Outcome::Accepted => write!(f, "\nFAIL {}: accepted", case.name)?,
+                Outcome::Rejected(err) => write!(f, "\nFAIL {}: {err}", case.name)?,
+                Outcome::NotUtf8 => write!(f, "\nFAIL {}: not UTF-8", case.name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses each `y_*.json`, `n_*.json` and `i_*.json` file directly in
+/// `dir` with `options`, and reports what happened to each.
+///
+/// Other files are ignored. Only failing to list or read the directory is
+/// an error. Suites include deeply nested cases, so pass options with a
+/// depth limit; to certify against RFC 8259, use
+/// `ParserOptions::strict_rfc8259()`, which has one.
+pub fn run_conformance(
+    dir: impl AsRef<Path>,
+    options: &ParserOptions,
+) -> io::Result<ConformanceReport> {
+    let mut cases = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Some(expected) = Expected::from_name(name) else {
+            continue;
+        };
+        if !name.ends_with(".json") {
+            continue;
+        }
+        let outcome = match String::from_utf8(fs::read(&path)?) {
+            Ok(input) => match JsonParser::with_options(&input, options.clone())
+                .and_then(|mut parser| parser.parse())
+            {
+                Ok(_) => Outcome::Accepted,
+                Err(err) => Outcome::Rejected(err),
+            },
+            Err(_) => Outcome::NotUtf8,
+        };
+        cases.push(CaseResult {
+            name: name.to_string(),
+            path,
+            expected,
+            outcome,
+        });
+    }
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(ConformanceReport { cases })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn test_conformant_suite() {
+        let dir = TempDir::new("conformance-ok");
+        dir.write("y_array_empty.json", b"[]");
+        dir.write("y_string_unicode.json", b"[\"\\u00e9\"]");
+        dir.write("n_array_extra_comma.json", b"[1,]");
+        dir.write("n_string_invalid_utf8.json", &[b'"', 0xff, b'"']);
+        dir.write("i_number_huge_exp.json", b"[1e999999]");
+        dir.write("README.md", b"not a case");
+        dir.write("x_other.json", b"{}");
+
+        let report = run_conformance(&dir.0, &ParserOptions::untrusted()).unwrap();
+        let names: Vec<_> = report.cases.iter().map(|case| case.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "i_number_huge_exp.json",
+                "n_array_extra_comma.json",
+                "n_string_invalid_utf8.json",
+                "y_array_empty.json",
+                "y_string_unicode.json",
+            ]
+        );
+        assert!(report.is_conformant(), "{report}");
+        assert_eq!(report.cases[2].outcome, Outcome::NotUtf8);
+        assert_eq!(report.count(Expected::Accept), (2, 2));
+        assert_eq!(
+            report.to_string(),
+            "y_: 2/2 accepted\nn_: 2/2 rejected\ni_: 1 accepted, 0 rejected"
+        );
+    }
+
+    #[test]
+    fn test_failures_are_listed() {
+        let dir = TempDir::new("conformance-fail");
+        dir.write("y_comment.json", b"[1] // ok");
+        dir.write("n_trailing_comma.json", b"[1,]");
+
+        let options = ParserOptions::lenient();
+        let report = run_conformance(&dir.0, &options).unwrap();
+        assert!(!report.is_conformant());
+        let failed: Vec<_> = report.failures().map(|case| case.name.as_str()).collect();
+        assert_eq!(failed, ["n_trailing_comma.json"]);
+        assert!(
+            report
+                .to_string()
+                .ends_with("\nFAIL n_trailing_comma.json: accepted")
+        );
+
+        let report = run_conformance(&dir.0, &ParserOptions::default()).unwrap();
+        let failure = report.failures().next().unwrap();
+        assert_eq!(failure.name, "y_comment.json");
+        assert!(matches!(
+            failure.outcome,
+            Outcome::Rejected(JsonError::DisabledFeature { .. })
+        ));
+    }
+
+    #[test]
+    fn test_missing_directory_is_an_error() {
+        assert!(run_conformance("/definitely/not/here", &ParserOptions::default()).is_err());
+    }
+}
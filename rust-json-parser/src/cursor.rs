@@ -0,0 +1,373 @@
+use crate::{JsonPath, JsonValue, PathSegment};
+
+/// A movable position inside a document that remembers how it got there.
+///
+/// Movement methods return `false` and leave the cursor where it was when the
+/// target does not exist, so an explorer can try a move without checking first.
+#[derive(Debug, Clone)]
+pub struct JsonCursor<'a> {
+    /// Every node from the root down to the current one.
+    stack: Vec<&'a JsonValue>,
+    path: JsonPath,
+}
+
+impl<'a> JsonCursor<'a> {
+    pub fn new(root: &'a JsonValue) -> Self {
+        JsonCursor {
+            stack: vec![root],
+            path: JsonPath::new(),
+        }
+    }
+
+    pub fn current(&self) -> &'a JsonValue {
+        self.stack[self.stack.len() - 1]
+    }
+
+    pub fn path(&self) -> &JsonPath {
+        &self.path
+    }
+
+    /// Number of steps below the root.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Moves to the member `key` of the current object.
+    pub fn down_key(&mut self, key: &str) -> bool {
+        self.down(PathSegment::Key(key.to_string()))
+    }
+
+    /// Moves to element `index` of the current array.
+    pub fn down_index(&mut self, index: usize) -> bool {
+        self.down(PathSegment::Index(index))
+    }
+
+    /// Moves to the first element or member of the current container.
+    pub fn first_child(&mut self) -> bool {
+        match first_segment(self.current()) {
+            Some(segment) => self.down(segment),
+            None => false,
+        }
+    }
+
+    /// Moves back to the parent.
+    pub fn up(&mut self) -> bool {
+        if self.stack.len() == 1 {
+            return false;
+        }
+        self.stack.pop();
+        self.path.pop();
+        true
+    }
+
+    pub fn to_root(&mut self) {
+        self.stack.truncate(1);
+        self.path = JsonPath::new();
+    }
+
+    /// Moves to the next element or member of the parent, in document order.
+    pub fn next_sibling(&mut self) -> bool {
+        self.sibling(1)
+    }
+
+    pub fn prev_sibling(&mut self) -> bool {
+        self.sibling(-1)
+    }
+
+    fn down(&mut self, segment: PathSegment) -> bool {
+        match child(self.current(), &segment) {
+            Some(next) => {
+                self.stack.push(next);
+                push_segment(&mut self.path, segment);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn sibling(&mut self, offset: isize) -> bool {
+        let Some(segment) = self.path.segments().last() else {
+            return false;
+        };
+        let parent = self.stack[self.stack.len() - 2];
+        let Some(target) = sibling_segment(parent, segment, offset) else {
+            return false;
+        };
+        self.up();
+        self.down(target)
+    }
+}
+
+/// Like [`JsonCursor`], but allows editing the node under the cursor.
+///
+/// The cursor keeps only its path and re-resolves it from the root on each
+/// access, so every operation costs time proportional to the depth.
+#[derive(Debug)]
+pub struct JsonCursorMut<'a> {
+    root: &'a mut JsonValue,
+    path: JsonPath,
+}
+
+impl<'a> JsonCursorMut<'a> {
+    pub fn new(root: &'a mut JsonValue) -> Self {
+        JsonCursorMut {
+            root,
+            path: JsonPath::new(),
+        }
+    }
+
+    pub fn current(&self) -> &JsonValue {
+        resolve(self.root, &self.path).expect("cursor path always resolves")
+    }
+
+    pub fn current_mut(&mut self) -> &mut JsonValue {
+        resolve_mut(self.root, &self.path).expect("cursor path always resolves")
+    }
+
+    pub fn path(&self) -> &JsonPath {
+        &self.path
+    }
+
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    pub fn down_key(&mut self, key: &str) -> bool {
+        self.down(PathSegment::Key(key.to_string()))
+    }
+
+    pub fn down_index(&mut self, index: usize) -> bool {
+        self.down(PathSegment::Index(index))
+    }
+
+    pub fn first_child(&mut self) -> bool {
+        match first_segment(self.current()) {
+            Some(segment) => self.down(segment),
+            None => false,
+        }
+    }
+
+    pub fn up(&mut self) -> bool {
+        self.path.pop().is_some()
+    }
+
+    pub fn to_root(&mut self) {
+        self.path = JsonPath::new();
+    }
+
+    pub fn next_sibling(&mut self) -> bool {
+        self.sibling(1)
+    }
+
+    pub fn prev_sibling(&mut self) -> bool {
+        self.sibling(-1)
+    }
+
+    /// Replaces the node under the cursor, returning the old one.
+    pub fn replace(&mut self, value: JsonValue) -> JsonValue {
+        std::mem::replace(self.current_mut(), value)
+    }
+
+    /// Removes the node under the cursor from its parent and moves up to the
+    /// parent. The root cannot be removed, so this returns `None` there.
+    pub fn remove(&mut self) -> Option<JsonValue> {
+        let segment = self.path.pop()?;
+        match (self.current_mut(), segment) {
+            (JsonValue::Object(obj), PathSegment::Key(key)) => obj.remove(&key),
+            (JsonValue::Array(arr), PathSegment::Index(index)) => arr.remove(index),
+            _ => None,
+        }
+    }
+
+    fn down(&mut self, segment: PathSegment) -> bool {
+        if child(self.current(), &segment).is_none() {
+            return false;
+        }
+        push_segment(&mut self.path, segment);
+        true
+    }
+
+    fn sibling(&mut self, offset: isize) -> bool {
+        let Some(segment) = self.path.segments().last().cloned() else {
+            return false;
+        };
+        self.path.pop();
+        match sibling_segment(self.current(), &segment, offset) {
+            Some(target) => self.down(target),
+            None => {
+                push_segment(&mut self.path, segment);
+                false
+            }
+        }
+    }
+}
+
+impl JsonValue {
+    /// A read-only cursor starting at this value.
+    pub fn cursor(&self) -> JsonCursor<'_> {
+        JsonCursor::new(self)
+    }
+
+    /// An editing cursor starting at this value.
+    pub fn cursor_mut(&mut self) -> JsonCursorMut<'_> {
+        JsonCursorMut::new(self)
+    }
+}
+
+fn push_segment(path: &mut JsonPath, segment: PathSegment) {
+    match segment {
+        PathSegment::Key(key) => path.push_key(key),
+        PathSegment::Index(index) => path.push_index(index),
+    }
+}
+
+fn child<'v>(value: &'v JsonValue, segment: &PathSegment) -> Option<&'v JsonValue> {
+    match (value, segment) {
+        (JsonValue::Object(obj), PathSegment::Key(key)) => obj.get(key),
+        (JsonValue::Array(arr), PathSegment::Index(index)) => arr.get(*index),
+        _ => None,
+    }
+}
+
+fn child_mut<'v>(value: &'v mut JsonValue, segment: &PathSegment) -> Option<&'v mut JsonValue> {
+    match (value, segment) {
+        (JsonValue::Object(obj), PathSegment::Key(key)) => obj.get_mut(key),
+        (JsonValue::Array(arr), PathSegment::Index(index)) => arr.get_mut(*index),
+        _ => None,
+    }
+}
+
+fn resolve<'v>(root: &'v JsonValue, path: &JsonPath) -> Option<&'v JsonValue> {
+    path.segments()
+        .iter()
+        .try_fold(root, |node, segment| child(node, segment))
+}
+
+fn resolve_mut<'v>(root: &'v mut JsonValue, path: &JsonPath) -> Option<&'v mut JsonValue> {
+    path.segments()
+        .iter()
+        .try_fold(root, |node, segment| child_mut(node, segment))
+}
+
+fn first_segment(value: &JsonValue) -> Option<PathSegment> {
+    match value {
+        JsonValue::Object(obj) => obj.keys().next().map(|k| PathSegment::Key(k.to_string())),
+        JsonValue::Array(arr) if !arr.is_empty() => Some(PathSegment::Index(0)),
+        _ => None,
+    }
+}
+
+/// The segment `offset` positions away from `segment` inside `parent`.
+fn sibling_segment(
+    parent: &JsonValue,
+    segment: &PathSegment,
+    offset: isize,
+) -> Option<PathSegment> {
+    match (parent, segment) {
+        (JsonValue::Array(arr), PathSegment::Index(index)) => index
+            .checked_add_signed(offset)
+            .filter(|&i| i < arr.len())
+            .map(PathSegment::Index),
+        (JsonValue::Object(obj), PathSegment::Key(key)) => {
+            let position = obj.keys().position(|k| k == key)?;
+            let target = position.checked_add_signed(offset)?;
+            obj.keys()
+                .nth(target)
+                .map(|k| PathSegment::Key(k.to_string()))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn sample() -> JsonValue {
+        parse_json(r#"{"users": [{"name": "ann"}, {"name": "bob"}], "count": 2}"#).unwrap()
+    }
+
+    #[test]
+    fn test_down_and_up_track_path() {
+        let doc = sample();
+        let mut cursor = doc.cursor();
+        assert!(cursor.down_key("users"));
+        assert!(cursor.down_index(1));
+        assert!(cursor.down_key("name"));
+        assert_eq!(cursor.path().to_pointer(), "/users/1/name");
+        assert_eq!(cursor.current().as_str(), Some("bob"));
+        assert_eq!(cursor.depth(), 3);
+
+        assert!(cursor.up());
+        assert_eq!(cursor.path().to_pointer(), "/users/1");
+        cursor.to_root();
+        assert!(!cursor.up(), "The root has no parent");
+        assert_eq!(cursor.current(), &doc);
+    }
+
+    #[test]
+    fn test_failed_moves_stay_put() {
+        let doc = sample();
+        let mut cursor = doc.cursor();
+        assert!(!cursor.down_key("missing"));
+        assert!(!cursor.down_index(0), "An object has no indices");
+        assert!(cursor.down_key("count"));
+        assert!(!cursor.first_child());
+        assert!(!cursor.next_sibling());
+        assert_eq!(cursor.path().to_pointer(), "/count");
+    }
+
+    #[test]
+    fn test_sibling_navigation() {
+        let doc = sample();
+        let mut cursor = doc.cursor();
+        assert!(cursor.first_child());
+        assert_eq!(cursor.path().to_pointer(), "/users");
+        assert!(cursor.next_sibling());
+        assert_eq!(cursor.path().to_pointer(), "/count");
+        assert!(cursor.prev_sibling());
+
+        assert!(cursor.first_child());
+        assert!(!cursor.prev_sibling());
+        assert!(cursor.next_sibling());
+        assert_eq!(cursor.path().to_pointer(), "/users/1");
+        assert!(!cursor.next_sibling());
+    }
+
+    #[test]
+    fn test_mut_cursor_edits_in_place() {
+        let mut doc = sample();
+        let mut cursor = doc.cursor_mut();
+        assert!(cursor.down_key("users"));
+        assert!(cursor.first_child());
+        assert!(cursor.next_sibling());
+        assert!(cursor.down_key("name"));
+        let old = cursor.replace(JsonValue::String("bea".into()));
+        assert_eq!(old.as_str(), Some("bob"));
+        assert_eq!(cursor.path().to_pointer(), "/users/1/name");
+
+        cursor.to_root();
+        assert!(cursor.down_key("count"));
+        assert_eq!(cursor.remove(), Some(JsonValue::Number(2.0)));
+        assert!(cursor.path().is_root());
+        assert_eq!(cursor.remove(), None);
+
+        assert_eq!(
+            doc,
+            parse_json(r#"{"users": [{"name": "ann"}, {"name": "bea"}]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_mut_cursor_failed_sibling_keeps_position() {
+        let mut doc = parse_json("[1, 2]").unwrap();
+        let mut cursor = doc.cursor_mut();
+        assert!(cursor.down_index(1));
+        assert!(!cursor.next_sibling());
+        assert_eq!(cursor.path().to_pointer(), "/1");
+        *cursor.current_mut() = JsonValue::Null;
+        assert_eq!(doc, parse_json("[1, null]").unwrap());
+    }
+}
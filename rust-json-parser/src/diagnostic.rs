@@ -0,0 +1,235 @@
+//! Labeled-span reports for [`JsonError`], in the style of `miette` and
+//! `ariadne`.
+//!
+//! Label spans are byte ranges into the source, the unit those crates use,
+//! so a [`Diagnostic`] can be handed to either one field for field, or
+//! printed as is with [`Diagnostic::render`].
+
+use crate::error::{alternatives, locate};
+use crate::{JsonError, JsonErrorKind};
+use std::ops::Range;
+
+/// A part of the source to underline, with a note on what is wrong there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// Byte range into the source text.
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+/// A report on one error: what went wrong, where, and how to fix it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Stable identifier, from [`JsonErrorKind::code`].
+    pub code: &'static str,
+    pub message: String,
+    /// File name or other label for the source, shown before line numbers.
+    pub source_name: Option<String>,
+    pub labels: Vec<Label>,
+    /// Context such as the path of the value the error was found in.
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn with_source_name(mut self, name: impl Into<String>) -> Self {
+        self.source_name = Some(name.into());
+        self
+    }
+
+    /// Formats the report with each label under its line of `source`:
+    ///
+    /// ```text
+    /// error[unexpected_token]: Unexpected token at position 11: expected ':', found Number(1.0)
+    ///  --> config.json:2:10
+    ///   |
+    /// 2 |   "port" 1
+    ///   |          ^ expected ':'
+    ///   |
+    ///   = note: in the value at /server/port
+    /// ```
+    ///
+    /// `source` must be the text the spans point into.
+    pub fn render(&self, source: &str) -> String {
+        let mut out = format!("error[{}]: {}", self.code, self.message);
+        let lines: Vec<_> = self
+            .labels
+            .iter()
+            .map(|label| {
+                let position = source[..label.span.start.min(source.len())].chars().count();
+                (label, locate(source, position))
+            })
+            .collect();
+        let width = lines
+            .iter()
+            .map(|(_, (line, _, _))| (line + 1).to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(width);
+        let name = self.source_name.as_deref().unwrap_or("<input>");
+        for (label, (line, column, text)) in &lines {
+            let pad: String = text
+                .chars()
+                .take(*column)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let carets = "^".repeat(source[label.span.clone()].chars().count().max(1));
+            out.push_str(&format!(
+                "\n{gutter}--> {name}:{}:{}\n{gutter} |\n{:>width$} | {text}\n{gutter} | {pad}{carets} {}",
+                line + 1,
+                column + 1,
+                line + 1,
+                label.message
+            ));
+        }
+        if !self.notes.is_empty() || self.help.is_some() {
+            out.push_str(&format!("\n{gutter} |"));
+        }
+        for note in &self.notes {
+            out.push_str(&format!("\n{gutter} = note: {note}"));
+        }
+        if let Some(help) = &self.help {
+            out.push_str(&format!("\n{gutter} = help: {help}"));
+        }
+        out
+    }
+}
+
+impl JsonError {
+    /// Builds a labeled report on the error, with spans into `source`, the
+    /// text that produced it.
+    pub fn diagnostic(&self, source: &str) -> Diagnostic {
+        let labels = self
+            .byte_range(source)
+            .map(|span| {
+                vec![Label {
+                    span,
+                    message: label_message(self),
+                }]
+            })
+            .unwrap_or_default();
+        let notes = self
+            .path()
+            .map(|path| vec![format!("in the value at {path}")])
+            .unwrap_or_default();
+        Diagnostic {
+            code: self.kind().code(),
+            message: self.to_string(),
+            source_name: None,
+            labels,
+            notes,
+            help: help(self),
+        }
+    }
+}
+
+fn label_message(error: &JsonError) -> String {
+    if let Some(expected) = error.expected() {
+        let expected = format!("expected {}", alternatives(expected));
+        return match error.help() {
+            Some(help) => format!("{expected} — {help}"),
+            None => expected,
+        };
+    }
+    match error {
+        JsonError::InvalidNumber { .. } => "not a valid number".to_string(),
+        JsonError::InvalidEscape { .. } => "unknown escape".to_string(),
+        JsonError::InvalidUnicode { .. } => "bad \\u escape".to_string(),
+        JsonError::InvalidUtf8 { .. } => "not valid UTF-8".to_string(),
+        JsonError::DepthLimitExceeded { .. } => "nested too deeply".to_string(),
+        JsonError::TokenLimitExceeded { .. } => "one token too many".to_string(),
+        JsonError::StringLimitExceeded { .. } => "string too long".to_string(),
+        JsonError::TimeBudgetExceeded { .. } => "out of time here".to_string(),
+        JsonError::DisabledFeature { feature, .. } => format!("{feature} are disabled"),
+        JsonError::DuplicateKey { .. } => "key already used in this object".to_string(),
+        _ => "here".to_string(),
+    }
+}
+
+fn help(error: &JsonError) -> Option<String> {
+    let help = match error.kind() {
+        JsonErrorKind::UnexpectedEndOfInput => {
+            "the input ends before the document is complete; it may be truncated".to_string()
+        }
+        JsonErrorKind::InvalidEscape => {
+            "valid escapes are \\\" \\\\ \\/ \\b \\f \\n \\r \\t and \\uXXXX".to_string()
+        }
+        JsonErrorKind::InvalidUnicode => "\\u must be followed by four hex digits".to_string(),
+        JsonErrorKind::DepthLimitExceeded
+        | JsonErrorKind::InputTooLarge
+        | JsonErrorKind::TokenLimitExceeded
+        | JsonErrorKind::StringLimitExceeded
+        | JsonErrorKind::TimeBudgetExceeded => {
+            "raise the limit with ParserOptions::limits if the input is trusted".to_string()
+        }
+        JsonErrorKind::DisabledFeature => match error {
+            JsonError::DisabledFeature { option, .. } => {
+                format!("enable it with ParserOptions::{option}(true)")
+            }
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(help)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonParser;
+
+    fn parse_error(input: &str) -> JsonError {
+        JsonParser::new(input)
+            .and_then(|mut parser| parser.parse())
+            .unwrap_err()
+    }
+
+    #[test]
+    fn test_labels_use_byte_spans() {
+        let input = "{\"é\": tru}";
+        let diagnostic = parse_error(input).diagnostic(input);
+        assert_eq!(diagnostic.code, "unexpected_token");
+        assert_eq!(diagnostic.labels.len(), 1);
+        assert_eq!(&input[diagnostic.labels[0].span.clone()], "t");
+        assert_eq!(
+            diagnostic.labels[0].message,
+            "expected keyword — did you mean \"true\"?"
+        );
+
+        let diagnostic = JsonError::OutputLimitExceeded { limit: 1 }.diagnostic("");
+        assert!(diagnostic.labels.is_empty());
+    }
+
+    #[test]
+    fn test_render_with_notes_and_help() {
+        let input = "{\n  \"server\": {\n    \"port\" 1}}";
+        let rendered = parse_error(input)
+            .diagnostic(input)
+            .with_source_name("config.json")
+            .render(input);
+        assert_eq!(
+            rendered,
+            "error[unexpected_token]: at /server/port: Unexpected token at position 27: expected ':', found Number(1.0)\n --> config.json:3:12\n  |\n3 |     \"port\" 1}}\n  |            ^ expected ':'\n  |\n  = note: in the value at /server/port"
+        );
+
+        let input = "[1, 2,]";
+        let rendered = parse_error(input).diagnostic(input).render(input);
+        assert!(rendered.contains("--> <input>:1:6"), "{rendered}");
+        assert!(
+            rendered.ends_with("= help: enable it with ParserOptions::allow_trailing_commas(true)"),
+            "{rendered}"
+        );
+    }
+
+    #[test]
+    fn test_end_of_input_label() {
+        let input = "[1, ";
+        let diagnostic = parse_error(input).diagnostic(input);
+        assert_eq!(diagnostic.labels[0].span, 4..4);
+        assert!(
+            diagnostic
+                .render(input)
+                .contains("|     ^ expected JSON value")
+        );
+    }
+}
@@ -0,0 +1,577 @@
+//! Lossless editing of JSON text, for files that people also edit by hand.
+
+use crate::comments::scan;
+use crate::path::{parse_index, pointer_tokens};
+use crate::value::escape_json_string;
+use crate::{
+    Comments, ExpectedToken, FormatConfig, JsonError, JsonParser, JsonPath, JsonString, JsonValue,
+    ParserOptions, Result, Token, Tokenizer,
+};
+use std::fmt;
+use std::ops::Range;
+
+/// A JSON document kept as its original text.
+///
+/// Whitespace, key order, number spellings such as `1.50` and comments all
+/// survive, because edits splice new text into the regions they change and
+/// leave every other byte alone. New values are written compactly; new
+/// members and elements copy the indentation of their siblings.
+///
+/// ```
+/// use rust_json_parser::{Document, JsonValue, ParserOptions};
+///
+/// let text = "{\n  \"port\": 8080, // dev\n  \"debug\": true\n}";
+/// let mut doc = Document::parse_with(text, ParserOptions::new().allow_comments(true))?;
+/// doc.replace("/port", &JsonValue::Number(9090.0))?;
+/// doc.remove("/debug")?;
+/// assert_eq!(doc.as_str(), "{\n  \"port\": 9090 // dev\n}");
+/// # Ok::<(), rust_json_parser::JsonError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document {
+    text: String,
+    options: ParserOptions,
+    root: Node,
+}
+
+/// A value, located by byte offsets into the document text.
+#[derive(Debug, Clone)]
+struct Node {
+    span: Range<usize>,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Scalar,
+    /// The closing bracket is the last byte of the node's span.
+    Container {
+        children: Vec<Child>,
+        /// Just past a comma after the last child, if there is one.
+        trailing_comma: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Child {
+    /// For object members, the key and the span of its literal.
+    key: Option<(JsonString, Range<usize>)>,
+    /// Just past the `[`, `{` or `,` that precedes this child.
+    lead: usize,
+    value: Node,
+}
+
+impl Child {
+    fn start(&self) -> usize {
+        match &self.key {
+            Some((_, span)) => span.start,
+            None => self.value.span.start,
+        }
+    }
+}
+
+impl Document {
+    pub fn parse(text: impl Into<String>) -> Result<Self> {
+        Self::parse_with(text, ParserOptions::default())
+    }
+
+    /// Parses `text` in the dialect `options` allows, so comments and
+    /// trailing commas can be kept. Nesting is capped at depth 128 unless
+    /// `options` set a depth limit of their own.
+    pub fn parse_with(text: impl Into<String>, options: ParserOptions) -> Result<Self> {
+        let text = text.into();
+        // The node tree is built recursively, on every edit
+        let limits = options.limits.capped();
+        let options = options.limits(limits);
+        let root = Builder::build(&text, &options)?;
+        Ok(Self {
+            text,
+            options,
+            root,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    /// The document as a value tree.
+    pub fn to_value(&self) -> JsonValue {
+        JsonParser::with_options(&self.text, self.options.clone())
+            .and_then(|mut parser| parser.parse())
+            .expect("document text was validated when it was built")
+    }
+
+    /// Every comment, attached to the node that follows it, for carrying
+    /// through [`Document::to_value`] and
+    /// [`JsonValue::serialize_with_comments`]. Comments with no node after
+    /// them, such as those before a closing bracket, are left out.
+    pub fn comments(&self) -> Comments {
+        let mut comments = Comments::new();
+        let mut attach = |path: &JsonPath, gap: &str| {
+            for comment in scan(gap) {
+                comments.push(path.clone(), comment);
+            }
+        };
+        attach(&JsonPath::new(), &self.text[..self.root.span.start]);
+
+        let mut stack = vec![(JsonPath::new(), &self.root)];
+        while let Some((path, node)) = stack.pop() {
+            let Kind::Container { children, .. } = &node.kind else {
+                continue;
+            };
+            for (index, child) in children.iter().enumerate().rev() {
+                let mut child_path = path.clone();
+                match &child.key {
+                    Some((key, _)) => child_path.push_key(key.as_str()),
+                    None => child_path.push_index(index),
+                }
+                attach(&child_path, &self.text[child.lead..child.start()]);
+                stack.push((child_path, &child.value));
+            }
+        }
+        comments
+    }
+
+    /// The source text of the value at `pointer`, exactly as written.
+    pub fn raw(&self, pointer: &str) -> Option<&str> {
+        let node = self.node(&pointer_tokens(pointer)?)?;
+        Some(&self.text[node.span.clone()])
+    }
+
+    /// Overwrites the value at `pointer`, which must exist.
+    pub fn replace(&mut self, pointer: &str, value: &JsonValue) -> Result<()> {
+        let span = pointer_tokens(pointer)
+            .and_then(|tokens| self.node(&tokens))
+            .map(|node| node.span.clone())
+            .ok_or_else(|| invalid_pointer(pointer))?;
+        let text = compact(value)?;
+        self.splice(span, &text)
+    }
+
+    /// Adds a value the way JSON Patch `add` does: `/obj/key` creates or
+    /// replaces a member, `/arr/2` inserts before element 2 and `/arr/-`
+    /// appends. The root is replaced.
+    pub fn insert(&mut self, pointer: &str, value: &JsonValue) -> Result<()> {
+        let mut tokens = pointer_tokens(pointer).ok_or_else(|| invalid_pointer(pointer))?;
+        let Some(last) = tokens.pop() else {
+            return self.replace(pointer, value);
+        };
+        let parent = self.node(&tokens).ok_or_else(|| invalid_pointer(pointer))?;
+        let Kind::Container { children, .. } = &parent.kind else {
+            return Err(invalid_pointer(pointer));
+        };
+        let is_object = self.text.as_bytes()[parent.span.start] == b'{';
+        let text = compact(value)?;
+
+        if is_object {
+            if member(children, &last).is_some() {
+                return self.replace(pointer, value);
+            }
+            let separator = children
+                .last()
+                .and_then(|child| {
+                    let (_, key) = child.key.as_ref()?;
+                    let between = &self.text[key.end..child.value.span.start];
+                    between.trim().eq(":").then_some(between)
+                })
+                .unwrap_or(":");
+            let entry = format!("{}{separator}{text}", escape_json_string(&last));
+            let (at, insertion) = self.child_insertion(parent, children.len(), &entry);
+            return self.splice(at..at, &insertion);
+        }
+
+        let index = match last.as_str() {
+            "-" => children.len(),
+            token => parse_index(token)
+                .filter(|&i| i <= children.len())
+                .ok_or_else(|| invalid_pointer(pointer))?,
+        };
+        let (at, insertion) = self.child_insertion(parent, index, &text);
+        self.splice(at..at, &insertion)
+    }
+
+    /// Deletes the member or element at `pointer` along with the comma that
+    /// separated it from its neighbours.
+    pub fn remove(&mut self, pointer: &str) -> Result<()> {
+        let mut tokens = pointer_tokens(pointer).ok_or_else(|| invalid_pointer(pointer))?;
+        let last = tokens.pop().ok_or_else(|| invalid_pointer(pointer))?;
+        let parent = self.node(&tokens).ok_or_else(|| invalid_pointer(pointer))?;
+        let index = child_index(parent, &last).ok_or_else(|| invalid_pointer(pointer))?;
+        let Kind::Container { children, .. } = &parent.kind else {
+            unreachable!("child_index only finds children of containers")
+        };
+
+        if children.len() == 1 {
+            // Leave an empty container rather than a blank line inside one
+            return self.splice(parent.span.start + 1..parent.span.end - 1, "");
+        }
+        if index + 1 < children.len() {
+            // The comments in the gap before a child are about it, so this
+            // child's go with it and the next one's stay, after the
+            // whitespace that led this child
+            let after_space = |child: &Child| {
+                let gap = &self.text[child.lead..child.start()];
+                child.lead + gap.len() - gap.trim_start().len()
+            };
+            let span = after_space(&children[index])..after_space(&children[index + 1]);
+            return self.splice(span, "");
+        }
+        // The last child: drop the comma before it and its own line, but keep
+        // a comment that ends the previous line
+        let child = &children[index];
+        let gap = &self.text[child.lead..child.start()];
+        let kept = match gap.rfind('\n') {
+            Some(newline) if gap[newline..].trim().is_empty() => gap[..newline].to_string(),
+            _ => String::new(),
+        };
+        self.splice(child.lead - 1..child.value.span.end, &kept)
+    }
+
+    fn node(&self, tokens: &[String]) -> Option<&Node> {
+        tokens.iter().try_fold(&self.root, |node, token| {
+            let index = child_index(node, token)?;
+            match &node.kind {
+                Kind::Container { children, .. } => Some(&children[index].value),
+                Kind::Scalar => None,
+            }
+        })
+    }
+
+    /// Where and what to insert so that `text` becomes the `index`-th child
+    /// of `parent`, copying the whitespace in front of an existing sibling.
+    fn child_insertion(&self, parent: &Node, index: usize, text: &str) -> (usize, String) {
+        let Kind::Container {
+            children,
+            trailing_comma,
+        } = &parent.kind
+        else {
+            unreachable!("only containers get children")
+        };
+        // Prefer a gap that follows a comma over the one after the bracket
+        let Some(sibling) = children.get(index.max(1)).or(children.last()) else {
+            return (parent.span.start + 1, text.to_string());
+        };
+        let gap = indentation(&self.text[sibling.lead..sibling.start()]);
+        match children.get(index) {
+            Some(next) => (next.start(), format!("{text},{gap}")),
+            None => {
+                let last = children.last().expect("empty containers returned above");
+                match trailing_comma {
+                    Some(comma) => (*comma, format!("{gap}{text},")),
+                    None => (last.value.span.end, format!(",{gap}{text}")),
+                }
+            }
+        }
+    }
+
+    /// Replaces `span` with `text`, keeping the document unchanged if the
+    /// result would not parse.
+    fn splice(&mut self, span: Range<usize>, text: &str) -> Result<()> {
+        let mut edited = self.text.clone();
+        edited.replace_range(span, text);
+        *self = Self::parse_with(edited, self.options.clone())?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// The last member named `key`, which is the one parsing keeps.
+fn member(children: &[Child], key: &str) -> Option<usize> {
+    children
+        .iter()
+        .rposition(|child| matches!(&child.key, Some((k, _)) if k == key))
+}
+
+fn child_index(node: &Node, token: &str) -> Option<usize> {
+    let Kind::Container { children, .. } = &node.kind else {
+        return None;
+    };
+    match children.first() {
+        Some(Child { key: Some(_), .. }) => member(children, token),
+        Some(_) => parse_index(token).filter(|&i| i < children.len()),
+        None => None,
+    }
+}
+
+/// The line break and indentation at the end of `gap`, or the whole gap
+/// when it is all on one line.
+fn indentation(gap: &str) -> &str {
+    match gap.rfind('\n') {
+        Some(newline) if gap[newline..].trim().is_empty() => &gap[newline..],
+        Some(_) => "\n",
+        None if gap.trim().is_empty() => gap,
+        None => " ",
+    }
+}
+
+fn compact(value: &JsonValue) -> Result<String> {
+    value.try_serialize_with(&FormatConfig::new())
+}
+
+fn invalid_pointer(pointer: &str) -> JsonError {
+    JsonError::InvalidPointer {
+        pointer: pointer.to_string(),
+    }
+}
+
+/// Builds the node tree from the token stream of text that already parsed.
+struct Builder {
+    tokens: Vec<(Token, Range<usize>)>,
+    /// Byte offset of each character, plus the end of the text.
+    bytes: Vec<usize>,
+    next: usize,
+    options: ParserOptions,
+}
+
+impl Builder {
+    fn build(text: &str, options: &ParserOptions) -> Result<Node> {
+        let mut parser = JsonParser::with_options(text, options.clone())?;
+        parser.parse()?;
+        let tokens = Tokenizer::with_options(text, options.clone()).tokenize_spanned()?;
+        let mut builder = Builder {
+            tokens,
+            bytes: text
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain([text.len()])
+                .collect(),
+            next: 0,
+            options: options.clone(),
+        };
+        let root = builder.node();
+        if let Some((token, span)) = builder.tokens.get(builder.next) {
+            return Err(JsonError::UnexpectedToken {
+                expected: vec![ExpectedToken::EndOfInput],
+                found: format!("{token:?}"),
+                position: span.start,
+                path: JsonPath::new(),
+            }
+            .redacted(options.redaction));
+        }
+        Ok(root)
+    }
+
+    fn advance(&mut self) -> (Token, Range<usize>) {
+        let (token, span) = self.tokens[self.next].clone();
+        self.next += 1;
+        (token, self.bytes[span.start]..self.bytes[span.end])
+    }
+
+    fn node(&mut self) -> Node {
+        let (token, span) = self.advance();
+        let close = match token {
+            Token::LeftBracket => Token::RightBracket,
+            Token::LeftBrace => Token::RightBrace,
+            _ => {
+                return Node {
+                    span,
+                    kind: Kind::Scalar,
+                };
+            }
+        };
+        let start = span.start;
+        let mut lead = span.end;
+        let mut children = Vec::new();
+        let mut trailing_comma = None;
+        loop {
+            let (token, span) = self.advance();
+            if token == close {
+                return Node {
+                    span: start..span.end,
+                    kind: Kind::Container {
+                        children,
+                        trailing_comma,
+                    },
+                };
+            }
+            if token == Token::Comma {
+                lead = span.end;
+                trailing_comma = Some(span.end);
+                continue;
+            }
+            trailing_comma = None;
+            let key = match token {
+                Token::String(key) if close == Token::RightBrace => {
+                    self.advance(); // Consume ':'
+                    Some((self.options.fold_key(key), span))
+                }
+                _ => {
+                    // Not a key: step back so the value is read whole
+                    self.next -= 1;
+                    None
+                }
+            };
+            let value = self.node();
+            children.push(Child { key, lead, value });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "{\n  // Server settings\n  \"port\": 8080,\n  \"ratio\": 1.50,\n  \"tags\": [\"a\", \"b\"],\n  \"empty\": {}\n}\n";
+
+    fn jsonc(text: &str) -> Document {
+        let options = ParserOptions::new()
+            .allow_comments(true)
+            .allow_trailing_commas(true);
+        Document::parse_with(text, options).unwrap()
+    }
+
+    #[test]
+    fn test_round_trips_unchanged() {
+        let doc = jsonc(CONFIG);
+        assert_eq!(doc.as_str(), CONFIG);
+        assert_eq!(doc.raw("/ratio"), Some("1.50"));
+        assert_eq!(doc.raw("/tags"), Some("[\"a\", \"b\"]"));
+        assert_eq!(doc.raw("/tags/2"), None);
+        assert_eq!(
+            doc.to_value().pointer("/port"),
+            Some(&JsonValue::Number(8080.0))
+        );
+    }
+
+    #[test]
+    fn test_replace_touches_only_the_value() {
+        let mut doc = jsonc(CONFIG);
+        doc.replace("/port", &JsonValue::Number(9090.0)).unwrap();
+        doc.replace("/tags/1", &JsonValue::String("c\"d".into()))
+            .unwrap();
+        assert_eq!(
+            doc.as_str(),
+            CONFIG
+                .replace("8080", "9090")
+                .replace("\"b\"]", "\"c\\\"d\"]")
+        );
+    }
+
+    #[test]
+    fn test_insert_copies_sibling_layout() {
+        let mut doc = jsonc(CONFIG);
+        doc.insert("/host", &JsonValue::String("x".into())).unwrap();
+        doc.insert("/tags/-", &JsonValue::Null).unwrap();
+        doc.insert("/tags/0", &JsonValue::Boolean(true)).unwrap();
+        doc.insert("/empty/k", &JsonValue::Number(1.0)).unwrap();
+        assert_eq!(
+            doc.as_str(),
+            "{\n  // Server settings\n  \"port\": 8080,\n  \"ratio\": 1.50,\n  \"tags\": [true, \"a\", \"b\", null],\n  \"empty\": {\"k\":1},\n  \"host\": \"x\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_after_trailing_comma() {
+        let mut doc = jsonc("[\n  1,\n  2,\n]");
+        doc.insert("/-", &JsonValue::Number(3.0)).unwrap();
+        assert_eq!(doc.as_str(), "[\n  1,\n  2,\n  3,\n]");
+    }
+
+    #[test]
+    fn test_remove_takes_the_separator() {
+        let mut doc = jsonc(CONFIG);
+        doc.remove("/ratio").unwrap();
+        doc.remove("/empty").unwrap();
+        doc.remove("/tags/0").unwrap();
+        assert_eq!(
+            doc.as_str(),
+            "{\n  // Server settings\n  \"port\": 8080,\n  \"tags\": [\"b\"]\n}\n"
+        );
+        doc.remove("/tags/0").unwrap();
+        assert_eq!(doc.raw("/tags"), Some("[]"));
+    }
+
+    #[test]
+    fn test_remove_takes_the_comments_before_a_member() {
+        let mut doc = jsonc("{ // about a\n \"a\": 1,\n // about b\n \"b\": 2 }");
+        doc.remove("/a").unwrap();
+        assert_eq!(doc.as_str(), "{ // about b\n \"b\": 2 }");
+
+        let mut doc = jsonc("[\n  // one\n  1,\n  // two\n  2,\n  // three\n  3\n]");
+        doc.remove("/1").unwrap();
+        assert_eq!(doc.as_str(), "[\n  // one\n  1,\n  // three\n  3\n]");
+    }
+
+    #[test]
+    fn test_bad_pointers_leave_document_unchanged() {
+        let mut doc = jsonc(CONFIG);
+        let cases = vec![
+            doc.clone().replace("/missing", &JsonValue::Null),
+            doc.clone().insert("/port/x", &JsonValue::Null),
+            doc.clone().insert("/tags/5", &JsonValue::Null),
+            doc.clone().remove(""),
+            doc.clone().remove("no-slash"),
+        ];
+        for result in cases {
+            assert!(matches!(result, Err(JsonError::InvalidPointer { .. })));
+        }
+        assert!(matches!(
+            doc.replace("/port", &JsonValue::Number(f64::NAN)),
+            Err(JsonError::NonFiniteNumber { .. })
+        ));
+        assert_eq!(doc.as_str(), CONFIG);
+    }
+
+    #[test]
+    fn test_comments_survive_the_value_tree() {
+        let doc =
+            jsonc("// top\n{\n  /* a */ \"a\": 1, // b\n  \"b\": [\n    // x\n    true\n  ]\n}");
+        let comments = doc.comments();
+        assert_eq!(comments.get(&JsonPath::new()), ["// top"]);
+        assert_eq!(comments.len(), 4);
+
+        let mut value = doc.to_value();
+        *value.pointer_mut("/a").unwrap() = JsonValue::Number(2.0);
+        let pretty = value.serialize_with_comments(&FormatConfig::pretty(2), &comments);
+        assert_eq!(
+            pretty,
+            "// top\n{\n  /* a */\n  \"a\": 2,\n  // b\n  \"b\": [\n    // x\n    true\n  ]\n}"
+        );
+        let compact = value.serialize_with_comments(&FormatConfig::new(), &comments);
+        assert_eq!(
+            compact,
+            "// top\n{/* a */ \"a\":2,// b\n\"b\":[// x\ntrue]}"
+        );
+        for text in [pretty, compact] {
+            assert_eq!(jsonc(&text).to_value(), value);
+            assert_eq!(jsonc(&text).comments(), comments);
+        }
+    }
+
+    #[test]
+    fn test_deep_text_is_an_error() {
+        let deep = "[".repeat(200_000) + &"]".repeat(200_000);
+        assert_eq!(
+            Document::parse(deep.as_str())
+                .map(drop)
+                .map_err(|e| e.kind()),
+            Err(crate::JsonErrorKind::DepthLimitExceeded)
+        );
+        assert_eq!(
+            crate::set_raw(&deep, "/0", &JsonValue::Null).map_err(|e| e.kind()),
+            Err(crate::JsonErrorKind::DepthLimitExceeded)
+        );
+        let nested = "[".repeat(200) + &"]".repeat(200);
+        let options = ParserOptions::new().limits(crate::Limits::new().max_depth(256));
+        assert!(Document::parse_with(nested, options).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_invalid_text() {
+        assert!(Document::parse("{\"a\": }").is_err());
+        assert!(matches!(
+            Document::parse("1 2"),
+            Err(JsonError::UnexpectedToken { position: 2, .. })
+        ));
+    }
+}
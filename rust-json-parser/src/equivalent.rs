@@ -0,0 +1,247 @@
+use crate::{JsonObject, JsonValue};
+
+/// Relaxations [`JsonValue::equivalent`] applies on top of `==`.
+///
+/// The defaults give the same answer as `==`: arrays compare in order, object
+/// keys in any order and numbers exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EqOptions {
+    unordered_arrays: bool,
+    ordered_keys: bool,
+    abs_tolerance: f64,
+    rel_tolerance: f64,
+}
+
+impl EqOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares arrays as multisets: the same elements, each the same number
+    /// of times, in any order.
+    pub fn unordered_arrays(mut self, enabled: bool) -> Self {
+        self.unordered_arrays = enabled;
+        self
+    }
+
+    /// Also requires object members to appear in the same order.
+    pub fn ordered_keys(mut self, enabled: bool) -> Self {
+        self.ordered_keys = enabled;
+        self
+    }
+
+    /// Treats numbers as equal when they differ by at most `tolerance`.
+    pub fn abs_tolerance(mut self, tolerance: f64) -> Self {
+        self.abs_tolerance = tolerance;
+        self
+    }
+
+    /// Treats numbers as equal when they differ by at most `tolerance` times
+    /// the larger magnitude, so `1e-9` accepts `0.1 + 0.2` for `0.3`.
+    ///
+    /// Combines with [`EqOptions::abs_tolerance`]: passing either check is
+    /// enough, which keeps comparisons near zero workable.
+    pub fn rel_tolerance(mut self, tolerance: f64) -> Self {
+        self.rel_tolerance = tolerance;
+        self
+    }
+
+    fn numbers_equal(&self, a: f64, b: f64) -> bool {
+        if a == b {
+            return true;
+        }
+        let diff = (a - b).abs();
+        diff <= self.abs_tolerance || diff <= self.rel_tolerance * a.abs().max(b.abs())
+    }
+}
+
+impl JsonValue {
+    /// Structural equality with the relaxations selected in `options`.
+    pub fn equivalent(&self, other: &JsonValue, options: &EqOptions) -> bool {
+        match (self, other) {
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                if a.len() != b.len() {
+                    false
+                } else if options.unordered_arrays {
+                    same_multiset(a, b, options)
+                } else {
+                    a.iter()
+                        .zip(b.iter())
+                        .all(|(x, y)| x.equivalent(y, options))
+                }
+            }
+            (JsonValue::Object(a), JsonValue::Object(b)) => objects_equivalent(a, b, options),
+            (JsonValue::Number(a), JsonValue::Number(b)) => options.numbers_equal(*a, *b),
+            _ => self == other,
+        }
+    }
+}
+
+fn objects_equivalent(a: &JsonObject, b: &JsonObject, options: &EqOptions) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    if options.ordered_keys {
+        return a
+            .iter()
+            .zip(b.iter())
+            .all(|((ka, va), (kb, vb))| ka == kb && va.equivalent(vb, options));
+    }
+    a.iter()
+        .all(|(key, va)| b.get(key).is_some_and(|vb| va.equivalent(vb, options)))
+}
+
+/// Pairs every element of `a` with a distinct equivalent element of `b`.
+///
+/// With a tolerance, equivalence is not transitive (`1.0 ~ 1.5 ~ 2.0` but not
+/// `1.0 ~ 2.0`), so taking the first unused match can block a pairing that
+/// exists. This searches augmenting paths instead, as in bipartite matching.
+fn same_multiset(a: &[JsonValue], b: &[JsonValue], options: &EqOptions) -> bool {
+    let edges: Vec<Vec<usize>> = a
+        .iter()
+        .map(|x| {
+            (0..b.len())
+                .filter(|&j| x.equivalent(&b[j], options))
+                .collect()
+        })
+        .collect();
+    let mut owner: Vec<Option<usize>> = vec![None; b.len()];
+    (0..a.len()).all(|i| {
+        let mut seen = vec![false; b.len()];
+        augment(i, &edges, &mut owner, &mut seen)
+    })
+}
+
+fn augment(i: usize, edges: &[Vec<usize>], owner: &mut [Option<usize>], seen: &mut [bool]) -> bool {
+    for &j in &edges[i] {
+        if seen[j] {
+            continue;
+        }
+        seen[j] = true;
+        let free = match owner[j] {
+            None => true,
+            Some(other) => augment(other, edges, owner, seen),
+        };
+        if free {
+            owner[j] = Some(i);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn equivalent(a: &str, b: &str, options: EqOptions) -> bool {
+        parse_json(a)
+            .unwrap()
+            .equivalent(&parse_json(b).unwrap(), &options)
+    }
+
+    #[test]
+    fn test_defaults_match_partial_eq() {
+        let cases = vec![
+            (r#"{"a": 1, "b": 2}"#, r#"{"b": 2, "a": 1}"#),
+            ("[1, 2]", "[2, 1]"),
+            ("[1, [2]]", "[1, [2]]"),
+            ("1", "\"1\""),
+        ];
+        for (a, b) in cases {
+            assert_eq!(
+                equivalent(a, b, EqOptions::new()),
+                parse_json(a).unwrap() == parse_json(b).unwrap(),
+                "{a} vs {b}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unordered_arrays() {
+        let options = EqOptions::new().unordered_arrays(true);
+        let cases = vec![
+            ("[1, 2, 3]", "[3, 1, 2]", true),
+            (r#"[{"id": [1, 2]}, 0]"#, r#"[0, {"id": [2, 1]}]"#, true),
+            ("[1, 1, 2]", "[1, 2, 2]", false),
+            ("[1, 2]", "[1, 2, 2]", false),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(equivalent(a, b, options), expected, "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn test_ordered_keys() {
+        let options = EqOptions::new().ordered_keys(true);
+        assert!(equivalent(
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"a": 1, "b": 2}"#,
+            options
+        ));
+        assert!(!equivalent(
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"b": 2, "a": 1}"#,
+            options
+        ));
+        assert!(!equivalent(
+            r#"{"x": {"a": 1, "b": 2}}"#,
+            r#"{"x": {"b": 2, "a": 1}}"#,
+            options
+        ));
+    }
+
+    #[test]
+    fn test_numeric_tolerance() {
+        let cases = vec![
+            (EqOptions::new(), "[0.30000000000000004]", "[0.3]", false),
+            (
+                EqOptions::new().rel_tolerance(1e-9),
+                "[0.30000000000000004]",
+                "[0.3]",
+                true,
+            ),
+            (
+                EqOptions::new().rel_tolerance(1e-9),
+                "[1e-20]",
+                "[0]",
+                false,
+            ),
+            (
+                EqOptions::new().abs_tolerance(1e-12),
+                "[1e-20]",
+                "[0]",
+                true,
+            ),
+            (
+                EqOptions::new().abs_tolerance(0.01),
+                r#"{"x": 1.005}"#,
+                r#"{"x": 1}"#,
+                true,
+            ),
+            (
+                EqOptions::new().abs_tolerance(0.01),
+                r#"{"x": 1.02}"#,
+                r#"{"x": 1}"#,
+                false,
+            ),
+        ];
+        for (options, a, b, expected) in cases {
+            assert_eq!(
+                equivalent(a, b, options),
+                expected,
+                "{a} vs {b} with {options:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tolerant_multiset_finds_a_pairing() {
+        // Greedy pairing would give 1.0 to 1.4 and leave nothing close to 0.9.
+        let options = EqOptions::new().unordered_arrays(true).abs_tolerance(0.5);
+        assert!(equivalent("[1.4, 0.9]", "[1.0, 1.9]", options));
+        assert!(!equivalent("[1.0, 1.0]", "[1.0, 2.0]", options));
+    }
+}
@@ -1,71 +1,663 @@
+use crate::tokenizer::suggest_keyword;
+use crate::{JsonPath, PathSegment, Redaction};
 use std::fmt;
+use std::ops::Range;
+use std::time::Duration;
+/// An error from parsing, serializing or editing JSON.
+///
+/// Variants with a `position` also carry the `path` of the array element
+/// or object member the parser was reading when it failed, from the root
+/// down. It is the root for an error at the top level and for errors that
+/// did not come from descending into a document.
 #[derive(Debug, Clone, PartialEq)]
 pub enum JsonError {
     UnexpectedToken {
-        expected: String,
+        /// Every token the parser would have accepted instead.
+        expected: Vec<ExpectedToken>,
         found: String,
         position: usize,
+        path: JsonPath,
     },
     UnexpectedEndOfInput {
-        expected: String,
+        expected: Vec<ExpectedToken>,
         position: usize,
+        path: JsonPath,
     },
     InvalidNumber {
         value: String,
         position: usize,
+        path: JsonPath,
     },
     InvalidEscape {
         character: char,
         position: usize,
+        path: JsonPath,
     },
     InvalidUnicode {
         sequence: String,
         position: usize,
+        path: JsonPath,
     },
+    /// Bytes that are not valid UTF-8, from
+    /// [`JsonParser::from_slice`](crate::JsonParser::from_slice);
+    /// `position` counts the characters before them.
+    InvalidUtf8 {
+        position: usize,
+        path: JsonPath,
+    },
+    OutputLimitExceeded {
+        limit: usize,
+    },
+    InputTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    DepthLimitExceeded {
+        limit: usize,
+        position: usize,
+        path: JsonPath,
+    },
+    /// The input holds more tokens than [`Limits::max_tokens`](crate::Limits::max_tokens)
+    /// allows; `position` is where the first token past the limit starts.
+    TokenLimitExceeded {
+        limit: usize,
+        position: usize,
+        path: JsonPath,
+    },
+    /// A string longer than [`Limits::max_string_chars`](crate::Limits::max_string_chars).
+    StringLimitExceeded {
+        limit: usize,
+        position: usize,
+        path: JsonPath,
+    },
+    /// Parsing ran past [`Limits::max_duration`](crate::Limits::max_duration);
+    /// `position` is where it stopped.
+    TimeBudgetExceeded {
+        limit: Duration,
+        position: usize,
+        path: JsonPath,
+    },
+    /// The input uses a syntax extension that is switched off.
+    DisabledFeature {
+        feature: String,
+        option: String,
+        position: usize,
+        path: JsonPath,
+    },
+    /// A NaN or infinite number, which JSON has no syntax for, at the given
+    /// JSON Pointer.
+    NonFiniteNumber {
+        pointer: String,
+    },
+    /// A JSON Pointer that is malformed or does not name a location that
+    /// can be edited.
+    InvalidPointer {
+        pointer: String,
+    },
+    /// An object repeats a key under [`DuplicateKeys::Error`](crate::DuplicateKeys::Error).
+    DuplicateKey {
+        key: String,
+        position: usize,
+        path: JsonPath,
+    },
+}
+/// The category of a [`JsonError`], for branching on without matching the
+/// fields of each variant.
+///
+/// The strings from [`JsonErrorKind::code`] are stable across versions, so
+/// they can be mapped to API error codes or stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum JsonErrorKind {
+    UnexpectedToken,
+    UnexpectedEndOfInput,
+    InvalidNumber,
+    InvalidEscape,
+    InvalidUnicode,
+    InvalidUtf8,
+    OutputLimitExceeded,
+    InputTooLarge,
+    DepthLimitExceeded,
+    TokenLimitExceeded,
+    StringLimitExceeded,
+    TimeBudgetExceeded,
+    DisabledFeature,
+    NonFiniteNumber,
+    InvalidPointer,
+    DuplicateKey,
+}
+
+impl JsonErrorKind {
+    /// A snake_case identifier for the kind, such as `"unexpected_token"`.
+    pub fn code(self) -> &'static str {
+        match self {
+            JsonErrorKind::UnexpectedToken => "unexpected_token",
+            JsonErrorKind::UnexpectedEndOfInput => "unexpected_end_of_input",
+            JsonErrorKind::InvalidNumber => "invalid_number",
+            JsonErrorKind::InvalidEscape => "invalid_escape",
+            JsonErrorKind::InvalidUnicode => "invalid_unicode",
+            JsonErrorKind::InvalidUtf8 => "invalid_utf8",
+            JsonErrorKind::OutputLimitExceeded => "output_limit_exceeded",
+            JsonErrorKind::InputTooLarge => "input_too_large",
+            JsonErrorKind::DepthLimitExceeded => "depth_limit_exceeded",
+            JsonErrorKind::TokenLimitExceeded => "token_limit_exceeded",
+            JsonErrorKind::StringLimitExceeded => "string_limit_exceeded",
+            JsonErrorKind::TimeBudgetExceeded => "time_budget_exceeded",
+            JsonErrorKind::DisabledFeature => "disabled_feature",
+            JsonErrorKind::NonFiniteNumber => "non_finite_number",
+            JsonErrorKind::InvalidPointer => "invalid_pointer",
+            JsonErrorKind::DuplicateKey => "duplicate_key",
+        }
+    }
+}
+
+impl fmt::Display for JsonErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
 }
+
+/// One thing the parser would have accepted where it found an unexpected
+/// token, for offering completions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExpectedToken {
+    Comma,
+    Colon,
+    RightBracket,
+    RightBrace,
+    /// Any JSON value.
+    Value,
+    String,
+    /// A string used as an object key.
+    Key,
+    /// `true`, `false` or `null`.
+    Keyword,
+    /// A digit, to complete a number such as `-` or `1e`, or the hex
+    /// digits of a `\u` escape.
+    Digit,
+    EndOfInput,
+    /// The quote that closes a string.
+    Quote(char),
+    /// The `*/` that closes a block comment.
+    CommentEnd,
+    /// A character after `\` in a string.
+    EscapeCharacter,
+    /// A control character written as an escape, under
+    /// [`ParserOptions::strict_strings`](crate::ParserOptions::strict_strings).
+    EscapedControlCharacter,
+    /// Whatever completes the document, from
+    /// [`transcode`](crate::transcode).
+    Continuation,
+}
+
+impl fmt::Display for ExpectedToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpectedToken::Comma => f.write_str("','"),
+            ExpectedToken::Colon => f.write_str("':'"),
+            ExpectedToken::RightBracket => f.write_str("']'"),
+            ExpectedToken::RightBrace => f.write_str("'}'"),
+            ExpectedToken::Value => f.write_str("JSON value"),
+            ExpectedToken::String => f.write_str("string"),
+            ExpectedToken::Key => f.write_str("string key"),
+            ExpectedToken::Keyword => f.write_str("keyword"),
+            ExpectedToken::Digit => f.write_str("digit"),
+            ExpectedToken::EndOfInput => f.write_str("end of input"),
+            ExpectedToken::Quote(quote) => write!(f, "'{quote}'"),
+            ExpectedToken::CommentEnd => f.write_str("'*/'"),
+            ExpectedToken::EscapeCharacter => f.write_str("escape character"),
+            ExpectedToken::EscapedControlCharacter => f.write_str("escaped control character"),
+            ExpectedToken::Continuation => f.write_str("the rest of the document"),
+        }
+    }
+}
+
+/// The alternatives as prose: `',' or ']'`, `a, b or c`.
+pub(crate) fn alternatives(expected: &[ExpectedToken]) -> String {
+    let mut out = String::new();
+    for (i, token) in expected.iter().enumerate() {
+        if i > 0 {
+            out.push_str(if i + 1 == expected.len() {
+                " or "
+            } else {
+                ", "
+            });
+        }
+        out.push_str(&token.to_string());
+    }
+    out
+}
+
 impl fmt::Display for JsonError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = self.path() {
+            write!(f, "at {path}: ")?;
+        }
         match self {
             JsonError::UnexpectedToken {
                 expected,
                 found,
                 position,
+                ..
             } => {
                 write!(
                     f,
-                    "Unexpected token at position {position}: expected {expected}, found {found}"
+                    "Unexpected token at position {position}: expected {}, found {found}",
+                    alternatives(expected)
                 )
             }
-            JsonError::UnexpectedEndOfInput { expected, position } => {
+            JsonError::UnexpectedEndOfInput {
+                expected, position, ..
+            } => {
                 write!(
                     f,
-                    "Unexpected end of input at position {position}: expected {expected}"
+                    "Unexpected end of input at position {position}: expected {}",
+                    alternatives(expected)
                 )
             }
-            JsonError::InvalidNumber { value, position } => {
+            JsonError::InvalidNumber {
+                value, position, ..
+            } => {
                 write!(f, "Invalid number at position {position}: value {value}")
             }
             JsonError::InvalidEscape {
                 character,
                 position,
+                ..
             } => {
                 write!(
                     f,
                     "Invalid escape sequence '{character}' at position {position}"
                 )
             }
-            JsonError::InvalidUnicode { sequence, position } => {
+            JsonError::InvalidUnicode {
+                sequence, position, ..
+            } => {
                 write!(
                     f,
                     "Invalid Unicode escape '\\u{sequence}' at position {position}"
                 )
             }
+            JsonError::InvalidUtf8 { position, .. } => {
+                write!(f, "Invalid UTF-8 at position {position}")
+            }
+            JsonError::OutputLimitExceeded { limit } => {
+                write!(f, "Serialized output exceeds the limit of {limit} bytes")
+            }
+            JsonError::InputTooLarge { size, limit } => {
+                write!(
+                    f,
+                    "Input of {size} bytes exceeds the limit of {limit} bytes"
+                )
+            }
+            JsonError::DepthLimitExceeded {
+                limit, position, ..
+            } => {
+                write!(
+                    f,
+                    "Nesting deeper than {limit} levels at position {position}"
+                )
+            }
+            JsonError::TokenLimitExceeded {
+                limit, position, ..
+            } => {
+                write!(f, "More than {limit} tokens at position {position}")
+            }
+            JsonError::StringLimitExceeded {
+                limit, position, ..
+            } => {
+                write!(
+                    f,
+                    "String longer than {limit} characters at position {position}"
+                )
+            }
+            JsonError::TimeBudgetExceeded {
+                limit, position, ..
+            } => {
+                write!(
+                    f,
+                    "Parsing took longer than {limit:?}; stopped at position {position}"
+                )
+            }
+            JsonError::DisabledFeature {
+                feature,
+                option,
+                position,
+                ..
+            } => {
+                write!(
+                    f,
+                    "{feature} are not allowed (position {position}); enable ParserOptions::{option}"
+                )
+            }
+            JsonError::NonFiniteNumber { pointer } => {
+                write!(
+                    f,
+                    "Non-finite number at '{pointer}' cannot be written as JSON"
+                )
+            }
+            JsonError::InvalidPointer { pointer } => {
+                write!(f, "JSON Pointer '{pointer}' does not resolve")
+            }
+            JsonError::DuplicateKey { key, position, .. } => {
+                write!(f, "Duplicate key \"{key}\" at position {position}")
+            }
+        }?;
+        if let Some(help) = self.help() {
+            write!(f, " — {help}")?;
         }
+        Ok(())
+    }
+}
+
+impl JsonError {
+    /// Formats the error with the line of `input` it occurred on and a caret
+    /// under the offending column, in the style of compiler diagnostics:
+    ///
+    /// ```text
+    /// error: at /a: Unexpected token at position 9: expected keyword, found tru — did you mean "true"?
+    ///  --> line 2, column 8
+    ///   |
+    /// 2 |   "a": tru
+    ///   |        ^
+    /// ```
+    ///
+    /// `input` must be the text that produced the error. Errors without a
+    /// position render as the message alone.
+    pub fn render(&self, input: &str) -> String {
+        let Some(position) = self.position() else {
+            return format!("error: {self}");
+        };
+        let (line, column, text) = locate(input, position);
+        let number = (line + 1).to_string();
+        let gutter = " ".repeat(number.len());
+        // Keep tabs so the caret lines up however wide they display
+        let pad: String = text
+            .chars()
+            .take(column)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        format!(
+            "error: {self}\n{gutter}--> line {number}, column {}\n{gutter} |\n{number} | {text}\n{gutter} | {pad}^",
+            column + 1
+        )
+    }
+
+    /// The category of the error.
+    pub fn kind(&self) -> JsonErrorKind {
+        match self {
+            JsonError::UnexpectedToken { .. } => JsonErrorKind::UnexpectedToken,
+            JsonError::UnexpectedEndOfInput { .. } => JsonErrorKind::UnexpectedEndOfInput,
+            JsonError::InvalidNumber { .. } => JsonErrorKind::InvalidNumber,
+            JsonError::InvalidEscape { .. } => JsonErrorKind::InvalidEscape,
+            JsonError::InvalidUnicode { .. } => JsonErrorKind::InvalidUnicode,
+            JsonError::InvalidUtf8 { .. } => JsonErrorKind::InvalidUtf8,
+            JsonError::OutputLimitExceeded { .. } => JsonErrorKind::OutputLimitExceeded,
+            JsonError::InputTooLarge { .. } => JsonErrorKind::InputTooLarge,
+            JsonError::DepthLimitExceeded { .. } => JsonErrorKind::DepthLimitExceeded,
+            JsonError::TokenLimitExceeded { .. } => JsonErrorKind::TokenLimitExceeded,
+            JsonError::StringLimitExceeded { .. } => JsonErrorKind::StringLimitExceeded,
+            JsonError::TimeBudgetExceeded { .. } => JsonErrorKind::TimeBudgetExceeded,
+            JsonError::DisabledFeature { .. } => JsonErrorKind::DisabledFeature,
+            JsonError::NonFiniteNumber { .. } => JsonErrorKind::NonFiniteNumber,
+            JsonError::InvalidPointer { .. } => JsonErrorKind::InvalidPointer,
+            JsonError::DuplicateKey { .. } => JsonErrorKind::DuplicateKey,
+        }
+    }
+
+    /// What the parser was looking for, for unexpected tokens and
+    /// unexpected ends of input.
+    pub fn expected(&self) -> Option<&[ExpectedToken]> {
+        match self {
+            JsonError::UnexpectedToken { expected, .. }
+            | JsonError::UnexpectedEndOfInput { expected, .. } => Some(expected),
+            _ => None,
+        }
+    }
+
+    /// The input ended before the document was complete, so the same
+    /// bytes followed by more input may still parse. Streaming readers can
+    /// wait for more data and retry.
+    pub fn is_eof(&self) -> bool {
+        self.kind() == JsonErrorKind::UnexpectedEndOfInput
+    }
+
+    /// The input is malformed, and no amount of further input will fix it.
+    pub fn is_syntax(&self) -> bool {
+        matches!(
+            self.kind(),
+            JsonErrorKind::UnexpectedToken
+                | JsonErrorKind::InvalidNumber
+                | JsonErrorKind::InvalidEscape
+                | JsonErrorKind::InvalidUnicode
+                | JsonErrorKind::InvalidUtf8
+                | JsonErrorKind::DisabledFeature
+                | JsonErrorKind::DuplicateKey
+        )
+    }
+
+    /// A configured size or depth limit was exceeded; the input may be
+    /// valid JSON.
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self.kind(),
+            JsonErrorKind::OutputLimitExceeded
+                | JsonErrorKind::InputTooLarge
+                | JsonErrorKind::DepthLimitExceeded
+                | JsonErrorKind::TokenLimitExceeded
+                | JsonErrorKind::StringLimitExceeded
+                | JsonErrorKind::TimeBudgetExceeded
+        )
+    }
+
+    /// The bytes of `input` the error points at: the offending character,
+    /// or an empty range at the end for a truncated input. `input` must be
+    /// the text that produced the error.
+    pub fn byte_range(&self, input: &str) -> Option<Range<usize>> {
+        let mut chars = input.char_indices().skip(self.position()?);
+        let start = chars.next().map_or(input.len(), |(i, _)| i);
+        let end = chars.next().map_or(input.len(), |(i, _)| i);
+        Some(start..end)
+    }
+
+    /// The character offset the error points at, if it has one.
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            JsonError::UnexpectedToken { position, .. }
+            | JsonError::UnexpectedEndOfInput { position, .. }
+            | JsonError::InvalidNumber { position, .. }
+            | JsonError::InvalidEscape { position, .. }
+            | JsonError::InvalidUnicode { position, .. }
+            | JsonError::InvalidUtf8 { position, .. }
+            | JsonError::DepthLimitExceeded { position, .. }
+            | JsonError::TokenLimitExceeded { position, .. }
+            | JsonError::StringLimitExceeded { position, .. }
+            | JsonError::TimeBudgetExceeded { position, .. }
+            | JsonError::DisabledFeature { position, .. }
+            | JsonError::DuplicateKey { position, .. } => Some(*position),
+            JsonError::OutputLimitExceeded { .. }
+            | JsonError::InputTooLarge { .. }
+            | JsonError::NonFiniteNumber { .. }
+            | JsonError::InvalidPointer { .. } => None,
+        }
+    }
+
+    /// Moves the reported position `by` characters later, for an error
+    /// found in a piece cut out of a larger input.
+    pub(crate) fn shifted(mut self, by: usize) -> Self {
+        if let Some(position) = self.position_mut() {
+            *position += by;
+        }
+        self
+    }
+
+    fn position_mut(&mut self) -> Option<&mut usize> {
+        match self {
+            JsonError::UnexpectedToken { position, .. }
+            | JsonError::UnexpectedEndOfInput { position, .. }
+            | JsonError::InvalidNumber { position, .. }
+            | JsonError::InvalidEscape { position, .. }
+            | JsonError::InvalidUnicode { position, .. }
+            | JsonError::InvalidUtf8 { position, .. }
+            | JsonError::DepthLimitExceeded { position, .. }
+            | JsonError::TokenLimitExceeded { position, .. }
+            | JsonError::StringLimitExceeded { position, .. }
+            | JsonError::TimeBudgetExceeded { position, .. }
+            | JsonError::DisabledFeature { position, .. }
+            | JsonError::DuplicateKey { position, .. } => Some(position),
+            JsonError::OutputLimitExceeded { .. }
+            | JsonError::InputTooLarge { .. }
+            | JsonError::NonFiniteNumber { .. }
+            | JsonError::InvalidPointer { .. } => None,
+        }
+    }
+
+    /// The error with text quoted from the input shortened or removed, so
+    /// it can be logged when the input holds secrets. Positions and paths
+    /// are kept. An unknown escape character is replaced by `?` when
+    /// nothing may be quoted; text the caller supplied, such as a JSON
+    /// Pointer, is left alone.
+    pub fn redacted(mut self, redaction: Redaction) -> Self {
+        self.redact(redaction);
+        self
+    }
+
+    fn redact(&mut self, redaction: Redaction) {
+        match self {
+            JsonError::UnexpectedToken { found: text, .. }
+            | JsonError::InvalidNumber { value: text, .. }
+            | JsonError::InvalidUnicode { sequence: text, .. }
+            | JsonError::DuplicateKey { key: text, .. } => redaction.apply(text),
+            JsonError::InvalidEscape { character, .. } => {
+                if matches!(redaction, Redaction::Omit | Redaction::Truncate(0)) {
+                    *character = '?';
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// A likely fix, when there is one: for a misspelt keyword such as
+    /// `tru` or `None`, the keyword it was probably meant to be.
+    ///
+    /// ```
+    /// use rust_json_parser::{JsonError, JsonParser};
+    ///
+    /// let err = JsonParser::new("[ture]").unwrap_err();
+    /// assert!(matches!(&err, JsonError::UnexpectedToken { found, .. } if found == "ture"));
+    /// assert_eq!(err.help().as_deref(), Some("did you mean \"true\"?"));
+    /// ```
+    pub fn help(&self) -> Option<String> {
+        match self {
+            JsonError::UnexpectedToken {
+                expected, found, ..
+            } if expected == &[ExpectedToken::Keyword] => {
+                suggest_keyword(found).map(|keyword| format!("did you mean \"{keyword}\"?"))
+            }
+            _ => None,
+        }
+    }
+
+    /// The array element or object member being read when the error was
+    /// found, from the root down. `None` at the top level and for errors
+    /// without a position.
+    ///
+    /// ```
+    /// use rust_json_parser::JsonParser;
+    ///
+    /// let input = r#"{"users": [{"id": 1}, {"id": 2,}]}"#;
+    /// let err = JsonParser::new(input)?.parse().unwrap_err();
+    /// assert_eq!(err.path().unwrap().to_string(), "/users/1");
+    /// # Ok::<(), rust_json_parser::JsonError>(())
+    /// ```
+    pub fn path(&self) -> Option<&JsonPath> {
+        let path = match self {
+            JsonError::UnexpectedToken { path, .. }
+            | JsonError::UnexpectedEndOfInput { path, .. }
+            | JsonError::InvalidNumber { path, .. }
+            | JsonError::InvalidEscape { path, .. }
+            | JsonError::InvalidUnicode { path, .. }
+            | JsonError::InvalidUtf8 { path, .. }
+            | JsonError::DepthLimitExceeded { path, .. }
+            | JsonError::TokenLimitExceeded { path, .. }
+            | JsonError::StringLimitExceeded { path, .. }
+            | JsonError::TimeBudgetExceeded { path, .. }
+            | JsonError::DisabledFeature { path, .. }
+            | JsonError::DuplicateKey { path, .. } => Some(path),
+            JsonError::OutputLimitExceeded { .. }
+            | JsonError::InputTooLarge { .. }
+            | JsonError::NonFiniteNumber { .. }
+            | JsonError::InvalidPointer { .. } => None,
+        };
+        path.filter(|path| !path.is_root())
+    }
+
+    fn path_mut(&mut self) -> Option<&mut JsonPath> {
+        match self {
+            JsonError::UnexpectedToken { path, .. }
+            | JsonError::UnexpectedEndOfInput { path, .. }
+            | JsonError::InvalidNumber { path, .. }
+            | JsonError::InvalidEscape { path, .. }
+            | JsonError::InvalidUnicode { path, .. }
+            | JsonError::InvalidUtf8 { path, .. }
+            | JsonError::DepthLimitExceeded { path, .. }
+            | JsonError::TokenLimitExceeded { path, .. }
+            | JsonError::StringLimitExceeded { path, .. }
+            | JsonError::TimeBudgetExceeded { path, .. }
+            | JsonError::DisabledFeature { path, .. }
+            | JsonError::DuplicateKey { path, .. } => Some(path),
+            JsonError::OutputLimitExceeded { .. }
+            | JsonError::InputTooLarge { .. }
+            | JsonError::NonFiniteNumber { .. }
+            | JsonError::InvalidPointer { .. } => None,
+        }
+    }
+
+    /// The error as found inside the child `segment` names, for a parser
+    /// returning from that child.
+    pub(crate) fn inside(mut self, segment: PathSegment) -> Self {
+        if let Some(path) = self.path_mut() {
+            path.push_front(segment);
+        }
+        self
+    }
+
+    /// The error as found under `prefix`, for an error from a value read
+    /// out of a larger document.
+    pub(crate) fn under(mut self, prefix: &JsonPath) -> Self {
+        if let Some(path) = self.path_mut() {
+            path.prepend(prefix);
+        }
+        self
     }
 }
 
 impl std::error::Error for JsonError {}
 
+/// The zero-based line and column of character offset `position`, and the
+/// text of that line without its line break.
+pub(crate) fn locate(input: &str, position: usize) -> (usize, usize, &str) {
+    let (mut line, mut column, mut line_start) = (0, 0, 0);
+    for (offset, (byte, c)) in input.char_indices().enumerate() {
+        if offset == position {
+            break;
+        }
+        if c == '\n' {
+            line += 1;
+            column = 0;
+            line_start = byte + 1;
+        } else {
+            column += 1;
+        }
+    }
+    let line_end = input[line_start..]
+        .find('\n')
+        .map_or(input.len(), |end| line_start + end);
+    let text = input[line_start..line_end].trim_end_matches('\r');
+    (line, column, text)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -73,47 +665,80 @@ mod tests {
     #[test]
     fn test_error_creation() {
         let error = JsonError::UnexpectedToken {
-            expected: "number".into(),
+            expected: vec![ExpectedToken::Value],
             found: "@".into(),
             position: 5,
+            path: JsonPath::new(),
         };
         // Error should be Debug-printable
         assert!(format!("{error:?}").contains("UnexpectedToken"));
     }
     #[test]
+    fn test_expected_alternatives() {
+        let error = JsonError::UnexpectedToken {
+            expected: vec![ExpectedToken::Comma, ExpectedToken::RightBracket],
+            found: "}".into(),
+            position: 3,
+            path: JsonPath::new(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Unexpected token at position 3: expected ',' or ']', found }"
+        );
+        assert_eq!(
+            error.expected(),
+            Some(&[ExpectedToken::Comma, ExpectedToken::RightBracket][..])
+        );
+        assert_eq!(alternatives(&[ExpectedToken::Key]), "string key");
+        assert_eq!(
+            alternatives(&[
+                ExpectedToken::Comma,
+                ExpectedToken::Colon,
+                ExpectedToken::RightBrace
+            ]),
+            "',', ':' or '}'"
+        );
+    }
+    #[test]
     fn test_error_display() {
         let error = JsonError::UnexpectedToken {
-            expected: "valid JSON".into(),
+            expected: vec![ExpectedToken::Value],
             found: "@".into(),
             position: 0,
+            path: JsonPath::new(),
         };
         let message = format!("{error}");
         assert!(message.contains("position 0"));
-        assert!(message.contains("valid JSON"));
+        assert!(message.contains("JSON value"));
         assert!(message.contains("@"));
     }
     #[test]
     fn test_error_variants() {
         let token_error = JsonError::UnexpectedToken {
-            expected: "number".into(),
+            expected: vec![ExpectedToken::Value],
             found: "x".into(),
             position: 3,
+            path: JsonPath::new(),
         };
         let eof_error = JsonError::UnexpectedEndOfInput {
-            expected: "closing quote".into(),
+            expected: vec![ExpectedToken::Quote('"')],
             position: 10,
+            path: JsonPath::new(),
         };
         let num_error = JsonError::InvalidNumber {
             value: "12.34.56".into(),
             position: 0,
+            path: JsonPath::new(),
         };
         let escape_error = JsonError::InvalidEscape {
             character: 'q',
             position: 5,
+            path: JsonPath::new(),
         };
         let unicode_error = JsonError::InvalidUnicode {
             sequence: "00GG".into(),
             position: 3,
+            path: JsonPath::new(),
         };
         // All variants should be Debug-printable
         assert!(format!("{token_error:?}").contains("UnexpectedToken"));
@@ -128,23 +753,26 @@ mod tests {
         let cases = vec![
             (
                 JsonError::UnexpectedToken {
-                    expected: "string".into(),
+                    expected: vec![ExpectedToken::String],
                     found: "true".into(),
                     position: 42,
+                    path: JsonPath::new(),
                 },
                 "Unexpected token at position 42: expected string, found true",
             ),
             (
                 JsonError::UnexpectedEndOfInput {
-                    expected: "}".into(),
+                    expected: vec![ExpectedToken::RightBrace],
                     position: 100,
+                    path: JsonPath::new(),
                 },
-                "Unexpected end of input at position 100: expected }",
+                "Unexpected end of input at position 100: expected '}'",
             ),
             (
                 JsonError::InvalidNumber {
                     value: "1.2.3".into(),
                     position: 5,
+                    path: JsonPath::new(),
                 },
                 "Invalid number at position 5: value 1.2.3",
             ),
@@ -152,6 +780,7 @@ mod tests {
                 JsonError::InvalidEscape {
                     character: 'q',
                     position: 5,
+                    path: JsonPath::new(),
                 },
                 "Invalid escape sequence 'q' at position 5",
             ),
@@ -159,9 +788,82 @@ mod tests {
                 JsonError::InvalidUnicode {
                     sequence: "00GG".into(),
                     position: 3,
+                    path: JsonPath::new(),
                 },
                 "Invalid Unicode escape '\\u00GG' at position 3",
             ),
+            (
+                JsonError::OutputLimitExceeded { limit: 64 },
+                "Serialized output exceeds the limit of 64 bytes",
+            ),
+            (
+                JsonError::InputTooLarge {
+                    size: 2048,
+                    limit: 1024,
+                },
+                "Input of 2048 bytes exceeds the limit of 1024 bytes",
+            ),
+            (
+                JsonError::DepthLimitExceeded {
+                    limit: 8,
+                    position: 9,
+                    path: JsonPath::new(),
+                },
+                "Nesting deeper than 8 levels at position 9",
+            ),
+            (
+                JsonError::TokenLimitExceeded {
+                    limit: 100,
+                    position: 301,
+                    path: JsonPath::new(),
+                },
+                "More than 100 tokens at position 301",
+            ),
+            (
+                JsonError::StringLimitExceeded {
+                    limit: 16,
+                    position: 2,
+                    path: JsonPath::new(),
+                },
+                "String longer than 16 characters at position 2",
+            ),
+            (
+                JsonError::TimeBudgetExceeded {
+                    limit: Duration::from_millis(250),
+                    position: 4096,
+                    path: JsonPath::new(),
+                },
+                "Parsing took longer than 250ms; stopped at position 4096",
+            ),
+            (
+                JsonError::DisabledFeature {
+                    feature: "comments".into(),
+                    option: "allow_comments".into(),
+                    position: 4,
+                    path: JsonPath::new(),
+                },
+                "comments are not allowed (position 4); enable ParserOptions::allow_comments",
+            ),
+            (
+                JsonError::NonFiniteNumber {
+                    pointer: "/a/0".into(),
+                },
+                "Non-finite number at '/a/0' cannot be written as JSON",
+            ),
+            (
+                JsonError::InvalidPointer {
+                    pointer: "/a/-".into(),
+                },
+                "JSON Pointer '/a/-' does not resolve",
+            ),
+            (
+                JsonError::DuplicateKey {
+                    key: "id".into(),
+                    position: 9,
+                    path: JsonPath::new(),
+                },
+                "Duplicate key \"id\" at position 9",
+            ),
         ];
 
         for (error, expected_msg) in cases {
@@ -169,20 +871,187 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_structured_accessors() {
+        let cases = vec![
+            (
+                JsonError::UnexpectedToken {
+                    expected: vec![ExpectedToken::Colon],
+                    found: "Comma".into(),
+                    position: 4,
+                    path: JsonPath::new(),
+                },
+                "unexpected_token",
+                Some(4),
+                Some(&[ExpectedToken::Colon][..]),
+            ),
+            (
+                JsonError::UnexpectedEndOfInput {
+                    expected: vec![ExpectedToken::RightBracket],
+                    position: 9,
+                    path: JsonPath::new(),
+                },
+                "unexpected_end_of_input",
+                Some(9),
+                Some(&[ExpectedToken::RightBracket][..]),
+            ),
+            (
+                JsonError::InvalidEscape {
+                    character: 'q',
+                    position: 2,
+                    path: JsonPath::new(),
+                },
+                "invalid_escape",
+                Some(2),
+                None,
+            ),
+            (
+                JsonError::InputTooLarge { size: 9, limit: 1 },
+                "input_too_large",
+                None,
+                None,
+            ),
+        ];
+        for (error, code, position, expected) in cases {
+            assert_eq!(error.kind().code(), code, "{error}");
+            assert_eq!(error.kind().to_string(), code);
+            assert_eq!(error.position(), position, "{error}");
+            assert_eq!(error.expected(), expected, "{error}");
+        }
+    }
+
+    #[test]
+    fn test_predicates_separate_truncated_from_invalid() {
+        let parse = |input: &str| {
+            crate::JsonParser::new(input)
+                .and_then(|mut parser| parser.parse())
+                .unwrap_err()
+        };
+        for input in ["[1, 2", r#"{"a": {"b": "#, r#""open"#, "", r#"{"a""#] {
+            let error = parse(input);
+            assert!(error.is_eof() && !error.is_syntax(), "{input}: {error}");
+        }
+        for input in [
+            "[1 2]",
+            "[1}",
+            r#""\q""#,
+            "1.2.3",
+            "[1,]",
+            r#"{"a": [1 2]}"#,
+        ] {
+            let error = parse(input);
+            assert!(error.is_syntax() && !error.is_eof(), "{input}: {error}");
+        }
+        let limited = crate::JsonParser::with_options(
+            "[[1]]",
+            crate::ParserOptions::new().limits(crate::Limits::new().max_depth(1)),
+        )
+        .and_then(|mut parser| parser.parse())
+        .unwrap_err();
+        assert!(limited.is_limit() && !limited.is_syntax());
+    }
+
+    #[test]
+    fn test_every_prefix_of_a_document_is_eof() {
+        let document =
+            r#"{"n": -12.5e+3, "k": [true, false, null], "s": "a\"\u00e9\ud83d\ude00\\", "z": 0}"#;
+        for options in [
+            crate::ParserOptions::new(),
+            crate::ParserOptions::strict_rfc8259(),
+        ] {
+            for (end, _) in document.char_indices() {
+                let prefix = &document[..end];
+                let error = crate::JsonParser::with_options(prefix, options.clone())
+                    .and_then(|mut parser| parser.parse())
+                    .unwrap_err();
+                assert!(error.is_eof() && !error.is_syntax(), "{prefix}: {error}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_byte_range() {
+        let input = "[\"é\", @]";
+        let error = crate::Tokenizer::new(input).tokenize().unwrap_err();
+        let range = error.byte_range(input).unwrap();
+        assert_eq!(&input[range], "@");
+        let eof = JsonError::UnexpectedEndOfInput {
+            expected: vec![ExpectedToken::RightBracket],
+            position: 2,
+            path: JsonPath::new(),
+        };
+        assert_eq!(eof.byte_range("[é"), Some(3..3));
+        assert_eq!(
+            JsonError::OutputLimitExceeded { limit: 1 }.byte_range("[]"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_render_points_at_the_column() {
+        let input = "{\n  \"a\": tru\n}";
+        let error = crate::Tokenizer::new(input).tokenize().unwrap_err();
+        assert_eq!(
+            error.render(input),
+            "error: Unexpected token at position 9: expected keyword, found tru — did you mean \"true\"?\n --> line 2, column 8\n  |\n2 |   \"a\": tru\n  |        ^"
+        );
+    }
+
+    #[test]
+    fn test_render_edge_positions() {
+        let cases = vec![
+            // Past the end of the input
+            ("[1,", 3, 1, 4, "[1,"),
+            // Characters, not bytes, and tabs kept in the padding
+            ("é\t\r\nxy", 5, 2, 2, "xy"),
+            ("", 0, 1, 1, ""),
+        ];
+        for (input, position, line, column, text) in cases {
+            let error = JsonError::UnexpectedEndOfInput {
+                expected: vec![ExpectedToken::Value],
+                position,
+                path: JsonPath::new(),
+            };
+            let rendered = error.render(input);
+            assert!(
+                rendered.contains(&format!("--> line {line}, column {column}\n")),
+                "{rendered}"
+            );
+            assert!(
+                rendered.contains(&format!("{line} | {text}\n")),
+                "{rendered}"
+            );
+        }
+        let rendered = JsonError::UnexpectedEndOfInput {
+            expected: vec![ExpectedToken::Value],
+            position: 2,
+            path: JsonPath::new(),
+        }
+        .render("\tx");
+        assert!(rendered.ends_with("| \t ^"), "{rendered}");
+        assert_eq!(
+            JsonError::OutputLimitExceeded { limit: 4 }.render("[]"),
+            "error: Serialized output exceeds the limit of 4 bytes"
+        );
+    }
+
     #[test]
     fn test_equality_and_inequality() {
         let e1 = JsonError::InvalidNumber {
             value: "abc".into(),
             position: 1,
+            path: JsonPath::new(),
         };
         let e1_dup = e1.clone();
         let e2 = JsonError::InvalidNumber {
             value: "efg".into(),
             position: 1,
+            path: JsonPath::new(),
         };
         let e3 = JsonError::UnexpectedEndOfInput {
-            expected: "end".into(),
+            expected: vec![ExpectedToken::EndOfInput],
             position: 1,
+            path: JsonPath::new(),
         };
 
         // Test PartialEq works correctly
@@ -196,6 +1065,7 @@ mod tests {
         let err = JsonError::InvalidEscape {
             character: 'q',
             position: 5,
+            path: JsonPath::new(),
         };
         let msg = format!("{err}");
         assert!(msg.contains("escape"));
@@ -207,6 +1077,7 @@ mod tests {
         let err = JsonError::InvalidUnicode {
             sequence: "00GG".to_string(),
             position: 3,
+            path: JsonPath::new(),
         };
         let msg = format!("{err}");
         assert!(msg.contains("unicode") || msg.contains("Unicode"));
@@ -217,6 +1088,7 @@ mod tests {
         let err = JsonError::InvalidNumber {
             value: "12.3.4".into(),
             position: 0,
+            path: JsonPath::new(),
         };
         assert!(err.source().is_none());
     }
@@ -226,6 +1098,7 @@ mod tests {
         let err = JsonError::InvalidEscape {
             character: 'x',
             position: 0,
+            path: JsonPath::new(),
         };
         let _: &dyn std::error::Error = &err; // Must implement Error trait
     }
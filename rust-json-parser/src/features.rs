@@ -0,0 +1,120 @@
+//! Introspection of the optional subsystems compiled into this build.
+//!
+//! Applications can check these once at startup and fail fast instead of
+//! discovering a missing subsystem on first use.
+
+use std::fmt;
+
+/// `true` when the crate was built with the `python` feature.
+pub const PYTHON: bool = cfg!(feature = "python");
+
+/// `true` when the crate was built with the `async` feature.
+pub const ASYNC: bool = cfg!(feature = "async");
+
+/// `true` when the crate was built with the `unicode` feature.
+pub const UNICODE: bool = cfg!(feature = "unicode");
+
+/// `true` when the crate was built with the `diagnostics` feature.
+pub const DIAGNOSTICS: bool = cfg!(feature = "diagnostics");
+
+/// `true` when the crate was built with the `testing` feature.
+pub const TESTING: bool = cfg!(feature = "testing");
+
+/// `true` when the crate was built with the `simd` feature, which scans
+/// strings and whitespace several characters at a time.
+pub const SIMD: bool = cfg!(feature = "simd");
+
+/// `true` when the crate was built with the `arena` feature.
+pub const ARENA: bool = cfg!(feature = "arena");
+
+/// `true` when the crate was built with the `shared` feature.
+pub const SHARED: bool = cfg!(feature = "shared");
+
+const ENABLED: &[&str] = &[
+    #[cfg(feature = "python")]
+    "python",
+    #[cfg(feature = "async")]
+    "async",
+    #[cfg(feature = "unicode")]
+    "unicode",
+    #[cfg(feature = "diagnostics")]
+    "diagnostics",
+    #[cfg(feature = "testing")]
+    "testing",
+    #[cfg(feature = "simd")]
+    "simd",
+    #[cfg(feature = "arena")]
+    "arena",
+    #[cfg(feature = "shared")]
+    "shared",
+];
+
+/// Names of the Cargo features this build was compiled with.
+pub fn enabled() -> &'static [&'static str] {
+    ENABLED
+}
+
+/// Whether the named feature was compiled in.
+pub fn is_enabled(name: &str) -> bool {
+    ENABLED.contains(&name)
+}
+
+/// Checks that every feature in `names` was compiled in.
+pub fn require(names: &[&str]) -> Result<(), MissingFeature> {
+    match names.iter().find(|name| !is_enabled(name)) {
+        Some(name) => Err(MissingFeature {
+            name: name.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Returned by [`require`] for the first feature that is not available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingFeature {
+    pub name: String,
+}
+
+impl fmt::Display for MissingFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Feature '{}' is not enabled in this build of rust-json-parser",
+            self.name
+        )
+    }
+}
+
+impl std::error::Error for MissingFeature {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_matches_constants() {
+        assert_eq!(is_enabled("python"), PYTHON);
+        assert_eq!(enabled().contains(&"python"), PYTHON);
+        assert_eq!(is_enabled("async"), ASYNC);
+        assert_eq!(is_enabled("unicode"), UNICODE);
+        assert_eq!(is_enabled("diagnostics"), DIAGNOSTICS);
+        assert_eq!(is_enabled("testing"), TESTING);
+        assert_eq!(is_enabled("simd"), SIMD);
+        assert_eq!(is_enabled("arena"), ARENA);
+        assert_eq!(is_enabled("shared"), SHARED);
+    }
+
+    #[test]
+    fn test_unknown_feature_is_missing() {
+        assert!(!is_enabled("does-not-exist"));
+        let err = require(&["does-not-exist"]).unwrap_err();
+        assert_eq!(err.name, "does-not-exist");
+        assert!(err.to_string().contains("'does-not-exist'"));
+    }
+
+    #[test]
+    fn test_require_enabled_features() {
+        assert!(require(&[]).is_ok());
+        assert!(require(enabled()).is_ok());
+    }
+}
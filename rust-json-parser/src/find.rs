@@ -0,0 +1,108 @@
+use crate::path::push_children;
+use crate::{JsonPath, JsonValue, PathSegment};
+
+impl JsonValue {
+    /// Every node for which `predicate` returns `true`, with its location, in
+    /// document order.
+    ///
+    /// The predicate sees every node, containers included, starting with the
+    /// root itself. Matching a container does not stop the search from
+    /// descending into it.
+    pub fn find<F>(&self, predicate: F) -> Find<'_, F>
+    where
+        F: FnMut(&JsonPath, &JsonValue) -> bool,
+    {
+        Find {
+            stack: vec![(JsonPath::new(), self)],
+            predicate,
+        }
+    }
+
+    /// The values of every object member named `key`, at any depth.
+    pub fn find_key<'a>(
+        &'a self,
+        key: &'a str,
+    ) -> impl Iterator<Item = (JsonPath, &'a JsonValue)> + 'a {
+        self.find(
+            move |path, _| matches!(path.segments().last(), Some(PathSegment::Key(k)) if k == key),
+        )
+    }
+}
+
+/// Iterator returned by [`JsonValue::find`].
+#[derive(Debug, Clone)]
+pub struct Find<'a, F> {
+    /// Nodes still to visit, with the next one on top.
+    stack: Vec<(JsonPath, &'a JsonValue)>,
+    predicate: F,
+}
+
+impl<'a, F> Iterator for Find<'a, F>
+where
+    F: FnMut(&JsonPath, &JsonValue) -> bool,
+{
+    type Item = (JsonPath, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, value)) = self.stack.pop() {
+            push_children(&mut self.stack, &path, value);
+            if (self.predicate)(&path, value) {
+                return Some((path, value));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn pointers<'a>(matches: impl Iterator<Item = (JsonPath, &'a JsonValue)>) -> Vec<String> {
+        matches.map(|(path, _)| path.to_pointer()).collect()
+    }
+
+    #[test]
+    fn test_find_by_value() {
+        let value = parse_json(r#"{"a": 1, "b": [2, {"c": 3}], "d": "4"}"#).unwrap();
+        let big = value.find(|_, v| v.as_f64().is_some_and(|n| n >= 2.0));
+        assert_eq!(pointers(big), vec!["/b/0", "/b/1/c"]);
+    }
+
+    #[test]
+    fn test_find_visits_root_and_containers() {
+        let value = parse_json(r#"[{"x": []}]"#).unwrap();
+        let all = pointers(value.find(|_, _| true));
+        assert_eq!(all, vec!["", "/0", "/0/x"]);
+    }
+
+    #[test]
+    fn test_find_by_path() {
+        let value = parse_json(r#"{"deep": {"deeper": {"deepest": true}}}"#).unwrap();
+        let (path, found) = value.find(|path, _| path.len() == 3).next().unwrap();
+        assert_eq!(path.to_pointer(), "/deep/deeper/deepest");
+        assert_eq!(found, &JsonValue::Boolean(true));
+    }
+
+    #[test]
+    fn test_find_key() {
+        let value = parse_json(
+            r#"{"email": "a@x", "users": [{"email": "b@x"}, {"name": "c"}, ["email"]]}"#,
+        )
+        .unwrap();
+        let emails: Vec<(String, &str)> = value
+            .find_key("email")
+            .map(|(path, v)| (path.to_pointer(), v.as_str().unwrap()))
+            .collect();
+        assert_eq!(
+            emails,
+            vec![
+                ("/email".to_string(), "a@x"),
+                ("/users/0/email".to_string(), "b@x")
+            ]
+        );
+        assert_eq!(value.find_key("missing").count(), 0);
+    }
+}
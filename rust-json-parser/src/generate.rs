@@ -0,0 +1,400 @@
+use crate::schema::{Node, Rules, Schema, Type};
+use crate::shape::Kind;
+use crate::{JsonArray, JsonObject, JsonValue, Shape};
+
+/// Attempts at a value for one schema before giving up on it.
+const ATTEMPTS: usize = 16;
+
+/// Lowercase letters, digits and a space: readable strings that are easy
+/// to eyeball in a generated corpus.
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789 ";
+
+/// Produces random values that fit a [`Schema`] or a [`Shape`], for load
+/// test corpora and fuzzing.
+///
+/// The same seed and settings always produce the same values. Sizes are
+/// capped by [`max_items`](Generator::max_items) and
+/// [`max_string_chars`](Generator::max_string_chars) unless the schema
+/// requires more.
+///
+/// ```
+/// use rust_json_parser::{Generator, JsonParser, schema::Schema};
+///
+/// let schema = Schema::from_json(&JsonParser::new(
+///     r#"{
+///         "type": "object",
+///         "required": ["id", "sku"],
+///         "properties": {
+///             "id": {"type": "integer", "minimum": 1},
+///             "sku": {"type": "string", "pattern": "^[A-Z]{3}-\\d{4}$"}
+///         }
+///     }"#,
+/// )?.parse()?)?;
+///
+/// let mut generator = Generator::new(7);
+/// for _ in 0..100 {
+///     let value = generator.for_schema(&schema).unwrap();
+///     assert!(schema.is_valid(&value));
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+    max_items: usize,
+    max_string_chars: usize,
+    max_depth: usize,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Generator {
+            // xorshift64 never leaves zero, so mix the seed into a constant.
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+            max_items: 8,
+            max_string_chars: 16,
+            max_depth: 4,
+        }
+    }
+
+    /// Most elements in a generated array. Defaults to 8.
+    pub fn max_items(mut self, items: usize) -> Self {
+        self.max_items = items;
+        self
+    }
+
+    /// Most characters in a generated string. Defaults to 16.
+    pub fn max_string_chars(mut self, chars: usize) -> Self {
+        self.max_string_chars = chars;
+        self
+    }
+
+    /// Nesting past which values the schema leaves open are kept scalar; a
+    /// top-level container is depth 1. Defaults to 4.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// A random value valid against `schema`, or `None` if none was found,
+    /// as for `false` or contradictory keywords.
+    pub fn for_schema(&mut self, schema: &Schema) -> Option<JsonValue> {
+        self.node(&schema.node, 0)
+    }
+
+    /// A random value that fits `shape`. Optional members are present
+    /// about half the time and nullable values are `null` about a quarter
+    /// of the time.
+    pub fn for_shape(&mut self, shape: &Shape) -> JsonValue {
+        self.shape(shape, 0)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// A number in `0..n`; `n` must not be zero.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A number in `lo..=hi`.
+    fn between(&mut self, lo: usize, hi: usize) -> usize {
+        lo + self.below(hi.saturating_sub(lo) + 1)
+    }
+
+    /// A float in `0.0..1.0`.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn node(&mut self, node: &Node, depth: usize) -> Option<JsonValue> {
+        let rules = match node {
+            Node::Bool(false) => return None,
+            Node::Bool(true) => return Some(self.any(depth)),
+            Node::Rules(rules) => rules,
+        };
+        (0..ATTEMPTS)
+            .filter_map(|_| self.rules(rules, depth))
+            .find(|value| node.accepts(value))
+    }
+
+    fn rules(&mut self, rules: &Rules, depth: usize) -> Option<JsonValue> {
+        if let Some(constant) = &rules.constant {
+            return Some(constant.clone());
+        }
+        if let Some(allowed) = &rules.enumeration {
+            return allowed.get(self.below(allowed.len().max(1))).cloned();
+        }
+        let ty = match &rules.types {
+            Some(types) if types.is_empty() => return None,
+            Some(types) => types[self.below(types.len())],
+            None if depth >= self.max_depth => {
+                [Type::Null, Type::Boolean, Type::Number, Type::String][self.below(4)]
+            }
+            None => [
+                Type::Null,
+                Type::Boolean,
+                Type::Number,
+                Type::String,
+                Type::Array,
+                Type::Object,
+            ][self.below(6)],
+        };
+        Some(match ty {
+            Type::Null => JsonValue::Null,
+            Type::Boolean => JsonValue::Boolean(self.below(2) == 0),
+            Type::Integer | Type::Number => self.number(rules, ty == Type::Integer)?,
+            Type::String => JsonValue::String(self.string(rules)?.into()),
+            Type::Array => {
+                let min = rules.min_items.unwrap_or(0);
+                let max = rules.max_items.unwrap_or(usize::MAX).min(self.max_items);
+                let mut items = JsonArray::new();
+                for _ in 0..self.between(min, max.max(min)) {
+                    items.push(match &rules.items {
+                        Some(node) => self.node(node, depth + 1)?,
+                        None => self.any(depth + 1),
+                    });
+                }
+                JsonValue::Array(items)
+            }
+            Type::Object => {
+                let mut object = JsonObject::new();
+                for (key, node) in &rules.properties {
+                    if rules.required.contains(key) || self.below(2) == 0 {
+                        object.insert(key.clone(), self.node(node, depth + 1)?);
+                    }
+                }
+                for key in &rules.required {
+                    if !object.contains_key(key) {
+                        let value = match &rules.additional_properties {
+                            Some(node) => self.node(node, depth + 1)?,
+                            None => self.any(depth + 1),
+                        };
+                        object.insert(key.clone(), value);
+                    }
+                }
+                JsonValue::Object(object)
+            }
+        })
+    }
+
+    fn number(&mut self, rules: &Rules, integer: bool) -> Option<JsonValue> {
+        // Unbounded sides stay within 1000 of the other bound, or of zero.
+        const SPAN: f64 = 1000.0;
+        // Stay where every integer is exact in an f64.
+        const SAFE: f64 = 9_007_199_254_740_991.0;
+        let (mut lower, mut upper) = (rules.minimum, rules.maximum);
+        if integer {
+            lower = lower.map(f64::ceil);
+            upper = upper.map(f64::floor);
+        }
+        // The closest values past an exclusive bound: the next integer, or
+        // for other numbers the bound itself, which validation then rejects
+        // on the rare draw that lands on it.
+        let above = |n: f64| if integer { n.floor() + 1.0 } else { n };
+        let below = |n: f64| if integer { n.ceil() - 1.0 } else { n };
+        let lower = lower
+            .into_iter()
+            .chain(rules.exclusive_minimum.map(above))
+            .reduce(f64::max);
+        let upper = upper
+            .into_iter()
+            .chain(rules.exclusive_maximum.map(below))
+            .reduce(f64::min);
+        let (lo, hi) = match (lower, upper) {
+            (Some(lo), Some(hi)) => (lo, hi),
+            (Some(lo), None) => (lo, lo + SPAN),
+            (None, Some(hi)) => (hi - SPAN, hi),
+            (None, None) => (-SPAN, SPAN),
+        };
+        if integer {
+            let (lo, hi) = (lo.max(-SAFE), hi.min(SAFE));
+            if lo > hi {
+                return None;
+            }
+            let offset = self.unit() * (hi - lo + 1.0);
+            return Some(JsonValue::Number((lo + offset.floor()).min(hi)));
+        }
+        if lo > hi {
+            return None;
+        }
+        let n = lo + self.unit() * (hi - lo);
+        // Three decimals read better, when they keep the number in range.
+        let rounded = (n * 1000.0).round() / 1000.0;
+        Some(JsonValue::Number(if (lo..=hi).contains(&rounded) {
+            rounded
+        } else {
+            n
+        }))
+    }
+
+    fn string(&mut self, rules: &Rules) -> Option<String> {
+        let min = rules.min_length.unwrap_or(0);
+        let max = rules
+            .max_length
+            .unwrap_or(usize::MAX)
+            .min(self.max_string_chars)
+            .max(min);
+        let Some((_, pattern)) = &rules.pattern else {
+            let len = self.between(min, max);
+            return Some(
+                (0..len)
+                    .map(|_| ALPHABET[self.below(ALPHABET.len())] as char)
+                    .collect(),
+            );
+        };
+        (0..ATTEMPTS)
+            .filter_map(|_| pattern.sample(&mut |n| self.below(n), max))
+            .find(|s| {
+                let chars = s.chars().count();
+                chars >= min && chars <= rules.max_length.unwrap_or(usize::MAX)
+            })
+    }
+
+    /// Any value, with containers only above `max_depth`.
+    fn any(&mut self, depth: usize) -> JsonValue {
+        let kinds = if depth >= self.max_depth { 4 } else { 6 };
+        match self.below(kinds) {
+            0 => JsonValue::Null,
+            1 => JsonValue::Boolean(self.below(2) == 0),
+            2 => JsonValue::Number(self.between(0, 2000) as f64 - 1000.0),
+            3 => JsonValue::String(self.text().into()),
+            4 => {
+                let len = self.between(0, self.max_items);
+                JsonValue::Array((0..len).map(|_| self.any(depth + 1)).collect())
+            }
+            _ => {
+                let mut object = JsonObject::new();
+                for _ in 0..self.between(0, self.max_items) {
+                    let key = self.text();
+                    let value = self.any(depth + 1);
+                    object.insert(key, value);
+                }
+                JsonValue::Object(object)
+            }
+        }
+    }
+
+    fn text(&mut self) -> String {
+        let len = self.between(0, self.max_string_chars);
+        (0..len)
+            .map(|_| ALPHABET[self.below(ALPHABET.len())] as char)
+            .collect()
+    }
+
+    fn shape(&mut self, shape: &Shape, depth: usize) -> JsonValue {
+        if shape.nullable && self.below(4) == 0 {
+            return JsonValue::Null;
+        }
+        match &shape.kind {
+            Kind::Any => self.any(depth),
+            Kind::Null => JsonValue::Null,
+            Kind::Bool => JsonValue::Boolean(self.below(2) == 0),
+            Kind::Int => JsonValue::Number(self.between(0, 2000) as f64 - 1000.0),
+            Kind::Number => {
+                JsonValue::Number(((self.unit() * 2000.0 - 1000.0) * 1000.0).round() / 1000.0)
+            }
+            Kind::String => JsonValue::String(self.text().into()),
+            Kind::ArrayOf(items) => {
+                let len = self.between(0, self.max_items);
+                JsonValue::Array((0..len).map(|_| self.shape(items, depth + 1)).collect())
+            }
+            Kind::Object { members, .. } => {
+                let mut object = JsonObject::new();
+                for member in members {
+                    if member.required || self.below(2) == 0 {
+                        let value = self.shape(&member.shape, depth + 1);
+                        object.insert(member.key.clone(), value);
+                    }
+                }
+                JsonValue::Object(object)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonParser;
+
+    fn schema(input: &str) -> Schema {
+        Schema::from_json(&JsonParser::new(input).unwrap().parse().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_values_fit_the_schema() {
+        let schemas = [
+            r#"{"type": "integer", "exclusiveMinimum": 3, "maximum": 5}"#,
+            r#"{"type": "number", "minimum": 0.5, "exclusiveMaximum": 0.75}"#,
+            r#"{"type": ["string", "null"], "minLength": 20, "maxLength": 22}"#,
+            r#"{"type": "string", "pattern": "^(GET|POST) /[a-z]+$", "maxLength": 12}"#,
+            r#"{"enum": ["a", "b", 3], "type": "string"}"#,
+            r#"{"type": "array", "items": {"type": "boolean"}, "minItems": 10}"#,
+            r#"{
+                "type": "object",
+                "required": ["id", "extra"],
+                "properties": {"id": {"const": 1}, "tags": {"items": {"type": "string"}}},
+                "additionalProperties": {"type": "integer"}
+            }"#,
+            "{}",
+            "true",
+        ];
+        let mut generator = Generator::new(42);
+        for input in schemas {
+            let schema = schema(input);
+            for _ in 0..50 {
+                let value = generator.for_schema(&schema).expect(input);
+                assert!(schema.is_valid(&value), "{input}: {value}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsatisfiable_schemas() {
+        let mut generator = Generator::new(1);
+        for input in [
+            "false",
+            r#"{"type": "integer", "minimum": 1.2, "maximum": 1.8}"#,
+            r#"{"type": "string", "pattern": "a^"}"#,
+            r#"{"required": ["a"], "type": "object", "additionalProperties": false}"#,
+        ] {
+            assert_eq!(generator.for_schema(&schema(input)), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_values_fit_the_shape() {
+        let shape = Shape::object()
+            .key("id", Shape::int())
+            .key("tags", Shape::array_of(Shape::string()))
+            .optional_key(
+                "owner",
+                Shape::object().key("name", Shape::string()).nullable(),
+            )
+            .exact();
+        let mut generator = Generator::new(3).max_items(3);
+        for _ in 0..100 {
+            let value = generator.for_shape(&shape);
+            assert!(shape.matches(&value), "{value}");
+            assert!(value.get("tags").unwrap().as_array().unwrap().len() <= 3);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_values() {
+        let schema = schema(r#"{"type": "array", "items": {}}"#);
+        let run = |seed| {
+            let mut generator = Generator::new(seed).max_depth(3);
+            (0..20)
+                .map(|_| generator.for_schema(&schema).unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(9), run(9));
+        assert_ne!(run(9), run(10));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::JsonString;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// A dictionary of strings shared between every document parsed with it.
+///
+/// Hand one to [`ParserOptions::interner`](crate::ParserOptions::interner)
+/// and each object key, and each string value up to
+/// [`Interner::max_value_len`] bytes, is looked up here instead of being
+/// allocated again: a thousand documents with the same keys hold one copy
+/// of each. Text short enough to be stored inline (see [`JsonString`])
+/// costs nothing to repeat, so it never enters the dictionary. Clones of
+/// an `Interner` share its dictionary, and it can be used from several
+/// threads at once.
+///
+/// Strings stay in the dictionary until [`Interner::purge`] finds no
+/// document holding them.
+///
+/// ```
+/// use rust_json_parser::{Interner, JsonParser, ParserOptions};
+///
+/// let interner = Interner::new();
+/// let options = ParserOptions::new().interner(interner.clone());
+/// let json = r#"{"status": "ok", "last_modified_by_user_id": "account-migration-service"}"#;
+/// let a = JsonParser::with_options(json, options.clone())?.parse()?;
+/// let b = JsonParser::with_options(json, options)?.parse()?;
+/// assert_eq!(interner.len(), 2);
+/// assert_eq!(a, b);
+/// # Ok::<(), rust_json_parser::JsonError>(())
+/// ```
+#[derive(Clone)]
+pub struct Interner {
+    strings: Arc<Mutex<HashSet<Arc<str>>>>,
+    max_value_len: usize,
+}
+
+impl Interner {
+    /// An empty dictionary that shares keys and string values of up to 64
+    /// bytes; longer values, such as descriptions, rarely repeat.
+    pub fn new() -> Self {
+        Self {
+            strings: Arc::default(),
+            max_value_len: 64,
+        }
+    }
+
+    /// Only shares string values up to `bytes` long; `0` shares keys only.
+    pub fn max_value_len(mut self, bytes: usize) -> Self {
+        self.max_value_len = bytes;
+        self
+    }
+
+    /// The shared copy of `text`, added to the dictionary if it is new, or
+    /// `text` stored inline if it fits.
+    pub fn intern(&self, text: &str) -> JsonString {
+        if let Some(inline) = JsonString::inline(text) {
+            return inline;
+        }
+        let mut strings = self.lock();
+        let shared = match strings.get(text) {
+            Some(shared) => Arc::clone(shared),
+            None => {
+                let shared: Arc<str> = Arc::from(text);
+                strings.insert(Arc::clone(&shared));
+                shared
+            }
+        };
+        JsonString::shared(shared)
+    }
+
+    /// Number of distinct strings in the dictionary.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops the strings no document holds anymore, returning how many.
+    pub fn purge(&self) -> usize {
+        let mut strings = self.lock();
+        let before = strings.len();
+        strings.retain(|s| Arc::strong_count(s) > 1);
+        before - strings.len()
+    }
+
+    /// A string value as the parser stores it: shared if it is short
+    /// enough, otherwise owned as read.
+    pub(crate) fn value(&self, s: String) -> JsonString {
+        if s.len() <= self.max_value_len {
+            self.intern(&s)
+        } else {
+            s.into()
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<Arc<str>>> {
+        // The set is never left half-updated, so a panic elsewhere while it
+        // was locked does not matter.
+        self.strings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Interner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interner")
+            .field("len", &self.len())
+            .field("max_value_len", &self.max_value_len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonParser, JsonValue, ParserOptions};
+
+    fn parse_with(input: &str, interner: &Interner) -> JsonValue {
+        let options = ParserOptions::new().interner(interner.clone());
+        JsonParser::with_options(input, options)
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    fn text_ptr(value: &JsonValue, key: &str) -> *const u8 {
+        value.get(key).and_then(JsonValue::as_str).unwrap().as_ptr()
+    }
+
+    #[test]
+    fn test_documents_share_keys_and_short_values() {
+        let interner = Interner::new().max_value_len(30);
+        let json = r#"{
+            "billing_contact_address": "customer-success-team-eu",
+            "bio": "a description longer than thirty bytes"
+        }"#;
+        let a = parse_with(json, &interner);
+        let b = parse_with(json, &interner);
+        assert_eq!(a, b);
+        // The first key and its value; "bio" is inline and the long value
+        // is not shared.
+        assert_eq!(interner.len(), 2);
+        let key = "billing_contact_address";
+        assert_eq!(text_ptr(&a, key), text_ptr(&b, key));
+        assert_ne!(text_ptr(&a, "bio"), text_ptr(&b, "bio"));
+        let first_key = |v: &JsonValue| v.as_object().unwrap().keys().next().unwrap().as_ptr();
+        assert_eq!(first_key(&a), first_key(&b));
+    }
+
+    #[test]
+    fn test_keys_only() {
+        let interner = Interner::new().max_value_len(0);
+        let value = parse_with(
+            r#"{"a_key_too_long_to_inline": "a value too long to inline"}"#,
+            &interner,
+        );
+        assert_eq!(interner.len(), 1);
+        let a = value.get("a_key_too_long_to_inline");
+        assert!(matches!(a, Some(JsonValue::String(s)) if !s.is_shared()));
+    }
+
+    #[test]
+    fn test_short_text_stays_inline() {
+        let interner = Interner::new();
+        let value = parse_with(r#"{"id": "x"}"#, &interner);
+        assert!(interner.is_empty());
+        assert!(matches!(value.get("id"), Some(JsonValue::String(s)) if s.is_inline()));
+    }
+
+    #[test]
+    fn test_purge_keeps_strings_in_use() {
+        let interner = Interner::new();
+        let kept = parse_with(r#"{"a_key_too_long_to_inline": 1}"#, &interner);
+        drop(parse_with(
+            r#"{"another_key_too_long_to_inline": 2}"#,
+            &interner,
+        ));
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.purge(), 1);
+        assert_eq!(interner.len(), 1);
+        assert!(kept.get("a_key_too_long_to_inline").is_some());
+    }
+}
@@ -0,0 +1,567 @@
+use crate::path::{parse_index, pointer_tokens};
+use crate::tape::{Scanner, char_offset};
+use crate::tokenizer::Tokenizer;
+use crate::{
+    ExpectedToken, JsonArray, JsonObject, JsonParser, JsonPath, JsonValue, ParserOptions,
+    PathSegment, Result, unescape_json_string,
+};
+use std::borrow::Cow;
+
+/// A value in JSON text that is only parsed as far as it is read.
+///
+/// [`LazyValue::parse`] just finds where the document's value ends. Looking
+/// up a member or element then skims past the ones before it, matching
+/// brackets and checking strings without building anything, and returns another
+/// `LazyValue` over the text of the one found. Nothing is decoded until
+/// [`to_value`](LazyValue::to_value) or [`as_str`](LazyValue::as_str) is
+/// called, so reading a few fields of a large document costs little more
+/// than finding them.
+///
+/// Strings are checked as they are skimmed, with the same code as
+/// [`parse_to_tape`](crate::parse_to_tape): escapes must be valid and
+/// control characters escaped, as under
+/// [`ParserOptions::strict_strings`]. Other syntax errors are reported
+/// when the part of the text holding them is read, which may be never;
+/// positions are character offsets into the whole text.
+///
+/// ```
+/// use rust_json_parser::LazyValue;
+///
+/// let json = r#"{"meta": {"huge": [1, 2, 3]}, "users": [{"name": "Ada"}]}"#;
+/// let doc = LazyValue::parse(json)?;
+/// let name = doc.pointer("/users/0/name")?.unwrap();
+/// assert_eq!(name.as_str()?.as_deref(), Some("Ada"));
+/// assert_eq!(doc.get("meta")?.unwrap().raw(), r#"{"huge": [1, 2, 3]}"#);
+/// # Ok::<(), rust_json_parser::JsonError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LazyValue<'a> {
+    /// The whole document, for error positions.
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> LazyValue<'a> {
+    /// The value `json` holds. Fails if there is no value, if its brackets
+    /// or quotes do not balance, or if anything but whitespace follows it.
+    pub fn parse(json: &'a str) -> Result<Self> {
+        let mut skimmer = Skimmer::new(json, 0);
+        let value = skimmer.value()?;
+        skimmer.scan.skip_whitespace();
+        if skimmer.scan.pos < json.len() {
+            return Err(skimmer.scan.unexpected(vec![ExpectedToken::EndOfInput]));
+        }
+        Ok(value)
+    }
+
+    /// The value's text exactly as it appears in the document.
+    pub fn raw(&self) -> &'a str {
+        &self.text[self.start..self.end]
+    }
+
+    /// The type the value's first character announces, named as
+    /// [`JsonValue::type_name`] does. The rest of the value may still turn
+    /// out to be malformed.
+    pub fn type_name(&self) -> &'static str {
+        match self.text.as_bytes()[self.start] {
+            b'{' => "object",
+            b'[' => "array",
+            b'"' => "string",
+            b't' | b'f' => "boolean",
+            b'n' => "null",
+            _ => "number",
+        }
+    }
+
+    /// Parses the value in full, in one pass over its text. Containers
+    /// are filled from an explicit stack, so any depth of nesting is fine.
+    pub fn to_value(&self) -> Result<JsonValue> {
+        // The containers being read, each with the key its parent holds it
+        // under and how many children it has had
+        let mut open: Vec<(Option<String>, JsonValue, usize)> = Vec::new();
+        let mut skimmer = Skimmer::new(self.text, self.start);
+        let mut key = None;
+        loop {
+            skimmer.scan.skip_whitespace();
+            let mut done = match skimmer.scan.peek() {
+                Some(b'[') => {
+                    skimmer.scan.pos += 1;
+                    open.push((key.take(), JsonValue::Array(JsonArray::new()), 0));
+                    None
+                }
+                Some(b'{') => {
+                    skimmer.scan.pos += 1;
+                    open.push((key.take(), JsonValue::Object(JsonObject::new()), 0));
+                    None
+                }
+                _ => {
+                    let child = match open.last() {
+                        Some((_, JsonValue::Object(_), _)) => key.clone().map(PathSegment::Key),
+                        Some((_, _, count)) => Some(PathSegment::Index(count - 1)),
+                        None => None,
+                    };
+                    let value = skimmer
+                        .value()
+                        .and_then(|value| value.scalar())
+                        .map_err(|err| err.under(&reading_path(&open, child)))?;
+                    Some((key.take(), value))
+                }
+            };
+            // Add what was read to its parent, closing every container
+            // that completes, until one has a child left to read
+            key = loop {
+                let Some((_, parent, count)) = open.last_mut() else {
+                    let (_, value) = done.expect("the outermost value was read");
+                    return Ok(value);
+                };
+                match (&mut *parent, done.take()) {
+                    (JsonValue::Array(array), Some((_, value))) => array.push(value),
+                    (JsonValue::Object(object), Some((Some(key), value))) => {
+                        object.insert(key, value);
+                    }
+                    _ => {}
+                }
+                let index = *count;
+                *count += 1;
+                let (close, closing) = match parent {
+                    JsonValue::Object(_) => (b'}', ExpectedToken::RightBrace),
+                    _ => (b']', ExpectedToken::RightBracket),
+                };
+                let more = skimmer
+                    .separator(close, closing, index == 0)
+                    .map_err(|err| err.under(&reading_path(&open, None)))?;
+                if more {
+                    match close {
+                        b'}' => {
+                            let key = skimmer
+                                .key()
+                                .map_err(|err| err.under(&reading_path(&open, None)))?;
+                            break Some(key.into_owned());
+                        }
+                        _ => break None,
+                    }
+                }
+                skimmer.scan.pos += 1;
+                let (key, value, _) = open.pop().expect("checked above");
+                done = Some((key, value));
+            };
+        }
+    }
+
+    /// Parses a value that is not an array or object.
+    fn scalar(&self) -> Result<JsonValue> {
+        let raw = self.raw();
+        let mut tokenizer = Tokenizer::new(raw).more_follows(self.end < self.text.len());
+        tokenizer
+            .tokenize_spanned()
+            .and_then(|tokens| {
+                JsonParser::from_tokens(raw, tokens, tokenizer.clock(), ParserOptions::default())
+                    .parse()
+            })
+            .map_err(|err| err.shifted(char_offset(self.text, self.start)))
+    }
+
+    /// The text of a string, borrowed from the document unless it has
+    /// escapes to decode; `None` for anything else.
+    pub fn as_str(&self) -> Result<Option<Cow<'a, str>>> {
+        if self.type_name() != "string" {
+            return Ok(None);
+        }
+        decode(self.text, self.start, self.end).map(Some)
+    }
+
+    /// The members of an object, in document order, each read as the
+    /// iteration reaches it; nothing for anything else. Stops after the
+    /// first error.
+    pub fn members(&self) -> LazyMembers<'a> {
+        LazyMembers {
+            skimmer: Skimmer::new(self.text, self.start + 1),
+            done: self.type_name() != "object",
+            first: true,
+        }
+    }
+
+    /// The elements of an array, in order, each read as the iteration
+    /// reaches it; nothing for anything else. Stops after the first error.
+    pub fn elements(&self) -> LazyElements<'a> {
+        LazyElements {
+            skimmer: Skimmer::new(self.text, self.start + 1),
+            done: self.type_name() != "array",
+            first: true,
+        }
+    }
+
+    /// The first member named `key`. Members after it are not read.
+    pub fn get(&self, key: &str) -> Result<Option<LazyValue<'a>>> {
+        for member in self.members() {
+            let (name, value) = member?;
+            if name == key {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The element at `index`. Elements after it are not read.
+    pub fn get_index(&self, index: usize) -> Result<Option<LazyValue<'a>>> {
+        for (i, element) in self.elements().enumerate() {
+            let element = element?;
+            if i == index {
+                return Ok(Some(element));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Looks up a value by RFC 6901 JSON Pointer, reading only the members
+    /// and elements on the way to it. Returns `Ok(None)` when the pointer
+    /// is malformed or does not resolve.
+    pub fn pointer(&self, pointer: &str) -> Result<Option<LazyValue<'a>>> {
+        let Some(tokens) = pointer_tokens(pointer) else {
+            return Ok(None);
+        };
+        let mut node = *self;
+        for token in &tokens {
+            let next = match node.type_name() {
+                "object" => node.get(token)?,
+                "array" => match parse_index(token) {
+                    Some(index) => node.get_index(index)?,
+                    None => None,
+                },
+                _ => None,
+            };
+            match next {
+                Some(next) => node = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(node))
+    }
+}
+
+/// Iterator returned by [`LazyValue::members`].
+#[derive(Debug, Clone)]
+pub struct LazyMembers<'a> {
+    /// Just inside the opening brace, or after the last member read.
+    skimmer: Skimmer<'a>,
+    done: bool,
+    first: bool,
+}
+
+impl<'a> LazyMembers<'a> {
+    fn member(&mut self) -> Result<Option<(Cow<'a, str>, LazyValue<'a>)>> {
+        let skimmer = &mut self.skimmer;
+        if !skimmer.separator(b'}', ExpectedToken::RightBrace, self.first)? {
+            return Ok(None);
+        }
+        self.first = false;
+        let key = skimmer.key()?;
+        let value = skimmer.value()?;
+        Ok(Some((key, value)))
+    }
+}
+
+impl<'a> Iterator for LazyMembers<'a> {
+    type Item = Result<(Cow<'a, str>, LazyValue<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let member = self.member();
+        self.done = !matches!(member, Ok(Some(_)));
+        member.transpose()
+    }
+}
+
+/// Iterator returned by [`LazyValue::elements`].
+#[derive(Debug, Clone)]
+pub struct LazyElements<'a> {
+    /// Just inside the opening bracket, or after the last element read.
+    skimmer: Skimmer<'a>,
+    done: bool,
+    first: bool,
+}
+
+impl<'a> Iterator for LazyElements<'a> {
+    type Item = Result<LazyValue<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let element = self
+            .skimmer
+            .separator(b']', ExpectedToken::RightBracket, self.first)
+            .and_then(|more| more.then(|| self.skimmer.value()).transpose());
+        self.first = false;
+        self.done = !matches!(element, Ok(Some(_)));
+        element.transpose()
+    }
+}
+
+/// Finds where values end, checking that brackets pair up and that
+/// strings are well formed, without building anything.
+#[derive(Debug, Clone)]
+struct Skimmer<'a> {
+    scan: Scanner<'a>,
+}
+
+impl<'a> Skimmer<'a> {
+    fn new(text: &'a str, pos: usize) -> Self {
+        Self {
+            scan: Scanner::new(text, pos),
+        }
+    }
+
+    /// Moves past the `,` before the next member or element, returning
+    /// `false` instead at the `close` bracket. The first has no comma.
+    fn separator(&mut self, close: u8, closing: ExpectedToken, first: bool) -> Result<bool> {
+        self.scan.skip_whitespace();
+        if self.scan.peek() == Some(close) {
+            return Ok(false);
+        }
+        if !first {
+            if self.scan.peek() != Some(b',') {
+                return Err(self.scan.unexpected(vec![ExpectedToken::Comma, closing]));
+            }
+            self.scan.pos += 1;
+        }
+        Ok(true)
+    }
+
+    /// Reads the key of a member and the colon after it.
+    fn key(&mut self) -> Result<Cow<'a, str>> {
+        self.scan.skip_whitespace();
+        if self.scan.peek() != Some(b'"') {
+            return Err(self.scan.unexpected(vec![ExpectedToken::Key]));
+        }
+        let start = self.scan.pos;
+        self.scan.string()?;
+        let end = self.scan.pos;
+        self.scan.skip_whitespace();
+        if self.scan.peek() != Some(b':') {
+            return Err(self.scan.unexpected(vec![ExpectedToken::Colon]));
+        }
+        self.scan.pos += 1;
+        decode(self.scan.text, start, end)
+    }
+
+    /// Skims the value after any whitespace at `pos` and moves past it.
+    fn value(&mut self) -> Result<LazyValue<'a>> {
+        self.scan.skip_whitespace();
+        let start = self.scan.pos;
+        match self.scan.peek() {
+            None | Some(b',' | b':' | b']' | b'}') => {
+                return Err(self.scan.unexpected(vec![ExpectedToken::Value]));
+            }
+            Some(b'"') => {
+                self.scan.string()?;
+            }
+            Some(b'{' | b'[') => {
+                let mut closers = Vec::new();
+                loop {
+                    match self.scan.peek() {
+                        None => {
+                            let expected = match closers.last() {
+                                Some(b'}') => ExpectedToken::RightBrace,
+                                _ => ExpectedToken::RightBracket,
+                            };
+                            return Err(self.scan.unexpected(vec![expected]));
+                        }
+                        Some(b'"') => {
+                            self.scan.string()?;
+                            continue;
+                        }
+                        Some(b'{') => closers.push(b'}'),
+                        Some(b'[') => closers.push(b']'),
+                        Some(c @ (b'}' | b']')) => {
+                            if closers.pop() != Some(c) {
+                                return Err(self.scan.unexpected(vec![ExpectedToken::Value]));
+                            }
+                            if closers.is_empty() {
+                                self.scan.pos += 1;
+                                break;
+                            }
+                        }
+                        Some(_) => {}
+                    }
+                    self.scan.pos += 1;
+                }
+            }
+            Some(_) => {
+                while !matches!(
+                    self.scan.peek(),
+                    None | Some(b' ' | b'\t' | b'\n' | b'\r' | b',' | b':' | b']' | b'}')
+                ) {
+                    self.scan.pos += 1;
+                }
+            }
+        }
+        Ok(LazyValue {
+            text: self.scan.text,
+            start,
+            end: self.scan.pos,
+        })
+    }
+}
+
+/// The path of `child` in the innermost of the `open` containers of
+/// [`LazyValue::to_value`], or of that container when `child` is `None`.
+/// A container's count includes the child being read.
+fn reading_path(
+    open: &[(Option<String>, JsonValue, usize)],
+    child: Option<PathSegment>,
+) -> JsonPath {
+    let mut path = JsonPath::new();
+    for pair in open.windows(2) {
+        let [(_, parent, count), (key, _, _)] = pair else {
+            unreachable!("windows of two");
+        };
+        match (parent, key) {
+            (JsonValue::Object(_), Some(key)) => path.push_key(key.as_str()),
+            _ => path.push_index(count - 1),
+        }
+    }
+    if let Some(child) = child {
+        path.push(child);
+    }
+    path
+}
+
+/// The text of the string literal at `start..end` of `text`.
+fn decode(text: &str, start: usize, end: usize) -> Result<Cow<'_, str>> {
+    let literal = &text[start..end];
+    let inner = &literal[1..literal.len() - 1];
+    if inner.contains('\\') {
+        unescape_json_string(literal)
+            .map(Cow::Owned)
+            .map_err(|err| err.shifted(char_offset(text, start)))
+    } else {
+        Ok(Cow::Borrowed(inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonErrorKind;
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_reads_only_what_is_asked() {
+        // Everything after "a" is broken, but is only read when asked for.
+        let doc = LazyValue::parse(r#"{"a": [1, {"b": "x\"y"}], "z": [tru,, {"k" 1}]}"#).unwrap();
+        let b = doc.pointer("/a/1/b").unwrap().unwrap();
+        assert_eq!(b.raw(), r#""x\"y""#);
+        assert_eq!(b.as_str().unwrap().as_deref(), Some("x\"y"));
+        assert!(matches!(
+            doc.get("a").unwrap().unwrap().get_index(0),
+            Ok(Some(v)) if v.raw() == "1"
+        ));
+        let z = doc.get("z").unwrap().unwrap();
+        assert!(z.to_value().is_err());
+        assert!(z.get_index(2).is_err());
+        assert!(doc.pointer("/a/5").unwrap().is_none());
+        assert!(doc.pointer("/a/x").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_to_value_matches_parser() {
+        let input = r#" {"a": [1, 2.5e1, null], "b": {"c": "d\u00e9"}, "e": true} "#;
+        let doc = LazyValue::parse(input).unwrap();
+        assert_eq!(doc.to_value().unwrap(), parse_json(input).unwrap());
+        assert_eq!(
+            doc.get("b").unwrap().unwrap().to_value().unwrap(),
+            parse_json(r#"{"c": "dé"}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_to_value_of_deep_nesting() {
+        let depth = 100_000;
+        let input = "{\"a\": [".repeat(depth) + "1" + &"]}".repeat(depth);
+        let value = LazyValue::parse(&input).unwrap().to_value().unwrap();
+        let mut node = &value;
+        for _ in 0..depth {
+            node = node.get("a").unwrap().get_index(0).unwrap();
+        }
+        assert_eq!(node, &JsonValue::Number(1.0));
+    }
+
+    #[test]
+    fn test_to_value_errors_carry_the_path() {
+        let input = r#"{"a": [1, {"b": tru}], "c": [1 2]}"#;
+        let doc = LazyValue::parse(input).unwrap();
+        let err = doc.to_value().unwrap_err();
+        assert_eq!(
+            err.path().map(|path| path.to_string()).as_deref(),
+            Some("/a/1/b")
+        );
+        assert_eq!(err.position(), Some(16));
+        let err = doc.get("c").unwrap().unwrap().to_value().unwrap_err();
+        assert_eq!(err.position(), Some(31));
+    }
+
+    #[test]
+    fn test_iterates_members_and_elements() {
+        let doc = LazyValue::parse(r#"{"a": [], "b\n": {"x": [1, [2]]}}"#).unwrap();
+        let members: Vec<(String, &str)> = doc
+            .members()
+            .map(|member| {
+                let (key, value) = member.unwrap();
+                (key.into_owned(), value.type_name())
+            })
+            .collect();
+        assert_eq!(
+            members,
+            [("a".to_string(), "array"), ("b\n".to_string(), "object")]
+        );
+        let x = doc.pointer("/b\n/x").unwrap().unwrap();
+        let raws: Vec<&str> = x.elements().map(|e| e.unwrap().raw()).collect();
+        assert_eq!(raws, ["1", "[2]"]);
+        assert_eq!(doc.get("a").unwrap().unwrap().elements().count(), 0);
+        assert_eq!(x.members().count(), 0);
+    }
+
+    #[test]
+    fn test_skimmed_strings_are_checked() {
+        let cases = [
+            (r#"["x\q", 1]"#, JsonErrorKind::InvalidEscape, 3),
+            (r#"["\é", 1]"#, JsonErrorKind::InvalidEscape, 2),
+            ("[\"a\u{1}b\", 1]", JsonErrorKind::UnexpectedToken, 3),
+            (r#"[{"k": "\ud800"}, 1]"#, JsonErrorKind::InvalidUnicode, 8),
+            (r#"[["\u12"], 1]"#, JsonErrorKind::InvalidUnicode, 3),
+        ];
+        for (input, kind, position) in cases {
+            let err = LazyValue::parse(input).unwrap_err();
+            assert_eq!(err.kind(), kind, "{input}");
+            assert_eq!(err.position(), Some(position), "{input}");
+        }
+        let doc = LazyValue::parse(r#"{"a\u00e9": 1, "b": "\"\/"}"#).unwrap();
+        assert_eq!(doc.get("aé").unwrap().unwrap().raw(), "1");
+        assert_eq!(
+            doc.get("b").unwrap().unwrap().as_str().unwrap().as_deref(),
+            Some("\"/")
+        );
+    }
+
+    #[test]
+    fn test_errors_are_positioned_in_the_whole_text() {
+        assert!(LazyValue::parse("").is_err());
+        assert!(LazyValue::parse("[1, 2").is_err());
+        assert!(LazyValue::parse("[1} ").is_err());
+        assert!(LazyValue::parse("[1] 2").is_err());
+
+        let doc = LazyValue::parse(r#"{"a": [1 2], "b": tru}"#).unwrap();
+        let err = doc.get("a").unwrap().unwrap().elements().nth(1).unwrap();
+        assert_eq!(err.unwrap_err().position(), Some(9));
+        let err = doc.get("b").unwrap().unwrap().to_value().unwrap_err();
+        assert_eq!(err.position(), Some(18));
+        let mut elements = LazyValue::parse("[1 2, 3]").unwrap().elements();
+        assert!(elements.next().unwrap().is_ok());
+        assert!(elements.next().unwrap().is_err());
+        assert!(elements.next().is_none());
+    }
+}
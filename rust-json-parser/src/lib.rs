@@ -1,14 +1,120 @@
 // Declare modules
+mod access;
+#[cfg(feature = "arena")]
+mod arena;
+mod array;
+mod canonical;
+pub mod codec;
+mod comments;
+mod conformance;
+mod cursor;
+#[cfg(feature = "diagnostics")]
+mod diagnostic;
+mod document;
+mod equivalent;
 mod error;
+pub mod features;
+mod find;
+mod generate;
+mod intern;
+mod lazy;
+mod line_index;
+mod lint;
+mod manifest;
+mod mask;
+mod merge;
+mod minify;
+mod node;
+mod normalize;
+mod object;
+mod options;
 mod parser;
+pub mod patch;
+mod path;
+mod pattern;
+mod pool;
+mod progress;
+mod query;
+mod raw;
+mod recovery;
+mod render;
+mod report;
+mod scan;
+pub mod schema;
+mod select;
+mod ser;
+mod sha256;
+mod shape;
+mod size;
+mod stats;
+mod stream;
+mod string;
+mod summary;
+mod tape;
+#[cfg(test)]
+mod test_support;
+#[cfg(feature = "testing")]
+pub mod testing;
 mod tokenizer;
+mod transform;
+pub mod typescript;
+mod validate;
 mod value;
+mod visit;
+mod writer;
 
 // Re-export for clean API
-pub use error::JsonError;
+pub use access::{JsonAccess, JsonAccessError};
+#[cfg(feature = "arena")]
+pub use arena::{Arena, JsonValueRef};
+pub use array::JsonArray;
+pub use comments::Comments;
+pub use conformance::{CaseResult, ConformanceReport, Expected, Outcome, run_conformance};
+pub use cursor::{JsonCursor, JsonCursorMut};
+#[cfg(feature = "diagnostics")]
+pub use diagnostic::{Diagnostic, Label};
+pub use document::Document;
+pub use equivalent::EqOptions;
+pub use error::{ExpectedToken, JsonError, JsonErrorKind};
+pub use find::Find;
+pub use generate::Generator;
+pub use intern::Interner;
+pub use lazy::{LazyElements, LazyMembers, LazyValue};
+pub use line_index::{LineCol, LineIndex};
+pub use lint::{LintOptions, lint, lint_with};
+pub use manifest::{
+    Manifest, ManifestError, NodeCounts, load_verified, manifest_path, save_with_manifest,
+};
+pub use mask::{MaskRules, MaskedDisplay};
+pub use merge::{ArrayMerge, Conflict, MergeStrategy, merge3};
+pub use minify::{minify, minify_with};
+pub use normalize::{NormalizeOptions, TrimStrings};
+pub use object::{Entry, JsonObject, OccupiedEntry, VacantEntry};
+pub use options::{
+    DuplicateKeys, Limits, ParserOptions, Redaction, SuffixHandler, byte_size_suffix,
+    duration_suffix,
+};
 pub use parser::JsonParser;
+pub use path::{JsonPath, PathSegment, Paths};
+pub use progress::parse_with_progress;
+pub use query::CompiledPath;
+pub use raw::{get_raw, set_raw};
+pub use recovery::{Recovery, parse_with_recovery};
+pub use render::{render_tokens, write_tokens};
+pub use report::{JsonReport, JsonWarning, WarningKind};
+pub use select::Selection;
+pub use ser::{FormatConfig, NonFinite, to_string_pretty};
+pub use shape::{Shape, ShapeMismatch};
+pub use stats::{FieldSpec, FieldStats, Histogram, ScanSummary, StreamStats};
+pub use stream::{StreamError, TokenReader, project, transcode};
+pub use string::JsonString;
+pub use summary::Summary;
+pub use tape::{Tape, TapeElements, TapeMembers, TapeValue, parse_to_tape};
 pub use tokenizer::{Token, Tokenizer};
-pub use value::JsonValue;
+pub use validate::{FileError, FileReport, validate_dir};
+pub use value::{JsonValue, PruneOptions, escape_json_string, unescape_json_string};
+pub use visit::{JsonVisitor, JsonVisitorMut};
+pub use writer::{JsonWriter, WriterError};
 
 // Convenience type alias
 pub type Result<T> = std::result::Result<T, JsonError>;
@@ -16,10 +122,8 @@ pub type Result<T> = std::result::Result<T, JsonError>;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::parse_json;
 
-    fn parse_json(input: &str) -> Result<JsonValue> {
-        JsonParser::new(input)?.parse()
-    }
     #[test]
     fn test_integration() {
         // Test the full parsing pipeline
@@ -28,7 +132,7 @@ mod tests {
         assert_eq!(parse_json("null").unwrap(), JsonValue::Null);
         assert_eq!(
             parse_json(r#""hello""#).unwrap(),
-            JsonValue::String("hello".to_string())
+            JsonValue::String("hello".into())
         );
     }
     #[test]
@@ -42,8 +146,9 @@ mod tests {
                 expected,
                 found,
                 position,
+                ..
             }) => {
-                assert_eq!(expected, "valid JSON value");
+                assert_eq!(expected, [ExpectedToken::Value]);
                 assert_eq!(found, "@");
                 assert_eq!(position, 0);
             }
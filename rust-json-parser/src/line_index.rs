@@ -0,0 +1,130 @@
+use std::ops::Range;
+
+/// A zero-based line and byte column in a source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    pub line: usize,
+    /// Bytes from the start of the line.
+    pub column: usize,
+}
+
+/// Where each line of a source text starts, for converting between byte
+/// offsets and [`LineCol`] without rescanning the text on every lookup.
+///
+/// Lines end after each `\n`; a `\r` before it counts as part of the line.
+/// Building the index is O(n) in the text length, and each conversion is
+/// O(log n) in the number of lines.
+///
+/// ```
+/// use rust_json_parser::{JsonParser, LineCol, LineIndex};
+///
+/// let input = "{\n  \"a\": tru\n}";
+/// let index = LineIndex::new(input);
+/// let err = JsonParser::new(input).and_then(|mut p| p.parse()).unwrap_err();
+/// let start = err.byte_range(input).unwrap().start;
+/// assert_eq!(index.line_col(start), Some(LineCol { line: 1, column: 7 }));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Byte offset of the start of each line; the first is always 0.
+    starts: Vec<usize>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            starts,
+            len: source.len(),
+        }
+    }
+
+    /// Number of lines; text ending in `\n` has an empty last line.
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// The line and column of byte `offset`, or `None` past the end of the
+    /// text. The end of the text itself is a valid offset.
+    pub fn line_col(&self, offset: usize) -> Option<LineCol> {
+        if offset > self.len {
+            return None;
+        }
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        Some(LineCol {
+            line,
+            column: offset - self.starts[line],
+        })
+    }
+
+    /// The byte offset of `position`, or `None` when the line does not
+    /// exist or is shorter than the column. The column may point just past
+    /// the last character of the line, at its line break.
+    pub fn offset(&self, position: LineCol) -> Option<usize> {
+        let line = self.line_range(position.line)?;
+        let offset = line.start + position.column;
+        (offset <= line.end).then_some(offset)
+    }
+
+    /// The bytes of line `line`, without its `\n`.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.starts.get(line)?;
+        let end = self.starts.get(line + 1).map_or(self.len, |next| next - 1);
+        Some(start..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(line: usize, column: usize) -> LineCol {
+        LineCol { line, column }
+    }
+
+    #[test]
+    fn test_offsets_to_line_col_and_back() {
+        let source = "[1,\r\n 2,\n\n \"é\"]\n";
+        let index = LineIndex::new(source);
+        assert_eq!(index.line_count(), 5);
+        for (offset, expected) in [
+            (0, at(0, 0)),
+            (3, at(0, 3)),
+            (4, at(0, 4)),
+            (5, at(1, 0)),
+            (8, at(1, 3)),
+            (9, at(2, 0)),
+            (10, at(3, 0)),
+            (12, at(3, 2)),
+            (14, at(3, 4)),
+            (source.len(), at(4, 0)),
+        ] {
+            assert_eq!(index.line_col(offset), Some(expected), "offset {offset}");
+            assert_eq!(index.offset(expected), Some(offset), "{expected:?}");
+        }
+        assert_eq!(index.line_col(source.len() + 1), None);
+    }
+
+    #[test]
+    fn test_out_of_range_positions() {
+        let index = LineIndex::new("ab\ncd");
+        assert_eq!(index.offset(at(0, 2)), Some(2));
+        assert_eq!(index.offset(at(0, 3)), None);
+        assert_eq!(index.offset(at(1, 2)), Some(5));
+        assert_eq!(index.offset(at(2, 0)), None);
+        assert_eq!(index.line_range(0), Some(0..2));
+        assert_eq!(index.line_range(1), Some(3..5));
+        assert_eq!(index.line_range(2), None);
+    }
+
+    #[test]
+    fn test_empty_source() {
+        let index = LineIndex::new("");
+        assert_eq!(index.line_count(), 1);
+        assert_eq!(index.line_col(0), Some(at(0, 0)));
+        assert_eq!(index.line_range(0), Some(0..0));
+    }
+}
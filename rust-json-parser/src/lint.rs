@@ -0,0 +1,536 @@
+use crate::path::OpenPath;
+use crate::{
+    ExpectedToken, JsonError, JsonPath, JsonReport, JsonWarning, ParserOptions, Token, Tokenizer,
+    WarningKind,
+};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// Thresholds for [`lint_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LintOptions {
+    pub(crate) max_depth: usize,
+    pub(crate) max_string_chars: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 32,
+            max_string_chars: 4096,
+        }
+    }
+}
+
+impl LintOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nesting past which containers are flagged; a top-level container is
+    /// depth 1. Defaults to 32.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// String length, in characters, past which strings are flagged.
+    /// Defaults to 4096.
+    pub fn max_string_chars(mut self, chars: usize) -> Self {
+        self.max_string_chars = chars;
+        self
+    }
+}
+
+/// Checks JSON text for valid but questionable constructs: duplicate keys,
+/// numbers that lose precision as `f64`, deep nesting, very long strings
+/// and escapes that are not needed.
+///
+/// A document that does not parse is reported as an error with no
+/// warnings, so [`JsonReport::is_ok`] says whether the input is valid and
+/// [`JsonReport::is_empty`] whether it is also clean.
+pub fn lint(input: &str) -> JsonReport {
+    lint_with(input, &LintOptions::default())
+}
+
+/// [`lint`] with thresholds from `options`.
+pub fn lint_with(input: &str, options: &LintOptions) -> JsonReport {
+    let mut report = JsonReport::new();
+    let mut tokenizer = Tokenizer::with_options(input, ParserOptions::default());
+    let checked = tokenizer
+        .tokenize_with_paths()
+        .and_then(|tokens| check_syntax(&tokens, tokenizer.char_count()).map(|()| tokens));
+    let tokens = match checked {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            report.errors.push(error);
+            return report;
+        }
+    };
+
+    let mut linter = Linter {
+        options,
+        warnings: Vec::new(),
+        path: JsonPath::new(),
+        stack: Vec::new(),
+    };
+    for (token, span) in tokens {
+        let raw: String = tokenizer.text(span.clone()).collect();
+        linter.token(token, &raw, span.start);
+    }
+    report.warnings = linter.warnings;
+    report
+}
+
+/// What the next token of a document must be.
+enum Expect {
+    Value,
+    /// The first element of an array, or its `]`.
+    FirstElement,
+    /// A key, or the object's `}` when `first`.
+    Key {
+        first: bool,
+    },
+    Colon,
+    CommaOrClose,
+}
+
+/// Checks that `tokens` start with one value, reporting the error
+/// [`JsonParser::parse`](crate::JsonParser::parse) gives for the same
+/// text with default options. Open containers are kept on a stack, so
+/// nesting of any depth is checked without recursing; `end` is the
+/// character offset of the end of input.
+fn check_syntax(tokens: &[(Token, Range<usize>)], end: usize) -> Result<(), JsonError> {
+    // Whether each open container is an object
+    let mut open: Vec<bool> = Vec::new();
+    let mut path = OpenPath::default();
+    let mut expect = Expect::Value;
+    let mut tokens = tokens.iter().peekable();
+    let closes = |open: &[bool], token: &Token| match open.last() {
+        Some(true) => *token == Token::RightBrace,
+        Some(false) => *token == Token::RightBracket,
+        None => false,
+    };
+    loop {
+        let expected = match expect {
+            Expect::Value | Expect::FirstElement => vec![ExpectedToken::Value],
+            Expect::Key { first: true } => vec![ExpectedToken::Key, ExpectedToken::RightBrace],
+            Expect::Key { first: false } => vec![ExpectedToken::Key],
+            Expect::Colon => vec![ExpectedToken::Colon],
+            Expect::CommaOrClose if open.last() == Some(&true) => {
+                vec![ExpectedToken::Comma, ExpectedToken::RightBrace]
+            }
+            Expect::CommaOrClose => vec![ExpectedToken::Comma, ExpectedToken::RightBracket],
+        };
+        let Some((token, span)) = tokens.next() else {
+            return Err(JsonError::UnexpectedEndOfInput {
+                expected,
+                position: end,
+                path: path.path(),
+            });
+        };
+        let completed = match (&expect, token) {
+            (Expect::Value | Expect::FirstElement, Token::LeftBracket | Token::LeftBrace) => {
+                let object = *token == Token::LeftBrace;
+                open.push(object);
+                expect = if object {
+                    Expect::Key { first: true }
+                } else {
+                    Expect::FirstElement
+                };
+                false
+            }
+            (
+                Expect::Value | Expect::FirstElement,
+                Token::Null | Token::Boolean(_) | Token::Number(_) | Token::String(_),
+            ) => true,
+            (Expect::FirstElement, Token::RightBracket)
+            | (Expect::Key { first: true }, Token::RightBrace) => {
+                open.pop();
+                true
+            }
+            (Expect::Key { .. }, Token::String(_)) => {
+                expect = Expect::Colon;
+                false
+            }
+            (Expect::Colon, Token::Colon) => {
+                expect = Expect::Value;
+                false
+            }
+            (Expect::CommaOrClose, Token::Comma) => {
+                if tokens.peek().is_some_and(|(next, _)| closes(&open, next)) {
+                    return Err(JsonError::DisabledFeature {
+                        feature: "trailing commas".to_string(),
+                        option: "allow_trailing_commas".to_string(),
+                        position: span.start,
+                        path: path.path(),
+                    });
+                }
+                let object = *open
+                    .last()
+                    .expect("a comma is only expected in a container");
+                expect = if object {
+                    Expect::Key { first: false }
+                } else {
+                    Expect::Value
+                };
+                false
+            }
+            (Expect::CommaOrClose, token) if closes(&open, token) => {
+                open.pop();
+                true
+            }
+            _ => {
+                return Err(JsonError::UnexpectedToken {
+                    expected,
+                    found: format!("{token:?}"),
+                    position: span.start,
+                    path: path.path(),
+                });
+            }
+        };
+        path.step(token);
+        if completed {
+            if open.is_empty() {
+                // What follows the value is left alone, as the parser does
+                return Ok(());
+            }
+            expect = Expect::CommaOrClose;
+        }
+    }
+}
+
+/// An open container.
+struct Frame {
+    /// Keys seen so far, for objects.
+    keys: Option<HashSet<String>>,
+    /// The next token is a key.
+    expect_key: bool,
+    /// Key of the member whose value comes next.
+    key: Option<String>,
+    /// Children seen so far, for arrays.
+    len: usize,
+}
+
+struct Linter<'a> {
+    options: &'a LintOptions,
+    warnings: Vec<JsonWarning>,
+    /// Path of the innermost open container.
+    path: JsonPath,
+    stack: Vec<Frame>,
+}
+
+impl Linter<'_> {
+    fn token(&mut self, token: Token, raw: &str, position: usize) {
+        match token {
+            Token::Comma => {
+                if let Some(frame) = self.stack.last_mut() {
+                    frame.expect_key = frame.keys.is_some();
+                }
+            }
+            Token::Colon => {}
+            Token::RightBrace | Token::RightBracket => {
+                self.stack.pop();
+                if !self.stack.is_empty() {
+                    self.path.pop();
+                }
+            }
+            Token::String(key) if self.stack.last().is_some_and(|frame| frame.expect_key) => {
+                self.escapes(raw, position, &key);
+                let frame = self.stack.last_mut().expect("checked above");
+                frame.expect_key = false;
+                let keys = frame.keys.as_mut().expect("only objects expect keys");
+                let duplicate = !keys.insert(key.clone());
+                frame.key = Some(key.clone());
+                if duplicate {
+                    let mut path = self.path.clone();
+                    path.push_key(key);
+                    self.warn(
+                        WarningKind::DuplicateKey,
+                        format!("Duplicate key {raw}; only the last value is kept"),
+                        position,
+                        path,
+                    );
+                }
+            }
+            value => self.value(value, raw, position),
+        }
+    }
+
+    fn value(&mut self, token: Token, raw: &str, position: usize) {
+        // The value's segment goes on `path` for as long as it is read: to
+        // its end for a container, just for this call for a scalar
+        let in_container = match self.stack.last_mut() {
+            Some(frame) => {
+                match frame.key.take() {
+                    Some(key) => self.path.push_key(key),
+                    None => {
+                        self.path.push_index(frame.len);
+                        frame.len += 1;
+                    }
+                }
+                true
+            }
+            None => false,
+        };
+        match token {
+            Token::LeftBrace | Token::LeftBracket => {
+                let depth = self.stack.len() + 1;
+                if depth == self.options.max_depth + 1 {
+                    self.warn(
+                        WarningKind::DeepNesting,
+                        format!("Nesting depth exceeds {}", self.options.max_depth),
+                        position,
+                        self.path.clone(),
+                    );
+                }
+                let object = token == Token::LeftBrace;
+                self.stack.push(Frame {
+                    keys: object.then(HashSet::new),
+                    expect_key: object,
+                    key: None,
+                    len: 0,
+                });
+                return;
+            }
+            Token::String(s) => {
+                let len = s.chars().count();
+                if len > self.options.max_string_chars {
+                    self.warn(
+                        WarningKind::LongString,
+                        format!(
+                            "String of {len} characters exceeds {}",
+                            self.options.max_string_chars
+                        ),
+                        position,
+                        self.path.clone(),
+                    );
+                }
+                if let Some((offset, message)) = non_canonical_escape(raw) {
+                    self.warn(
+                        WarningKind::NonCanonicalEscape,
+                        message,
+                        position + offset,
+                        self.path.clone(),
+                    );
+                }
+            }
+            Token::Number(n)
+                if significant_digits(raw) != significant_digits(&format!("{:e}", n.abs())) =>
+            {
+                self.warn(
+                    WarningKind::PrecisionLoss,
+                    format!("Number {raw} is read as {n}"),
+                    position,
+                    self.path.clone(),
+                );
+            }
+            _ => {}
+        }
+        if in_container {
+            self.path.pop();
+        }
+    }
+
+    fn escapes(&mut self, raw: &str, position: usize, key: &str) {
+        if let Some((offset, message)) = non_canonical_escape(raw) {
+            let mut path = self.path.clone();
+            path.push_key(key);
+            self.warn(
+                WarningKind::NonCanonicalEscape,
+                message,
+                position + offset,
+                path,
+            );
+        }
+    }
+
+    fn warn(&mut self, kind: WarningKind, message: String, position: usize, path: JsonPath) {
+        self.warnings.push(JsonWarning {
+            kind,
+            message,
+            position,
+            path,
+        });
+    }
+}
+
+/// The first escape in the string literal `raw` that is not needed or has
+/// a shorter form, as a character offset into `raw` and a message.
+fn non_canonical_escape(raw: &str) -> Option<(usize, String)> {
+    let chars: Vec<char> = raw.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '\\' {
+            i += 1;
+            continue;
+        }
+        match chars.get(i + 1) {
+            Some('/') => return Some((i, "Escape \\/ is not needed; write /".to_string())),
+            Some('u') => {
+                let hex: String = chars[i + 2..(i + 6).min(chars.len())].iter().collect();
+                let c = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                let shorter = match c {
+                    Some('"') => Some("\\\"".to_string()),
+                    Some('\\') => Some("\\\\".to_string()),
+                    Some('\u{8}') => Some("\\b".to_string()),
+                    Some('\u{c}') => Some("\\f".to_string()),
+                    Some('\n') => Some("\\n".to_string()),
+                    Some('\r') => Some("\\r".to_string()),
+                    Some('\t') => Some("\\t".to_string()),
+                    Some(c @ ' '..='~') => Some(c.to_string()),
+                    _ => None,
+                };
+                if let Some(shorter) = shorter {
+                    return Some((i, format!("Escape \\u{hex} can be written as {shorter}")));
+                }
+                i += 6;
+            }
+            _ => i += 2,
+        }
+    }
+    None
+}
+
+/// The digits of a decimal number without leading or trailing zeros, and
+/// the power of ten of the last one, so equal values compare equal however
+/// they are spelled.
+fn significant_digits(number: &str) -> (String, i64) {
+    let number = number.trim_start_matches('-');
+    let (mantissa, exponent) = number
+        .split_once(['e', 'E'])
+        .map_or((number, 0), |(m, e)| (m, e.parse().unwrap_or(0)));
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{int}{frac}");
+    let digits = digits.trim_start_matches('0');
+    let trimmed = digits.trim_end_matches('0');
+    if trimmed.is_empty() {
+        return (String::new(), 0);
+    }
+    let exponent = exponent - frac.len() as i64 + (digits.len() - trimmed.len()) as i64;
+    (trimmed.to_string(), exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<(WarningKind, String, usize)> {
+        lint(input)
+            .warnings
+            .into_iter()
+            .map(|w| (w.kind, w.path.to_string(), w.position))
+            .collect()
+    }
+
+    #[test]
+    fn test_clean_document() {
+        let report = lint(r#"{"a": [1, 2.5, -0, 1e3, 0.1], "b": "café \n"}"#);
+        assert!(report.is_empty(), "{report}");
+    }
+
+    #[test]
+    fn test_flags_each_kind() {
+        let cases = vec![
+            (
+                r#"{"a": 1, "b": {"x": 1, "x": 2}}"#,
+                (WarningKind::DuplicateKey, "/b/x", 23),
+            ),
+            (
+                "[1, 12345678901234567890]",
+                (WarningKind::PrecisionLoss, "/1", 4),
+            ),
+            ("[1e400]", (WarningKind::PrecisionLoss, "/0", 1)),
+            (
+                r#"{"url": "a\/b"}"#,
+                (WarningKind::NonCanonicalEscape, "/url", 10),
+            ),
+            (r#"["\u0041"]"#, (WarningKind::NonCanonicalEscape, "/0", 2)),
+            (
+                r#"{"\u000a": 1}"#,
+                (WarningKind::NonCanonicalEscape, "/\n", 2),
+            ),
+        ];
+        for (input, (kind, path, position)) in cases {
+            assert_eq!(
+                kinds(input),
+                vec![(kind, path.to_string(), position)],
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let options = LintOptions::new().max_depth(2).max_string_chars(3);
+        let report = lint_with(r#"{"a": [[1], "abcd", "abc"]}"#, &options);
+        let found: Vec<_> = report
+            .warnings
+            .iter()
+            .map(|w| (w.kind, w.path.to_string()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (WarningKind::DeepNesting, "/a/0".to_string()),
+                (WarningKind::LongString, "/a/1".to_string()),
+            ]
+        );
+        assert!(report.is_ok());
+        assert!(report.to_string().ends_with("0 errors, 2 warnings"));
+    }
+
+    #[test]
+    fn test_invalid_input_is_an_error() {
+        let report = lint("[1, 2");
+        assert!(!report.is_ok());
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_errors_match_the_parser() {
+        for input in [
+            "",
+            "[1, 2",
+            "[1 2]",
+            "[1,]",
+            r#"{"a": 1,}"#,
+            r#"{"a" 1}"#,
+            r#"{"a": }"#,
+            "{1: 2}",
+            r#"{"a": [1, {"b": ]}]}"#,
+            r#"{"a": 1 "b": 2}"#,
+            "[}",
+            "{]",
+            "]",
+            r#"{"a": [1,"#,
+        ] {
+            let parsed = crate::JsonParser::new(input).and_then(|mut parser| parser.parse());
+            assert_eq!(lint(input).errors, vec![parsed.unwrap_err()], "{input}");
+        }
+        assert!(lint("[1] 2").is_ok());
+    }
+
+    #[test]
+    fn test_deep_nesting_is_checked_without_recursing() {
+        let depth = 200_000;
+        let deep = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let report = lint(&deep);
+        assert!(report.is_ok());
+        let kinds: Vec<_> = report.warnings.iter().map(|w| w.kind).collect();
+        assert_eq!(kinds, vec![WarningKind::DeepNesting]);
+
+        let report = lint(&deep[..deep.len() - 1]);
+        assert!(matches!(
+            report.errors[..],
+            [JsonError::UnexpectedEndOfInput { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_significant_digits() {
+        assert_eq!(significant_digits("1.50e2"), significant_digits("150"));
+        assert_eq!(significant_digits("0.001"), ("1".to_string(), -3));
+        assert_eq!(significant_digits("-0.0"), (String::new(), 0));
+    }
+}
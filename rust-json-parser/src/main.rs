@@ -1,6 +1,65 @@
-use rust_json_parser::{JsonParser, JsonValue, Result, Tokenizer};
+use rust_json_parser::{JsonParser, JsonValue, Limits, Result, Tokenizer, validate_dir};
+use std::env;
+use std::process::ExitCode;
 
-fn main() {
+const USAGE: &str = "usage: jsonp check-dir <dir> [threads]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("check-dir") => check_dir(&args[1..]),
+        Some(_) => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+        None => {
+            run_examples();
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// `jsonp check-dir <dir> [threads]`: validates every `*.json` file under
+/// `dir` and prints the ones that fail. Exits with 1 if any did, 2 if the
+/// directory could not be read.
+fn check_dir(args: &[String]) -> ExitCode {
+    let (dir, threads) = match args {
+        [dir] => (dir, 0),
+        [dir, threads] => match threads.parse() {
+            Ok(threads) => (dir, threads),
+            Err(_) => {
+                eprintln!("{USAGE}");
+                return ExitCode::from(2);
+            }
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let reports = match validate_dir(dir, &Limits::untrusted(), threads) {
+        Ok(reports) => reports,
+        Err(e) => {
+            eprintln!("{dir}: {e}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut failed = 0;
+    for report in &reports {
+        if let Some(error) = &report.error {
+            println!("{}: {error}", report.path.display());
+            failed += 1;
+        }
+    }
+    println!("{} files checked, {failed} failed", reports.len());
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run_examples() {
     // Example 1: Complex Object
     let json1 = r#"{"name": "Alice", "age": 30}"#;
     println!("=== Processing Example 1 (Complex Object) ===");
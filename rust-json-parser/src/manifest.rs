@@ -0,0 +1,432 @@
+use crate::{
+    JsonAccess, JsonAccessError, JsonError, JsonObject, JsonParser, JsonValue, JsonVisitor, Limits,
+    ParserOptions, sha256,
+};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How many nodes of each JSON type a document holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NodeCounts {
+    pub nulls: usize,
+    pub booleans: usize,
+    pub numbers: usize,
+    pub strings: usize,
+    pub arrays: usize,
+    pub objects: usize,
+}
+
+impl NodeCounts {
+    pub fn total(&self) -> usize {
+        self.nulls + self.booleans + self.numbers + self.strings + self.arrays + self.objects
+    }
+}
+
+impl JsonVisitor for NodeCounts {
+    fn visit_null(&mut self) {
+        self.nulls += 1;
+    }
+    fn visit_bool(&mut self, _value: bool) {
+        self.booleans += 1;
+    }
+    fn visit_number(&mut self, _value: f64) {
+        self.numbers += 1;
+    }
+    fn visit_string(&mut self, _value: &str) {
+        self.strings += 1;
+    }
+    fn enter_array(&mut self, _array: &[JsonValue]) {
+        self.arrays += 1;
+    }
+    fn enter_object(&mut self, _object: &JsonObject) {
+        self.objects += 1;
+    }
+}
+
+impl JsonValue {
+    /// Counts every node in this value, including itself.
+    pub fn node_counts(&self) -> NodeCounts {
+        let mut counts = NodeCounts::default();
+        self.accept(&mut counts);
+        counts
+    }
+}
+
+/// Sidecar metadata describing a saved document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Manifest {
+    /// Version of this crate that wrote the document.
+    pub crate_version: String,
+    /// SHA-256 of the canonical form: compact, with object keys sorted.
+    pub sha256: String,
+    pub nodes: NodeCounts,
+    /// Limits the document must be loaded under. A time budget is not
+    /// recorded, since it depends on the machine doing the loading.
+    pub limits: Limits,
+}
+
+impl Manifest {
+    pub fn for_value(value: &JsonValue, limits: &Limits) -> Self {
+        Manifest {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            sha256: canonical_sha256(value),
+            nodes: value.node_counts(),
+            limits: Limits {
+                max_duration: None,
+                ..*limits
+            },
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let count = |n: usize| JsonValue::Number(n as f64);
+        let limit = |n: Option<usize>| n.map_or(JsonValue::Null, count);
+
+        let nodes: JsonObject = [
+            ("nulls", self.nodes.nulls),
+            ("booleans", self.nodes.booleans),
+            ("numbers", self.nodes.numbers),
+            ("strings", self.nodes.strings),
+            ("arrays", self.nodes.arrays),
+            ("objects", self.nodes.objects),
+        ]
+        .into_iter()
+        .map(|(k, n)| (k.to_string(), count(n)))
+        .collect();
+
+        let limits: JsonObject = [
+            ("max_depth", self.limits.max_depth),
+            ("max_input_bytes", self.limits.max_input_bytes),
+            ("max_tokens", self.limits.max_tokens),
+            ("max_string_chars", self.limits.max_string_chars),
+        ]
+        .into_iter()
+        .map(|(k, n)| (k.to_string(), limit(n)))
+        .collect();
+
+        let manifest: JsonObject = [
+            (
+                "crate_version",
+                JsonValue::String(self.crate_version.as_str().into()),
+            ),
+            ("sha256", JsonValue::String(self.sha256.as_str().into())),
+            ("nodes", JsonValue::Object(nodes)),
+            ("limits", JsonValue::Object(limits)),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        JsonValue::Object(manifest)
+    }
+
+    pub fn from_json(value: &JsonValue) -> Result<Self, JsonAccessError> {
+        let root = value.access();
+        let nodes = root.field("nodes")?;
+        let limits = root.field("limits")?;
+        Ok(Manifest {
+            crate_version: root.get_str("crate_version")?.to_string(),
+            sha256: root.get_str("sha256")?.to_string(),
+            nodes: NodeCounts {
+                nulls: count(&nodes.field("nulls")?)?,
+                booleans: count(&nodes.field("booleans")?)?,
+                numbers: count(&nodes.field("numbers")?)?,
+                strings: count(&nodes.field("strings")?)?,
+                arrays: count(&nodes.field("arrays")?)?,
+                objects: count(&nodes.field("objects")?)?,
+            },
+            limits: Limits {
+                max_depth: optional_count(&limits.field("max_depth")?)?,
+                max_input_bytes: optional_count(&limits.field("max_input_bytes")?)?,
+                max_tokens: added_limit(&limits, "max_tokens")?,
+                max_string_chars: added_limit(&limits, "max_string_chars")?,
+                max_duration: None,
+            },
+        })
+    }
+}
+
+/// Errors from [`save_with_manifest`] and [`load_verified`].
+#[derive(Debug)]
+pub enum ManifestError {
+    Io(io::Error),
+    /// The document or the manifest did not parse.
+    Json(JsonError),
+    /// The manifest parsed but is missing fields or has the wrong shape.
+    InvalidManifest(JsonAccessError),
+    /// The document does not match what the manifest recorded.
+    Mismatch {
+        field: &'static str,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io(err) => write!(f, "I/O error: {err}"),
+            ManifestError::Json(err) => write!(f, "{err}"),
+            ManifestError::InvalidManifest(err) => write!(f, "Invalid manifest: {err}"),
+            ManifestError::Mismatch {
+                field,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Document does not match its manifest: {field} is {actual}, expected {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ManifestError::Io(err) => Some(err),
+            ManifestError::Json(err) => Some(err),
+            ManifestError::InvalidManifest(err) => Some(err),
+            ManifestError::Mismatch { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ManifestError {
+    fn from(err: io::Error) -> Self {
+        ManifestError::Io(err)
+    }
+}
+
+impl From<JsonError> for ManifestError {
+    fn from(err: JsonError) -> Self {
+        ManifestError::Json(err)
+    }
+}
+
+impl From<JsonAccessError> for ManifestError {
+    fn from(err: JsonAccessError) -> Self {
+        ManifestError::InvalidManifest(err)
+    }
+}
+
+/// Where the manifest for the document at `path` lives: `<path>.manifest`.
+pub fn manifest_path(path: impl AsRef<Path>) -> PathBuf {
+    let mut name = OsString::from(path.as_ref());
+    name.push(".manifest");
+    PathBuf::from(name)
+}
+
+/// Writes `value` to `path` and its [`Manifest`] to [`manifest_path`].
+///
+/// Fails without writing anything if `value` itself exceeds `limits`, so a
+/// saved artifact can always be loaded back.
+pub fn save_with_manifest(
+    value: &JsonValue,
+    path: impl AsRef<Path>,
+    limits: &Limits,
+) -> Result<Manifest, ManifestError> {
+    let path = path.as_ref();
+    let text = value.to_string();
+    JsonParser::with_options(&text, ParserOptions::new().limits(*limits))?.parse()?;
+
+    let manifest = Manifest::for_value(value, limits);
+    fs::write(path, text)?;
+    fs::write(manifest_path(path), manifest.to_json().to_string())?;
+    Ok(manifest)
+}
+
+/// Loads a document saved by [`save_with_manifest`], checking it against its
+/// manifest: it must parse under the recorded limits and have the recorded
+/// hash and node counts.
+pub fn load_verified(path: impl AsRef<Path>) -> Result<JsonValue, ManifestError> {
+    let path = path.as_ref();
+    let manifest_text = fs::read_to_string(manifest_path(path))?;
+    let manifest = Manifest::from_json(&JsonParser::new(&manifest_text)?.parse()?)?;
+
+    let text = fs::read_to_string(path)?;
+    let options = ParserOptions::new().limits(manifest.limits);
+    let value = JsonParser::with_options(&text, options)?.parse()?;
+
+    let sha256 = canonical_sha256(&value);
+    if sha256 != manifest.sha256 {
+        return Err(ManifestError::Mismatch {
+            field: "sha256",
+            expected: manifest.sha256,
+            actual: sha256,
+        });
+    }
+    let nodes = value.node_counts();
+    if nodes != manifest.nodes {
+        return Err(ManifestError::Mismatch {
+            field: "node counts",
+            expected: format!("{:?}", manifest.nodes),
+            actual: format!("{nodes:?}"),
+        });
+    }
+    Ok(value)
+}
+
+fn canonical_sha256(value: &JsonValue) -> String {
+    let mut canonical = value.clone();
+    canonical.sort_keys();
+    sha256::hex_digest(canonical.to_string().as_bytes())
+}
+
+fn count(field: &JsonAccess<'_>) -> Result<usize, JsonAccessError> {
+    let n = field.as_f64()?;
+    if n >= 0.0 && n.fract() == 0.0 {
+        Ok(n as usize)
+    } else {
+        Err(JsonAccessError {
+            path: field.path().clone(),
+            expected: "non-negative integer",
+            found: Some("number"),
+        })
+    }
+}
+
+fn optional_count(field: &JsonAccess<'_>) -> Result<Option<usize>, JsonAccessError> {
+    if field.value().is_null() {
+        Ok(None)
+    } else {
+        count(field).map(Some)
+    }
+}
+
+/// A limit that manifests written before it existed leave out.
+fn added_limit(limits: &JsonAccess<'_>, key: &str) -> Result<Option<usize>, JsonAccessError> {
+    match limits.value().get(key) {
+        Some(_) => optional_count(&limits.field(key)?),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse_json;
+
+    fn scratch_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rust-json-parser-{name}-{}.json",
+            std::process::id()
+        ))
+    }
+
+    fn cleanup(path: &Path) {
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(manifest_path(path));
+    }
+
+    #[test]
+    fn test_node_counts() {
+        let value = parse_json(r#"{"a": [1, 2, null], "b": {"c": "x", "d": true}}"#).unwrap();
+        let counts = value.node_counts();
+        assert_eq!(
+            counts,
+            NodeCounts {
+                nulls: 1,
+                booleans: 1,
+                numbers: 2,
+                strings: 1,
+                arrays: 1,
+                objects: 2,
+            }
+        );
+        assert_eq!(counts.total(), 8);
+    }
+
+    #[test]
+    fn test_hash_ignores_key_order() {
+        let a = parse_json(r#"{"x": 1, "y": {"b": 2, "a": 3}}"#).unwrap();
+        let b = parse_json(r#"{"y": {"a": 3, "b": 2}, "x": 1}"#).unwrap();
+        assert_eq!(
+            Manifest::for_value(&a, &Limits::new()).sha256,
+            Manifest::for_value(&b, &Limits::new()).sha256
+        );
+    }
+
+    #[test]
+    fn test_manifest_json_round_trip() {
+        let value = parse_json("[1, {}]").unwrap();
+        let manifest = Manifest::for_value(&value, &Limits::new().max_depth(4));
+        assert_eq!(Manifest::from_json(&manifest.to_json()), Ok(manifest));
+        let manifest = Manifest::for_value(&value, &Limits::untrusted());
+        assert_eq!(Manifest::from_json(&manifest.to_json()), Ok(manifest));
+    }
+
+    #[test]
+    fn test_manifest_without_newer_limits() {
+        let value = parse_json("[1, {}]").unwrap();
+        let manifest = Manifest::for_value(&value, &Limits::new().max_depth(4));
+        let mut json = manifest.to_json();
+        if let Some(JsonValue::Object(limits)) = json.pointer_mut("/limits") {
+            limits.remove("max_tokens");
+            limits.remove("max_string_chars");
+        }
+        assert_eq!(Manifest::from_json(&json), Ok(manifest));
+    }
+
+    #[test]
+    fn test_save_and_load_verified() {
+        let path = scratch_file("manifest-ok");
+        let value = parse_json(r#"{"rows": [1, 2, 3], "ok": true}"#).unwrap();
+        let manifest = save_with_manifest(&value, &path, &Limits::new().max_depth(3)).unwrap();
+        assert_eq!(manifest.crate_version, env!("CARGO_PKG_VERSION"));
+
+        let loaded = load_verified(&path);
+        cleanup(&path);
+        assert_eq!(loaded.unwrap(), value);
+    }
+
+    #[test]
+    fn test_tampered_document_is_rejected() {
+        let path = scratch_file("manifest-tampered");
+        let value = parse_json(r#"{"amount": 10}"#).unwrap();
+        save_with_manifest(&value, &path, &Limits::new()).unwrap();
+        fs::write(&path, r#"{"amount": 1000}"#).unwrap();
+
+        let result = load_verified(&path);
+        cleanup(&path);
+        assert!(matches!(
+            result,
+            Err(ManifestError::Mismatch {
+                field: "sha256",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_recorded_limits_are_enforced() {
+        let path = scratch_file("manifest-limits");
+        let value = parse_json("[[1]]").unwrap();
+        let too_shallow = save_with_manifest(&value, &path, &Limits::new().max_depth(1));
+        assert!(matches!(
+            too_shallow,
+            Err(ManifestError::Json(JsonError::DepthLimitExceeded { .. }))
+        ));
+        assert!(!path.exists());
+
+        save_with_manifest(&value, &path, &Limits::new().max_depth(2)).unwrap();
+        fs::write(&path, "[[[1]]]").unwrap();
+        let result = load_verified(&path);
+        cleanup(&path);
+        assert!(matches!(
+            result,
+            Err(ManifestError::Json(JsonError::DepthLimitExceeded { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_manifest() {
+        let path = scratch_file("manifest-invalid");
+        fs::write(&path, "[]").unwrap();
+        fs::write(manifest_path(&path), r#"{"sha256": 5}"#).unwrap();
+        let result = load_verified(&path);
+        cleanup(&path);
+        assert!(matches!(result, Err(ManifestError::InvalidManifest(_))));
+    }
+}
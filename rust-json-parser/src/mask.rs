@@ -0,0 +1,246 @@
+use crate::value::escape_json_string;
+use crate::{JsonPath, JsonValue};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Maps object keys to the placeholder written in place of their values.
+///
+/// Keys are matched exactly, at any depth of the document.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MaskRules {
+    keys: HashMap<String, String>,
+}
+
+impl MaskRules {
+    /// Placeholder used by [`MaskRules::mask_key`].
+    pub const DEFAULT_MASK: &'static str = "***";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Masks every value stored under `key` with [`MaskRules::DEFAULT_MASK`].
+    pub fn mask_key(self, key: impl Into<String>) -> Self {
+        self.mask_key_with(key, Self::DEFAULT_MASK)
+    }
+
+    /// Masks every value stored under `key` with a custom placeholder.
+    pub fn mask_key_with(mut self, key: impl Into<String>, mask: impl Into<String>) -> Self {
+        self.keys.insert(key.into(), mask.into());
+        self
+    }
+
+    /// Returns the placeholder for `key`, if it is masked.
+    pub fn mask_for(&self, key: &str) -> Option<&str> {
+        self.keys.get(key).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Lazily serializes a value in compact form with masked keys replaced.
+///
+/// Created by [`JsonValue::display_masked`]. Nothing is cloned: masking
+/// happens while the output is written.
+#[derive(Debug, Clone, Copy)]
+pub struct MaskedDisplay<'a> {
+    value: &'a JsonValue,
+    rules: &'a MaskRules,
+}
+
+impl JsonValue {
+    /// Returns a [`fmt::Display`] adapter that masks sensitive keys on the fly.
+    pub fn display_masked<'a>(&'a self, rules: &'a MaskRules) -> MaskedDisplay<'a> {
+        MaskedDisplay { value: self, rules }
+    }
+
+    /// Replaces the value at each RFC 6901 pointer with `replacement`.
+    ///
+    /// Pointers that do not resolve are skipped. Returns how many values were
+    /// replaced.
+    pub fn redact(&mut self, pointers: &[&str], replacement: JsonValue) -> usize {
+        let mut redacted = 0;
+        for pointer in pointers {
+            if let Some(target) = self.pointer_mut(pointer) {
+                *target = replacement.clone();
+                redacted += 1;
+            }
+        }
+        redacted
+    }
+
+    /// Like [`JsonValue::redact`], with pointer patterns as accepted by
+    /// [`JsonValue::pointer_matches`], so `/**/password` redacts every
+    /// password at any depth.
+    ///
+    /// A match inside a value that was already replaced is skipped. Returns
+    /// how many values were replaced.
+    pub fn redact_matching(&mut self, patterns: &[&str], replacement: JsonValue) -> usize {
+        let mut replaced: Vec<JsonPath> = Vec::new();
+        for pattern in patterns {
+            let paths: Vec<JsonPath> = self
+                .pointer_matches(pattern)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect();
+            for path in paths {
+                if replaced.iter().any(|done| path.starts_with(done)) {
+                    continue;
+                }
+                if let Some(target) = self.pointer_mut(&path.to_pointer()) {
+                    *target = replacement.clone();
+                    replaced.push(path);
+                }
+            }
+        }
+        replaced.len()
+    }
+}
+
+impl fmt::Display for MaskedDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_masked(self.value, self.rules, f)
+    }
+}
+
+fn write_masked(value: &JsonValue, rules: &MaskRules, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match value {
+        JsonValue::Array(arr) => {
+            write!(f, "[")?;
+            for (i, item) in arr.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write_masked(item, rules, f)?;
+            }
+            write!(f, "]")
+        }
+        JsonValue::Object(obj) => {
+            write!(f, "{{")?;
+            for (i, (key, val)) in obj.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}:", escape_json_string(key))?;
+                match rules.mask_for(key) {
+                    Some(mask) => write!(f, "{}", escape_json_string(mask))?,
+                    None => write_masked(val, rules, f)?,
+                }
+            }
+            write!(f, "}}")
+        }
+        // Scalars have no keys to mask
+        scalar => write!(f, "{scalar}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_masks_top_level_key() {
+        let value = parse_json(r#"{"password": "hunter2"}"#).unwrap();
+        let rules = MaskRules::new().mask_key("password");
+        assert_eq!(
+            value.display_masked(&rules).to_string(),
+            r#"{"password":"***"}"#
+        );
+    }
+
+    #[test]
+    fn test_masks_nested_keys_and_whole_subtrees() {
+        let value = parse_json(r#"[{"auth": {"token": "abc", "scopes": ["a"]}}]"#).unwrap();
+        let rules = MaskRules::new().mask_key_with("auth", "<redacted>");
+        assert_eq!(
+            value.display_masked(&rules).to_string(),
+            r#"[{"auth":"<redacted>"}]"#
+        );
+    }
+
+    #[test]
+    fn test_unmasked_output_matches_display() {
+        let value = parse_json(r#"{"user": {"name": "Ann\n", "age": 3}}"#).unwrap();
+        let rules = MaskRules::new();
+        assert!(rules.is_empty());
+        assert_eq!(value.display_masked(&rules).to_string(), value.to_string());
+    }
+
+    #[test]
+    fn test_original_value_is_untouched() {
+        let value = parse_json(r#"{"secret": 42}"#).unwrap();
+        let rules = MaskRules::new().mask_key("secret");
+        let _ = value.display_masked(&rules).to_string();
+        assert_eq!(value.get("secret"), Some(&JsonValue::Number(42.0)));
+    }
+
+    #[test]
+    fn test_redact_by_pointer() {
+        let mut value =
+            parse_json(r#"{"user": {"name": "Ann", "password": "pw"}, "tokens": ["a", "b"]}"#)
+                .unwrap();
+        let placeholder = JsonValue::String("[REDACTED]".into());
+        let count = value.redact(&["/user/password", "/tokens/1"], placeholder);
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            value,
+            parse_json(
+                r#"{"user": {"name": "Ann", "password": "[REDACTED]"}, "tokens": ["a", "[REDACTED]"]}"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_redact_skips_missing_paths() {
+        let mut value = parse_json(r#"{"a": 1}"#).unwrap();
+        let count = value.redact(&["/b", "/a/c", "not-a-pointer"], JsonValue::Null);
+        assert_eq!(count, 0);
+        assert_eq!(value, parse_json(r#"{"a": 1}"#).unwrap());
+    }
+
+    #[test]
+    fn test_redact_whole_subtree() {
+        let mut value = parse_json(r#"{"auth": {"token": "t", "expires": 5}}"#).unwrap();
+        value.redact(&["/auth"], JsonValue::Null);
+        assert_eq!(value, parse_json(r#"{"auth": null}"#).unwrap());
+    }
+
+    #[test]
+    fn test_redact_matching_patterns() {
+        let mut value = parse_json(
+            r#"{"users": [{"name": "Ann", "password": "a"}, {"name": "Bob", "password": "b"}],
+                "db": {"password": "c"}}"#,
+        )
+        .unwrap();
+        let count = value.redact_matching(&["/**/password"], JsonValue::Null);
+        assert_eq!(count, 3);
+        assert_eq!(
+            value,
+            parse_json(
+                r#"{"users": [{"name": "Ann", "password": null}, {"name": "Bob", "password": null}],
+                    "db": {"password": null}}"#
+            )
+            .unwrap()
+        );
+
+        // The whole of "db" goes first, so nothing inside it is counted.
+        let mut value = parse_json(r#"{"db": {"password": "c"}}"#).unwrap();
+        let count = value.redact_matching(&["/db", "/**/password"], JsonValue::Null);
+        assert_eq!(count, 1);
+        assert_eq!(value, parse_json(r#"{"db": null}"#).unwrap());
+    }
+
+    #[test]
+    fn test_mask_for_lookup() {
+        let rules = MaskRules::new().mask_key("a").mask_key_with("b", "x");
+        assert_eq!(rules.mask_for("a"), Some(MaskRules::DEFAULT_MASK));
+        assert_eq!(rules.mask_for("b"), Some("x"));
+        assert_eq!(rules.mask_for("c"), None);
+    }
+}
@@ -0,0 +1,448 @@
+use crate::{JsonArray, JsonObject, JsonPath, JsonValue};
+use std::fmt;
+
+/// How [`JsonValue::deep_merge`] combines two arrays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayMerge {
+    /// The incoming array replaces the existing one.
+    #[default]
+    Replace,
+    /// The incoming elements are appended to the existing array.
+    Concat,
+}
+
+/// Selects how [`JsonValue::deep_merge`] resolves conflicts.
+///
+/// Objects are always unioned member by member. By default arrays are
+/// replaced and a `null` overlay value is stored like any other value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MergeStrategy {
+    arrays: ArrayMerge,
+    null_deletes: bool,
+}
+
+impl MergeStrategy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The semantics of RFC 7396 JSON Merge Patch: arrays are replaced and
+    /// `null` removes the member.
+    pub fn merge_patch() -> Self {
+        Self::new().null_deletes(true)
+    }
+
+    pub fn arrays(mut self, arrays: ArrayMerge) -> Self {
+        self.arrays = arrays;
+        self
+    }
+
+    /// When set, an object member whose overlay value is `null` is removed
+    /// from the result instead of being set to `null`.
+    pub fn null_deletes(mut self, deletes: bool) -> Self {
+        self.null_deletes = deletes;
+        self
+    }
+}
+
+impl JsonValue {
+    /// Merges `other` into `self`, recursing into objects present on both sides.
+    ///
+    /// Anything that is not an object-object (or, with [`ArrayMerge::Concat`],
+    /// array-array) pair is overwritten by the value from `other`; an object
+    /// is merged into an empty one, so its `null`s delete under
+    /// [`MergeStrategy::null_deletes`] wherever it lands. Merging
+    /// several layers in turn (defaults, then environment, then user
+    /// overrides) gives the usual "last layer wins" configuration semantics.
+    pub fn deep_merge(&mut self, other: JsonValue, strategy: &MergeStrategy) {
+        match (self, other) {
+            (JsonValue::Object(base), JsonValue::Object(overlay)) => {
+                merge_objects(base, overlay, strategy);
+            }
+            (JsonValue::Array(base), JsonValue::Array(overlay))
+                if strategy.arrays == ArrayMerge::Concat =>
+            {
+                base.extend(overlay);
+            }
+            (base, JsonValue::Object(overlay)) => {
+                let mut fresh = JsonObject::new();
+                merge_objects(&mut fresh, overlay, strategy);
+                *base = JsonValue::Object(fresh);
+            }
+            (base, overlay) => *base = overlay,
+        }
+    }
+}
+
+fn merge_objects(base: &mut JsonObject, overlay: JsonObject, strategy: &MergeStrategy) {
+    for (key, value) in overlay {
+        if strategy.null_deletes && value.is_null() {
+            base.remove(&key);
+            continue;
+        }
+        match base.get_mut(&key) {
+            Some(existing) => existing.deep_merge(value, strategy),
+            None => {
+                let mut fresh = JsonValue::Null;
+                fresh.deep_merge(value, strategy);
+                base.insert(key, fresh);
+            }
+        }
+    }
+}
+
+/// A place where both sides of a [`merge3`] changed the same value in
+/// different ways.
+///
+/// `None` means the value is absent on that side: it was not in the base,
+/// or that side deleted it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub path: JsonPath,
+    pub base: Option<JsonValue>,
+    pub ours: Option<JsonValue>,
+    pub theirs: Option<JsonValue>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match (&self.ours, &self.theirs) {
+            (Some(_), Some(_)) if self.base.is_none() => "both added",
+            (Some(_), Some(_)) => "both modified",
+            (None, _) => "deleted by ours, modified by theirs",
+            (_, None) => "modified by ours, deleted by theirs",
+        };
+        let path = if self.path.is_root() {
+            "the root".to_string()
+        } else {
+            self.path.to_string()
+        };
+        write!(f, "conflict at {path}: {kind}")
+    }
+}
+
+/// Three-way merges two edited copies of `base`.
+///
+/// A change made on one side only is taken as is, and so is the same change
+/// made on both. Objects are merged member by member, so edits to different
+/// members never conflict; the result keeps the member order of `ours`,
+/// followed by members only `theirs` added. Arrays are merged element by
+/// element only while all three have the same length; otherwise an array
+/// changed on both sides is a conflict as a whole, since positions no
+/// longer line up. Every conflict is reported, each with its path.
+///
+/// ```
+/// use rust_json_parser::{JsonParser, merge3};
+///
+/// let parse = |s| JsonParser::new(s).unwrap().parse().unwrap();
+/// let base = parse(r#"{"name": "app", "port": 80}"#);
+/// let ours = parse(r#"{"name": "api", "port": 80}"#);
+/// let theirs = parse(r#"{"name": "app", "port": 8080}"#);
+/// let merged = merge3(&base, &ours, &theirs).unwrap();
+/// assert_eq!(merged.to_string(), r#"{"name":"api","port":8080}"#);
+/// ```
+pub fn merge3(
+    base: &JsonValue,
+    ours: &JsonValue,
+    theirs: &JsonValue,
+) -> Result<JsonValue, Vec<Conflict>> {
+    let mut merge = Merge3 {
+        path: JsonPath::new(),
+        conflicts: Vec::new(),
+    };
+    let merged = merge.value(Some(base), ours, theirs);
+    if merge.conflicts.is_empty() {
+        Ok(merged)
+    } else {
+        Err(merge.conflicts)
+    }
+}
+
+struct Merge3 {
+    path: JsonPath,
+    conflicts: Vec<Conflict>,
+}
+
+impl Merge3 {
+    /// Merges a value present on both sides; `base` is `None` when both
+    /// sides added it.
+    fn value(
+        &mut self,
+        base: Option<&JsonValue>,
+        ours: &JsonValue,
+        theirs: &JsonValue,
+    ) -> JsonValue {
+        if ours == theirs || base == Some(theirs) {
+            return ours.clone();
+        }
+        if base == Some(ours) {
+            return theirs.clone();
+        }
+        match (base, ours, theirs) {
+            (Some(JsonValue::Object(base)), JsonValue::Object(ours), JsonValue::Object(theirs)) => {
+                return JsonValue::Object(self.objects(base, ours, theirs));
+            }
+            (None, JsonValue::Object(ours), JsonValue::Object(theirs)) => {
+                return JsonValue::Object(self.objects(&JsonObject::new(), ours, theirs));
+            }
+            (Some(JsonValue::Array(base)), JsonValue::Array(ours), JsonValue::Array(theirs))
+                if ours.len() == base.len() && theirs.len() == base.len() =>
+            {
+                let mut merged = JsonArray::with_capacity(base.len());
+                for (index, ((base, ours), theirs)) in base.iter().zip(ours).zip(theirs).enumerate()
+                {
+                    self.path.push_index(index);
+                    merged.push(self.value(Some(base), ours, theirs));
+                    self.path.pop();
+                }
+                return JsonValue::Array(merged);
+            }
+            _ => {}
+        }
+        self.conflict(base, Some(ours), Some(theirs));
+        ours.clone()
+    }
+
+    fn objects(&mut self, base: &JsonObject, ours: &JsonObject, theirs: &JsonObject) -> JsonObject {
+        let mut merged = JsonObject::new();
+        let added = theirs.keys().filter(|key| !ours.contains_key(key));
+        for key in ours.keys().chain(added) {
+            self.path.push_key(key.clone());
+            let base = base.get(key);
+            let value = match (ours.get(key), theirs.get(key)) {
+                (Some(ours), Some(theirs)) => Some(self.value(base, ours, theirs)),
+                (Some(kept), None) | (None, Some(kept)) => {
+                    self.one_sided(base, kept, ours.contains_key(key))
+                }
+                (None, None) => None,
+            };
+            if let Some(value) = value {
+                merged.insert(key.clone(), value);
+            }
+            self.path.pop();
+        }
+        merged
+    }
+
+    /// A member present on one side only: added there, or deleted on the
+    /// other side, which conflicts when the side that kept it changed it.
+    fn one_sided(
+        &mut self,
+        base: Option<&JsonValue>,
+        kept: &JsonValue,
+        in_ours: bool,
+    ) -> Option<JsonValue> {
+        match base {
+            None => Some(kept.clone()),
+            Some(base) if base == kept => None,
+            Some(_) => {
+                if in_ours {
+                    self.conflict(base, Some(kept), None);
+                } else {
+                    self.conflict(base, None, Some(kept));
+                }
+                Some(kept.clone())
+            }
+        }
+    }
+
+    fn conflict(
+        &mut self,
+        base: Option<&JsonValue>,
+        ours: Option<&JsonValue>,
+        theirs: Option<&JsonValue>,
+    ) {
+        self.conflicts.push(Conflict {
+            path: self.path.clone(),
+            base: base.cloned(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn merged(base: &str, overlay: &str, strategy: MergeStrategy) -> JsonValue {
+        let mut value = parse_json(base).unwrap();
+        value.deep_merge(parse_json(overlay).unwrap(), &strategy);
+        value
+    }
+
+    #[test]
+    fn test_objects_are_unioned_recursively() {
+        let result = merged(
+            r#"{"db": {"host": "localhost", "port": 5432}, "debug": false}"#,
+            r#"{"db": {"port": 6543}, "name": "app"}"#,
+            MergeStrategy::new(),
+        );
+        let expected = parse_json(
+            r#"{"db": {"host": "localhost", "port": 6543}, "debug": false, "name": "app"}"#,
+        )
+        .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_array_strategies() {
+        let cases = vec![
+            (ArrayMerge::Replace, "[3]"),
+            (ArrayMerge::Concat, "[1, 2, 3]"),
+        ];
+        for (arrays, expected) in cases {
+            let result = merged(
+                r#"{"a": [1, 2]}"#,
+                r#"{"a": [3]}"#,
+                MergeStrategy::new().arrays(arrays),
+            );
+            assert_eq!(
+                result.get("a"),
+                Some(&parse_json(expected).unwrap()),
+                "{arrays:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_null_handling() {
+        let kept = merged(
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"a": null}"#,
+            MergeStrategy::new(),
+        );
+        assert_eq!(kept, parse_json(r#"{"a": null, "b": 2}"#).unwrap());
+
+        let deleted = merged(
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"a": null, "c": {"d": null, "e": 5}}"#,
+            MergeStrategy::merge_patch(),
+        );
+        assert_eq!(deleted, parse_json(r#"{"b": 2, "c": {"e": 5}}"#).unwrap());
+    }
+
+    #[test]
+    fn test_merge_patch_rfc7396_examples() {
+        // Appendix A of RFC 7396: target, patch, result
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"b":"c"}"#, r#"{"a":"b","b":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, r#"{}"#),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"c"}"#, r#"{"a":["b"]}"#, r#"{"a":["b"]}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"{"a":[{"b":"c"}]}"#, r#"{"a":[1]}"#, r#"{"a":[1]}"#),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"b"}"#, r#"["c"]"#, r#"["c"]"#),
+            (r#"{"a":"foo"}"#, "null", "null"),
+            (r#"{"a":"foo"}"#, r#""bar""#, r#""bar""#),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"[1,2]"#, r#"{"a":"b","c":null}"#, r#"{"a":"b"}"#),
+            (
+                r#"{}"#,
+                r#"{"a":{"bb":{"ccc":null}}}"#,
+                r#"{"a":{"bb":{}}}"#,
+            ),
+            // An object patch landing on a non-object member
+            (
+                r#"{"a":1}"#,
+                r#"{"a":{"b":null,"c":1}}"#,
+                r#"{"a":{"c":1}}"#,
+            ),
+            (r#"[1]"#, r#"{"b":null}"#, r#"{}"#),
+        ];
+        for (target, patch, expected) in cases {
+            let result = merged(target, patch, MergeStrategy::merge_patch());
+            assert_eq!(result.to_string(), expected, "{target} + {patch}");
+        }
+    }
+
+    #[test]
+    fn test_type_mismatch_overwrites() {
+        let result = merged(r#"{"a": {"b": 1}}"#, r#"{"a": [1]}"#, MergeStrategy::new());
+        assert_eq!(result, parse_json(r#"{"a": [1]}"#).unwrap());
+
+        let result = merged(r#"[1, 2]"#, r#"{"x": 1}"#, MergeStrategy::new());
+        assert_eq!(result, parse_json(r#"{"x": 1}"#).unwrap());
+    }
+
+    #[test]
+    fn test_layering_keeps_base_key_order() {
+        let mut config = parse_json(r#"{"z": 1, "a": 1}"#).unwrap();
+        for layer in [r#"{"a": 2, "m": 2}"#, r#"{"z": 3}"#] {
+            config.deep_merge(parse_json(layer).unwrap(), &MergeStrategy::new());
+        }
+        assert_eq!(config.to_string(), r#"{"z":3,"a":2,"m":2}"#);
+    }
+
+    fn merged3(base: &str, ours: &str, theirs: &str) -> Result<JsonValue, Vec<Conflict>> {
+        merge3(
+            &parse_json(base).unwrap(),
+            &parse_json(ours).unwrap(),
+            &parse_json(theirs).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_merge3_combines_independent_edits() {
+        let result = merged3(
+            r#"{"a": 1, "b": {"c": 2, "d": 3}, "gone": true, "list": [1, 2]}"#,
+            r#"{"a": 10, "b": {"c": 2, "d": 3}, "list": [1, 20], "new": 1}"#,
+            r#"{"a": 1, "b": {"c": 2, "d": 30}, "gone": true, "list": [1, 2], "x": 2}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            result.to_string(),
+            r#"{"a":10,"b":{"c":2,"d":30},"list":[1,20],"new":1,"x":2}"#
+        );
+    }
+
+    #[test]
+    fn test_merge3_same_change_on_both_sides() {
+        let result = merged3(r#"{"a": 1}"#, r#"{"a": 2, "b": 3}"#, r#"{"a": 2, "b": 3}"#);
+        assert_eq!(result.unwrap().to_string(), r#"{"a":2,"b":3}"#);
+    }
+
+    #[test]
+    fn test_merge3_reports_every_conflict() {
+        let conflicts = merged3(
+            r#"{"a": 1, "b": {"c": 1}, "d": 1, "e": [1]}"#,
+            r#"{"a": 2, "b": {"c": 2}, "e": [1, 2], "f": 1}"#,
+            r#"{"a": 3, "b": {"c": 3}, "d": 2, "e": [0], "f": 2}"#,
+        )
+        .unwrap_err();
+        let found: Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            found,
+            vec![
+                "conflict at /a: both modified",
+                "conflict at /b/c: both modified",
+                "conflict at /e: both modified",
+                "conflict at /f: both added",
+                "conflict at /d: deleted by ours, modified by theirs",
+            ]
+        );
+        let d = &conflicts[4];
+        assert_eq!(d.base, Some(JsonValue::Number(1.0)));
+        assert_eq!(d.ours, None);
+        assert_eq!(d.theirs, Some(JsonValue::Number(2.0)));
+    }
+
+    #[test]
+    fn test_merge3_root_conflict() {
+        let conflicts = merged3("1", "2", "3").unwrap_err();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "conflict at the root: both modified"
+        );
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{ParserOptions, Result, Token, Tokenizer};
+
+/// Removes insignificant whitespace from JSON text.
+///
+/// Works on the token stream, so keys keep their order and every literal
+/// keeps its exact spelling (`1.50`, `"é"`). Only lexical errors are
+/// reported; the structure is not checked.
+pub fn minify(input: &str) -> Result<String> {
+    minify_with(input, &ParserOptions::default())
+}
+
+/// [`minify`] accepting the dialect of `options`. Comments are removed;
+/// other extensions such as single-quoted strings are copied as written.
+pub fn minify_with(input: &str, options: &ParserOptions) -> Result<String> {
+    let mut tokenizer = Tokenizer::with_options(input, options.clone());
+    let tokens = tokenizer.tokenize_spanned()?;
+
+    let mut out = String::with_capacity(input.len());
+    let mut previous_was_scalar = false;
+    for (token, span) in tokens {
+        let scalar = is_scalar(&token);
+        // Keep adjacent literals apart so `1 2` does not become `12`
+        if scalar && previous_was_scalar {
+            out.push(' ');
+        }
+        out.extend(tokenizer.text(span));
+        previous_was_scalar = scalar;
+    }
+    Ok(out)
+}
+
+fn is_scalar(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::String(_) | Token::Number(_) | Token::Boolean(_) | Token::Null
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonError;
+
+    #[test]
+    fn test_strips_whitespace_and_keeps_spelling() {
+        let input = "{\n  \"b\" : [ 1.50 , -0, 2E3 ],\n  \"a\":\t\"x \\u00e9 y\"\n}\n";
+        assert_eq!(
+            minify(input).unwrap(),
+            r#"{"b":[1.50,-0,2E3],"a":"x \u00e9 y"}"#
+        );
+    }
+
+    #[test]
+    fn test_lenient_input_drops_comments() {
+        let input = "// header\n[1, /* two */ 2, 'three']";
+        let options = ParserOptions::new()
+            .allow_comments(true)
+            .allow_single_quotes(true);
+        assert_eq!(minify_with(input, &options).unwrap(), "[1,2,'three']");
+        assert!(matches!(
+            minify(input),
+            Err(JsonError::DisabledFeature { .. })
+        ));
+    }
+
+    #[test]
+    fn test_adjacent_literals_stay_separate() {
+        assert_eq!(minify("[1 2]").unwrap(), "[1 2]");
+        assert_eq!(minify("true  null").unwrap(), "true null");
+    }
+
+    #[test]
+    fn test_lexical_errors_are_reported() {
+        assert!(matches!(
+            minify(r#"{"a": tru}"#),
+            Err(JsonError::UnexpectedToken { .. })
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+//! The pointer [`JsonArray`](crate::JsonArray) and
+//! [`JsonObject`](crate::JsonObject) keep their contents behind.
+//!
+//! By default it is a `Box`, and a clone copies the whole tree. With the
+//! `shared` feature it is an `Arc`: cloning a value, however large, only
+//! bumps a reference count, clones can be handed to other threads, and
+//! contents are copied on write, one container at a time, when a clone
+//! that shares them is changed. Editing one value deep inside a clone
+//! copies only the containers on the way down to it, so keeping every
+//! version of a document costs little more than the edits, and comparing or
+//! diffing two versions skips whatever they still share.
+
+#[cfg(not(feature = "shared"))]
+pub(crate) type Node<T> = Box<T>;
+
+#[cfg(feature = "shared")]
+pub(crate) type Node<T> = std::sync::Arc<T>;
+
+/// The contents, for changing; shared contents are copied first.
+#[cfg(not(feature = "shared"))]
+pub(crate) fn make_mut<T: Clone>(node: &mut Node<T>) -> &mut T {
+    node
+}
+
+#[cfg(feature = "shared")]
+pub(crate) fn make_mut<T: Clone>(node: &mut Node<T>) -> &mut T {
+    std::sync::Arc::make_mut(node)
+}
+
+/// The contents, copied out if another clone shares them.
+#[cfg(not(feature = "shared"))]
+#[expect(clippy::boxed_local, reason = "takes what the `Arc` version takes")]
+pub(crate) fn into_inner<T: Clone>(node: Node<T>) -> T {
+    *node
+}
+
+#[cfg(feature = "shared")]
+pub(crate) fn into_inner<T: Clone>(node: Node<T>) -> T {
+    std::sync::Arc::unwrap_or_clone(node)
+}
+
+/// The contents if nothing else shares them; otherwise drops this
+/// reference and returns `None`.
+#[cfg(not(feature = "shared"))]
+#[expect(clippy::boxed_local, reason = "takes what the `Arc` version takes")]
+pub(crate) fn into_unique<T>(node: Node<T>) -> Option<T> {
+    Some(*node)
+}
+
+#[cfg(feature = "shared")]
+pub(crate) fn into_unique<T>(node: Node<T>) -> Option<T> {
+    std::sync::Arc::into_inner(node)
+}
+
+/// The contents, for changing, if nothing else shares them.
+#[cfg(not(feature = "shared"))]
+pub(crate) fn get_mut<T>(node: &mut Node<T>) -> Option<&mut T> {
+    Some(node)
+}
+
+#[cfg(feature = "shared")]
+pub(crate) fn get_mut<T>(node: &mut Node<T>) -> Option<&mut T> {
+    std::sync::Arc::get_mut(node)
+}
+
+/// Whether both point at the same contents, which only clones sharing
+/// them under the `shared` feature do.
+pub(crate) fn ptr_eq<T>(a: &Node<T>, b: &Node<T>) -> bool {
+    std::ptr::eq::<T>(&**a, &**b)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::JsonValue;
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_changing_a_clone_leaves_the_original() {
+        let original = parse_json(r#"{"a": [1, {"b": 2}], "c": {"d": [3]}}"#).unwrap();
+        let mut copy = original.clone();
+        *copy.pointer_mut("/a/1/b").unwrap() = JsonValue::Null;
+        copy.as_object_mut().unwrap().remove("c");
+        let a = copy.pointer_mut("/a").and_then(JsonValue::as_array_mut);
+        a.unwrap().push(JsonValue::Boolean(true));
+        assert_eq!(
+            original,
+            parse_json(r#"{"a": [1, {"b": 2}], "c": {"d": [3]}}"#).unwrap()
+        );
+        assert_eq!(
+            copy,
+            parse_json(r#"{"a": [1, {"b": null}, true]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_only_shared_clones_are_ptr_eq() {
+        let original = parse_json(r#"{"a": [1]}"#).unwrap();
+        let copy = original.clone();
+        let object = |v: &JsonValue| v.as_object().unwrap().clone();
+        let array = |v: &JsonValue| v.get("a").unwrap().as_array().unwrap().clone();
+        assert_eq!(
+            object(&copy).ptr_eq(&object(&original)),
+            cfg!(feature = "shared")
+        );
+        assert_eq!(
+            array(&copy).ptr_eq(&array(&original)),
+            cfg!(feature = "shared")
+        );
+        assert!(!object(&parse_json(r#"{"a": [1]}"#).unwrap()).ptr_eq(&object(&original)));
+        assert!(!crate::JsonArray::new().ptr_eq(&crate::JsonArray::new()));
+    }
+
+    #[cfg(feature = "shared")]
+    #[test]
+    fn test_editing_a_snapshot_copies_only_the_path() {
+        let v1 = parse_json(
+            r#"{"db": {"primary": {"port": 5432}, "replicas": [{"port": 5433}]},
+                "cache": {"ttl": 60}}"#,
+        )
+        .unwrap();
+        let mut v2 = v1.clone();
+        *v2.pointer_mut("/db/primary/port").unwrap() = JsonValue::Number(6432.0);
+
+        let shared = |pointer: &str| {
+            let at = |v: &JsonValue| v.pointer(pointer).cloned();
+            match (at(&v1), at(&v2)) {
+                (Some(JsonValue::Object(a)), Some(JsonValue::Object(b))) => a.ptr_eq(&b),
+                (Some(JsonValue::Array(a)), Some(JsonValue::Array(b))) => a.ptr_eq(&b),
+                _ => unreachable!("{pointer}"),
+            }
+        };
+        for pointer in ["", "/db", "/db/primary"] {
+            assert!(!shared(pointer), "{pointer} was not copied");
+        }
+        for pointer in ["/cache", "/db/replicas", "/db/replicas/0"] {
+            assert!(shared(pointer), "{pointer} was copied");
+        }
+        assert_eq!(
+            v1.pointer("/db/primary/port"),
+            Some(&JsonValue::Number(5432.0))
+        );
+        assert_eq!(
+            crate::patch::diff(&v1, &v2).to_json().to_string(),
+            r#"[{"op":"replace","path":"/db/primary/port","value":6432}]"#
+        );
+    }
+
+    #[cfg(feature = "shared")]
+    #[test]
+    fn test_clones_share_contents() {
+        let original = parse_json(r#"{"servers": [{"host": "a"}, {"host": "b"}]}"#).unwrap();
+        let servers = |v: &JsonValue| v.get("servers").unwrap().as_array().unwrap().as_ptr();
+        let copy = original.clone();
+        assert_eq!(servers(&copy), servers(&original));
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let copy = original.clone();
+                std::thread::spawn(move || copy.pointer("/servers/1/host").cloned())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Some(JsonValue::String("b".into())));
+        }
+    }
+
+    #[cfg(feature = "shared")]
+    #[test]
+    fn test_dropping_deep_clones() {
+        let mut deep = JsonValue::Null;
+        for _ in 0..100_000 {
+            deep = JsonValue::Array(vec![deep].into());
+        }
+        let copy = deep.clone();
+        drop(deep);
+        drop(copy);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{JsonObject, JsonString, JsonValue, PruneOptions};
+
+/// How [`JsonValue::normalize`] treats whitespace in string values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrimStrings {
+    /// Leave strings as they are.
+    #[default]
+    None,
+    /// Strip leading and trailing whitespace.
+    Trim,
+    /// Strip leading and trailing whitespace and collapse every inner run of
+    /// whitespace into a single space.
+    Collapse,
+}
+
+/// Selects the steps [`JsonValue::normalize`] applies. Everything is off by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NormalizeOptions {
+    #[cfg(feature = "unicode")]
+    nfc_keys: bool,
+    #[cfg(feature = "unicode")]
+    nfc_strings: bool,
+    trim_strings: TrimStrings,
+    canonical_numbers: bool,
+    prune: Option<PruneOptions>,
+}
+
+impl NormalizeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rewrites object keys to Unicode Normalization Form C, so keys that
+    /// only differ in how accents are encoded compare equal.
+    ///
+    /// If two keys normalize to the same string, the later value wins and the
+    /// member keeps the earlier position.
+    #[cfg(feature = "unicode")]
+    pub fn nfc_keys(mut self, enabled: bool) -> Self {
+        self.nfc_keys = enabled;
+        self
+    }
+
+    /// Rewrites string values to Unicode Normalization Form C. Keys are
+    /// left to [`NormalizeOptions::nfc_keys`].
+    #[cfg(feature = "unicode")]
+    pub fn nfc_strings(mut self, enabled: bool) -> Self {
+        self.nfc_strings = enabled;
+        self
+    }
+
+    pub fn trim_strings(mut self, policy: TrimStrings) -> Self {
+        self.trim_strings = policy;
+        self
+    }
+
+    /// Turns `-0` into `0`, and non-finite numbers (which JSON cannot
+    /// represent) into `null`.
+    pub fn canonical_numbers(mut self, enabled: bool) -> Self {
+        self.canonical_numbers = enabled;
+        self
+    }
+
+    /// Prunes the result as [`JsonValue::prune_with`] would, after the other steps.
+    pub fn prune(mut self, options: PruneOptions) -> Self {
+        self.prune = Some(options);
+        self
+    }
+}
+
+impl JsonValue {
+    /// Applies every step selected in `options` in a single bottom-up pass.
+    pub fn normalize(&mut self, options: &NormalizeOptions) {
+        match self {
+            JsonValue::String(s) => {
+                #[cfg(feature = "unicode")]
+                if options.nfc_strings {
+                    *s = nfc(std::mem::take(s).into_string()).into();
+                }
+                trim(s, options.trim_strings);
+            }
+            JsonValue::Number(n) if options.canonical_numbers => {
+                if !n.is_finite() {
+                    *self = JsonValue::Null;
+                } else if *n == 0.0 {
+                    *n = 0.0;
+                }
+            }
+            JsonValue::Array(arr) => {
+                for item in arr.iter_mut() {
+                    item.normalize(options);
+                }
+                if let Some(prune) = &options.prune {
+                    arr.retain(|item| !prune.removes(item));
+                }
+            }
+            JsonValue::Object(obj) => {
+                for value in obj.values_mut() {
+                    value.normalize(options);
+                }
+                normalize_keys(obj, options);
+                if let Some(prune) = &options.prune {
+                    obj.retain(|_, value| !prune.removes(value));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn trim(s: &mut JsonString, policy: TrimStrings) {
+    match policy {
+        TrimStrings::None => {}
+        TrimStrings::Trim => {
+            let trimmed = s.trim();
+            if trimmed.len() != s.len() {
+                *s = trimmed.into();
+            }
+        }
+        TrimStrings::Collapse => {
+            *s = s.split_whitespace().collect::<Vec<_>>().join(" ").into();
+        }
+    }
+}
+
+#[cfg(feature = "unicode")]
+fn needs_nfc(s: &str) -> bool {
+    use unicode_normalization::{IsNormalized, is_nfc_quick};
+
+    is_nfc_quick(s.chars()) != IsNormalized::Yes
+}
+
+/// `s` in Unicode Normalization Form C, reusing it when it already is.
+#[cfg(feature = "unicode")]
+pub(crate) fn nfc(s: String) -> String {
+    use unicode_normalization::UnicodeNormalization;
+
+    if needs_nfc(&s) { s.nfc().collect() } else { s }
+}
+
+#[cfg(feature = "unicode")]
+fn normalize_keys(obj: &mut JsonObject, options: &NormalizeOptions) {
+    if !options.nfc_keys || !obj.keys().any(|key| needs_nfc(key)) {
+        return;
+    }
+    let entries = std::mem::take(obj);
+    *obj = entries
+        .into_iter()
+        .map(|(key, value)| (nfc(key.into_string()), value))
+        .collect();
+}
+
+#[cfg(not(feature = "unicode"))]
+fn normalize_keys(_obj: &mut JsonObject, _options: &NormalizeOptions) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn normalized(input: &str, options: NormalizeOptions) -> JsonValue {
+        let mut value = parse_json(input).unwrap();
+        value.normalize(&options);
+        value
+    }
+
+    #[test]
+    fn test_default_options_change_nothing() {
+        let input = r#"{"a": "  x  ", "b": null, "c": -0}"#;
+        assert_eq!(
+            normalized(input, NormalizeOptions::new()),
+            parse_json(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_trim_policies() {
+        let cases = vec![
+            (TrimStrings::None, "  two \t words  "),
+            (TrimStrings::Trim, "two \t words"),
+            (TrimStrings::Collapse, "two words"),
+        ];
+        for (policy, expected) in cases {
+            let value = normalized(
+                r#"["  two \t words  "]"#,
+                NormalizeOptions::new().trim_strings(policy),
+            );
+            assert_eq!(
+                value.get_index(0).and_then(JsonValue::as_str),
+                Some(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonical_numbers() {
+        let mut value = JsonValue::Array(
+            vec![
+                JsonValue::Number(-0.0),
+                JsonValue::Number(f64::NAN),
+                JsonValue::Number(1.5),
+            ]
+            .into(),
+        );
+        value.normalize(&NormalizeOptions::new().canonical_numbers(true));
+        let items = value.as_array().unwrap();
+        assert!(items[0].as_f64().unwrap().is_sign_positive());
+        assert!(items[1].is_null());
+        assert_eq!(items[2], JsonValue::Number(1.5));
+    }
+
+    #[test]
+    fn test_steps_combine_in_one_pass() {
+        let options = NormalizeOptions::new()
+            .trim_strings(TrimStrings::Trim)
+            .prune(PruneOptions::new());
+        let value = normalized(
+            r#"{"name": " svc ", "tags": [null], "meta": {"owner": null}, "port": 80}"#,
+            options,
+        );
+        assert_eq!(value, parse_json(r#"{"name": "svc", "port": 80}"#).unwrap());
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_nfc_keys() {
+        // "é" as "e" + combining acute accent, then precomposed
+        let value = normalized(
+            "{\"cafe\u{301}\": 1, \"x\": 2, \"caf\u{e9}\": 3}",
+            NormalizeOptions::new().nfc_keys(true),
+        );
+        let obj = value.as_object().unwrap();
+        assert_eq!(obj.len(), 2);
+        assert_eq!(obj.keys().next().map(JsonString::as_str), Some("caf\u{e9}"));
+        assert_eq!(obj.get("caf\u{e9}"), Some(&JsonValue::Number(3.0)));
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_nfc_strings() {
+        let value = normalized(
+            "{\"cafe\u{301}\": \"cafe\u{301} \", \"n\": [\"n\u{303}\"]}",
+            NormalizeOptions::new()
+                .nfc_strings(true)
+                .trim_strings(TrimStrings::Trim),
+        );
+        let expected = parse_json("{\"cafe\u{301}\": \"caf\u{e9}\", \"n\": [\"\u{f1}\"]}").unwrap();
+        assert_eq!(value, expected);
+    }
+}
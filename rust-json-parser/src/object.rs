@@ -0,0 +1,745 @@
+use crate::array::{drop_iteratively, has_children};
+use crate::node::{self, Node};
+use crate::{JsonString, JsonValue};
+use std::collections::HashMap;
+use std::fmt;
+
+/// The members of a JSON object, kept in insertion order.
+///
+/// Lookups go through a key index, so `get`/`insert` cost the same as a
+/// `HashMap`. Iteration follows the order keys were first inserted, which
+/// makes serialized output stable. Equality ignores order, like a map's.
+///
+/// A key may also hold several members, added with
+/// [`JsonObject::append`]: [`JsonObject::get`] then sees the last of them
+/// and [`JsonObject::get_all`] every one, in order.
+///
+/// Like [`JsonArray`](crate::JsonArray), the members live behind a single
+/// pointer, allocated with the first of them and, with the `shared`
+/// feature, shared by clones until one of them is changed.
+#[derive(Clone, Default)]
+pub struct JsonObject {
+    members: Option<Node<Members>>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Members {
+    entries: Vec<(JsonString, JsonValue)>,
+    /// Position of the last member for each key.
+    index: HashMap<JsonString, usize>,
+    /// Whether some key holds more than one member.
+    repeated: bool,
+}
+
+impl JsonObject {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            members: (capacity > 0).then(|| {
+                Node::new(Members {
+                    entries: Vec::with_capacity(capacity),
+                    index: HashMap::with_capacity(capacity),
+                    repeated: false,
+                })
+            }),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+
+    /// Whether `self` and `other` are clones that still share their
+    /// members, as they do with the `shared` feature until one is changed.
+    /// Shared members compare equal without being walked.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.members, &other.members) {
+            (Some(a), Some(b)) => node::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.position(key).map(|i| &self.entries()[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.position(key).map(|i| &mut self.entries_mut()[i].1)
+    }
+
+    /// Every value for `key`, in order. Yields at most one value unless
+    /// members were added with [`JsonObject::append`].
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a JsonValue> + 'a {
+        let entries = match (self.is_repeated(), self.position(key)) {
+            (true, _) => self.entries(),
+            (false, Some(i)) => &self.entries()[i..=i],
+            (false, None) => &[],
+        };
+        entries
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Looks up `key` ignoring letter case (`content-type` finds
+    /// `Content-Type`).
+    ///
+    /// An exact match wins; otherwise this scans members in document order
+    /// and returns the first whose key matches.
+    pub fn get_ignore_case(&self, key: &str) -> Option<&JsonValue> {
+        self.get(key).or_else(|| {
+            self.entries()
+                .iter()
+                .find(|(k, _)| eq_ignore_case(k, key))
+                .map(|(_, v)| v)
+        })
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Inserts a member, returning the previous value for `key`.
+    ///
+    /// Replacing an existing key keeps its original position. Of a key with
+    /// several members, the last is replaced.
+    pub fn insert(&mut self, key: impl Into<JsonString>, value: JsonValue) -> Option<JsonValue> {
+        let key = key.into();
+        match self.position(&key) {
+            Some(i) => Some(std::mem::replace(&mut self.entries_mut()[i].1, value)),
+            None => {
+                self.members_mut().push(key, value);
+                None
+            }
+        }
+    }
+
+    /// Adds a member after the others, keeping any members `key` already
+    /// has.
+    pub fn append(&mut self, key: impl Into<JsonString>, value: JsonValue) {
+        self.members_mut().push(key.into(), value);
+    }
+
+    /// The member for `key`, for in-place inspection, update or insertion.
+    /// Of a key with several members, this is the last.
+    pub fn entry(&mut self, key: impl Into<JsonString>) -> Entry<'_> {
+        let key = key.into();
+        match self.position(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry {
+                object: self,
+                index,
+            }),
+            None => Entry::Vacant(VacantEntry { object: self, key }),
+        }
+    }
+
+    /// Removes a member, shifting later members down to keep their order.
+    /// Every member of a repeated key is removed, and the last value
+    /// returned.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        let i = self.position(key)?;
+        let repeated = self.is_repeated();
+        let value = self.remove_at(i);
+        if repeated {
+            self.retain(|k, _| k != key);
+        }
+        Some(value)
+    }
+
+    /// Keeps only the members for which `keep` returns `true`.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&JsonString, &mut JsonValue) -> bool,
+    {
+        if let Some(members) = self.existing_mut() {
+            members.entries.retain_mut(|(k, v)| keep(k, v));
+            members.reindex();
+        }
+    }
+
+    /// Reorders the members by key, in byte order.
+    pub fn sort_keys(&mut self) {
+        if let Some(members) = self.existing_mut() {
+            members.entries.sort_by(|a, b| a.0.cmp(&b.0));
+            members.reindex();
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.entries().iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut {
+            inner: self.entries_mut().iter_mut(),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &JsonString> {
+        self.entries().iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        self.entries().iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut JsonValue> {
+        self.entries_mut().iter_mut().map(|(_, v)| v)
+    }
+
+    /// Heap bytes owned by the object itself: the member list, the key index
+    /// and both copies of every key. Values are not included.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        let Some(members) = &self.members else {
+            return 0;
+        };
+        let entries = members.entries.capacity() * size_of::<(JsonString, JsonValue)>();
+        // The index stores a key and a position per slot, plus a control byte.
+        let index = members.index.capacity() * (size_of::<(JsonString, usize)>() + 1);
+        let keys: usize = self.keys().map(|k| k.heap_bytes()).sum();
+        size_of::<Members>() + entries + index + 2 * keys
+    }
+
+    /// Empties the object, yielding its values if no clone shares them;
+    /// keys are discarded.
+    pub(crate) fn drain_values(&mut self) -> impl Iterator<Item = JsonValue> + use<> {
+        self.members
+            .take()
+            .and_then(node::into_unique)
+            .into_iter()
+            .flat_map(|members| members.entries)
+            .map(|(_, v)| v)
+    }
+
+    /// Empties the object, moving its values into `stack`, and returns the
+    /// emptied allocation, unless another clone shares it.
+    pub(crate) fn recycle_into(&mut self, stack: &mut Vec<JsonValue>) -> Option<Node<Members>> {
+        let mut members = self.members.take()?;
+        if let Some(Members {
+            entries,
+            index,
+            repeated,
+        }) = node::get_mut(&mut members)
+        {
+            index.clear();
+            *repeated = false;
+            stack.extend(entries.drain(..).map(|(_, v)| v));
+            return Some(members);
+        }
+        let entries = node::into_unique(members).map(|members| members.entries);
+        stack.extend(entries.into_iter().flatten().map(|(_, v)| v));
+        None
+    }
+
+    /// An empty object filling an allocation [`JsonObject::recycle_into`]
+    /// returned.
+    pub(crate) fn recycled(members: Node<Members>) -> Self {
+        Self {
+            members: Some(members),
+        }
+    }
+
+    fn entries(&self) -> &[(JsonString, JsonValue)] {
+        self.members
+            .as_ref()
+            .map_or(&[], |members| &members.entries)
+    }
+
+    fn entries_mut(&mut self) -> &mut [(JsonString, JsonValue)] {
+        self.existing_mut()
+            .map_or(&mut [], |members| &mut members.entries)
+    }
+
+    /// Where the member for `key` is, or the last of them.
+    pub(crate) fn position(&self, key: &str) -> Option<usize> {
+        self.members.as_ref()?.index.get(key).copied()
+    }
+
+    fn is_repeated(&self) -> bool {
+        self.members
+            .as_ref()
+            .is_some_and(|members| members.repeated)
+    }
+
+    /// The members, allocating room for them if there is none yet.
+    fn members_mut(&mut self) -> &mut Members {
+        node::make_mut(self.members.get_or_insert_with(Node::default))
+    }
+
+    /// The members, if any room for them was allocated.
+    fn existing_mut(&mut self) -> Option<&mut Members> {
+        self.members.as_mut().map(node::make_mut)
+    }
+
+    /// The value of the member at position `i`.
+    pub(crate) fn value_at_mut(&mut self, i: usize) -> Option<&mut JsonValue> {
+        self.entries_mut().get_mut(i).map(|(_, v)| v)
+    }
+
+    /// Removes the member at position `i`.
+    pub(crate) fn remove_at(&mut self, i: usize) -> JsonValue {
+        let members = self.members_mut();
+        let (key, value) = members.entries.remove(i);
+        if members.repeated {
+            members.reindex();
+            return value;
+        }
+        members.index.remove(&key);
+        for (k, _) in &members.entries[i..] {
+            if let Some(pos) = members.index.get_mut(k) {
+                *pos -= 1;
+            }
+        }
+        value
+    }
+}
+
+impl Members {
+    /// Appends a member, returning its position.
+    fn push(&mut self, key: JsonString, value: JsonValue) -> usize {
+        let index = self.entries.len();
+        if self.index.insert(key.clone(), index).is_some() {
+            self.repeated = true;
+        }
+        self.entries.push((key, value));
+        index
+    }
+
+    fn reindex(&mut self) {
+        self.index.clear();
+        for (i, (k, _)) in self.entries.iter().enumerate() {
+            self.index.insert(k.clone(), i);
+        }
+        self.repeated = self.index.len() < self.entries.len();
+    }
+}
+
+/// Compares two strings after Unicode lowercasing, without allocating.
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .eq(b.chars().flat_map(char::to_lowercase))
+}
+
+/// A view into one member of a [`JsonObject`], from [`JsonObject::entry`].
+pub enum Entry<'a> {
+    Occupied(OccupiedEntry<'a>),
+    Vacant(VacantEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts `default` if the member is missing, then returns the value.
+    pub fn or_insert(self, default: JsonValue) -> &'a mut JsonValue {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> JsonValue>(self, default: F) -> &'a mut JsonValue {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Runs `f` on the existing value, if any.
+    pub fn and_modify<F: FnOnce(&mut JsonValue)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+/// An [`Entry`] for a key already present.
+pub struct OccupiedEntry<'a> {
+    object: &'a mut JsonObject,
+    index: usize,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    pub fn key(&self) -> &str {
+        &self.object.entries()[self.index].0
+    }
+
+    pub fn get(&self) -> &JsonValue {
+        &self.object.entries()[self.index].1
+    }
+
+    pub fn get_mut(&mut self) -> &mut JsonValue {
+        &mut self.object.entries_mut()[self.index].1
+    }
+
+    pub fn into_mut(self) -> &'a mut JsonValue {
+        &mut self.object.entries_mut()[self.index].1
+    }
+
+    /// Replaces the value, returning the old one.
+    pub fn insert(&mut self, value: JsonValue) -> JsonValue {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes the member, preserving the order of the others.
+    pub fn remove(self) -> JsonValue {
+        self.object.remove_at(self.index)
+    }
+}
+
+/// An [`Entry`] for a key not yet present.
+pub struct VacantEntry<'a> {
+    object: &'a mut JsonObject,
+    key: JsonString,
+}
+
+impl<'a> VacantEntry<'a> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Appends the member and returns its value.
+    pub fn insert(self, value: JsonValue) -> &'a mut JsonValue {
+        let members = self.object.members_mut();
+        let index = members.push(self.key, value);
+        &mut members.entries[index].1
+    }
+}
+
+impl JsonValue {
+    /// The [`Entry`] for `key` in this object, for in-place upserts.
+    ///
+    /// `null` is first replaced by an empty object, so a fresh
+    /// [`JsonValue::default`] can be filled in directly. Any other non-object
+    /// returns `None`.
+    pub fn entry(&mut self, key: impl Into<JsonString>) -> Option<Entry<'_>> {
+        if self.is_null() {
+            *self = JsonValue::new_object();
+        }
+        self.as_object_mut().map(|obj| obj.entry(key))
+    }
+
+    /// The member `key`, inserting `default()` first if it is missing.
+    ///
+    /// Returns `None` under the same conditions as [`JsonValue::entry`], so
+    /// nested upserts chain with `?`.
+    pub fn get_or_insert_with<F>(
+        &mut self,
+        key: impl Into<JsonString>,
+        default: F,
+    ) -> Option<&mut JsonValue>
+    where
+        F: FnOnce() -> JsonValue,
+    {
+        self.entry(key).map(|entry| entry.or_insert_with(default))
+    }
+}
+
+impl PartialEq for JsonObject {
+    fn eq(&self, other: &Self) -> bool {
+        if self.ptr_eq(other) {
+            return true;
+        }
+        if self.len() != other.len() {
+            return false;
+        }
+        if !self.is_repeated() && !other.is_repeated() {
+            return self.iter().all(|(k, v)| other.get(k) == Some(v));
+        }
+        // The values of each key, in order, match
+        self.keys().all(|k| self.get_all(k).eq(other.get_all(k)))
+    }
+}
+
+impl fmt::Debug for JsonObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl From<HashMap<String, JsonValue>> for JsonObject {
+    fn from(map: HashMap<String, JsonValue>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K: Into<JsonString>> FromIterator<(K, JsonValue)> for JsonObject {
+    fn from_iter<I: IntoIterator<Item = (K, JsonValue)>>(iter: I) -> Self {
+        let mut obj = JsonObject::new();
+        obj.extend(iter);
+        obj
+    }
+}
+
+impl<K: Into<JsonString>> Extend<(K, JsonValue)> for JsonObject {
+    fn extend<I: IntoIterator<Item = (K, JsonValue)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+/// Borrowing iterator over `(key, value)` pairs, in order.
+pub struct Iter<'a> {
+    inner: std::slice::Iter<'a, (JsonString, JsonValue)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a JsonString, &'a JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Iterator over `(key, mutable value)` pairs, in order.
+pub struct IterMut<'a> {
+    inner: std::slice::IterMut<'a, (JsonString, JsonValue)>,
+}
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a JsonString, &'a mut JsonValue);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (&*k, v))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> IntoIterator for &'a JsonObject {
+    type Item = (&'a JsonString, &'a JsonValue);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut JsonObject {
+    type Item = (&'a JsonString, &'a mut JsonValue);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl IntoIterator for JsonObject {
+    type Item = (JsonString, JsonValue);
+    type IntoIter = std::vec::IntoIter<(JsonString, JsonValue)>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.members
+            .take()
+            .map_or_else(Vec::new, |members| node::into_inner(members).entries)
+            .into_iter()
+    }
+}
+
+impl Drop for JsonObject {
+    fn drop(&mut self) {
+        if self.values().any(has_children) {
+            drop_iteratively(self.drain_values().collect());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(pairs: &[(&str, f64)]) -> JsonObject {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), JsonValue::Number(*v)))
+            .collect()
+    }
+
+    fn keys(obj: &JsonObject) -> Vec<&str> {
+        obj.keys().map(JsonString::as_str).collect()
+    }
+
+    #[test]
+    fn test_preserves_insertion_order() {
+        let obj = object(&[("z", 1.0), ("a", 2.0), ("m", 3.0)]);
+        assert_eq!(keys(&obj), vec!["z", "a", "m"]);
+    }
+
+    #[test]
+    fn test_insert_existing_key_keeps_position() {
+        let mut obj = object(&[("a", 1.0), ("b", 2.0)]);
+        let old = obj.insert("a".to_string(), JsonValue::Null);
+        assert_eq!(old, Some(JsonValue::Number(1.0)));
+        assert_eq!(keys(&obj), vec!["a", "b"]);
+        assert_eq!(obj.get("a"), Some(&JsonValue::Null));
+        assert_eq!(obj.len(), 2);
+    }
+
+    #[test]
+    fn test_remove_shifts_and_reindexes() {
+        let mut obj = object(&[("a", 1.0), ("b", 2.0), ("c", 3.0)]);
+        assert_eq!(obj.remove("a"), Some(JsonValue::Number(1.0)));
+        assert_eq!(obj.remove("a"), None);
+        assert_eq!(keys(&obj), vec!["b", "c"]);
+        assert_eq!(obj.get("c"), Some(&JsonValue::Number(3.0)));
+        assert!(!obj.contains_key("a"));
+    }
+
+    #[test]
+    fn test_retain_and_sort_keys() {
+        let mut obj = object(&[("d", 4.0), ("b", 2.0), ("c", 3.0), ("a", 1.0)]);
+        obj.retain(|k, _| k != "c");
+        obj.sort_keys();
+        assert_eq!(keys(&obj), vec!["a", "b", "d"]);
+        assert_eq!(obj.get("d"), Some(&JsonValue::Number(4.0)));
+    }
+
+    #[test]
+    fn test_equality_ignores_order() {
+        let left = object(&[("a", 1.0), ("b", 2.0)]);
+        let right = object(&[("b", 2.0), ("a", 1.0)]);
+        assert_eq!(left, right);
+        assert_ne!(left, object(&[("a", 1.0)]));
+        assert_ne!(left, object(&[("a", 1.0), ("b", 3.0)]));
+    }
+
+    #[test]
+    fn test_iter_mut_and_into_iter() {
+        let mut obj = object(&[("a", 1.0), ("b", 2.0)]);
+        for (_, v) in obj.iter_mut() {
+            *v = JsonValue::Boolean(true);
+        }
+        let owned: Vec<_> = obj.into_iter().collect();
+        assert_eq!(
+            owned,
+            vec![
+                ("a".into(), JsonValue::Boolean(true)),
+                ("b".into(), JsonValue::Boolean(true)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_hash_map() {
+        let mut map = HashMap::new();
+        map.insert("k".to_string(), JsonValue::Null);
+        let obj = JsonObject::from(map);
+        assert_eq!(obj.get("k"), Some(&JsonValue::Null));
+    }
+
+    #[test]
+    fn test_entry_inserts_and_updates() {
+        let mut obj = object(&[("hits", 1.0)]);
+        obj.entry("hits")
+            .and_modify(|v| *v = JsonValue::Number(2.0));
+        obj.entry("misses").or_insert(JsonValue::Number(0.0));
+        *obj.entry("hits").or_insert_with(|| unreachable!()) = JsonValue::Number(3.0);
+        assert_eq!(keys(&obj), vec!["hits", "misses"]);
+        assert_eq!(obj.get("hits"), Some(&JsonValue::Number(3.0)));
+        assert_eq!(obj.get("misses"), Some(&JsonValue::Number(0.0)));
+    }
+
+    #[test]
+    fn test_occupied_entry_remove_keeps_order() {
+        let mut obj = object(&[("a", 1.0), ("b", 2.0), ("c", 3.0)]);
+        match obj.entry("b") {
+            Entry::Occupied(entry) => {
+                assert_eq!(entry.key(), "b");
+                assert_eq!(entry.remove(), JsonValue::Number(2.0));
+            }
+            Entry::Vacant(_) => panic!("Expected an occupied entry"),
+        }
+        assert_eq!(keys(&obj), vec!["a", "c"]);
+        assert_eq!(obj.get("c"), Some(&JsonValue::Number(3.0)));
+        assert!(matches!(obj.entry("b"), Entry::Vacant(_)));
+    }
+
+    #[test]
+    fn test_debug_lists_members_in_order() {
+        let obj = object(&[("b", 1.0), ("a", 2.0)]);
+        assert_eq!(
+            format!("{obj:?}"),
+            r#"{"b": Number(1.0), "a": Number(2.0)}"#
+        );
+    }
+
+    #[test]
+    fn test_value_entry_upserts_nested_members() {
+        let mut config = JsonValue::default();
+        *config
+            .get_or_insert_with("server", JsonValue::new_object)
+            .and_then(|server| server.get_or_insert_with("port", || JsonValue::Number(80.0)))
+            .unwrap() = JsonValue::Number(8080.0);
+        config
+            .entry("retries")
+            .unwrap()
+            .or_insert(JsonValue::Number(3.0));
+        config
+            .entry("retries")
+            .unwrap()
+            .and_modify(|n| *n = JsonValue::Number(5.0));
+
+        let server = config.get("server").unwrap();
+        assert_eq!(server.get("port"), Some(&JsonValue::Number(8080.0)));
+        assert_eq!(config.get("retries"), Some(&JsonValue::Number(5.0)));
+    }
+
+    #[test]
+    fn test_value_entry_rejects_non_objects() {
+        let mut value = JsonValue::Number(1.0);
+        assert!(value.entry("a").is_none());
+        assert!(value.get_or_insert_with("a", JsonValue::default).is_none());
+        assert_eq!(value, JsonValue::Number(1.0));
+    }
+
+    #[test]
+    fn test_append_keeps_repeated_keys() {
+        let mut obj = object(&[("a", 1.0), ("b", 2.0)]);
+        obj.append("a", JsonValue::Number(3.0));
+        assert_eq!(keys(&obj), vec!["a", "b", "a"]);
+        assert_eq!(obj.get("a"), Some(&JsonValue::Number(3.0)));
+        let all: Vec<_> = obj.get_all("a").collect();
+        assert_eq!(all, [&JsonValue::Number(1.0), &JsonValue::Number(3.0)]);
+        assert_eq!(obj.get_all("b").count(), 1);
+
+        obj.insert("a", JsonValue::Null);
+        assert_eq!(obj.get_all("a").last(), Some(&JsonValue::Null));
+        assert_eq!(obj.remove("a"), Some(JsonValue::Null));
+        assert_eq!(keys(&obj), vec!["b"]);
+        assert_eq!(obj.get_all("a").next(), None);
+    }
+
+    #[test]
+    fn test_get_ignore_case() {
+        let obj = object(&[("Content-Type", 1.0), ("content-type", 2.0), ("ÄRGER", 3.0)]);
+        assert_eq!(
+            obj.get_ignore_case("content-type"),
+            Some(&JsonValue::Number(2.0))
+        );
+        assert_eq!(
+            obj.get_ignore_case("CONTENT-TYPE"),
+            Some(&JsonValue::Number(1.0))
+        );
+        assert_eq!(obj.get_ignore_case("ärger"), Some(&JsonValue::Number(3.0)));
+        assert_eq!(obj.get_ignore_case("content"), None);
+    }
+}
@@ -0,0 +1,482 @@
+use crate::{Interner, JsonError, JsonPath, JsonString};
+use std::time::{Duration, Instant};
+
+/// Converts a number written with a unit suffix (`10KB`, `2h`) into a plain number.
+///
+/// Receives the numeric part and the suffix, and returns `None` when the
+/// suffix is not one the handler understands.
+pub type SuffixHandler = fn(value: f64, suffix: &str) -> Option<f64>;
+
+/// What to do when an object repeats a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Keep the last value seen for the key.
+    #[default]
+    LastWins,
+    /// Keep every member: a repeated key stays repeated in the object, in
+    /// document order. [`JsonValue::get`](crate::JsonValue::get) sees the
+    /// last value and [`JsonValue::get_all`](crate::JsonValue::get_all)
+    /// every one.
+    Collect,
+    /// Fail with [`JsonError::DuplicateKey`](crate::JsonError::DuplicateKey).
+    Error,
+}
+
+/// How much of the input error messages may quote, for inputs that hold
+/// secrets. See [`JsonError::redacted`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Redaction {
+    /// Quote offending text in full.
+    #[default]
+    Full,
+    /// Quote at most this many characters of it, followed by `…`.
+    Truncate(usize),
+    /// Quote none of it; errors keep their kind, position and path.
+    Omit,
+}
+
+impl Redaction {
+    /// Shortens or replaces `text` quoted from the input.
+    pub(crate) fn apply(self, text: &mut String) {
+        match self {
+            Redaction::Full => {}
+            Redaction::Truncate(max) => {
+                if let Some((cut, _)) = text.char_indices().nth(max) {
+                    text.truncate(cut);
+                    text.push('…');
+                }
+            }
+            Redaction::Omit => *text = "<redacted>".to_string(),
+        }
+    }
+}
+
+/// Optional extensions to strict JSON syntax.
+///
+/// Everything is off by default, so `ParserOptions::default()` parses plain JSON.
+#[derive(Debug, Clone, Default)]
+pub struct ParserOptions {
+    pub(crate) allow_digit_separators: bool,
+    pub(crate) allow_comments: bool,
+    pub(crate) allow_trailing_commas: bool,
+    pub(crate) allow_single_quotes: bool,
+    pub(crate) suffix_handlers: Vec<SuffixHandler>,
+    pub(crate) duplicate_keys: DuplicateKeys,
+    pub(crate) lowercase_keys: bool,
+    #[cfg(feature = "unicode")]
+    pub(crate) nfc: bool,
+    pub(crate) strict_whitespace: bool,
+    pub(crate) strict_strings: bool,
+    pub(crate) strict_numbers: bool,
+    pub(crate) reject_trailing_content: bool,
+    pub(crate) redaction: Redaction,
+    pub(crate) limits: Limits,
+    pub(crate) interner: Option<Interner>,
+}
+
+impl ParserOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Exactly the grammar of RFC 8259: only space, tab, line feed and
+    /// carriage return between tokens, no raw control characters in
+    /// strings, no leading zeros or bare `.` in numbers, no repeated keys
+    /// and nothing after the value. Nesting is capped at depth 128, as in
+    /// [`Limits::untrusted`], which the RFC allows parsers to do.
+    pub fn strict_rfc8259() -> Self {
+        Self::new()
+            .limits(Limits::new().max_depth(DEFAULT_MAX_DEPTH))
+            .strict_whitespace(true)
+            .strict_strings(true)
+            .strict_numbers(true)
+            .duplicate_keys(DuplicateKeys::Error)
+            .reject_trailing_content(true)
+    }
+
+    /// Accepts what people write in hand-edited config files: comments,
+    /// trailing commas, single-quoted strings and digit separators, with
+    /// loose whitespace, strings and numbers. The last of repeated keys is
+    /// kept.
+    pub fn lenient() -> Self {
+        Self::new()
+            .allow_comments(true)
+            .allow_trailing_commas(true)
+            .allow_single_quotes(true)
+            .allow_digit_separators(true)
+    }
+
+    /// Plain JSON with every [`Limits`] preset from [`Limits::untrusted`],
+    /// for input from outside the process such as user uploads.
+    ///
+    /// With these options [`JsonParser`](crate::JsonParser) returns an error,
+    /// never panics or overflows the stack, for any input. The parser is
+    /// recursive, not iterative: it builds each nested value with a call
+    /// frame, so it is the depth limit of 128 that keeps the stack small.
+    /// Raising [`Limits::max_depth`] or setting it to `None` gives up the
+    /// no-overflow guarantee. The size, token and string limits bound the
+    /// memory it allocates to a small multiple of the input size. Crate
+    /// tests feed it random bytes and mutated documents to back this up.
+    /// Input that arrives as bytes goes through
+    /// [`JsonParser::from_slice`](crate::JsonParser::from_slice), which
+    /// rejects invalid UTF-8 with an error.
+    pub fn untrusted() -> Self {
+        Self::new().limits(Limits::untrusted())
+    }
+
+    /// Accept `_` between digits, e.g. `5_000`.
+    pub fn allow_digit_separators(mut self, allow: bool) -> Self {
+        self.allow_digit_separators = allow;
+        self
+    }
+
+    /// Accept `// line` and `/* block */` comments wherever whitespace may appear.
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    /// Accept a comma after the last element of an array or object.
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.allow_trailing_commas = allow;
+        self
+    }
+
+    /// Accept strings delimited by `'`, in which `\'` is a valid escape.
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.allow_single_quotes = allow;
+        self
+    }
+
+    /// Registers a handler for numbers directly followed by letters.
+    ///
+    /// Handlers are tried in registration order; the first to return a value wins.
+    pub fn with_suffix_handler(mut self, handler: SuffixHandler) -> Self {
+        self.suffix_handlers.push(handler);
+        self
+    }
+
+    /// Chooses how repeated object keys are handled.
+    pub fn duplicate_keys(mut self, policy: DuplicateKeys) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Lowercases every object key as it is read, so lookups need not guess the
+    /// producer's casing. Keys that fold together count as duplicates under
+    /// [`ParserOptions::duplicate_keys`].
+    pub fn lowercase_keys(mut self, enabled: bool) -> Self {
+        self.lowercase_keys = enabled;
+        self
+    }
+
+    /// Rewrites string values and object keys to Unicode Normalization
+    /// Form C as they are read, so text that only differs in how accents
+    /// are encoded compares equal. Keys that normalize together count as
+    /// duplicates under [`ParserOptions::duplicate_keys`].
+    #[cfg(feature = "unicode")]
+    pub fn nfc(mut self, enabled: bool) -> Self {
+        self.nfc = enabled;
+        self
+    }
+
+    /// An object key as read, after [`ParserOptions::lowercase_keys`] and
+    /// [`ParserOptions::nfc`].
+    pub(crate) fn fold_key(&self, key: String) -> JsonString {
+        let key = if self.lowercase_keys {
+            key.to_lowercase()
+        } else {
+            key
+        };
+        let key = self.normalize(key);
+        match &self.interner {
+            Some(interner) => interner.intern(&key),
+            None => key.into(),
+        }
+    }
+
+    /// A string value as read, after [`ParserOptions::nfc`].
+    pub(crate) fn fold_string(&self, s: String) -> JsonString {
+        let s = self.normalize(s);
+        match &self.interner {
+            Some(interner) => interner.value(s),
+            None => s.into(),
+        }
+    }
+
+    fn normalize(&self, s: String) -> String {
+        #[cfg(feature = "unicode")]
+        if self.nfc {
+            return crate::normalize::nfc(s);
+        }
+        s
+    }
+
+    /// Shares object keys and short string values with every other
+    /// document parsed with `interner`, instead of allocating them for
+    /// each one.
+    pub fn interner(mut self, interner: Interner) -> Self {
+        self.interner = Some(interner);
+        self
+    }
+
+    /// Only accept the four whitespace characters JSON defines, not other
+    /// Unicode spaces such as U+00A0.
+    pub fn strict_whitespace(mut self, enabled: bool) -> Self {
+        self.strict_whitespace = enabled;
+        self
+    }
+
+    /// Reject unescaped control characters (U+0000 to U+001F) in strings.
+    pub fn strict_strings(mut self, enabled: bool) -> Self {
+        self.strict_strings = enabled;
+        self
+    }
+
+    /// Reject numbers outside the JSON grammar that `f64` parsing accepts,
+    /// such as `01`, `1.` and `1.e5`.
+    pub fn strict_numbers(mut self, enabled: bool) -> Self {
+        self.strict_numbers = enabled;
+        self
+    }
+
+    /// Fail when anything but whitespace follows the top-level value,
+    /// instead of ignoring it.
+    pub fn reject_trailing_content(mut self, enabled: bool) -> Self {
+        self.reject_trailing_content = enabled;
+        self
+    }
+
+    /// Limits how much of the input errors quote, so logging an error does
+    /// not leak the document. Off by default.
+    pub fn redact_errors(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Applies resource limits; inputs exceeding them are rejected with an error.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+/// Nesting allowed by APIs that walk a whole document recursively when the
+/// caller's limits set none, as in [`Limits::untrusted`].
+pub(crate) const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Resource limits for parsing untrusted input. Unlimited by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_input_bytes: Option<usize>,
+    pub(crate) max_tokens: Option<usize>,
+    pub(crate) max_string_chars: Option<usize>,
+    pub(crate) max_duration: Option<Duration>,
+}
+
+impl Limits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits for hostile input: depth 128, 16 MiB of text, one million
+    /// tokens and strings of up to one million characters.
+    pub fn untrusted() -> Self {
+        Self::new()
+            .max_depth(DEFAULT_MAX_DEPTH)
+            .max_input_bytes(16 * 1024 * 1024)
+            .max_tokens(1_000_000)
+            .max_string_chars(1_000_000)
+    }
+
+    /// These limits, with a depth of [`DEFAULT_MAX_DEPTH`] if they set none.
+    pub(crate) fn capped(mut self) -> Self {
+        self.max_depth.get_or_insert(DEFAULT_MAX_DEPTH);
+        self
+    }
+
+    /// Maximum nesting of arrays and objects; a top-level container is depth 1.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Maximum size of the input text, in UTF-8 bytes.
+    pub fn max_input_bytes(mut self, bytes: usize) -> Self {
+        self.max_input_bytes = Some(bytes);
+        self
+    }
+
+    /// Maximum number of tokens: brackets, commas, colons and literals.
+    pub fn max_tokens(mut self, tokens: usize) -> Self {
+        self.max_tokens = Some(tokens);
+        self
+    }
+
+    /// Maximum length of a string or key, in characters after unescaping.
+    pub fn max_string_chars(mut self, chars: usize) -> Self {
+        self.max_string_chars = Some(chars);
+        self
+    }
+
+    /// Maximum wall-clock time from the start of tokenizing to the parsed
+    /// value. The clock is read every 1024 tokens and values, so parsing
+    /// may run a little past the deadline before it stops.
+    pub fn max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+}
+
+/// Enforces [`Limits::max_duration`], started when tokenizing begins and
+/// handed on to the parser.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Clock {
+    deadline: Option<(Instant, Duration)>,
+    ticks: usize,
+}
+
+impl Clock {
+    /// How many ticks pass between reads of the system clock.
+    pub(crate) const INTERVAL: usize = 1024;
+
+    pub(crate) fn start(limits: &Limits) -> Self {
+        Self {
+            deadline: limits
+                .max_duration
+                .map(|limit| (Instant::now() + limit, limit)),
+            ticks: 0,
+        }
+    }
+
+    /// Counts one unit of work at `position`, failing once the deadline
+    /// has passed.
+    pub(crate) fn tick(&mut self, position: usize) -> Result<(), JsonError> {
+        let Some((deadline, limit)) = self.deadline else {
+            return Ok(());
+        };
+        self.ticks += 1;
+        if self.ticks % Self::INTERVAL == 1 && Instant::now() >= deadline {
+            return Err(JsonError::TimeBudgetExceeded {
+                limit,
+                position,
+                path: JsonPath::new(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Byte sizes: `B`, SI multiples (`KB`, `MB`, `GB`, `TB`) and binary
+/// multiples (`KiB`, `MiB`, `GiB`, `TiB`).
+pub fn byte_size_suffix(value: f64, suffix: &str) -> Option<f64> {
+    let multiplier = match suffix {
+        "B" => 1.0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(value * multiplier)
+}
+
+/// Durations, converted to seconds: `ms`, `s`, `m`, `h`, `d`.
+pub fn duration_suffix(value: f64, suffix: &str) -> Option<f64> {
+    let multiplier = match suffix {
+        "ms" => 0.001,
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        "d" => 86400.0,
+        _ => return None,
+    };
+    Some(value * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options_are_strict() {
+        let options = ParserOptions::default();
+        assert!(!options.allow_digit_separators);
+        assert!(options.suffix_handlers.is_empty());
+        assert_eq!(options.duplicate_keys, DuplicateKeys::LastWins);
+    }
+
+    #[test]
+    fn test_builder_methods() {
+        let options = ParserOptions::new()
+            .allow_digit_separators(true)
+            .with_suffix_handler(byte_size_suffix)
+            .with_suffix_handler(duration_suffix);
+        assert!(options.allow_digit_separators);
+        assert_eq!(options.suffix_handlers.len(), 2);
+    }
+
+    #[test]
+    fn test_presets() {
+        let strict = ParserOptions::strict_rfc8259();
+        assert!(strict.strict_whitespace && strict.strict_strings && strict.strict_numbers);
+        assert!(strict.reject_trailing_content);
+        assert_eq!(strict.duplicate_keys, DuplicateKeys::Error);
+        assert!(!strict.allow_comments && !strict.allow_trailing_commas);
+
+        let lenient = ParserOptions::lenient();
+        assert!(lenient.allow_comments && lenient.allow_trailing_commas);
+        assert!(lenient.allow_single_quotes && lenient.allow_digit_separators);
+        assert!(!lenient.strict_numbers && !lenient.reject_trailing_content);
+        assert_eq!(lenient.duplicate_keys, DuplicateKeys::LastWins);
+
+        let untrusted = ParserOptions::untrusted();
+        assert_eq!(untrusted.limits, Limits::untrusted());
+        assert!(untrusted.limits.max_depth.is_some() && untrusted.limits.max_tokens.is_some());
+        assert!(!untrusted.allow_comments && !untrusted.strict_numbers);
+    }
+
+    #[test]
+    fn test_redaction() {
+        let redact = |redaction: Redaction, text: &str| {
+            let mut text = text.to_string();
+            redaction.apply(&mut text);
+            text
+        };
+        assert_eq!(redact(Redaction::Full, "sk_live_123"), "sk_live_123");
+        assert_eq!(redact(Redaction::Truncate(4), "séc_live_123"), "séc_…");
+        assert_eq!(redact(Redaction::Truncate(4), "abcd"), "abcd");
+        assert_eq!(redact(Redaction::Truncate(0), "a"), "…");
+        assert_eq!(redact(Redaction::Omit, "sk_live_123"), "<redacted>");
+    }
+
+    #[test]
+    fn test_byte_size_suffix() {
+        let cases = [
+            ("B", 10.0),
+            ("KB", 10_000.0),
+            ("MB", 10_000_000.0),
+            ("KiB", 10_240.0),
+            ("MiB", 10_485_760.0),
+        ];
+        for (suffix, expected) in cases {
+            assert_eq!(byte_size_suffix(10.0, suffix), Some(expected), "{suffix}");
+        }
+        assert_eq!(byte_size_suffix(10.0, "kb"), None);
+        assert_eq!(byte_size_suffix(10.0, "h"), None);
+    }
+
+    #[test]
+    fn test_duration_suffix() {
+        assert_eq!(duration_suffix(2.0, "h"), Some(7200.0));
+        assert_eq!(duration_suffix(1.5, "m"), Some(90.0));
+        assert_eq!(duration_suffix(250.0, "ms"), Some(0.25));
+        assert_eq!(duration_suffix(1.0, "d"), Some(86400.0));
+        assert_eq!(duration_suffix(1.0, "KB"), None);
+    }
+}
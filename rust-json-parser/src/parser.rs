@@ -1,34 +1,307 @@
-use crate::{JsonError, JsonValue, Result, Token, Tokenizer};
-use std::collections::HashMap;
+use crate::options::Clock;
+use crate::pool::Pool;
+use crate::{
+    DuplicateKeys, ExpectedToken, JsonArray, JsonError, JsonObject, JsonPath, JsonString,
+    JsonValue, ParserOptions, PathSegment, Result, Selection, Token, Tokenizer,
+};
+use std::collections::VecDeque;
 use std::mem::discriminant;
+use std::ops::Range;
 
 #[derive(Debug)]
 pub struct JsonParser {
     tokens: Vec<Token>,
+    /// Character offset where each token starts.
+    starts: Vec<usize>,
+    /// Character offset of the end of input.
+    end: usize,
     current: usize,
+    depth: usize,
+    options: ParserOptions,
+    clock: Clock,
+    pub(crate) recovery: Option<Recovering>,
+    /// The tokenizer's decode buffer, kept for [`JsonParser::reset`].
+    chars: Vec<char>,
+    pool: Pool,
+}
+
+/// State for [`parse_with_recovery`](crate::parse_with_recovery).
+#[derive(Debug, Default)]
+pub(crate) struct Recovering {
+    /// Malformed literals, with the index of the token that follows each.
+    pub(crate) malformed: VecDeque<(usize, JsonError)>,
+    /// Path of the value being parsed.
+    path: JsonPath,
+    /// Where the value being parsed sits among the members or elements of
+    /// each container around it, which tells apart members that repeat a
+    /// key.
+    positions: Vec<usize>,
+    /// Values read as null in place of a malformed or missing one, in
+    /// document order.
+    pub(crate) incidents: Vec<Incident>,
+}
+
+/// A value [`Recovering`] read as null.
+#[derive(Debug)]
+pub(crate) struct Incident {
+    pub(crate) path: JsonPath,
+    pub(crate) positions: Vec<usize>,
+    pub(crate) error: JsonError,
+}
+
+impl Recovering {
+    /// Drops the incidents of the member at `position` once a later member
+    /// with the same key replaced it; those from `mark` on belong to the
+    /// new value.
+    fn replaced(&mut self, mark: usize, position: usize) {
+        let mut prefix = self.positions.clone();
+        prefix.push(position);
+        let mut seen = 0;
+        self.incidents.retain(|incident| {
+            seen += 1;
+            seen > mark || !incident.positions.starts_with(&prefix)
+        });
+    }
 }
 
 impl JsonParser {
     pub fn new(input: &str) -> Result<Self> {
-        let mut tokenizer = Tokenizer::new(input);
-        let tokens = tokenizer.tokenize()?;
-        Ok(Self { tokens, current: 0 })
+        Self::with_options(input, ParserOptions::default())
+    }
+
+    pub fn with_options(input: &str, options: ParserOptions) -> Result<Self> {
+        let mut parser = Self::from_tokens("", Vec::new(), Clock::default(), options);
+        parser.reset(input)?;
+        Ok(parser)
+    }
+
+    /// A parser over `input` given as bytes, such as a request body read
+    /// from a socket.
+    ///
+    /// The size limit is checked before the bytes are decoded, and bytes
+    /// that are not valid UTF-8 are reported as
+    /// [`JsonError::InvalidUtf8`] at the character they start on.
+    ///
+    /// ```
+    /// use rust_json_parser::{JsonError, JsonParser, ParserOptions};
+    ///
+    /// let value = JsonParser::from_slice(b"[1, 2]", ParserOptions::untrusted())?.parse()?;
+    /// assert_eq!(value.as_array().map(|items| items.len()), Some(2));
+    ///
+    /// let err = JsonParser::from_slice(b"[\"\xff\"]", ParserOptions::untrusted()).unwrap_err();
+    /// assert!(matches!(err, JsonError::InvalidUtf8 { position: 2, .. }));
+    /// # Ok::<(), JsonError>(())
+    /// ```
+    pub fn from_slice(input: &[u8], options: ParserOptions) -> Result<Self> {
+        check_input_size(input, &options)?;
+        let input = std::str::from_utf8(input).map_err(|err| JsonError::InvalidUtf8 {
+            // Each character of the valid prefix has one byte that is not a
+            // continuation byte (0b10xxxxxx)
+            position: input[..err.valid_up_to()]
+                .iter()
+                .filter(|&&byte| byte & 0xC0 != 0x80)
+                .count(),
+            path: JsonPath::new(),
+        })?;
+        Self::with_options(input, options)
     }
 
+    /// Starts over on `input` with the same options, keeping the buffers
+    /// the previous input was read into.
+    ///
+    /// Parsing many small messages with one parser this way saves the
+    /// allocations a new parser makes for each. If `input` fails to
+    /// tokenize, the error is returned and the parser is left empty.
+    ///
+    /// ```
+    /// use rust_json_parser::{JsonParser, JsonValue};
+    ///
+    /// let mut parser = JsonParser::new("")?;
+    /// for message in [r#"{"id": 1}"#, r#"{"id": 2}"#] {
+    ///     parser.reset(message)?;
+    ///     let value = parser.parse()?;
+    ///     assert!(value.get("id").and_then(JsonValue::as_f64).is_some());
+    /// }
+    /// # Ok::<(), rust_json_parser::JsonError>(())
+    /// ```
+    pub fn reset(&mut self, input: &str) -> Result<()> {
+        self.tokens.clear();
+        self.starts.clear();
+        self.end = 0;
+        self.current = 0;
+        self.depth = 0;
+        self.recovery = None;
+        check_input_size(input, &self.options)?;
+        let buffer = std::mem::take(&mut self.chars);
+        let options = std::mem::take(&mut self.options);
+        let mut tokenizer = Tokenizer::reusing(buffer, input, options);
+        let tokenized = tokenizer.tokenize_into(&mut self.tokens, &mut self.starts);
+        self.end = tokenizer.char_count();
+        self.clock = tokenizer.clock();
+        (self.chars, self.options) = tokenizer.into_parts();
+        if tokenized.is_err() {
+            self.tokens.clear();
+            self.starts.clear();
+        }
+        tokenized
+    }
+
+    /// A parser that reads malformed literals, and values missing before
+    /// a `,` or closing bracket, as null and records them in `recovery`.
+    pub(crate) fn recovering(input: &str, options: ParserOptions) -> Result<Self> {
+        check_input_size(input, &options)?;
+        let mut tokenizer = Tokenizer::with_options(input, options.clone());
+        let (tokens, malformed) = tokenizer.tokenize_recovering()?;
+        let mut parser = Self::from_tokens(input, tokens, tokenizer.clock(), options);
+        parser.recovery = Some(Recovering {
+            malformed: malformed.into(),
+            ..Recovering::default()
+        });
+        Ok(parser)
+    }
+
+    pub(crate) fn from_tokens(
+        input: &str,
+        tokens: Vec<(Token, Range<usize>)>,
+        clock: Clock,
+        options: ParserOptions,
+    ) -> Self {
+        let (tokens, starts) = tokens
+            .into_iter()
+            .map(|(token, span)| (token, span.start))
+            .unzip();
+        Self {
+            tokens,
+            starts,
+            end: input.chars().count(),
+            current: 0,
+            depth: 0,
+            options,
+            clock,
+            recovery: None,
+            chars: Vec::new(),
+            pool: Pool::default(),
+        }
+    }
+
+    /// Hands back a value this parser built, so the arrays and objects
+    /// parsed next reuse the allocations of its arrays and objects.
+    ///
+    /// Together with [`JsonParser::reset`], a loop parsing messages of
+    /// the same few shapes allocates little beyond long strings once the
+    /// first messages have been handed back. Any value can be handed back;
+    /// contents a clone still shares are left to the clone.
+    ///
+    /// ```
+    /// use rust_json_parser::JsonParser;
+    ///
+    /// let mut parser = JsonParser::new("")?;
+    /// for message in [r#"{"ids": [1, 2]}"#, r#"{"ids": [3]}"#] {
+    ///     parser.reset(message)?;
+    ///     let value = parser.parse()?;
+    ///     assert!(value.get("ids").is_some());
+    ///     parser.recycle(value);
+    /// }
+    /// # Ok::<(), rust_json_parser::JsonError>(())
+    /// ```
+    pub fn recycle(&mut self, value: JsonValue) {
+        self.pool.recycle(value);
+    }
+
+    /// Parses the next value. At the top level, errors are redacted as
+    /// [`ParserOptions::redact_errors`] says.
     pub fn parse(&mut self) -> Result<JsonValue> {
+        if self.depth > 0 {
+            return self.parse_value();
+        }
+        self.top_level(Self::parse_value)
+    }
+
+    /// Parses the next value like [`JsonParser::parse`], but builds only the
+    /// parts `selection` names.
+    ///
+    /// Everything else is still checked for syntax, limits included, but no
+    /// value is built for it, which saves most of the work when only a few
+    /// fields of a large document are read. Objects and arrays on the way
+    /// to a selected value keep just the selected members and elements, so
+    /// elements selected by index close up. Repeated keys are only looked
+    /// for among selected members.
+    ///
+    /// ```
+    /// use rust_json_parser::{JsonParser, Selection};
+    ///
+    /// let input = r#"{"id": 7, "blob": [1, 2, 3], "items": [{"sku": "a", "n": 1}, {"sku": "b"}]}"#;
+    /// let selection = Selection::new()
+    ///     .field("id", Selection::all())
+    ///     .field("items", Selection::new().elements(Selection::new().field("sku", Selection::all())));
+    /// let value = JsonParser::new(input)?.parse_selected(&selection)?;
+    /// assert_eq!(value.to_string(), r#"{"id":7,"items":[{"sku":"a"},{"sku":"b"}]}"#);
+    /// # Ok::<(), rust_json_parser::JsonError>(())
+    /// ```
+    pub fn parse_selected(&mut self, selection: &Selection) -> Result<JsonValue> {
+        self.top_level(|parser| parser.select(selection))
+    }
+
+    /// Runs `parse` on the document and checks that nothing follows it,
+    /// redacting errors as [`ParserOptions::redact_errors`] says.
+    fn top_level(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<JsonValue>,
+    ) -> Result<JsonValue> {
+        parse(self)
+            .and_then(|value| match self.peek() {
+                Some(t) if self.options.reject_trailing_content => {
+                    Err(JsonError::UnexpectedToken {
+                        expected: vec![ExpectedToken::EndOfInput],
+                        found: format!("{t:?}"),
+                        position: self.current_pos(),
+                        path: JsonPath::new(),
+                    })
+                }
+                _ => Ok(value),
+            })
+            .map_err(|err| err.redacted(self.options.redaction))
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue> {
+        self.clock.tick(self.current_pos())?;
+        if self.recover_value() {
+            return Ok(JsonValue::Null);
+        }
         let token = self.peek().ok_or(JsonError::UnexpectedEndOfInput {
-            expected: "JSON value".to_string(),
-            position: self.current,
+            expected: vec![ExpectedToken::Value],
+            position: self.current_pos(),
+            path: JsonPath::new(),
         })?;
 
         match token {
-            Token::LeftBracket => self.parse_array(),
-            Token::LeftBrace => self.parse_object(),
+            Token::LeftBracket => self.parse_nested(Self::parse_array),
+            Token::LeftBrace => self.parse_nested(Self::parse_object),
             // All other tokens are treated as potential primitives
             _ => self.parse_primitives(),
         }
     }
 
+    /// Parses a container one level deeper, enforcing the depth limit.
+    fn parse_nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<JsonValue>,
+    ) -> Result<JsonValue> {
+        if let Some(limit) = self.options.limits.max_depth
+            && self.depth >= limit
+        {
+            return Err(JsonError::DepthLimitExceeded {
+                limit,
+                position: self.current_pos(),
+                path: JsonPath::new(),
+            });
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
     /// Handles Null, Boolean, Number, and String variants.
     fn parse_primitives(&mut self) -> Result<JsonValue> {
         if let Some(token) = self.advance() {
@@ -36,58 +309,57 @@ impl JsonParser {
                 Token::Null => Ok(JsonValue::Null),
                 Token::Boolean(b) => Ok(JsonValue::Boolean(b)),
                 Token::Number(n) => Ok(JsonValue::Number(n)),
-                Token::String(s) => Ok(JsonValue::String(s)),
-                t => {
-                    let pos = self.previous_pos();
-                    Err(JsonError::UnexpectedToken {
-                        expected: "value".to_string(),
-                        found: format!("{t:?}"),
-                        position: pos,
-                    })
-                }
+                Token::String(s) => Ok(JsonValue::String(self.options.fold_string(s))),
+                t => Err(JsonError::UnexpectedToken {
+                    expected: vec![ExpectedToken::Value],
+                    found: format!("{t:?}"),
+                    position: self.previous_pos(),
+                    path: JsonPath::new(),
+                }),
             }
         } else {
             Err(JsonError::UnexpectedEndOfInput {
-                expected: "JSON value".to_string(),
-                position: self.current,
+                expected: vec![ExpectedToken::Value],
+                position: self.current_pos(),
+                path: JsonPath::new(),
             })
         }
     }
 
     fn parse_array(&mut self) -> Result<JsonValue> {
         self.advance(); // Consume '['
-        let mut elements = Vec::new();
-
-        if self.check(&Token::RightBracket) {
+        if self.check(&Token::RightBracket) && !self.at_malformed() {
             self.advance();
-            return Ok(JsonValue::Array(elements));
+            return Ok(JsonValue::Array(JsonArray::new()));
         }
 
+        let mut elements = self.pool.array();
+
         loop {
-            elements.push(self.parse()?);
+            let index = elements.len();
+            elements.push(self.child(|| PathSegment::Index(index), index)?);
 
             match self.advance() {
                 Some(Token::Comma) => {
-                    if self.check(&Token::RightBracket) {
-                        return Err(JsonError::UnexpectedToken {
-                            expected: "value".to_string(),
-                            found: "']' (trailing comma)".to_string(),
-                            position: self.previous_pos(),
-                        });
+                    if self.check(&Token::RightBracket) && !self.at_malformed() {
+                        self.trailing_comma()?;
+                        break;
                     }
                 }
                 Some(Token::RightBracket) => break,
                 Some(t) => {
                     return Err(JsonError::UnexpectedToken {
-                        expected: "',' or ']'".to_string(),
+                        expected: vec![ExpectedToken::Comma, ExpectedToken::RightBracket],
                         found: format!("{t:?}"),
                         position: self.previous_pos(),
+                        path: JsonPath::new(),
                     });
                 }
                 None => {
                     return Err(JsonError::UnexpectedEndOfInput {
-                        expected: "']'".to_string(),
-                        position: self.current,
+                        expected: vec![ExpectedToken::Comma, ExpectedToken::RightBracket],
+                        position: self.current_pos(),
+                        path: JsonPath::new(),
                     });
                 }
             }
@@ -97,74 +369,100 @@ impl JsonParser {
 
     fn parse_object(&mut self) -> Result<JsonValue> {
         self.advance(); // Consume '{'
-        let mut map = HashMap::new();
-
         if self.check(&Token::RightBrace) {
             self.advance();
-            return Ok(JsonValue::Object(map));
+            return Ok(JsonValue::Object(JsonObject::new()));
         }
 
+        let mut map = self.pool.object();
+
         loop {
-            let key = match self.advance() {
-                Some(Token::String(s)) => s,
-                Some(t) => {
-                    return Err(JsonError::UnexpectedToken {
-                        expected: "string key".to_string(),
-                        found: format!("{t:?}"),
-                        position: self.previous_pos(),
-                    });
-                }
-                None => {
-                    return Err(JsonError::UnexpectedEndOfInput {
-                        expected: "string key".to_string(),
-                        position: self.current,
-                    });
-                }
+            let key_pos = self.current_pos();
+            let key = self.key(map.is_empty())?;
+            let colon = self
+                .colon()
+                .map_err(|err| err.inside(PathSegment::Key(key.to_string())));
+            let mark = self.recovery.as_ref().map(|state| state.incidents.len());
+            let replaces = match self.options.duplicate_keys {
+                DuplicateKeys::Collect => None,
+                _ => map.position(&key),
             };
+            let position = replaces.unwrap_or(map.len());
+            let value =
+                colon.and_then(|()| self.child(|| PathSegment::Key(key.to_string()), position))?;
+            if let (Some(mark), Some(position)) = (mark, replaces)
+                && let Some(state) = &mut self.recovery
+            {
+                state.replaced(mark, position);
+            }
+            self.store(&mut map, key, value, key_pos)?;
+            if !self.separator(Token::RightBrace, ExpectedToken::RightBrace)? {
+                break;
+            }
+        }
+        Ok(JsonValue::Object(map))
+    }
 
-            match self.advance() {
-                Some(Token::Colon) => {}
-                Some(t) => {
-                    return Err(JsonError::UnexpectedToken {
-                        expected: "':'".to_string(),
-                        found: format!("{t:?}"),
-                        position: self.previous_pos(),
-                    });
-                }
-                None => {
-                    return Err(JsonError::UnexpectedEndOfInput {
-                        expected: "':'".to_string(),
-                        position: self.current,
-                    });
-                }
+    /// Parses a value, building only what `selection` names.
+    fn select(&mut self, selection: &Selection) -> Result<JsonValue> {
+        if selection.is_all() {
+            return self.parse_value();
+        }
+        self.clock.tick(self.current_pos())?;
+        match self.peek() {
+            Some(Token::LeftBracket) => self.parse_nested(|parser| parser.select_array(selection)),
+            Some(Token::LeftBrace) => self.parse_nested(|parser| parser.select_object(selection)),
+            _ => self.parse_value(),
+        }
+    }
+
+    fn select_array(&mut self, selection: &Selection) -> Result<JsonValue> {
+        self.advance(); // Consume '['
+        if self.check(&Token::RightBracket) {
+            self.advance();
+            return Ok(JsonValue::Array(JsonArray::new()));
+        }
+
+        let mut elements = self.pool.array();
+
+        for index in 0.. {
+            let inner = selection.element(index);
+            let value = self
+                .select(inner.unwrap_or(&Selection::NOTHING))
+                .map_err(|err| err.inside(PathSegment::Index(index)))?;
+            if inner.is_some() {
+                elements.push(value);
             }
+            if !self.separator(Token::RightBracket, ExpectedToken::RightBracket)? {
+                break;
+            }
+        }
+        Ok(JsonValue::Array(elements))
+    }
 
-            map.insert(key, self.parse()?);
+    fn select_object(&mut self, selection: &Selection) -> Result<JsonValue> {
+        self.advance(); // Consume '{'
+        if self.check(&Token::RightBrace) {
+            self.advance();
+            return Ok(JsonValue::Object(JsonObject::new()));
+        }
 
-            match self.advance() {
-                Some(Token::Comma) => {
-                    if self.check(&Token::RightBrace) {
-                        return Err(JsonError::UnexpectedToken {
-                            expected: "string key".to_string(),
-                            found: "'}' (trailing comma)".to_string(),
-                            position: self.previous_pos(),
-                        });
-                    }
-                }
-                Some(Token::RightBrace) => break,
-                Some(t) => {
-                    return Err(JsonError::UnexpectedToken {
-                        expected: "',' or '}'".to_string(),
-                        found: format!("{t:?}"),
-                        position: self.previous_pos(),
-                    });
-                }
-                None => {
-                    return Err(JsonError::UnexpectedEndOfInput {
-                        expected: "'}'".to_string(),
-                        position: self.current,
-                    });
-                }
+        let mut map = self.pool.object();
+        let mut first = true;
+        loop {
+            let key_pos = self.current_pos();
+            let key = self.key(first)?;
+            first = false;
+            let inner = selection.member(&key);
+            let value = self
+                .colon()
+                .and_then(|()| self.select(inner.unwrap_or(&Selection::NOTHING)))
+                .map_err(|err| err.inside(PathSegment::Key(key.to_string())))?;
+            if inner.is_some() {
+                self.store(&mut map, key, value, key_pos)?;
+            }
+            if !self.separator(Token::RightBrace, ExpectedToken::RightBrace)? {
+                break;
             }
         }
         Ok(JsonValue::Object(map))
@@ -172,9 +470,196 @@ impl JsonParser {
 
     // --- Helpers ---
 
-    /// Returns the index of the token just consumed.
+    /// Reads an object key; `first` when no member came before it in the
+    /// object.
+    fn key(&mut self, first: bool) -> Result<JsonString> {
+        // A closing brace is also accepted as the first token, or after a
+        // trailing comma where those are allowed
+        let expected_key = if first || self.options.allow_trailing_commas {
+            vec![ExpectedToken::Key, ExpectedToken::RightBrace]
+        } else {
+            vec![ExpectedToken::Key]
+        };
+        match self.advance() {
+            Some(Token::String(s)) => Ok(self.options.fold_key(s)),
+            Some(t) => Err(JsonError::UnexpectedToken {
+                expected: expected_key,
+                found: format!("{t:?}"),
+                position: self.previous_pos(),
+                path: JsonPath::new(),
+            }),
+            None => Err(JsonError::UnexpectedEndOfInput {
+                expected: expected_key,
+                position: self.current_pos(),
+                path: JsonPath::new(),
+            }),
+        }
+    }
+
+    fn colon(&mut self) -> Result<()> {
+        match self.advance() {
+            Some(Token::Colon) => Ok(()),
+            Some(t) => Err(JsonError::UnexpectedToken {
+                expected: vec![ExpectedToken::Colon],
+                found: format!("{t:?}"),
+                position: self.previous_pos(),
+                path: JsonPath::new(),
+            }),
+            None => Err(JsonError::UnexpectedEndOfInput {
+                expected: vec![ExpectedToken::Colon],
+                position: self.current_pos(),
+                path: JsonPath::new(),
+            }),
+        }
+    }
+
+    /// Adds a member to `map` as [`ParserOptions::duplicate_keys`] says.
+    fn store(
+        &self,
+        map: &mut JsonObject,
+        key: JsonString,
+        value: JsonValue,
+        key_pos: usize,
+    ) -> Result<()> {
+        match self.options.duplicate_keys {
+            DuplicateKeys::LastWins => {
+                map.insert(key, value);
+            }
+            DuplicateKeys::Collect => map.append(key, value),
+            DuplicateKeys::Error if map.contains_key(&key) => {
+                return Err(JsonError::DuplicateKey {
+                    key: key.into_string(),
+                    position: key_pos,
+                    path: JsonPath::new(),
+                });
+            }
+            DuplicateKeys::Error => {
+                map.insert(key, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the `,` after a member or element, and the closer after it if
+    /// the comma trails. Returns `false` once the container is closed.
+    fn separator(&mut self, close: Token, expected_close: ExpectedToken) -> Result<bool> {
+        match self.advance() {
+            Some(Token::Comma) if self.check(&close) => {
+                self.trailing_comma()?;
+                Ok(false)
+            }
+            Some(Token::Comma) => Ok(true),
+            Some(t) if t == close => Ok(false),
+            Some(t) => Err(JsonError::UnexpectedToken {
+                expected: vec![ExpectedToken::Comma, expected_close],
+                found: format!("{t:?}"),
+                position: self.previous_pos(),
+                path: JsonPath::new(),
+            }),
+            None => Err(JsonError::UnexpectedEndOfInput {
+                expected: vec![ExpectedToken::Comma, expected_close],
+                position: self.current_pos(),
+                path: JsonPath::new(),
+            }),
+        }
+    }
+
+    /// Parses a child value, adding its segment to the path of an error
+    /// from it, and tracking its path and its `position` in its parent
+    /// when recovering.
+    fn child(
+        &mut self,
+        segment: impl FnOnce() -> PathSegment,
+        position: usize,
+    ) -> Result<JsonValue> {
+        let Some(state) = &mut self.recovery else {
+            return self.parse().map_err(|err| err.inside(segment()));
+        };
+        state.path.push(segment());
+        state.positions.push(position);
+        let result = self.parse();
+        if let Some(state) = &mut self.recovery {
+            let segment = state.path.pop();
+            state.positions.pop();
+            if let Some(segment) = segment {
+                return result.map_err(|err| err.inside(segment));
+            }
+        }
+        result
+    }
+
+    /// Whether a malformed literal stands before the current token.
+    fn at_malformed(&self) -> bool {
+        self.recovery.as_ref().is_some_and(|state| {
+            state
+                .malformed
+                .front()
+                .is_some_and(|(index, _)| *index == self.current)
+        })
+    }
+
+    /// When recovering, records a malformed or missing value at the current
+    /// token so it can be read as null.
+    fn recover_value(&mut self) -> bool {
+        let position = self.current_pos();
+        let next = self.peek().cloned();
+        let at_malformed = self.at_malformed();
+        let Some(state) = &mut self.recovery else {
+            return false;
+        };
+        let error = if at_malformed {
+            state.malformed.pop_front().map(|(_, err)| err)
+        } else {
+            match next {
+                Some(t @ (Token::Comma | Token::RightBracket | Token::RightBrace)) => {
+                    Some(JsonError::UnexpectedToken {
+                        expected: vec![ExpectedToken::Value],
+                        found: format!("{t:?}"),
+                        position,
+                        path: JsonPath::new(),
+                    })
+                }
+                _ => None,
+            }
+        };
+        match error {
+            Some(error) => {
+                state.incidents.push(Incident {
+                    path: state.path.clone(),
+                    positions: state.positions.clone(),
+                    error: error.under(&state.path),
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Consumes the closer after a trailing comma, if the dialect allows one.
+    fn trailing_comma(&mut self) -> Result<()> {
+        if !self.options.allow_trailing_commas {
+            return Err(JsonError::DisabledFeature {
+                feature: "trailing commas".to_string(),
+                option: "allow_trailing_commas".to_string(),
+                position: self.previous_pos(),
+                path: JsonPath::new(),
+            });
+        }
+        self.advance();
+        Ok(())
+    }
+
+    /// Returns where the next token starts, or the end of input.
+    fn current_pos(&self) -> usize {
+        self.starts.get(self.current).copied().unwrap_or(self.end)
+    }
+
+    /// Returns where the token just consumed starts.
     fn previous_pos(&self) -> usize {
-        self.current.saturating_sub(1)
+        self.starts
+            .get(self.current.saturating_sub(1))
+            .copied()
+            .unwrap_or(self.end)
     }
 
     fn check(&self, expected: &Token) -> bool {
@@ -188,7 +673,8 @@ impl JsonParser {
 
     fn advance(&mut self) -> Option<Token> {
         if !self.is_at_end() {
-            let token = self.tokens[self.current].clone();
+            // Tokens are only ever read once, so this one can be moved out
+            let token = std::mem::replace(&mut self.tokens[self.current], Token::Null);
             self.current += 1;
             Some(token)
         } else {
@@ -201,14 +687,23 @@ impl JsonParser {
     }
 }
 
+pub(crate) fn check_input_size(
+    input: &(impl AsRef<[u8]> + ?Sized),
+    options: &ParserOptions,
+) -> Result<()> {
+    let size = input.as_ref().len();
+    match options.limits.max_input_bytes {
+        Some(limit) if size > limit => Err(JsonError::InputTooLarge { size, limit }),
+        _ => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Redaction;
+    use crate::test_support::parse_json;
     // Helper
-    fn parse_json(input: &str) -> Result<JsonValue> {
-        JsonParser::new(input)?.parse()
-    }
-
     mod parser_creation {
         use super::*;
 
@@ -218,6 +713,17 @@ mod tests {
             assert!(parser.is_ok());
         }
         #[test]
+        fn test_parser_with_options() {
+            let options = ParserOptions::new()
+                .allow_digit_separators(true)
+                .with_suffix_handler(crate::options::byte_size_suffix);
+            let value = JsonParser::with_options(r#"{"max": 1_024KiB}"#, options)
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert_eq!(value.get("max"), Some(&JsonValue::Number(1_048_576.0)));
+        }
+        #[test]
         fn test_parser_creation_tokenize_error() {
             let result = JsonParser::new(r#""\q""#);
             assert!(result.is_err());
@@ -225,11 +731,12 @@ mod tests {
                 Err(JsonError::InvalidEscape {
                     character,
                     position,
+                    ..
                 }) => {
                     assert_eq!(character, 'q');
                     assert_eq!(position, 1);
                 }
-                _ => panic!("Expected InvalidEscape error, got {:?}", result),
+                _ => panic!("Expected InvalidEscape error, got {result:?}"),
             }
         }
     }
@@ -272,6 +779,28 @@ mod tests {
                 _ => panic!("Expected UnexpectedEndOfInput error on exhausted parser"),
             }
         }
+
+        #[test]
+        fn test_reset_reuses_the_parser() {
+            let limits = crate::Limits::new().max_tokens(6);
+            let options = ParserOptions::new()
+                .limits(limits)
+                .allow_trailing_commas(true);
+            let mut parser = JsonParser::with_options("[1, 2,]", options).unwrap();
+            assert_eq!(parser.parse().unwrap().to_string(), "[1,2]");
+
+            parser.reset(r#"{"a": 1,}"#).unwrap();
+            assert_eq!(parser.parse().unwrap().to_string(), r#"{"a":1}"#);
+            assert!(parser.is_at_end());
+
+            assert!(matches!(
+                parser.reset("[1, 2, 3]"),
+                Err(JsonError::TokenLimitExceeded { .. })
+            ));
+            assert!(parser.is_at_end());
+            assert!(parser.reset("[1, \"x\"]").is_ok());
+            assert_eq!(parser.parse().unwrap().to_string(), r#"[1,"x"]"#);
+        }
     }
 
     mod success_cases {
@@ -282,12 +811,9 @@ mod tests {
             // Table-driven test: (input_string, expected_value)
             let cases = vec![
                 // Strings
-                (
-                    r#""hello world""#,
-                    JsonValue::String("hello world".to_string()),
-                ),
-                (r#""""#, JsonValue::String("".to_string())),
-                (r#""123""#, JsonValue::String("123".to_string())),
+                (r#""hello world""#, JsonValue::String("hello world".into())),
+                (r#""""#, JsonValue::String("".into())),
+                (r#""123""#, JsonValue::String("123".into())),
                 // Numbers
                 ("42.5", JsonValue::Number(42.5)),
                 ("0", JsonValue::Number(0.0)),
@@ -302,9 +828,9 @@ mod tests {
 
             for (input, expected) in cases {
                 let result = parse_json(input).unwrap_or_else(|e| {
-                    panic!("Failed to parse '{}': {}", input, e);
+                    panic!("Failed to parse '{input}': {e}");
                 });
-                assert_eq!(result, expected, "Input failed: {}", input);
+                assert_eq!(result, expected, "Input failed: {input}");
             }
         }
 
@@ -314,8 +840,7 @@ mod tests {
             for input in cases {
                 assert!(
                     parse_json(input).is_ok(),
-                    "Should handle whitespace for: {}",
-                    input
+                    "Should handle whitespace for: {input}"
                 );
             }
         }
@@ -328,35 +853,32 @@ mod tests {
         fn test_parse_string_with_newline() {
             let mut parser = JsonParser::new(r#""hello\nworld""#).unwrap();
             let value = parser.parse().unwrap();
-            assert_eq!(value, JsonValue::String("hello\nworld".to_string()));
+            assert_eq!(value, JsonValue::String("hello\nworld".into()));
         }
 
         #[test]
         fn test_parse_string_with_tab() {
             let mut parser = JsonParser::new(r#""col1\tcol2""#).unwrap();
             let value = parser.parse().unwrap();
-            assert_eq!(value, JsonValue::String("col1\tcol2".to_string()));
+            assert_eq!(value, JsonValue::String("col1\tcol2".into()));
         }
         #[test]
         fn test_parse_string_with_quotes() {
             let mut parser = JsonParser::new(r#""say \"hi\"""#).unwrap();
             let value = parser.parse().unwrap();
-            assert_eq!(value, JsonValue::String("say \"hi\"".to_string()));
+            assert_eq!(value, JsonValue::String("say \"hi\"".into()));
         }
         #[test]
         fn test_parse_string_with_unicode() {
             let mut parser = JsonParser::new(r#""\u0048\u0065\u006c\u006c\u006f""#).unwrap();
             let value = parser.parse().unwrap();
-            assert_eq!(value, JsonValue::String("Hello".to_string()));
+            assert_eq!(value, JsonValue::String("Hello".into()));
         }
         #[test]
         fn test_parse_complex_escapes() {
             let mut parser = JsonParser::new(r#""line1\nline2\t\"quoted\"\u0021""#).unwrap();
             let value = parser.parse().unwrap();
-            assert_eq!(
-                value,
-                JsonValue::String("line1\nline2\t\"quoted\"!".to_string())
-            );
+            assert_eq!(value, JsonValue::String("line1\nline2\t\"quoted\"!".into()));
         }
     }
 
@@ -368,8 +890,10 @@ mod tests {
             let result = parse_json("");
             assert!(result.is_err());
             match result {
-                Err(JsonError::UnexpectedEndOfInput { expected, position }) => {
-                    assert_eq!(expected, "JSON value");
+                Err(JsonError::UnexpectedEndOfInput {
+                    expected, position, ..
+                }) => {
+                    assert_eq!(expected, [ExpectedToken::Value]);
                     assert_eq!(position, 0);
                 }
                 _ => panic!("Expected UnexpectedEndOfInput error"),
@@ -378,26 +902,24 @@ mod tests {
 
         #[test]
         fn test_parse_error_invalid_token() {
-            let invalid_inputs = ["@", "$", "%", "^", "!", "None", "undefined", "tru"];
+            let invalid_inputs = ["@", "$", "%", "^", "!", "None", "undefined", "[tru]"];
             for input in invalid_inputs {
                 let result = parse_json(input);
                 assert!(
                     matches!(result, Err(JsonError::UnexpectedToken { .. })),
-                    "Should return UnexpectedToken for: {}",
-                    input
+                    "Should return UnexpectedToken for: {input}"
                 );
             }
         }
 
         #[test]
         fn test_malformed_numbers() {
-            let malformed = ["1.2.3", "1e", "--10", "1.0.e10"];
+            let malformed = ["1.2.3", "[1e]", "--10", "1.0.e10"];
             for input in malformed {
                 let result = parse_json(input);
                 assert!(
                     matches!(result, Err(JsonError::InvalidNumber { .. })),
-                    "Should return InvalidNumber for: {}",
-                    input
+                    "Should return InvalidNumber for: {input}"
                 );
             }
         }
@@ -448,6 +970,109 @@ mod tests {
             let result = parse_json(r#"{"a": 1 "b": 2}"#);
             assert!(result.is_err());
         }
+
+        #[test]
+        fn test_errors_name_the_container_path() {
+            let cases = vec![
+                (
+                    r#"{"users": [{}, {}, {}, {"address" {}}]}"#,
+                    "/users/3/address",
+                ),
+                (r#"{"a": [1, [true, :]]}"#, "/a/1/1"),
+                (r#"{"a/b": {"c": }}"#, "/a~1b/c"),
+                ("[[1 2]]", "/0"),
+            ];
+            for (input, path) in cases {
+                let err = parse_json(input).unwrap_err();
+                assert_eq!(
+                    err.path().map(|path| path.to_string()).as_deref(),
+                    Some(path),
+                    "Input: {input}"
+                );
+            }
+            let input = r#"{"users": [{}, {}, {}, {"address" {}}]}"#;
+            assert!(matches!(
+                parse_json(input),
+                Err(JsonError::UnexpectedToken { position: 34, .. })
+            ));
+            assert!(parse_json("[1 2]").unwrap_err().path().is_none());
+            // Found while tokenizing, before parsing starts
+            let err = parse_json(r#"[1, {"b": 1.2.3}]"#).unwrap_err();
+            assert_eq!(
+                err.path().map(|path| path.to_string()).as_deref(),
+                Some("/1/b")
+            );
+            assert!(err.to_string().starts_with("at /1/b: Invalid number"));
+            let input = "// list\n[1, /* two */ 2, [3 4]]";
+            let options = ParserOptions::lenient();
+            let err = JsonParser::with_options(input, options)
+                .unwrap()
+                .parse()
+                .unwrap_err();
+            assert_eq!(
+                err.path().map(|path| path.to_string()).as_deref(),
+                Some("/2")
+            );
+        }
+
+        #[test]
+        fn test_errors_point_at_source_characters() {
+            let cases = vec![
+                ("[1 2 3]", 3),
+                ("{\"key\" 1}", 7),
+                ("{\"é\": 1 \"b\": 2}", 8),
+                ("{123: 1}", 1),
+                ("  ]", 2),
+            ];
+            for (input, expected) in cases {
+                assert!(
+                    matches!(
+                        parse_json(input),
+                        Err(JsonError::UnexpectedToken { position, .. }) if position == expected
+                    ),
+                    "Input: {input}, got {:?}",
+                    parse_json(input)
+                );
+            }
+            assert_eq!(
+                parse_json("[1, 2"),
+                Err(JsonError::UnexpectedEndOfInput {
+                    expected: vec![ExpectedToken::Comma, ExpectedToken::RightBracket],
+                    position: 5,
+                    path: JsonPath::new(),
+                })
+            );
+        }
+
+        #[test]
+        fn test_redacted_errors_do_not_quote_the_input() {
+            let parse_redacted = |input: &str, redaction| {
+                JsonParser::with_options(input, ParserOptions::new().redact_errors(redaction))
+                    .and_then(|mut parser| parser.parse())
+                    .unwrap_err()
+                    .to_string()
+            };
+            let cases = [
+                (r#"{"a": ["ok" "sk_live_4eC39"]}"#, "sk_live_4eC39"),
+                (r#"{"token": sk_live_4eC39}"#, "found sk"),
+                (r#"["sk_live_\q"]"#, "'q'"),
+                ("[12.34.56]", "12.34.56"),
+                (r#"["\u12G4"]"#, "12G4"),
+            ];
+            for (input, quoted) in cases {
+                assert!(parse_redacted(input, Redaction::Full).contains(quoted));
+                let omitted = parse_redacted(input, Redaction::Omit);
+                assert!(!omitted.contains(quoted), "{omitted}");
+            }
+            assert_eq!(
+                parse_redacted(cases[0].0, Redaction::Omit),
+                "at /a: Unexpected token at position 12: expected ',' or ']', found <redacted>"
+            );
+            assert_eq!(
+                parse_redacted(cases[0].0, Redaction::Truncate(10)),
+                "at /a: Unexpected token at position 12: expected ',' or ']', found String(\"sk…"
+            );
+        }
     }
 
     mod array_tests {
@@ -455,49 +1080,61 @@ mod tests {
         #[test]
         fn test_parse_empty_array() {
             let value = parse_json("[]").unwrap();
-            assert_eq!(value, JsonValue::Array(vec![]));
+            assert_eq!(value, JsonValue::Array(JsonArray::new()));
         }
         #[test]
         fn test_parse_array_single() {
             let value = parse_json("[1]").unwrap();
-            assert_eq!(value, JsonValue::Array(vec![JsonValue::Number(1.0)]));
+            assert_eq!(value, JsonValue::Array(vec![JsonValue::Number(1.0)].into()));
         }
         #[test]
         fn test_parse_array_multiple() {
             let value = parse_json("[1, 2, 3]").unwrap();
-            let expected = JsonValue::Array(vec![
-                JsonValue::Number(1.0),
-                JsonValue::Number(2.0),
-                JsonValue::Number(3.0),
-            ]);
+            let expected = JsonValue::Array(
+                vec![
+                    JsonValue::Number(1.0),
+                    JsonValue::Number(2.0),
+                    JsonValue::Number(3.0),
+                ]
+                .into(),
+            );
             assert_eq!(value, expected);
         }
         #[test]
         fn test_parse_array_mixed_types() {
             let value = parse_json(r#"[1, "two", true, null]"#).unwrap();
-            let expected = JsonValue::Array(vec![
-                JsonValue::Number(1.0),
-                JsonValue::String("two".to_string()),
-                JsonValue::Boolean(true),
-                JsonValue::Null,
-            ]);
+            let expected = JsonValue::Array(
+                vec![
+                    JsonValue::Number(1.0),
+                    JsonValue::String("two".into()),
+                    JsonValue::Boolean(true),
+                    JsonValue::Null,
+                ]
+                .into(),
+            );
             assert_eq!(value, expected);
         }
         #[test]
         fn test_parse_nested_arrays() {
             let value = parse_json("[[1, 2], [3, 4]]").unwrap();
-            let expected = JsonValue::Array(vec![
-                JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)]),
-                JsonValue::Array(vec![JsonValue::Number(3.0), JsonValue::Number(4.0)]),
-            ]);
+            let expected = JsonValue::Array(
+                vec![
+                    JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)].into()),
+                    JsonValue::Array(vec![JsonValue::Number(3.0), JsonValue::Number(4.0)].into()),
+                ]
+                .into(),
+            );
             assert_eq!(value, expected);
         }
         #[test]
         fn test_parse_deeply_nested() {
             let value = parse_json("[[[1]]]").unwrap();
-            let expected = JsonValue::Array(vec![JsonValue::Array(vec![JsonValue::Array(vec![
-                JsonValue::Number(1.0),
-            ])])]);
+            let expected = JsonValue::Array(
+                vec![JsonValue::Array(
+                    vec![JsonValue::Array(vec![JsonValue::Number(1.0)].into())].into(),
+                )]
+                .into(),
+            );
             assert_eq!(value, expected);
         }
         #[test]
@@ -519,23 +1156,20 @@ mod tests {
         #[test]
         fn test_parse_empty_object() {
             let value = parse_json("{}").unwrap();
-            assert_eq!(value, JsonValue::Object(HashMap::new()));
+            assert_eq!(value, JsonValue::Object(JsonObject::new()));
         }
         #[test]
         fn test_parse_object_single_key() {
             let value = parse_json(r#"{"key": "value"}"#).unwrap();
-            let mut expected = HashMap::new();
-            expected.insert("key".to_string(), JsonValue::String("value".to_string()));
+            let mut expected = JsonObject::new();
+            expected.insert("key".to_string(), JsonValue::String("value".into()));
             assert_eq!(value, JsonValue::Object(expected));
         }
         #[test]
         fn test_parse_object_multiple_keys() {
             let value = parse_json(r#"{"name": "Alice", "age": 30}"#).unwrap();
             if let JsonValue::Object(obj) = value {
-                assert_eq!(
-                    obj.get("name"),
-                    Some(&JsonValue::String("Alice".to_string()))
-                );
+                assert_eq!(obj.get("name"), Some(&JsonValue::String("Alice".into())));
                 assert_eq!(obj.get("age"), Some(&JsonValue::Number(30.0)))
             } else {
                 panic!("Expected Object");
@@ -585,9 +1219,521 @@ mod tests {
         #[test]
         fn test_object_get() {
             let value = parse_json(r#"{"name": "Alice", "age": 30}"#).unwrap();
+            assert_eq!(value.get("name"), Some(&JsonValue::String("Alice".into())));
+        }
+    }
+
+    mod limits {
+        use super::*;
+        use crate::Limits;
+        use std::time::Duration;
+
+        fn parse_limited(input: &str, limits: Limits) -> Result<JsonValue> {
+            JsonParser::with_options(input, ParserOptions::new().limits(limits))?.parse()
+        }
+
+        #[test]
+        fn test_depth_limit() {
+            let limits = Limits::new().max_depth(2);
+            assert!(parse_limited(r#"{"a": [1, 2]}"#, limits).is_ok());
+            assert!(parse_limited("42", Limits::new().max_depth(0)).is_ok());
+            assert_eq!(
+                parse_limited(r#"{"a": [[1]]}"#, limits),
+                Err(JsonError::DepthLimitExceeded {
+                    limit: 2,
+                    position: 7,
+                    path: vec![PathSegment::Key("a".into()), PathSegment::Index(0)].into(),
+                })
+            );
+        }
+
+        #[test]
+        fn test_depth_resets_between_siblings() {
+            let limits = Limits::new().max_depth(2);
+            assert!(parse_limited("[[1], [2], {}]", limits).is_ok());
+        }
+
+        #[test]
+        fn test_input_size_limit() {
+            let limits = Limits::new().max_input_bytes(4);
+            assert!(parse_limited("[1]", limits).is_ok());
+            assert_eq!(
+                parse_limited(r#""hé""#, Limits::new().max_input_bytes(4)),
+                Err(JsonError::InputTooLarge { size: 5, limit: 4 })
+            );
+        }
+
+        #[test]
+        fn test_token_limit() {
+            let limits = Limits::new().max_tokens(5);
+            assert!(parse_limited("[1, 2] ", limits).is_ok());
+            assert_eq!(
+                parse_limited("[1, 2, 3]", limits),
+                Err(JsonError::TokenLimitExceeded {
+                    limit: 5,
+                    position: 7,
+                    path: vec![PathSegment::Index(2)].into(),
+                })
+            );
+        }
+
+        #[test]
+        fn test_time_budget() {
+            let input = format!("[{}0]", "0,".repeat(Clock::INTERVAL));
+            let generous = Limits::new().max_duration(Duration::from_secs(600));
+            assert!(parse_limited(&input, generous).is_ok());
+
+            let none = Limits::new().max_duration(Duration::ZERO);
+            assert_eq!(
+                parse_limited(&input, none),
+                Err(JsonError::TimeBudgetExceeded {
+                    limit: Duration::ZERO,
+                    position: 0,
+                    path: JsonPath::new(),
+                })
+            );
+
+            // Out of time after tokenizing, while building the value
+            let mut parser =
+                JsonParser::with_options(&input, ParserOptions::new().limits(generous)).unwrap();
+            parser.clock = Clock::start(&none);
+            let err = parser.parse().unwrap_err();
+            assert!(err.is_limit() && err.kind() == crate::JsonErrorKind::TimeBudgetExceeded);
+        }
+
+        #[test]
+        fn test_string_limit_counts_decoded_characters() {
+            let limits = Limits::new().max_string_chars(3);
+            assert!(parse_limited(r#"{"abc": "\u00e9\n\""}"#, limits).is_ok());
+            assert_eq!(
+                parse_limited(r#"["ok", "long"]"#, limits),
+                Err(JsonError::StringLimitExceeded {
+                    limit: 3,
+                    position: 7,
+                    path: vec![PathSegment::Index(1)].into(),
+                })
+            );
+        }
+    }
+
+    mod untrusted {
+        use super::*;
+        use crate::{Limits, Recovery, parse_with_recovery};
+
+        /// xorshift64, so every run checks the same inputs.
+        struct Rng(u64);
+
+        impl Rng {
+            fn next(&mut self) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+            }
+
+            fn below(&mut self, n: usize) -> usize {
+                (self.next() % n as u64) as usize
+            }
+        }
+
+        const SEEDS: &[&str] = &[
+            r#"{"a": [1, -2.5e3, true, false, null], "b": {"c": "d\n\u00e9\ud83d\ude00"}}"#,
+            r#"[[], {}, [[[]]], {"": ""}, 0, -0, 1E+2, "\\\"\/"]"#,
+            "// c\n[1, /* c */ 2,] ",
+            "{'a': 1_000, \"b\": 10KB}",
+        ];
+
+        const ALPHABET: &[u8] = b"{}[],:\"'\\/*-+.0123456789eEtrufalsn \n\t_ux";
+
+        /// Every entry point that takes untrusted input, under both
+        /// dialects: the bytes as they are, and as text with invalid UTF-8
+        /// replaced.
+        fn exercise(bytes: &[u8]) {
+            let input = &*String::from_utf8_lossy(bytes);
+            for options in [
+                ParserOptions::untrusted(),
+                ParserOptions::lenient().limits(Limits::untrusted()),
+            ] {
+                if let Ok(value) =
+                    JsonParser::from_slice(bytes, options.clone()).and_then(|mut p| p.parse())
+                {
+                    let _ = value.to_string();
+                }
+                if let Ok(value) =
+                    JsonParser::with_options(input, options.clone()).and_then(|mut p| p.parse())
+                {
+                    let _ = value.to_string();
+                }
+                let _ = Tokenizer::with_options(input, options.clone()).tokenize();
+                let _ = parse_with_recovery(input, &options, |_, _| Recovery::Skip);
+            }
+        }
+
+        #[test]
+        fn test_random_bytes_never_panic() {
+            let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+            for _ in 0..3000 {
+                let len = rng.below(48);
+                let bytes: Vec<u8> = (0..len)
+                    .map(|_| match rng.below(4) {
+                        0 => rng.next() as u8,
+                        _ => ALPHABET[rng.below(ALPHABET.len())],
+                    })
+                    .collect();
+                exercise(&bytes);
+            }
+        }
+
+        #[test]
+        fn test_mutated_documents_never_panic() {
+            let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+            for _ in 0..3000 {
+                let mut bytes = SEEDS[rng.below(SEEDS.len())].as_bytes().to_vec();
+                for _ in 0..=rng.below(4) {
+                    let at = rng.below(bytes.len() + 1);
+                    match rng.below(4) {
+                        0 if at < bytes.len() => {
+                            bytes.remove(at);
+                        }
+                        1 => bytes.insert(at, ALPHABET[rng.below(ALPHABET.len())]),
+                        2 => bytes.truncate(at),
+                        _ if at < bytes.len() => bytes[at] = rng.next() as u8,
+                        _ => {}
+                    }
+                }
+                exercise(&bytes);
+            }
+        }
+
+        #[test]
+        fn test_hostile_shapes_hit_limits() {
+            let cases = [
+                "[".repeat(100_000),
+                r#"{"a":"#.repeat(50_000),
+                "[0,".repeat(400_000),
+                format!("\"{}\"", "x".repeat(1_000_001)),
+            ];
+            for input in &cases {
+                let err = JsonParser::with_options(input, ParserOptions::untrusted())
+                    .and_then(|mut parser| parser.parse())
+                    .unwrap_err();
+                assert!(err.is_limit(), "{err}");
+                exercise(input.as_bytes());
+            }
+        }
+
+        #[test]
+        fn test_from_slice_reports_invalid_utf8() {
+            let cases: [(&[u8], usize); 4] = [
+                (b"\xff", 0),
+                (b"[\"\xc3\xa9\xff\"]", 3),
+                (b"{\"a\": \"\xe2\x82\"}", 7),
+                (b"[1, \"\xf0\x9f\x98\x80\", \"\xc3\"]", 10),
+            ];
+            for (input, position) in cases {
+                let err = JsonParser::from_slice(input, ParserOptions::untrusted()).unwrap_err();
+                assert_eq!(
+                    err,
+                    JsonError::InvalidUtf8 {
+                        position,
+                        path: JsonPath::new(),
+                    }
+                );
+                assert!(err.is_syntax());
+            }
+        }
+
+        #[test]
+        fn test_from_slice_checks_size_before_decoding() {
+            let options = ParserOptions::new().limits(Limits::new().max_input_bytes(4));
+            let err = JsonParser::from_slice(b"[\xff, 1]", options.clone()).unwrap_err();
+            assert_eq!(err, JsonError::InputTooLarge { size: 6, limit: 4 });
+
+            let value = JsonParser::from_slice("\"é\"".as_bytes(), options)
+                .and_then(|mut parser| parser.parse())
+                .unwrap();
+            assert_eq!(value.as_str(), Some("é"));
+        }
+    }
+
+    mod dialects {
+        use super::*;
+
+        fn parse_lenient(input: &str) -> Result<JsonValue> {
+            let options = ParserOptions::new()
+                .allow_comments(true)
+                .allow_trailing_commas(true)
+                .allow_single_quotes(true);
+            JsonParser::with_options(input, options)?.parse()
+        }
+
+        #[test]
+        fn test_disabled_features_name_their_option() {
+            let cases = vec![
+                ("[1] // note", "comments", "allow_comments", 4, vec![]),
+                ("/* a */ 1", "comments", "allow_comments", 0, vec![]),
+                (
+                    "[1, 2,]",
+                    "trailing commas",
+                    "allow_trailing_commas",
+                    5,
+                    vec![],
+                ),
+                (
+                    r#"{"a": 1,}"#,
+                    "trailing commas",
+                    "allow_trailing_commas",
+                    7,
+                    vec![],
+                ),
+                (
+                    "['hi']",
+                    "single-quoted strings",
+                    "allow_single_quotes",
+                    1,
+                    vec![PathSegment::Index(0)],
+                ),
+            ];
+            for (input, feature, option, position, path) in cases {
+                assert_eq!(
+                    parse_json(input),
+                    Err(JsonError::DisabledFeature {
+                        feature: feature.to_string(),
+                        option: option.to_string(),
+                        position,
+                        path: path.into(),
+                    }),
+                    "Input: {input}"
+                );
+            }
+        }
+
+        #[test]
+        fn test_enabled_features_parse() {
+            let value =
+                parse_lenient("// config\n{'name': 'it\\'s', /* inline */ \"tags\": [1, 2,],}")
+                    .unwrap();
+            assert_eq!(
+                value,
+                parse_json(r#"{"name": "it's", "tags": [1, 2]}"#).unwrap()
+            );
+        }
+
+        #[test]
+        fn test_lone_slash_is_still_a_syntax_error() {
+            let result = parse_lenient("[1 / 2]");
+            assert!(matches!(result, Err(JsonError::UnexpectedToken { .. })));
+        }
+
+        #[test]
+        fn test_unterminated_block_comment() {
+            assert_eq!(
+                parse_lenient("[1] /* open"),
+                Err(JsonError::UnexpectedEndOfInput {
+                    expected: vec![ExpectedToken::CommentEnd],
+                    position: 4,
+                    path: JsonPath::new(),
+                })
+            );
+        }
+    }
+
+    mod presets {
+        use super::*;
+        use crate::JsonErrorKind;
+
+        fn parse_with(input: &str, options: ParserOptions) -> Result<JsonValue> {
+            JsonParser::with_options(input, options)?.parse()
+        }
+
+        #[test]
+        fn test_strict_rejects_what_default_tolerates() {
+            let cases = vec![
+                ("[1,\u{a0}2]", JsonErrorKind::UnexpectedToken),
+                ("\"tab\there\"", JsonErrorKind::UnexpectedToken),
+                ("01", JsonErrorKind::InvalidNumber),
+                ("[1.]", JsonErrorKind::InvalidNumber),
+                ("1.e5", JsonErrorKind::InvalidNumber),
+                ("[-]", JsonErrorKind::InvalidNumber),
+                (r#"{"a": 1, "a": 2}"#, JsonErrorKind::DuplicateKey),
+                ("[1] [2]", JsonErrorKind::UnexpectedToken),
+            ];
+            for (input, kind) in cases {
+                assert_eq!(
+                    parse_with(input, ParserOptions::strict_rfc8259()).map_err(|e| e.kind()),
+                    Err(kind),
+                    "Input: {input}"
+                );
+                if input != "[-]" {
+                    assert!(parse_json(input).is_ok(), "Input: {input}");
+                }
+            }
+        }
+
+        #[test]
+        fn test_strict_caps_depth() {
+            let deep = "[".repeat(200_000) + &"]".repeat(200_000);
+            assert_eq!(
+                parse_with(&deep, ParserOptions::strict_rfc8259()).map_err(|e| e.kind()),
+                Err(JsonErrorKind::DepthLimitExceeded)
+            );
+        }
+
+        #[test]
+        fn test_strict_accepts_rfc8259() {
+            let input = " {\"a\": [0, -0.5, 1E+2, 10e-3, \"\\u0000\"], \"b\": {\"a\": null}}\r\n";
+            assert_eq!(
+                parse_with(input, ParserOptions::strict_rfc8259()).unwrap(),
+                parse_json(input).unwrap()
+            );
+        }
+
+        #[test]
+        fn test_strict_errors_point_at_the_offence() {
+            let strict = ParserOptions::strict_rfc8259;
+            assert_eq!(
+                parse_with(r#"{"a": 1, "a": 2}"#, strict()),
+                Err(JsonError::DuplicateKey {
+                    key: "a".to_string(),
+                    position: 9,
+                    path: JsonPath::new(),
+                })
+            );
+            assert_eq!(
+                parse_with("\"a\nb\"", strict()),
+                Err(JsonError::UnexpectedToken {
+                    expected: vec![ExpectedToken::EscapedControlCharacter],
+                    found: "U+000A".to_string(),
+                    position: 2,
+                    path: JsonPath::new(),
+                })
+            );
+            assert!(matches!(
+                parse_with("1 2", strict()),
+                Err(JsonError::UnexpectedToken { position: 2, .. })
+            ));
+        }
+
+        #[test]
+        fn test_lenient_accepts_hand_edited_files() {
+            let input = "{\n  // port\n  'port': 8_080,\n  \"hosts\": ['a', 'b',],\n}";
+            let value = parse_with(input, ParserOptions::lenient()).unwrap();
+            assert_eq!(
+                value,
+                parse_json(r#"{"port": 8080, "hosts": ["a", "b"]}"#).unwrap()
+            );
+        }
+    }
+
+    mod duplicate_keys {
+        use super::*;
+
+        #[test]
+        fn test_lowercase_keys_fold_into_duplicates() {
+            let input = r#"{"Content-Type": "a", "content-type": "b", "X": {"Y": 1}}"#;
+            let options = ParserOptions::new().lowercase_keys(true);
+            let value = JsonParser::with_options(input, options.clone())
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert_eq!(
+                value,
+                parse_json(r#"{"content-type": "b", "x": {"y": 1}}"#).unwrap()
+            );
+
+            let collected = options.duplicate_keys(DuplicateKeys::Collect);
+            let value = JsonParser::with_options(input, collected)
+                .unwrap()
+                .parse()
+                .unwrap();
+            assert_eq!(value.get_all("content-type").count(), 2);
+        }
+
+        #[cfg(feature = "unicode")]
+        #[test]
+        fn test_nfc_folds_keys_into_duplicates() {
+            // "é" as "e" + combining acute accent, then precomposed
+            let input = "{\"cafe\u{301}\": \"cafe\u{301}\", \"caf\u{e9}\": 2}";
+            let options = ParserOptions::new()
+                .nfc(true)
+                .duplicate_keys(DuplicateKeys::Error);
+            let err = JsonParser::with_options(input, options.clone())
+                .unwrap()
+                .parse()
+                .unwrap_err();
+            assert!(matches!(err, JsonError::DuplicateKey { .. }), "{err:?}");
+
+            let value =
+                JsonParser::with_options(input, options.duplicate_keys(DuplicateKeys::Collect))
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+            assert_eq!(
+                value.get_all("caf\u{e9}").collect::<Vec<_>>(),
+                [
+                    &JsonValue::String("caf\u{e9}".into()),
+                    &JsonValue::Number(2.0)
+                ]
+            );
+        }
+
+        fn parse_collect(input: &str) -> Result<JsonValue> {
+            let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Collect);
+            JsonParser::with_options(input, options)?.parse()
+        }
+
+        #[test]
+        fn test_last_wins_by_default() {
+            let value = parse_json(r#"{"a": 1, "a": 2}"#).unwrap();
+            assert_eq!(value.get("a"), Some(&JsonValue::Number(2.0)));
+        }
+
+        #[test]
+        fn test_collect_keeps_every_value_in_order() {
+            let value = parse_collect(r#"{"a": 1, "b": true, "a": [2], "a": null}"#).unwrap();
+            assert_eq!(
+                value.get_all("a").collect::<Vec<_>>(),
+                [
+                    &JsonValue::Number(1.0),
+                    &JsonValue::Array(vec![JsonValue::Number(2.0)].into()),
+                    &JsonValue::Null,
+                ]
+            );
+            assert_eq!(value.get("a"), Some(&JsonValue::Null));
+            assert_eq!(
+                value.get_all("b").collect::<Vec<_>>(),
+                [&JsonValue::Boolean(true)]
+            );
+            assert_eq!(value.get_all("missing").next(), None);
+        }
+
+        #[test]
+        fn test_collect_leaves_single_members_alone() {
+            let input = r#"{"a":1,"b":[2],"b":3}"#;
+            let value = parse_collect(input).unwrap();
+            assert_eq!(value.to_string(), input);
+            assert_eq!(value.get("a"), Some(&JsonValue::Number(1.0)));
+            assert_eq!(value.get_all("a").count(), 1);
+            let b: Vec<_> = value.get_all("b").collect();
+            assert_eq!(b, [&parse_json("[2]").unwrap(), &JsonValue::Number(3.0)]);
+
+            assert_eq!(
+                parse_collect(r#"{"a": [1]}"#).unwrap(),
+                parse_json(r#"{"a": [1]}"#).unwrap()
+            );
+            assert_ne!(
+                parse_collect(r#"{"a": 1, "a": 2}"#).unwrap(),
+                parse_collect(r#"{"a": 2, "a": 1}"#).unwrap()
+            );
+        }
+
+        #[test]
+        fn test_collect_applies_to_nested_objects() {
+            let value = parse_collect(r#"[{"k": {"x": 1, "x": 2}}]"#).unwrap();
+            let outer = value.get_index(0).unwrap().get("k").unwrap();
             assert_eq!(
-                value.get("name"),
-                Some(&JsonValue::String("Alice".to_string()))
+                outer.get_all("x").collect::<Vec<_>>(),
+                [&JsonValue::Number(1.0), &JsonValue::Number(2.0)]
             );
         }
     }
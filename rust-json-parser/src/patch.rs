@@ -0,0 +1,938 @@
+//! JSON Patch ([RFC 6902](https://www.rfc-editor.org/rfc/rfc6902)): a list
+//! of operations that edit a document by JSON Pointer, applied with
+//! [`apply`], computed between two documents with [`diff`] and undone with
+//! [`invert`].
+//!
+//! ```
+//! use rust_json_parser::JsonParser;
+//! use rust_json_parser::patch::{self, Patch};
+//!
+//! let mut doc = JsonParser::new(r#"{"name": "svc", "tags": ["a"]}"#)?.parse()?;
+//! let patch = Patch::from_json(&JsonParser::new(
+//!     r#"[
+//!         {"op": "test", "path": "/name", "value": "svc"},
+//!         {"op": "add", "path": "/tags/-", "value": "b"},
+//!         {"op": "move", "from": "/name", "path": "/id"}
+//!     ]"#,
+//! )?.parse()?)?;
+//! patch::apply(&mut doc, &patch)?;
+//! assert_eq!(doc.to_string(), r#"{"tags":["a","b"],"id":"svc"}"#);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::path::{parse_index, pointer_tokens};
+use crate::{JsonAccess, JsonAccessError, JsonObject, JsonPath, JsonValue, PathSegment};
+use std::fmt;
+
+/// One step of a [`Patch`]. Paths are JSON Pointers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// Sets an object member, inserts before an array element or appends
+    /// at `-`, or replaces the whole document at the empty path.
+    Add { path: String, value: JsonValue },
+    /// Deletes a member or element, which must exist.
+    Remove { path: String },
+    /// Overwrites a value, which must exist.
+    Replace { path: String, value: JsonValue },
+    /// Removes the value at `from` and adds it at `path`.
+    Move { from: String, path: String },
+    /// Adds a copy of the value at `from` at `path`.
+    Copy { from: String, path: String },
+    /// Checks that the value at `path` equals `value`.
+    Test { path: String, value: JsonValue },
+}
+
+impl Operation {
+    /// The `op` member naming this operation, such as `"add"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Add { .. } => "add",
+            Operation::Remove { .. } => "remove",
+            Operation::Replace { .. } => "replace",
+            Operation::Move { .. } => "move",
+            Operation::Copy { .. } => "copy",
+            Operation::Test { .. } => "test",
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Operation::Add { path, .. }
+            | Operation::Remove { path }
+            | Operation::Replace { path, .. }
+            | Operation::Move { path, .. }
+            | Operation::Copy { path, .. }
+            | Operation::Test { path, .. } => path,
+        }
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut member = JsonObject::new();
+        member.insert("op".to_string(), JsonValue::String(self.name().into()));
+        match self {
+            Operation::Move { from, .. } | Operation::Copy { from, .. } => {
+                member.insert("from".to_string(), JsonValue::String(from.as_str().into()));
+            }
+            _ => {}
+        }
+        member.insert("path".to_string(), JsonValue::String(self.path().into()));
+        match self {
+            Operation::Add { value, .. }
+            | Operation::Replace { value, .. }
+            | Operation::Test { value, .. } => {
+                member.insert("value".to_string(), value.clone());
+            }
+            _ => {}
+        }
+        JsonValue::Object(member)
+    }
+
+    fn from_json(index: usize, op: &JsonAccess<'_>) -> Result<Self, PatchError> {
+        let pointer = |key: &str| -> Result<String, PatchError> {
+            let field = op.field(key).map_err(|err| JsonAccessError {
+                expected: "JSON Pointer",
+                ..err
+            })?;
+            let pointer = field.as_str()?;
+            match pointer_tokens(pointer) {
+                Some(_) => Ok(pointer.to_string()),
+                None => Err(JsonAccessError {
+                    path: field.path().clone(),
+                    expected: "JSON Pointer",
+                    found: Some("string"),
+                }
+                .into()),
+            }
+        };
+        let value = || -> Result<JsonValue, PatchError> { Ok(op.field("value")?.value().clone()) };
+        let path = pointer("path")?;
+        Ok(match op.get_str("op")? {
+            "add" => Operation::Add {
+                path,
+                value: value()?,
+            },
+            "remove" => Operation::Remove { path },
+            "replace" => Operation::Replace {
+                path,
+                value: value()?,
+            },
+            "move" => Operation::Move {
+                from: pointer("from")?,
+                path,
+            },
+            "copy" => Operation::Copy {
+                from: pointer("from")?,
+                path,
+            },
+            "test" => Operation::Test {
+                path,
+                value: value()?,
+            },
+            other => {
+                return Err(PatchError::UnknownOperation {
+                    index,
+                    op: other.to_string(),
+                });
+            }
+        })
+    }
+}
+
+/// A JSON Patch document: operations applied in order by [`apply`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch {
+    pub operations: Vec<Operation>,
+}
+
+impl Patch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a patch document: an array of operation objects. Members an
+    /// operation does not use are ignored, as RFC 6902 requires.
+    pub fn from_json(value: &JsonValue) -> Result<Self, PatchError> {
+        let root = value.access();
+        let operations = (0..root.as_array()?.len())
+            .map(|index| Operation::from_json(index, &root.index(index)?))
+            .collect::<Result<_, _>>()?;
+        Ok(Patch { operations })
+    }
+
+    /// The patch as a JSON Patch document.
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::Array(self.operations.iter().map(Operation::to_json).collect())
+    }
+}
+
+impl From<Vec<Operation>> for Patch {
+    fn from(operations: Vec<Operation>) -> Self {
+        Self { operations }
+    }
+}
+
+/// Errors from reading or applying a [`Patch`]. `index` is the position of
+/// the failing operation in the patch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchError {
+    /// The patch document is not an array of well-formed operations.
+    InvalidPatch(JsonAccessError),
+    UnknownOperation {
+        index: usize,
+        op: String,
+    },
+    /// The operation names a location that does not exist, or cannot
+    /// take the change: the parent is missing or a scalar, or an array
+    /// index is out of range.
+    PathNotFound {
+        index: usize,
+        path: String,
+    },
+    /// A `move` into a child of the value being moved.
+    MoveIntoChild {
+        index: usize,
+        from: String,
+        path: String,
+    },
+    /// A `test` found a different value at `path`, or none.
+    TestFailed {
+        index: usize,
+        path: String,
+        expected: Box<JsonValue>,
+        actual: Option<Box<JsonValue>>,
+    },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::InvalidPatch(err) => write!(f, "Invalid patch: {err}"),
+            PatchError::UnknownOperation { index, op } => {
+                write!(f, "Operation {index}: unknown op '{op}'")
+            }
+            PatchError::PathNotFound { index, path } => {
+                write!(f, "Operation {index}: path '{path}' not found")
+            }
+            PatchError::MoveIntoChild { index, from, path } => {
+                write!(
+                    f,
+                    "Operation {index}: cannot move '{from}' into its child '{path}'"
+                )
+            }
+            PatchError::TestFailed {
+                index,
+                path,
+                expected,
+                actual,
+            } => {
+                write!(
+                    f,
+                    "Operation {index}: test of '{path}' expected {expected}, "
+                )?;
+                match actual {
+                    Some(actual) => write!(f, "found {actual}"),
+                    None => write!(f, "found nothing"),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PatchError::InvalidPatch(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<JsonAccessError> for PatchError {
+    fn from(err: JsonAccessError) -> Self {
+        PatchError::InvalidPatch(err)
+    }
+}
+
+/// Applies `patch` to `target`, operation by operation.
+///
+/// The patch applies atomically: if any operation fails, `target` is left
+/// as it was and the error names the operation. This works on a copy of
+/// `target`, so it needs memory for a second document.
+pub fn apply(target: &mut JsonValue, patch: &Patch) -> Result<(), PatchError> {
+    let mut patched = target.clone();
+    for (index, operation) in patch.operations.iter().enumerate() {
+        apply_operation(&mut patched, index, operation)?;
+    }
+    *target = patched;
+    Ok(())
+}
+
+fn apply_operation(
+    target: &mut JsonValue,
+    index: usize,
+    operation: &Operation,
+) -> Result<(), PatchError> {
+    let not_found = |path: &str| PatchError::PathNotFound {
+        index,
+        path: path.to_string(),
+    };
+    // Operations built in code may hold malformed pointers, which resolve
+    // nowhere
+    let tokens = |path: &str| pointer_tokens(path).ok_or_else(|| not_found(path));
+    match operation {
+        Operation::Add { path, value } => {
+            add(target, tokens(path)?, value.clone()).ok_or_else(|| not_found(path))
+        }
+        Operation::Remove { path } => remove(target, tokens(path)?)
+            .map(drop)
+            .ok_or_else(|| not_found(path)),
+        Operation::Replace { path, value } => {
+            let slot = target
+                .resolve_mut(&tokens(path)?)
+                .ok_or_else(|| not_found(path))?;
+            *slot = value.clone();
+            Ok(())
+        }
+        Operation::Move { from, path } => {
+            let (from_tokens, to_tokens) = (tokens(from)?, tokens(path)?);
+            if from_tokens == to_tokens {
+                return target
+                    .resolve(&from_tokens)
+                    .map(drop)
+                    .ok_or_else(|| not_found(from));
+            }
+            if to_tokens.starts_with(&from_tokens) {
+                return Err(PatchError::MoveIntoChild {
+                    index,
+                    from: from.clone(),
+                    path: path.clone(),
+                });
+            }
+            let value = remove(target, from_tokens).ok_or_else(|| not_found(from))?;
+            add(target, to_tokens, value).ok_or_else(|| not_found(path))
+        }
+        Operation::Copy { from, path } => {
+            let value = target
+                .resolve(&tokens(from)?)
+                .cloned()
+                .ok_or_else(|| not_found(from))?;
+            add(target, tokens(path)?, value).ok_or_else(|| not_found(path))
+        }
+        Operation::Test { path, value } => match target.resolve(&tokens(path)?) {
+            Some(actual) if actual == value => Ok(()),
+            actual => Err(PatchError::TestFailed {
+                index,
+                path: path.clone(),
+                expected: Box::new(value.clone()),
+                actual: actual.cloned().map(Box::new),
+            }),
+        },
+    }
+}
+
+/// A patch that turns `a` into `b`.
+///
+/// Objects are compared member by member, so only changed members appear.
+/// Arrays are compared element by element after setting aside the elements
+/// they start and end with in common, so inserting or removing a run of
+/// elements in one place gives one operation per element; moves within an
+/// array show up as replacements. A value whose type changed is replaced
+/// whole. Equal documents give an empty patch.
+///
+/// ```
+/// use rust_json_parser::JsonParser;
+/// use rust_json_parser::patch::{apply, diff};
+///
+/// let a = JsonParser::new(r#"{"v": 1, "tags": ["x", "z"], "old": true}"#)?.parse()?;
+/// let b = JsonParser::new(r#"{"v": 2, "tags": ["x", "y", "z"]}"#)?.parse()?;
+/// let patch = diff(&a, &b);
+/// assert_eq!(
+///     patch.to_json().to_string(),
+///     r#"[{"op":"replace","path":"/v","value":2},{"op":"add","path":"/tags/1","value":"y"},{"op":"remove","path":"/old"}]"#
+/// );
+/// let mut doc = a.clone();
+/// apply(&mut doc, &patch)?;
+/// assert_eq!(doc, b);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn diff(a: &JsonValue, b: &JsonValue) -> Patch {
+    let mut operations = Vec::new();
+    diff_into(&mut operations, &mut JsonPath::new(), a, b);
+    Patch { operations }
+}
+
+fn diff_into(out: &mut Vec<Operation>, path: &mut JsonPath, a: &JsonValue, b: &JsonValue) {
+    if a == b {
+        return;
+    }
+    match (a, b) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            for (key, old) in a.iter() {
+                path.push_key(key.as_str());
+                match b.get(key) {
+                    Some(new) => diff_into(out, path, old, new),
+                    None => out.push(Operation::Remove {
+                        path: path.to_pointer(),
+                    }),
+                }
+                path.pop();
+            }
+            for (key, new) in b.iter().filter(|(key, _)| !a.contains_key(key)) {
+                path.push_key(key.as_str());
+                out.push(Operation::Add {
+                    path: path.to_pointer(),
+                    value: new.clone(),
+                });
+                path.pop();
+            }
+        }
+        (JsonValue::Array(a), JsonValue::Array(b)) => {
+            let prefix = a.iter().zip(b.iter()).take_while(|(x, y)| x == y).count();
+            let suffix = a[prefix..]
+                .iter()
+                .rev()
+                .zip(b[prefix..].iter().rev())
+                .take_while(|(x, y)| x == y)
+                .count();
+            let (a, b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+            let common = a.len().min(b.len());
+            for (offset, (old, new)) in a.iter().zip(b).enumerate() {
+                path.push_index(prefix + offset);
+                diff_into(out, path, old, new);
+                path.pop();
+            }
+            for (offset, new) in b.iter().enumerate().skip(common) {
+                path.push_index(prefix + offset);
+                out.push(Operation::Add {
+                    path: path.to_pointer(),
+                    value: new.clone(),
+                });
+                path.pop();
+            }
+            // From the back, so each index is still the one it was
+            for offset in (common..a.len()).rev() {
+                path.push_index(prefix + offset);
+                out.push(Operation::Remove {
+                    path: path.to_pointer(),
+                });
+                path.pop();
+            }
+        }
+        _ => out.push(Operation::Replace {
+            path: path.to_pointer(),
+            value: b.clone(),
+        }),
+    }
+}
+
+/// The patch that undoes `patch`, given the document it applies to:
+/// applying `patch` and then the result to `original` gives `original`
+/// back.
+///
+/// `test` operations have nothing to undo and are left out. Fails with the
+/// error [`apply`] would give when `patch` does not apply to `original`.
+///
+/// ```
+/// use rust_json_parser::JsonParser;
+/// use rust_json_parser::patch::{Patch, apply, invert};
+///
+/// let original = JsonParser::new(r#"{"a": 1, "list": [1, 2]}"#)?.parse()?;
+/// let patch = Patch::from_json(&JsonParser::new(
+///     r#"[{"op": "replace", "path": "/a", "value": 2}, {"op": "add", "path": "/list/-", "value": 3}]"#,
+/// )?.parse()?)?;
+/// let undo = invert(&patch, &original)?;
+/// assert_eq!(
+///     undo.to_json().to_string(),
+///     r#"[{"op":"remove","path":"/list/2"},{"op":"replace","path":"/a","value":1}]"#
+/// );
+///
+/// let mut doc = original.clone();
+/// apply(&mut doc, &patch)?;
+/// apply(&mut doc, &undo)?;
+/// assert_eq!(doc, original);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn invert(patch: &Patch, original: &JsonValue) -> Result<Patch, PatchError> {
+    let mut doc = original.clone();
+    let mut undo = Vec::with_capacity(patch.operations.len());
+    for (index, operation) in patch.operations.iter().enumerate() {
+        let before = undo_before(&doc, operation);
+        apply_operation(&mut doc, index, operation)?;
+        undo.push(match operation {
+            Operation::Move { from, path }
+                if before.is_empty() && pointer_tokens(from) != pointer_tokens(path) =>
+            {
+                // Nothing was overwritten: move the value back from where
+                // it landed
+                let to = pointer_tokens(path).unwrap_or_default();
+                vec![Operation::Move {
+                    from: landed_at(&doc, to),
+                    path: from.clone(),
+                }]
+            }
+            _ => before,
+        });
+    }
+    Ok(Patch {
+        operations: undo.into_iter().rev().flatten().collect(),
+    })
+}
+
+/// The operations that undo `operation`, worked out before it is applied
+/// to `doc`. A `move` that overwrites nothing gets none here, as moving the
+/// value back needs to know where it landed.
+fn undo_before(doc: &JsonValue, operation: &Operation) -> Vec<Operation> {
+    let old = |path: &str| doc.pointer(path).cloned();
+    match operation {
+        Operation::Add { path, .. } | Operation::Copy { path, .. } => {
+            undo_add(doc, path).into_iter().collect()
+        }
+        Operation::Remove { path } => old(path)
+            .map(|value| Operation::Add {
+                path: path.clone(),
+                value,
+            })
+            .into_iter()
+            .collect(),
+        Operation::Replace { path, .. } => old(path)
+            .map(|value| Operation::Replace {
+                path: path.clone(),
+                value,
+            })
+            .into_iter()
+            .collect(),
+        Operation::Test { .. } => Vec::new(),
+        Operation::Move { from, path } => {
+            let (Some(from_tokens), Some(to_tokens)) = (pointer_tokens(from), pointer_tokens(path))
+            else {
+                return Vec::new();
+            };
+            if from_tokens == to_tokens {
+                Vec::new()
+            } else if from_tokens.starts_with(&to_tokens) {
+                // The move replaces an ancestor of `from`, which held the
+                // moved value too
+                old(path)
+                    .map(|value| {
+                        vec![Operation::Replace {
+                            path: path.clone(),
+                            value,
+                        }]
+                    })
+                    .unwrap_or_default()
+            } else {
+                match (undo_add(doc, path), old(from)) {
+                    (Some(replace @ Operation::Replace { .. }), Some(value)) => vec![
+                        replace,
+                        Operation::Add {
+                            path: from.clone(),
+                            value,
+                        },
+                    ],
+                    _ => Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+/// What undoes adding a value at `path`: restoring the member it replaces,
+/// or removing the element or member it creates.
+fn undo_add(doc: &JsonValue, path: &str) -> Option<Operation> {
+    let mut tokens = pointer_tokens(path)?;
+    let Some(last) = tokens.pop() else {
+        return Some(Operation::Replace {
+            path: String::new(),
+            value: doc.clone(),
+        });
+    };
+    match doc.resolve(&tokens)? {
+        JsonValue::Object(map) => Some(match map.get(&last) {
+            Some(old) => Operation::Replace {
+                path: path.to_string(),
+                value: old.clone(),
+            },
+            None => Operation::Remove {
+                path: path.to_string(),
+            },
+        }),
+        JsonValue::Array(items) => {
+            let index = match last.as_str() {
+                "-" => items.len(),
+                token => parse_index(token)?,
+            };
+            tokens.push(index.to_string());
+            Some(Operation::Remove {
+                path: pointer(&tokens),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The pointer of a value just added at `tokens`, with `-` resolved to the
+/// index the element took.
+fn landed_at(doc: &JsonValue, mut tokens: Vec<String>) -> String {
+    if tokens.last().is_some_and(|last| last == "-") {
+        tokens.pop();
+        if let Some(JsonValue::Array(items)) = doc.resolve(&tokens) {
+            tokens.push(items.len().saturating_sub(1).to_string());
+        }
+    }
+    pointer(&tokens)
+}
+
+fn pointer(tokens: &[String]) -> String {
+    let segments: Vec<_> = tokens
+        .iter()
+        .map(|token| PathSegment::Key(token.clone()))
+        .collect();
+    JsonPath::from(segments).to_pointer()
+}
+
+fn add(target: &mut JsonValue, mut tokens: Vec<String>, value: JsonValue) -> Option<()> {
+    let Some(last) = tokens.pop() else {
+        *target = value;
+        return Some(());
+    };
+    match target.resolve_mut(&tokens)? {
+        JsonValue::Object(map) => {
+            map.insert(last, value);
+        }
+        JsonValue::Array(items) => {
+            let at = match last.as_str() {
+                "-" => items.len(),
+                token => parse_index(token).filter(|&i| i <= items.len())?,
+            };
+            items.insert(at, value);
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+fn remove(target: &mut JsonValue, mut tokens: Vec<String>) -> Option<JsonValue> {
+    let last = tokens.pop()?;
+    match target.resolve_mut(&tokens)? {
+        JsonValue::Object(map) => map.remove(&last),
+        JsonValue::Array(items) => items.remove(parse_index(&last)?),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn patched(doc: &str, patch: &str) -> Result<JsonValue, PatchError> {
+        let mut doc = parse_json(doc).unwrap();
+        apply(&mut doc, &Patch::from_json(&parse_json(patch).unwrap())?)?;
+        Ok(doc)
+    }
+
+    #[test]
+    fn test_rfc_examples() {
+        // RFC 6902, appendix A
+        let cases = [
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+                r#"{"baz": "qux", "foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": ["bar", "baz"]}"#,
+                r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "remove", "path": "/baz"}]"#,
+                r#"{"foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+                r#"[{"op": "remove", "path": "/foo/1"}]"#,
+                r#"{"foo": ["bar", "baz"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+                r#"{"baz": "boo", "foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+            ),
+            (
+                r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+                r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+                r#"{"foo": ["all", "cows", "eat", "grass"]}"#,
+            ),
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/child", "value": {"grandchild": {}}}]"#,
+                r#"{"foo": "bar", "child": {"grandchild": {}}}"#,
+            ),
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": "qux", "xyz": 123}]"#,
+                r#"{"foo": "bar", "baz": "qux"}"#,
+            ),
+            (
+                r#"{"foo": ["bar"]}"#,
+                r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+                r#"{"foo": ["bar", ["abc", "def"]]}"#,
+            ),
+            (
+                r#"{"/": 9, "~1": 10}"#,
+                r#"[{"op": "test", "path": "/~01", "value": 10}]"#,
+                r#"{"/": 9, "~1": 10}"#,
+            ),
+            (
+                r#"{"a": {"b": 1}}"#,
+                r#"[{"op": "copy", "from": "/a", "path": "/c"}, {"op": "replace", "path": "/c/b", "value": 2}]"#,
+                r#"{"a": {"b": 1}, "c": {"b": 2}}"#,
+            ),
+            (
+                r#"[1]"#,
+                r#"[{"op": "add", "path": "", "value": {"root": true}}]"#,
+                r#"{"root": true}"#,
+            ),
+        ];
+        for (doc, patch, expected) in cases {
+            assert_eq!(
+                patched(doc, patch),
+                Ok(parse_json(expected).unwrap()),
+                "{patch}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_failures_leave_the_document_alone() {
+        let doc = r#"{"baz": "qux", "foo": ["a", 2, "c"]}"#;
+        let mut value = parse_json(doc).unwrap();
+        let patch = Patch::from_json(&parse_json(
+            r#"[{"op": "remove", "path": "/baz"}, {"op": "test", "path": "/foo/1", "value": "2"}]"#,
+        ).unwrap())
+        .unwrap();
+        let err = apply(&mut value, &patch).unwrap_err();
+        assert_eq!(
+            err,
+            PatchError::TestFailed {
+                index: 1,
+                path: "/foo/1".into(),
+                expected: Box::new(JsonValue::String("2".into())),
+                actual: Some(Box::new(JsonValue::Number(2.0))),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            r#"Operation 1: test of '/foo/1' expected "2", found 2"#
+        );
+        assert_eq!(value, parse_json(doc).unwrap());
+
+        for (patch, expected) in [
+            (
+                r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#,
+                "/baz/bat",
+            ),
+            (r#"[{"op": "add", "path": "/foo/4", "value": 1}]"#, "/foo/4"),
+            (r#"[{"op": "remove", "path": "/nope"}]"#, "/nope"),
+            (
+                r#"[{"op": "replace", "path": "/foo/-", "value": 1}]"#,
+                "/foo/-",
+            ),
+            (r#"[{"op": "copy", "from": "/x", "path": "/y"}]"#, "/x"),
+            (r#"[{"op": "remove", "path": ""}]"#, ""),
+        ] {
+            assert!(
+                matches!(patched(doc, patch), Err(PatchError::PathNotFound { index: 0, ref path }) if path == expected),
+                "{patch}"
+            );
+        }
+        assert!(matches!(
+            patched(
+                doc,
+                r#"[{"op": "test", "path": "/missing", "value": null}]"#
+            ),
+            Err(PatchError::TestFailed { actual: None, .. })
+        ));
+        assert!(matches!(
+            patched(doc, r#"[{"op": "move", "from": "/foo", "path": "/foo/0"}]"#),
+            Err(PatchError::MoveIntoChild { .. })
+        ));
+        assert_eq!(
+            patched(doc, r#"[{"op": "move", "from": "/foo", "path": "/foo"}]"#),
+            Ok(parse_json(doc).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_malformed_patches() {
+        let read = |patch: &str| {
+            Patch::from_json(&parse_json(patch).unwrap())
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            read(r#"{"op": "add"}"#),
+            "Invalid patch: Expected array at the document root, found object"
+        );
+        assert_eq!(
+            read(r#"[{"op": "add", "path": "/a"}]"#),
+            "Invalid patch: Missing value at '/0/value'"
+        );
+        assert_eq!(
+            read(r#"[{"path": "/a"}]"#),
+            "Invalid patch: Missing string at '/0/op'"
+        );
+        assert_eq!(
+            read(r#"[{"op": "remove", "path": "a"}]"#),
+            "Invalid patch: Expected JSON Pointer at '/0/path', found string"
+        );
+        assert_eq!(
+            read(r#"[{"op": "move", "path": "/a"}]"#),
+            "Invalid patch: Missing JSON Pointer at '/0/from'"
+        );
+        assert_eq!(
+            read(r#"[{"op": "test", "path": "/a", "value": 1}, {"op": "frob", "path": ""}]"#),
+            "Operation 1: unknown op 'frob'"
+        );
+    }
+
+    #[test]
+    fn test_diff_produces_a_patch_that_applies() {
+        let docs = [
+            r#"{"a": 1, "b": [1, 2, 3], "c": {"d": null}}"#,
+            r#"{"a": 1, "b": [0, 1, 2, 3], "c": {"d": null, "e": "x"}}"#,
+            r#"{"b": [1, 3], "c": {"d": [true]}}"#,
+            r#"{"a": "1", "b": [[1], {"k": 2}, 3, 4, 5], "f": {}}"#,
+            r#"[{"a": 1}, 2, 3]"#,
+            r#"[2, 3, {"a": 2}, 6]"#,
+            r#""scalar""#,
+            "{}",
+            "[]",
+        ];
+        for a in docs {
+            for b in docs {
+                let (a, b) = (parse_json(a).unwrap(), parse_json(b).unwrap());
+                let patch = diff(&a, &b);
+                let mut doc = a.clone();
+                apply(&mut doc, &patch).unwrap_or_else(|err| panic!("{a} -> {b}: {err}"));
+                assert_eq!(doc, b, "{a} -> {b}: {}", patch.to_json());
+            }
+        }
+    }
+
+    #[test]
+    fn test_diff_is_small() {
+        let diff_json = |a: &str, b: &str| {
+            diff(&parse_json(a).unwrap(), &parse_json(b).unwrap())
+                .to_json()
+                .to_string()
+        };
+        assert_eq!(diff_json(r#"{"a": [1, 2]}"#, r#"{"a": [1, 2]}"#), "[]");
+        assert_eq!(
+            diff_json("[1, 2, 3, 4, 5]", "[0, 1, 2, 3, 4, 5]"),
+            r#"[{"op":"add","path":"/0","value":0}]"#
+        );
+        assert_eq!(
+            diff_json("[1, 2, 3, 4, 5]", "[1, 2, 5]"),
+            r#"[{"op":"remove","path":"/3"},{"op":"remove","path":"/2"}]"#
+        );
+        assert_eq!(
+            diff_json(
+                r#"{"a": {"b": [1, {"c": 1}]}}"#,
+                r#"{"a": {"b": [1, {"c": 2}]}}"#
+            ),
+            r#"[{"op":"replace","path":"/a/b/1/c","value":2}]"#
+        );
+        assert_eq!(
+            diff_json(r#"{"a/b": 1}"#, r#"{"a/b": [1]}"#),
+            r#"[{"op":"replace","path":"/a~1b","value":[1]}]"#
+        );
+        assert_eq!(
+            diff_json("[1]", r#"{"0": 1}"#),
+            r#"[{"op":"replace","path":"","value":{"0":1}}]"#
+        );
+    }
+
+    #[test]
+    fn test_invert_undoes_every_operation() {
+        let original = r#"{"a": {"b": 1, "c": [1, 2, 3]}, "d": "x", "e": [[0]]}"#;
+        let patches = [
+            r#"[{"op": "add", "path": "/f", "value": 1}, {"op": "add", "path": "/d", "value": 2}]"#,
+            r#"[{"op": "add", "path": "/a/c/-", "value": 4}, {"op": "add", "path": "/a/c/0", "value": 0}]"#,
+            r#"[{"op": "remove", "path": "/a/c/1"}, {"op": "remove", "path": "/a"}]"#,
+            r#"[{"op": "replace", "path": "/a/b", "value": [true]}, {"op": "replace", "path": "", "value": 1}]"#,
+            r#"[{"op": "move", "from": "/a/c/0", "path": "/a/c/-"}, {"op": "move", "from": "/d", "path": "/e/0/0"}]"#,
+            r#"[{"op": "move", "from": "/d", "path": "/a"}, {"op": "move", "from": "/e/0", "path": "/e"}]"#,
+            r#"[{"op": "move", "from": "/a/c/2", "path": "/a/c/0"}, {"op": "move", "from": "/a/b", "path": "/a/b"}]"#,
+            r#"[{"op": "copy", "from": "/a", "path": "/d"}, {"op": "copy", "from": "/d/c", "path": "/e/1"}]"#,
+            r#"[{"op": "add", "path": "", "value": []}, {"op": "add", "path": "/-", "value": 1}]"#,
+            r#"[{"op": "test", "path": "/d", "value": "x"}, {"op": "move", "from": "/a/c", "path": "/c"}]"#,
+        ];
+        let original = parse_json(original).unwrap();
+        for patch in patches {
+            let patch = Patch::from_json(&parse_json(patch).unwrap()).unwrap();
+            let undo = invert(&patch, &original).unwrap();
+            let mut doc = original.clone();
+            apply(&mut doc, &patch).unwrap();
+            apply(&mut doc, &undo).unwrap_or_else(|err| panic!("{}: {err}", undo.to_json()));
+            assert_eq!(
+                doc,
+                original,
+                "{} undone by {}",
+                patch.to_json(),
+                undo.to_json()
+            );
+        }
+    }
+
+    #[test]
+    fn test_invert_fails_like_apply() {
+        let original = parse_json(r#"{"a": 1}"#).unwrap();
+        let patch = Patch::from_json(
+            &parse_json(r#"[{"op": "remove", "path": "/a"}, {"op": "remove", "path": "/a"}]"#)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            invert(&patch, &original),
+            Err(PatchError::PathNotFound {
+                index: 1,
+                path: "/a".into()
+            })
+        );
+        assert_eq!(invert(&Patch::new(), &original), Ok(Patch::new()));
+    }
+
+    #[test]
+    fn test_round_trips_through_json() {
+        let patch = Patch::from(vec![
+            Operation::Add {
+                path: "/a".into(),
+                value: JsonValue::Null,
+            },
+            Operation::Remove { path: "/b".into() },
+            Operation::Move {
+                from: "/c".into(),
+                path: "/d".into(),
+            },
+            Operation::Test {
+                path: "/e".into(),
+                value: JsonValue::Boolean(true),
+            },
+        ]);
+        let json = patch.to_json();
+        assert_eq!(
+            json.to_string(),
+            r#"[{"op":"add","path":"/a","value":null},{"op":"remove","path":"/b"},{"op":"move","from":"/c","path":"/d"},{"op":"test","path":"/e","value":true}]"#
+        );
+        assert_eq!(Patch::from_json(&json), Ok(patch));
+    }
+}
@@ -0,0 +1,439 @@
+use crate::{CompiledPath, JsonValue, Token};
+use std::fmt;
+
+/// One step from a container into one of its children.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Location of a node inside a document, from the root down.
+///
+/// Displays as an RFC 6901 JSON Pointer (`/users/0/name`); the root is the
+/// empty string.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct JsonPath {
+    segments: Vec<PathSegment>,
+}
+
+impl JsonPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    pub fn push_key(&mut self, key: impl Into<String>) {
+        self.segments.push(PathSegment::Key(key.into()));
+    }
+
+    pub fn push_index(&mut self, index: usize) {
+        self.segments.push(PathSegment::Index(index));
+    }
+
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.segments.pop()
+    }
+
+    pub(crate) fn push(&mut self, segment: PathSegment) {
+        self.segments.push(segment);
+    }
+
+    pub(crate) fn push_front(&mut self, segment: PathSegment) {
+        self.segments.insert(0, segment);
+    }
+
+    pub(crate) fn prepend(&mut self, prefix: &JsonPath) {
+        self.segments.splice(0..0, prefix.segments.iter().cloned());
+    }
+
+    pub(crate) fn starts_with(&self, prefix: &JsonPath) -> bool {
+        self.segments.starts_with(&prefix.segments)
+    }
+
+    /// Renders the path as an RFC 6901 JSON Pointer.
+    pub fn to_pointer(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            match segment {
+                PathSegment::Key(key) => write!(f, "/{}", escape_pointer_token(key))?,
+                PathSegment::Index(index) => write!(f, "/{index}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<PathSegment>> for JsonPath {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self { segments }
+    }
+}
+
+impl JsonValue {
+    /// Looks up a value by RFC 6901 JSON Pointer (`/users/0/name`).
+    ///
+    /// The empty pointer refers to the whole document. Returns `None` when
+    /// the pointer is malformed or does not resolve.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        self.resolve(&pointer_tokens(pointer)?)
+    }
+
+    /// Mutable counterpart of [`JsonValue::pointer`].
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        self.resolve_mut(&pointer_tokens(pointer)?)
+    }
+
+    /// Every value matching a pointer pattern, with its concrete location,
+    /// in document order.
+    ///
+    /// Patterns are pointers in which a `*` token matches any one key or
+    /// index and a `**` token matches any number of levels, none included:
+    /// `/users/*/email` finds each user's email and `/**/password` every
+    /// `password` member at any depth. A node is listed once however many
+    /// ways it matches. A malformed pattern matches nothing.
+    ///
+    /// To run the same pattern over many documents, compile it once with
+    /// [`CompiledPath::new`].
+    pub fn pointer_matches(&self, pattern: &str) -> Vec<(JsonPath, &JsonValue)> {
+        match CompiledPath::new(pattern) {
+            Ok(path) => path.matches(self),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Looks up a value by the unescaped reference tokens of a pointer.
+    pub(crate) fn resolve(&self, tokens: &[String]) -> Option<&JsonValue> {
+        tokens.iter().try_fold(self, |node, token| match node {
+            JsonValue::Object(obj) => obj.get(token),
+            JsonValue::Array(arr) => arr.get(parse_index(token)?),
+            _ => None,
+        })
+    }
+
+    pub(crate) fn resolve_mut(&mut self, tokens: &[String]) -> Option<&mut JsonValue> {
+        tokens.iter().try_fold(self, |node, token| match node {
+            JsonValue::Object(obj) => obj.get_mut(token),
+            JsonValue::Array(arr) => arr.get_mut(parse_index(token)?),
+            _ => None,
+        })
+    }
+}
+
+impl JsonValue {
+    /// Every leaf location in this value, in document order.
+    ///
+    /// Leaves are scalars and empty containers. A scalar root yields the
+    /// single root path.
+    pub fn paths(&self) -> Paths<'_> {
+        Paths {
+            stack: vec![(JsonPath::new(), self)],
+        }
+    }
+}
+
+/// Iterator returned by [`JsonValue::paths`].
+#[derive(Debug, Clone)]
+pub struct Paths<'a> {
+    /// Nodes still to visit, with the next one on top.
+    stack: Vec<(JsonPath, &'a JsonValue)>,
+}
+
+impl Iterator for Paths<'_> {
+    type Item = JsonPath;
+
+    fn next(&mut self) -> Option<JsonPath> {
+        while let Some((path, value)) = self.stack.pop() {
+            match value {
+                JsonValue::Array(arr) if !arr.is_empty() => {
+                    push_children(&mut self.stack, &path, value);
+                }
+                JsonValue::Object(obj) if !obj.is_empty() => {
+                    push_children(&mut self.stack, &path, value);
+                }
+                _ => return Some(path),
+            }
+        }
+        None
+    }
+}
+
+/// Pushes the children of `value` onto a depth-first `stack` so that they
+/// pop off in document order.
+pub(crate) fn push_children<'a>(
+    stack: &mut Vec<(JsonPath, &'a JsonValue)>,
+    path: &JsonPath,
+    value: &'a JsonValue,
+) {
+    let start = stack.len();
+    match value {
+        JsonValue::Array(arr) => {
+            for (index, item) in arr.iter().enumerate() {
+                let mut child = path.clone();
+                child.push_index(index);
+                stack.push((child, item));
+            }
+        }
+        JsonValue::Object(obj) => {
+            for (key, item) in obj.iter() {
+                let mut child = path.clone();
+                child.push_key(key.as_str());
+                stack.push((child, item));
+            }
+        }
+        _ => {}
+    }
+    stack[start..].reverse();
+}
+
+/// Splits a pointer into unescaped reference tokens.
+///
+/// Returns `None` unless the pointer is empty or starts with `/`.
+pub(crate) fn pointer_tokens(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    let rest = pointer.strip_prefix('/')?;
+    Some(rest.split('/').map(unescape_pointer_token).collect())
+}
+
+/// Parses an array index token: decimal digits without leading zeros.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    let valid = !token.is_empty()
+        && token.bytes().all(|b| b.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    if valid { token.parse().ok() } else { None }
+}
+
+/// The path of the member or element being read after `tokens`, the
+/// tokens read before an error.
+pub(crate) fn open_path<'a>(tokens: impl IntoIterator<Item = &'a Token>) -> JsonPath {
+    let mut open = OpenPath::default();
+    for token in tokens {
+        open.step(token);
+    }
+    open.path()
+}
+
+/// The path of the member or element being read, kept as the tokens of a
+/// document are read in order.
+///
+/// In an array, an element is being read from the `[` or `,` before it to
+/// its end; in an object, a member from its key to the end of its value.
+#[derive(Debug, Default)]
+pub(crate) struct OpenPath {
+    /// Each open container: whether it is an object, how many children it
+    /// has begun, and the segment of the one being read.
+    open: Vec<(bool, usize, Option<PathSegment>)>,
+}
+
+impl OpenPath {
+    pub(crate) fn step(&mut self, token: &Token) {
+        match token {
+            Token::LeftBrace => self.open.push((true, 0, None)),
+            Token::LeftBracket => self.open.push((false, 0, Some(PathSegment::Index(0)))),
+            Token::RightBrace | Token::RightBracket => {
+                self.open.pop();
+                if let Some((_, _, child)) = self.open.last_mut() {
+                    *child = None;
+                }
+            }
+            token => {
+                let Some((object, count, child)) = self.open.last_mut() else {
+                    return;
+                };
+                match token {
+                    Token::Comma if *object => *child = None,
+                    Token::Comma => {
+                        *count += 1;
+                        *child = Some(PathSegment::Index(*count));
+                    }
+                    Token::String(key) if *object && child.is_none() => {
+                        *child = Some(PathSegment::Key(key.clone()));
+                    }
+                    Token::Colon => {}
+                    // A scalar ends the member or element it is the value of
+                    _ => *child = None,
+                }
+            }
+        }
+    }
+
+    pub(crate) fn path(&self) -> JsonPath {
+        self.open
+            .iter()
+            .filter_map(|(_, _, child)| child.clone())
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+fn unescape_pointer_token(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Escapes `~` and `/` as `~0` and `~1`, per RFC 6901.
+fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_path() {
+        let path = JsonPath::new();
+        assert!(path.is_root());
+        assert_eq!(path.to_pointer(), "");
+    }
+
+    #[test]
+    fn test_push_and_pop() {
+        let mut path = JsonPath::new();
+        path.push_key("users");
+        path.push_index(3);
+        path.push_key("name");
+        assert_eq!(path.len(), 3);
+        assert_eq!(path.to_pointer(), "/users/3/name");
+
+        assert_eq!(path.pop(), Some(PathSegment::Key("name".to_string())));
+        assert_eq!(path.to_string(), "/users/3");
+    }
+
+    mod pointer_lookup {
+        use super::*;
+
+        use crate::test_support::parse_json;
+
+        #[test]
+        fn test_pointer_resolves_nested_values() {
+            let value = parse_json(r#"{"users": [{"name": "Ann"}, {"name": "Bob"}]}"#).unwrap();
+            assert_eq!(
+                value.pointer("/users/1/name").and_then(|v| v.as_str()),
+                Some("Bob")
+            );
+            assert_eq!(value.pointer(""), Some(&value));
+        }
+
+        #[test]
+        fn test_pointer_unescapes_tokens() {
+            let value = parse_json(r#"{"a/b": {"m~n": 1}, "": 2}"#).unwrap();
+            assert_eq!(value.pointer("/a~1b/m~0n"), Some(&JsonValue::Number(1.0)));
+            assert_eq!(value.pointer("/"), Some(&JsonValue::Number(2.0)));
+        }
+
+        #[test]
+        fn test_paths_lists_leaves_in_order() {
+            let value =
+                parse_json(r#"{"b": [1, {"c": null}], "a": {}, "e/f": [], "d": "x"}"#).unwrap();
+            let pointers: Vec<String> = value.paths().map(|p| p.to_pointer()).collect();
+            assert_eq!(pointers, vec!["/b/0", "/b/1/c", "/a", "/e~1f", "/d"]);
+            for path in value.paths() {
+                assert!(value.pointer(&path.to_pointer()).is_some());
+            }
+        }
+
+        #[test]
+        fn test_paths_of_scalar_root() {
+            let paths: Vec<JsonPath> = JsonValue::Number(1.0).paths().collect();
+            assert_eq!(paths, vec![JsonPath::new()]);
+        }
+
+        #[test]
+        fn test_pointer_misses() {
+            let value = parse_json(r#"{"arr": [1, 2]}"#).unwrap();
+            let misses = [
+                "/nope",
+                "/arr/2",
+                "/arr/01",
+                "/arr/-",
+                "/arr/x",
+                "arr",
+                "/arr/0/deeper",
+            ];
+            for pointer in misses {
+                assert!(
+                    value.pointer(pointer).is_none(),
+                    "{pointer} should not resolve"
+                );
+            }
+        }
+
+        #[test]
+        fn test_pointer_matches_wildcards() {
+            let value = parse_json(
+                r#"{"users": [{"email": "a@x", "auth": {"password": "p1"}}, {"email": "b@x"}],
+                    "password": "p0"}"#,
+            )
+            .unwrap();
+            let pointers = |pattern| -> Vec<String> {
+                value
+                    .pointer_matches(pattern)
+                    .into_iter()
+                    .map(|(path, _)| path.to_pointer())
+                    .collect()
+            };
+            assert_eq!(
+                pointers("/users/*/email"),
+                ["/users/0/email", "/users/1/email"]
+            );
+            assert_eq!(
+                pointers("/**/password"),
+                ["/users/0/auth/password", "/password"]
+            );
+            assert_eq!(pointers("/users/1/*"), ["/users/1/email"]);
+            assert_eq!(pointers("/users/*/nope"), Vec::<String>::new());
+            let (path, found) = &value.pointer_matches("/**/auth/*")[0];
+            assert_eq!(path.to_pointer(), "/users/0/auth/password");
+            assert_eq!(found.as_str(), Some("p1"));
+        }
+
+        #[test]
+        fn test_pointer_matches_each_node_once() {
+            let value = parse_json(r#"{"a": {"a": 1}}"#).unwrap();
+            let matches = value.pointer_matches("/**/**");
+            let pointers: Vec<String> = matches.iter().map(|(p, _)| p.to_pointer()).collect();
+            assert_eq!(pointers, ["", "/a", "/a/a"]);
+            assert_eq!(value.pointer_matches("/**/a/**").len(), 2);
+            assert_eq!(value.pointer_matches("")[0].1, &value);
+            assert!(value.pointer_matches("a/*").is_empty());
+        }
+
+        #[test]
+        fn test_pointer_mut_edits_in_place() {
+            let mut value = parse_json(r#"{"a": [0, {"b": 1}]}"#).unwrap();
+            *value.pointer_mut("/a/1/b").unwrap() = JsonValue::Boolean(true);
+            assert_eq!(value, parse_json(r#"{"a": [0, {"b": true}]}"#).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_pointer_escaping() {
+        let path = JsonPath::from(vec![
+            PathSegment::Key("a/b".to_string()),
+            PathSegment::Key("m~n".to_string()),
+        ]);
+        assert_eq!(path.to_pointer(), "/a~1b/m~0n");
+    }
+}
@@ -0,0 +1,773 @@
+//! A small regular expression engine for JSON Schema `pattern`, covering
+//! the ECMA-262 syntax schemas use in practice without pulling in a regex
+//! dependency.
+//!
+//! Supported: literals, `.`, classes (`[a-z]`, `[^...]`), the `\d \w \s`
+//! shorthands and their negations, `^`, `$`, `\b`, `\B`, groups (`(...)`,
+//! `(?:...)`), alternation and the `* + ? {n} {n,} {n,m}` quantifiers,
+//! greedy or lazy, with groups nested up to 128 deep. Backreferences and
+//! lookaround are rejected. Matching simulates the compiled program on all
+//! threads at once, so it takes time linear in the input and never
+//! backtracks.
+
+use std::fmt;
+
+/// Compiled programs larger than this are rejected, which bounds the
+/// cost of counted repetition such as `(a{1000}){1000}`.
+const MAX_PROGRAM: usize = 10_000;
+
+/// Nodes compiled, counting each copy a repetition makes, past which a
+/// pattern is rejected. Repeating something that compiles to nothing, as
+/// in `((?:){100000}){100000}`, adds no instructions but still costs this.
+const MAX_COMPILE_STEPS: usize = 4 * MAX_PROGRAM;
+
+/// Groups nested deeper than this are rejected, as parsing and compiling
+/// recurse once per level.
+const MAX_GROUP_DEPTH: usize = 128;
+
+/// A compiled pattern.
+#[derive(Debug, Clone)]
+pub(crate) struct Pattern {
+    program: Vec<Inst>,
+}
+
+/// Why a pattern failed to compile, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PatternError {
+    /// Character offset into the pattern.
+    pub(crate) position: usize,
+    pub(crate) message: &'static str,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at character {}", self.message, self.position)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Range(char, char),
+    /// `\d`, or `\D` when negated.
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    fn matches(&self, c: char) -> bool {
+        match *self {
+            ClassItem::Range(lo, hi) => lo <= c && c <= hi,
+            ClassItem::Digit(negated) => c.is_ascii_digit() != negated,
+            ClassItem::Word(negated) => is_word(c) != negated,
+            ClassItem::Space(negated) => (c.is_whitespace() || c == '\u{feff}') != negated,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Class {
+    items: Vec<ClassItem>,
+    negated: bool,
+}
+
+impl Class {
+    fn matches(&self, c: char) -> bool {
+        self.items.iter().any(|item| item.matches(c)) != self.negated
+    }
+}
+
+impl Class {
+    /// A random character in the class, found by picking a member and, for
+    /// negated ones, trying printable ASCII until one fits.
+    fn sample(&self, below: &mut dyn FnMut(usize) -> usize) -> Option<char> {
+        if !self.negated && !self.items.is_empty() {
+            let c = match self.items[below(self.items.len())] {
+                ClassItem::Range(lo, hi) => {
+                    let span = hi as usize - lo as usize + 1;
+                    char::from_u32(lo as u32 + below(span) as u32).unwrap_or(lo)
+                }
+                ClassItem::Digit(false) => (b'0' + below(10) as u8) as char,
+                ClassItem::Word(false) => {
+                    const WORD: &[u8] =
+                        b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
+                    WORD[below(WORD.len())] as char
+                }
+                ClassItem::Space(false) => ' ',
+                _ => printable(below),
+            };
+            if self.matches(c) {
+                return Some(c);
+            }
+        }
+        (0..256)
+            .map(|_| printable(below))
+            .find(|&c| self.matches(c))
+    }
+}
+
+/// A random printable ASCII character.
+fn printable(below: &mut dyn FnMut(usize) -> usize) -> char {
+    (b' ' + below(95) as u8) as char
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    /// `.`: anything but a line terminator.
+    Any,
+    Class(Class),
+    Start,
+    End,
+    /// `\b`, or `\B` when negated.
+    WordBoundary(bool),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Start,
+    End,
+    WordBoundary(bool),
+    /// Continue at both targets.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+impl Pattern {
+    pub(crate) fn new(pattern: &str) -> Result<Self, PatternError> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            pos: 0,
+            depth: 0,
+        };
+        let node = parser.alternation()?;
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("unmatched ')'"));
+        }
+        let mut compiler = Compiler {
+            program: Vec::new(),
+            steps: 0,
+        };
+        compiler.node(&node)?;
+        compiler.push(Inst::Match)?;
+        Ok(Pattern {
+            program: compiler.program,
+        })
+    }
+
+    /// Whether the pattern matches anywhere in `text`; like ECMA-262,
+    /// patterns are not implicitly anchored.
+    pub(crate) fn is_match(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        for pos in 0..=chars.len() {
+            // Starting a thread at every position makes the search
+            // unanchored.
+            if self.follow(&mut current, 0, &chars, pos) {
+                return true;
+            }
+            let Some(&c) = chars.get(pos) else { break };
+            next.clear();
+            for &pc in &current.list {
+                let advance = match &self.program[pc] {
+                    Inst::Char(expected) => *expected == c,
+                    Inst::Any => !is_line_terminator(c),
+                    Inst::Class(class) => class.matches(c),
+                    _ => false,
+                };
+                if advance && self.follow(&mut next, pc + 1, &chars, pos + 1) {
+                    return true;
+                }
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        false
+    }
+
+    /// A random string the pattern matches in full, walking the program
+    /// and taking each branch at random; `below(n)` picks a number under
+    /// `n`. Past `max_chars`, loops are left as soon as possible. `None`
+    /// when the walk hit an assertion it cannot satisfy; callers retry.
+    pub(crate) fn sample(
+        &self,
+        below: &mut dyn FnMut(usize) -> usize,
+        max_chars: usize,
+    ) -> Option<String> {
+        let mut out = String::new();
+        let mut chars = 0;
+        let mut ended = false;
+        let mut pc = 0;
+        // Bounds walks through loops that can match the empty string.
+        for _ in 0..MAX_PROGRAM * 4 {
+            let c = match &self.program[pc] {
+                Inst::Match => return self.is_match(&out).then_some(out),
+                Inst::Jump(target) => {
+                    pc = *target;
+                    continue;
+                }
+                Inst::Split(first, second) => {
+                    pc = if chars >= max_chars || below(2) == 0 {
+                        *second
+                    } else {
+                        *first
+                    };
+                    continue;
+                }
+                Inst::Start if chars > 0 => return None,
+                Inst::End => {
+                    ended = true;
+                    pc += 1;
+                    continue;
+                }
+                Inst::Start | Inst::WordBoundary(_) => {
+                    pc += 1;
+                    continue;
+                }
+                Inst::Char(c) => *c,
+                Inst::Any => loop {
+                    let c = printable(below);
+                    if !is_line_terminator(c) {
+                        break c;
+                    }
+                },
+                Inst::Class(class) => class.sample(below)?,
+            };
+            if ended {
+                return None;
+            }
+            out.push(c);
+            chars += 1;
+            pc += 1;
+        }
+        None
+    }
+
+    /// Adds the thread at `pc` to `threads`, following jumps, splits and
+    /// assertions. Returns whether it reaches `Match`.
+    fn follow(&self, threads: &mut Threads, pc: usize, chars: &[char], pos: usize) -> bool {
+        let mut stack = vec![pc];
+        while let Some(pc) = stack.pop() {
+            if !threads.visit(pc) {
+                continue;
+            }
+            match &self.program[pc] {
+                Inst::Match => return true,
+                Inst::Jump(target) => stack.push(*target),
+                Inst::Split(first, second) => {
+                    stack.push(*second);
+                    stack.push(*first);
+                }
+                Inst::Start => {
+                    if pos == 0 {
+                        stack.push(pc + 1);
+                    }
+                }
+                Inst::End => {
+                    if pos == chars.len() {
+                        stack.push(pc + 1);
+                    }
+                }
+                Inst::WordBoundary(negated) => {
+                    let before = pos > 0 && is_word(chars[pos - 1]);
+                    let after = chars.get(pos).is_some_and(|&c| is_word(c));
+                    if (before != after) != *negated {
+                        stack.push(pc + 1);
+                    }
+                }
+                Inst::Char(_) | Inst::Any | Inst::Class(_) => threads.list.push(pc),
+            }
+        }
+        false
+    }
+}
+
+/// The threads alive at one input position, as a sparse set of program
+/// counters.
+struct Threads {
+    list: Vec<usize>,
+    seen: Vec<bool>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Self {
+        Threads {
+            list: Vec::new(),
+            seen: vec![false; len],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        self.seen.fill(false);
+    }
+
+    /// Marks `pc` as visited, returning `false` if it already was.
+    fn visit(&mut self, pc: usize) -> bool {
+        !std::mem::replace(&mut self.seen[pc], true)
+    }
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// Groups open at `pos`.
+    depth: usize,
+}
+
+/// What a backslash escape stands for.
+enum Escape {
+    Char(char),
+    Class(ClassItem),
+    WordBoundary(bool),
+}
+
+impl Parser {
+    fn error(&self, message: &'static str) -> PatternError {
+        PatternError {
+            position: self.pos,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn alternation(&mut self) -> Result<Node, PatternError> {
+        let mut branches = vec![self.concatenation()?];
+        while self.eat('|') {
+            branches.push(self.concatenation()?);
+        }
+        Ok(if branches.len() == 1 {
+            branches.pop().expect("one branch")
+        } else {
+            Node::Alt(branches)
+        })
+    }
+
+    fn concatenation(&mut self) -> Result<Node, PatternError> {
+        let mut nodes = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+        Ok(Node::Concat(nodes))
+    }
+
+    fn atom(&mut self) -> Result<Node, PatternError> {
+        let c = self.peek().expect("caller checked for input");
+        self.pos += 1;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '[' => Node::Class(self.class()?),
+            '(' => {
+                if self.eat('?') && !self.eat(':') {
+                    return Err(self.error("lookaround and named groups are not supported"));
+                }
+                if self.depth == MAX_GROUP_DEPTH {
+                    return Err(self.error("groups are nested too deeply"));
+                }
+                self.depth += 1;
+                let inner = self.alternation()?;
+                self.depth -= 1;
+                if !self.eat(')') {
+                    return Err(self.error("missing ')'"));
+                }
+                inner
+            }
+            '*' | '+' | '?' => return Err(self.error("nothing to repeat")),
+            '{' => {
+                let start = self.pos;
+                if self.counted().is_some() {
+                    return Err(self.error("nothing to repeat"));
+                }
+                self.pos = start;
+                Node::Char('{')
+            }
+            '\\' => match self.escape(false)? {
+                Escape::Char(c) => Node::Char(c),
+                Escape::Class(item) => Node::Class(Class {
+                    items: vec![item],
+                    negated: false,
+                }),
+                Escape::WordBoundary(negated) => Node::WordBoundary(negated),
+            },
+            c => Node::Char(c),
+        })
+    }
+
+    fn quantified(&mut self, node: Node) -> Result<Node, PatternError> {
+        let start = self.pos;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.pos += 1;
+                match self.counted() {
+                    Some(bounds) => bounds,
+                    // Not a quantifier, so a literal '{' (ECMA-262 Annex B).
+                    None => {
+                        self.pos = start;
+                        return Ok(node);
+                    }
+                }
+            }
+            _ => return Ok(node),
+        };
+        if self.pos == start {
+            self.pos += 1;
+        }
+        if matches!(node, Node::Start | Node::End | Node::WordBoundary(_)) {
+            return Err(self.error("nothing to repeat"));
+        }
+        if max.is_some_and(|max| max < min) {
+            return Err(self.error("numbers out of order in quantifier"));
+        }
+        // Laziness changes which match is found, not whether one is.
+        self.eat('?');
+        Ok(Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+        })
+    }
+
+    /// Parses the rest of `{n}`, `{n,}` or `{n,m}` after the `{`, leaving
+    /// the position after the `}`; `None` if it is not one of those, with
+    /// the position unspecified.
+    fn counted(&mut self) -> Option<(u32, Option<u32>)> {
+        let min = self.number()?;
+        let max = if self.eat(',') {
+            if self.peek() == Some('}') {
+                None
+            } else {
+                Some(self.number()?)
+            }
+        } else {
+            Some(min)
+        };
+        self.eat('}').then_some((min, max))
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        digits.parse().ok()
+    }
+
+    /// Parses a class after its `[`.
+    fn class(&mut self) -> Result<Class, PatternError> {
+        let negated = self.eat('^');
+        let mut items = Vec::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("missing ']'"));
+            };
+            self.pos += 1;
+            if c == ']' {
+                return Ok(Class { items, negated });
+            }
+            let lo = match c {
+                '\\' => match self.escape(true)? {
+                    Escape::Char(c) => c,
+                    Escape::Class(item) => {
+                        items.push(item);
+                        continue;
+                    }
+                    Escape::WordBoundary(_) => unreachable!("\\b is a backspace in classes"),
+                },
+                c => c,
+            };
+            if self.peek() != Some('-') || self.chars.get(self.pos + 1) == Some(&']') {
+                items.push(ClassItem::Range(lo, lo));
+                continue;
+            }
+            self.pos += 1;
+            let hi = match self.peek() {
+                None => return Err(self.error("missing ']'")),
+                Some('\\') => {
+                    self.pos += 1;
+                    match self.escape(true)? {
+                        Escape::Char(c) => c,
+                        // `[a-\d]`: the '-' is literal (ECMA-262 Annex B).
+                        Escape::Class(item) => {
+                            items.extend([
+                                ClassItem::Range(lo, lo),
+                                ClassItem::Range('-', '-'),
+                                item,
+                            ]);
+                            continue;
+                        }
+                        Escape::WordBoundary(_) => unreachable!("\\b is a backspace in classes"),
+                    }
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    c
+                }
+            };
+            if hi < lo {
+                return Err(self.error("range out of order in class"));
+            }
+            items.push(ClassItem::Range(lo, hi));
+        }
+    }
+
+    /// Parses an escape after its backslash.
+    fn escape(&mut self, in_class: bool) -> Result<Escape, PatternError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error("trailing backslash"));
+        };
+        self.pos += 1;
+        Ok(match c {
+            'd' | 'D' => Escape::Class(ClassItem::Digit(c == 'D')),
+            'w' | 'W' => Escape::Class(ClassItem::Word(c == 'W')),
+            's' | 'S' => Escape::Class(ClassItem::Space(c == 'S')),
+            'b' if in_class => Escape::Char('\u{8}'),
+            'b' | 'B' => Escape::WordBoundary(c == 'B'),
+            'n' => Escape::Char('\n'),
+            'r' => Escape::Char('\r'),
+            't' => Escape::Char('\t'),
+            'f' => Escape::Char('\u{c}'),
+            'v' => Escape::Char('\u{b}'),
+            '0' if !self.peek().is_some_and(|c| c.is_ascii_digit()) => Escape::Char('\0'),
+            'x' => Escape::Char(self.hex(2)?),
+            'u' => Escape::Char(self.hex(4)?),
+            '1'..='9' => return Err(self.error("backreferences are not supported")),
+            c if c.is_ascii_alphanumeric() => return Err(self.error("unknown escape")),
+            c => Escape::Char(c),
+        })
+    }
+
+    fn hex(&mut self, digits: usize) -> Result<char, PatternError> {
+        let end = self.pos + digits;
+        let code = self
+            .chars
+            .get(self.pos..end)
+            .filter(|hex| hex.iter().all(char::is_ascii_hexdigit))
+            .and_then(|hex| u32::from_str_radix(&hex.iter().collect::<String>(), 16).ok())
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error("invalid hexadecimal escape"))?;
+        self.pos = end;
+        Ok(code)
+    }
+}
+
+fn too_large() -> PatternError {
+    PatternError {
+        position: 0,
+        message: "pattern is too large",
+    }
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+    /// Nodes compiled so far, for [`MAX_COMPILE_STEPS`].
+    steps: usize,
+}
+
+impl Compiler {
+    fn push(&mut self, inst: Inst) -> Result<usize, PatternError> {
+        if self.program.len() >= MAX_PROGRAM {
+            return Err(too_large());
+        }
+        self.program.push(inst);
+        Ok(self.program.len() - 1)
+    }
+
+    fn node(&mut self, node: &Node) -> Result<(), PatternError> {
+        self.steps += 1;
+        if self.steps > MAX_COMPILE_STEPS {
+            return Err(too_large());
+        }
+        match node {
+            Node::Char(c) => self.push(Inst::Char(*c)).map(drop),
+            Node::Any => self.push(Inst::Any).map(drop),
+            Node::Class(class) => self.push(Inst::Class(class.clone())).map(drop),
+            Node::Start => self.push(Inst::Start).map(drop),
+            Node::End => self.push(Inst::End).map(drop),
+            Node::WordBoundary(negated) => self.push(Inst::WordBoundary(*negated)).map(drop),
+            Node::Concat(nodes) => nodes.iter().try_for_each(|node| self.node(node)),
+            Node::Alt(branches) => {
+                let mut jumps = Vec::new();
+                for (i, branch) in branches.iter().enumerate() {
+                    if i + 1 == branches.len() {
+                        self.node(branch)?;
+                    } else {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.node(branch)?;
+                        jumps.push(self.push(Inst::Jump(0))?);
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    }
+                }
+                let end = self.program.len();
+                for jump in jumps {
+                    self.program[jump] = Inst::Jump(end);
+                }
+                Ok(())
+            }
+            Node::Repeat { node, min, max } => {
+                for _ in 0..*min {
+                    self.node(node)?;
+                }
+                match max {
+                    None => {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.node(node)?;
+                        self.push(Inst::Jump(split))?;
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    }
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.push(Inst::Split(0, 0))?);
+                            self.node(node)?;
+                        }
+                        let end = self.program.len();
+                        for split in splits {
+                            self.program[split] = Inst::Split(split + 1, end);
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Pattern::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn test_search_is_unanchored() {
+        assert!(matches("b", "abc"));
+        assert!(!matches("^b", "abc"));
+        assert!(matches("^a.c$", "abc"));
+        assert!(!matches("^a.c$", "abcd"));
+        assert!(matches("", "anything"));
+    }
+
+    #[test]
+    fn test_classes_and_escapes() {
+        assert!(matches(r"^[a-z_][a-z0-9_]*$", "user_id2"));
+        assert!(!matches(r"^[a-z_][a-z0-9_]*$", "2user"));
+        assert!(matches(r"^[^,]+$", "abc"));
+        assert!(!matches(r"^[^,]+$", "a,c"));
+        assert!(matches(r"^\d{3}-\d{4}$", "555-1234"));
+        assert!(matches(r"^\w+\s\W$", "hi !"));
+        assert!(matches(r"^[\d.-]+$", "1.5-2"));
+        assert!(matches(r"^é\x41\.$", "éA."));
+        assert!(matches(r"\bcat\b", "a cat sat"));
+        assert!(!matches(r"\bcat\b", "concatenate"));
+    }
+
+    #[test]
+    fn test_groups_alternation_and_quantifiers() {
+        assert!(matches("^(ab|cd)+$", "abcdab"));
+        assert!(!matches("^(ab|cd)+$", "abc"));
+        assert!(matches("^(?:x|y)?z$", "z"));
+        assert!(matches("^a{2,3}$", "aaa"));
+        assert!(!matches("^a{2,3}$", "aaaa"));
+        assert!(matches("^a{2,}$", "aaaaa"));
+        assert!(matches("^a*?b$", "aab"));
+        assert!(matches("^a{,2}$", "a{,2}"));
+        assert!(matches("^(a*)*$", &"a".repeat(10_000)));
+        assert!(!matches("^(a*)*$", &format!("{}b", "a".repeat(10_000))));
+    }
+
+    #[test]
+    fn test_samples_match() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut below = |n: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n as u64) as usize
+        };
+        for pattern in [
+            r"^[a-z]{3,8}-\d+$",
+            r"^(foo|bar)*baz\b",
+            r"^[^a-z]\W\s.$",
+            r"x?y+",
+        ] {
+            let compiled = Pattern::new(pattern).unwrap();
+            let mut produced = 0;
+            for _ in 0..50 {
+                if let Some(sample) = compiled.sample(&mut below, 20) {
+                    assert!(compiled.is_match(&sample), "{pattern} {sample:?}");
+                    produced += 1;
+                }
+            }
+            assert!(produced > 0, "{pattern}");
+        }
+        let impossible = Pattern::new("a^").unwrap();
+        assert_eq!(impossible.sample(&mut below, 20), None);
+    }
+
+    #[test]
+    fn test_rejected_patterns() {
+        for pattern in [
+            "(", "a)", "[a", "*a", "a**", r"\1", "(?=a)", r"\q", "[z-a]", "a{3,1}", r"\",
+        ] {
+            assert!(Pattern::new(pattern).is_err(), "{pattern}");
+        }
+        assert!(Pattern::new("(a{1000}){1000}").is_err());
+        assert!(Pattern::new("((?:){100000}){100000}").is_err());
+        assert!(Pattern::new("(?:){4294967295}").is_err());
+        assert!(Pattern::new("(a{1000}){9}").is_ok());
+
+        let nested = |depth| "(".repeat(depth) + "a" + &")".repeat(depth);
+        assert!(Pattern::new(&nested(MAX_GROUP_DEPTH)).is_ok());
+        assert_eq!(
+            Pattern::new(&nested(MAX_GROUP_DEPTH + 1)).unwrap_err(),
+            PatternError {
+                position: MAX_GROUP_DEPTH + 1,
+                message: "groups are nested too deeply",
+            }
+        );
+        assert!(Pattern::new(&"(".repeat(100_000)).is_err());
+    }
+}
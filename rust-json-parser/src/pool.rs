@@ -0,0 +1,102 @@
+//! Containers kept from values handed back to a parser, for the values it
+//! builds next.
+
+use crate::node::Node;
+use crate::object::Members;
+use crate::{JsonArray, JsonObject, JsonValue};
+
+/// Emptied array and object allocations, reused by the next values parsed.
+///
+/// Strings are not kept: short ones are stored inline and need none, and
+/// a long one's allocation is sized to its text.
+#[derive(Debug, Default)]
+pub(crate) struct Pool {
+    arrays: Vec<Node<Vec<JsonValue>>>,
+    objects: Vec<Node<Members>>,
+}
+
+impl Pool {
+    /// Most allocations of each kind kept; the rest are freed.
+    const MAX_SPARE: usize = 4096;
+
+    /// An empty array, in a recycled allocation if there is one.
+    pub(crate) fn array(&mut self) -> JsonArray {
+        self.arrays
+            .pop()
+            .map_or_else(JsonArray::new, JsonArray::recycled)
+    }
+
+    /// An empty object, in a recycled allocation if there is one.
+    pub(crate) fn object(&mut self) -> JsonObject {
+        self.objects
+            .pop()
+            .map_or_else(JsonObject::new, JsonObject::recycled)
+    }
+
+    /// Takes `value` apart, with an explicit stack like
+    /// [`drop_iteratively`](crate::array::drop_iteratively), keeping the
+    /// allocations of its containers. Contents another clone still shares
+    /// are left to it.
+    pub(crate) fn recycle(&mut self, value: JsonValue) {
+        let mut stack = vec![value];
+        while let Some(value) = stack.pop() {
+            match value {
+                JsonValue::Array(mut array) => {
+                    if let Some(items) = array.recycle_into(&mut stack)
+                        && self.arrays.len() < Self::MAX_SPARE
+                    {
+                        self.arrays.push(items);
+                    }
+                }
+                JsonValue::Object(mut object) => {
+                    if let Some(members) = object.recycle_into(&mut stack)
+                        && self.objects.len() < Self::MAX_SPARE
+                    {
+                        self.objects.push(members);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonParser;
+
+    #[test]
+    fn test_next_parse_reuses_recycled_containers() {
+        let mut parser = JsonParser::new(r#"{"ids": [1, 2, 3], "tags": {"a": 1}}"#).unwrap();
+        let value = parser.parse().unwrap();
+        let ids = value.get("ids").unwrap().as_array().unwrap().as_ptr();
+        parser.recycle(value);
+
+        parser.reset(r#"[{"x": null}, [true]]"#).unwrap();
+        let value = parser.parse().unwrap();
+        assert_eq!(value.to_string(), r#"[{"x":null},[true]]"#);
+        assert_eq!(value.as_array().unwrap().as_ptr(), ids);
+    }
+
+    #[test]
+    fn test_recycling_leaves_clones_intact() {
+        let mut parser = JsonParser::new(r#"{"a": [1, {"b": [2]}]}"#).unwrap();
+        let value = parser.parse().unwrap();
+        let copy = value.clone();
+        parser.recycle(value);
+        assert_eq!(copy.to_string(), r#"{"a":[1,{"b":[2]}]}"#);
+    }
+
+    #[test]
+    fn test_recycling_deep_values() {
+        let mut deep = JsonValue::Null;
+        for _ in 0..100_000 {
+            deep = JsonValue::Array(vec![deep].into());
+        }
+        let mut pool = Pool::default();
+        pool.recycle(deep);
+        assert_eq!(pool.arrays.len(), Pool::MAX_SPARE);
+        assert!(pool.array().is_empty());
+    }
+}
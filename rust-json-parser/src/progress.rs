@@ -0,0 +1,115 @@
+use crate::parser::check_input_size;
+use crate::{JsonParser, JsonValue, ParserOptions, Result, Tokenizer};
+
+/// Parses `input` like [`JsonParser::with_options`], calling
+/// `progress(bytes_processed, total)` each time another `every` bytes of the
+/// input have been read, for driving a progress bar over a large document.
+///
+/// `total` is `input.len()`. Once the value is built, `progress` is called a
+/// last time with `bytes_processed == total`, so a bar always ends full on
+/// success. On error no further calls are made. An `every` of 0 is treated
+/// as 1.
+///
+/// The steps track tokenizing only. The input is decoded into characters
+/// before the first call, and the value is built from the tokens after the
+/// last step and before the final call, so on a large document the bar
+/// waits at its last step while those run.
+///
+/// ```
+/// use rust_json_parser::{ParserOptions, parse_with_progress};
+///
+/// let input = format!("[{}]", vec!["1"; 1000].join(","));
+/// let mut calls = Vec::new();
+/// parse_with_progress(&input, &ParserOptions::default(), 1000, |done, total| {
+///     calls.push(done * 100 / total);
+/// })
+/// .unwrap();
+/// assert_eq!(calls, [49, 99, 100]);
+/// ```
+pub fn parse_with_progress<F>(
+    input: &str,
+    options: &ParserOptions,
+    every: usize,
+    mut progress: F,
+) -> Result<JsonValue>
+where
+    F: FnMut(usize, usize),
+{
+    check_input_size(input, options)?;
+    let every = every.max(1);
+    let total = input.len();
+    let mut tokenizer = Tokenizer::with_options(input, options.clone());
+    let mut tokens = Vec::new();
+    // Bytes and characters read up to the end of the last token
+    let (mut bytes, mut chars) = (0, 0);
+    let mut next_report = every;
+    let mut reported = None;
+    while let Some((token, span)) = tokenizer.next_token()? {
+        bytes += tokenizer
+            .text(chars..span.end)
+            .map(char::len_utf8)
+            .sum::<usize>();
+        chars = span.end;
+        tokens.push((token, span));
+        if bytes >= next_report {
+            progress(bytes, total);
+            reported = Some(bytes);
+            next_report = (bytes / every + 1) * every;
+        }
+    }
+
+    let value =
+        JsonParser::from_tokens(input, tokens, tokenizer.clock(), options.clone()).parse()?;
+    if reported != Some(total) {
+        progress(total, total);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonError, Limits};
+
+    fn reports(input: &str, every: usize) -> Result<Vec<(usize, usize)>> {
+        let mut calls = Vec::new();
+        parse_with_progress(input, &ParserOptions::default(), every, |done, total| {
+            calls.push((done, total))
+        })?;
+        Ok(calls)
+    }
+
+    #[test]
+    fn test_reports_in_steps_and_at_the_end() {
+        let input = r#"["aaaa", "bbbb", "cccc"]  "#;
+        assert_eq!(
+            reports(input, 8).unwrap(),
+            [(8, 26), (16, 26), (24, 26), (26, 26)]
+        );
+        assert_eq!(reports(input, 100).unwrap(), [(26, 26)]);
+        assert_eq!(reports("[1]", 0).unwrap(), [(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn test_counts_bytes_not_characters() {
+        assert_eq!(reports(r#"["é"]"#, 4).unwrap(), [(5, 6), (6, 6)]);
+    }
+
+    #[test]
+    fn test_stops_on_error() {
+        let mut calls = Vec::new();
+        let err = parse_with_progress("[1, 2, @]", &ParserOptions::default(), 1, |done, _| {
+            calls.push(done);
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            JsonError::UnexpectedToken { position: 7, .. }
+        ));
+        assert_eq!(calls, [1, 2, 3, 5, 6]);
+
+        let options = ParserOptions::new().limits(Limits::new().max_input_bytes(2));
+        let result = parse_with_progress("[1]", &options, 1, |_, _| panic!("not reached"));
+        assert!(matches!(result, Err(JsonError::InputTooLarge { .. })));
+    }
+}
@@ -1,9 +1,8 @@
-use crate::{JsonError, JsonParser, JsonValue};
+use crate::{FormatConfig, JsonArray, JsonError, JsonObject, JsonParser, JsonValue};
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::{PyBool, PyDict, PyList};
 use pyo3::{Bound, IntoPyObject, PyAny, PyErr};
-use std::collections::HashMap;
 
 impl<'py> IntoPyObject<'py> for JsonValue {
     type Target = PyAny;
@@ -107,27 +106,43 @@ pub fn py_to_json_value(obj: &Bound<PyAny>) -> PyResult<JsonValue> {
 }
 
 #[pyfunction]
-#[pyo3(signature = (obj, indent=None))]
-pub fn dumps(obj: Bound<PyAny>, indent: Option<usize>) -> PyResult<String> {
+#[pyo3(signature = (obj, indent=None, max_output_bytes=None, ensure_ascii=false))]
+pub fn dumps(
+    obj: Bound<PyAny>,
+    indent: Option<usize>,
+    max_output_bytes: Option<usize>,
+    ensure_ascii: bool,
+) -> PyResult<String> {
     // 1. Convert Python object to our Rust JsonValue enum
     let json_value = py_to_json_value(&obj)?;
 
-    // 2. Format based on indentation
-    match indent {
-        // Compact mode: use our Display implementation
-        None => Ok(json_value.to_string()),
+    // 2. Pick the layout: compact, or pretty-printed when an indent is given
+    let config = match indent {
+        None => FormatConfig::new(),
+        Some(n) => FormatConfig::pretty(n),
+    }
+    .ensure_ascii(ensure_ascii);
 
-        // Pretty-print mode: use a recursive formatter
-        Some(n) => Ok(json_value.pretty_print(n)),
+    // 3. Serialize, enforcing the output budget if one was given
+    match max_output_bytes {
+        None => Ok(json_value.try_serialize_with(&config)?),
+        Some(limit) => Ok(json_value.serialize_with_limited(&config, limit)?),
     }
 }
 
+/// Names of the optional Rust features compiled into this extension
+#[pyfunction]
+pub fn features() -> Vec<&'static str> {
+    crate::features::enabled().to_vec()
+}
+
 // Module registration
 #[pymodule]
 fn _rust_json_parser(m: &Bound<PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(parse_json, m)?)?;
     m.add_function(wrap_pyfunction!(parse_json_file, m)?)?;
     m.add_function(wrap_pyfunction!(dumps, m)?)?;
+    m.add_function(wrap_pyfunction!(features, m)?)?;
     m.add("__version__", "0.1.0")?;
     Ok(())
 }
@@ -148,12 +163,14 @@ fn try_number(obj: &Bound<PyAny>) -> Option<JsonValue> {
 }
 
 fn try_string(obj: &Bound<PyAny>) -> Option<JsonValue> {
-    obj.extract::<String>().ok().map(JsonValue::String)
+    obj.extract::<String>()
+        .ok()
+        .map(|s| JsonValue::String(s.into()))
 }
 
 fn try_array(obj: &Bound<PyAny>) -> PyResult<Option<JsonValue>> {
     if let Ok(list) = obj.cast::<PyList>() {
-        let mut arr = Vec::with_capacity(list.len());
+        let mut arr = JsonArray::with_capacity(list.len());
         for item in list.iter() {
             arr.push(py_to_json_value(&item)?);
         }
@@ -165,7 +182,7 @@ fn try_array(obj: &Bound<PyAny>) -> PyResult<Option<JsonValue>> {
 
 fn try_object(obj: &Bound<PyAny>) -> PyResult<Option<JsonValue>> {
     if let Ok(dict) = obj.cast::<PyDict>() {
-        let mut map = HashMap::with_capacity(dict.len());
+        let mut map = JsonObject::with_capacity(dict.len());
         for (key, val) in dict.iter() {
             let key_str = key
                 .extract::<String>()
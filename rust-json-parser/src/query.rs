@@ -0,0 +1,295 @@
+use crate::path::{parse_index, pointer_tokens};
+use crate::{JsonError, JsonPath, JsonValue, PathSegment, Result};
+
+/// A pointer or pointer pattern parsed once, to run against any number of
+/// documents without parsing it again.
+///
+/// Takes the syntax of [`JsonValue::pointer_matches`]: a JSON Pointer whose
+/// `*` tokens match any one key or index and whose `**` tokens match any
+/// number of levels. Without wildcards it resolves like
+/// [`JsonValue::pointer`].
+///
+/// ```
+/// use rust_json_parser::{CompiledPath, JsonParser};
+///
+/// let emails = CompiledPath::new("/users/*/email")?;
+/// for record in [r#"{"users": [{"email": "a@x"}]}"#, r#"{"users": []}"#] {
+///     let record = JsonParser::new(record)?.parse()?;
+///     for (path, email) in emails.matches(&record) {
+///         assert_eq!(path.to_pointer(), "/users/0/email");
+///         assert_eq!(email.as_str(), Some("a@x"));
+///     }
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledPath {
+    source: String,
+    tokens: Vec<Token>,
+    has_wildcards: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A member name, and the element it names when it is also an index.
+    Key { key: String, index: Option<usize> },
+    /// `*`: any one key or index.
+    Any,
+    /// `**`: any number of levels.
+    Deep,
+}
+
+impl Token {
+    /// Whether stepping into the child at `segment` consumes this token.
+    fn accepts(&self, segment: &PathSegment) -> bool {
+        match (self, segment) {
+            (Token::Any | Token::Deep, _) => true,
+            (Token::Key { key, .. }, PathSegment::Key(k)) => key == k,
+            (Token::Key { index, .. }, PathSegment::Index(i)) => *index == Some(*i),
+        }
+    }
+}
+
+impl CompiledPath {
+    /// Parses `pattern`, failing with [`JsonError::InvalidPointer`] unless
+    /// it is empty or starts with `/`.
+    pub fn new(pattern: &str) -> Result<Self> {
+        let tokens: Vec<Token> = pointer_tokens(pattern)
+            .ok_or_else(|| JsonError::InvalidPointer {
+                pointer: pattern.to_string(),
+            })?
+            .into_iter()
+            .map(|token| match token.as_str() {
+                "*" => Token::Any,
+                "**" => Token::Deep,
+                _ => Token::Key {
+                    index: parse_index(&token),
+                    key: token,
+                },
+            })
+            .collect();
+        let has_wildcards = tokens
+            .iter()
+            .any(|token| !matches!(token, Token::Key { .. }));
+        Ok(Self {
+            source: pattern.to_string(),
+            tokens,
+            has_wildcards,
+        })
+    }
+
+    /// The pattern as written.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the pattern has `*` or `**` tokens, and so may match more
+    /// than one value.
+    pub fn has_wildcards(&self) -> bool {
+        self.has_wildcards
+    }
+
+    /// Every value in `value` the pattern matches, with its location, in
+    /// document order.
+    pub fn matches<'a>(&self, value: &'a JsonValue) -> Vec<(JsonPath, &'a JsonValue)> {
+        let mut out = Vec::new();
+        self.walk(value, &self.start(), &mut JsonPath::new(), &mut out, false);
+        out
+    }
+
+    /// The first value the pattern matches, in document order.
+    pub fn get<'a>(&self, value: &'a JsonValue) -> Option<&'a JsonValue> {
+        if !self.has_wildcards {
+            return self.tokens.iter().try_fold(value, |node, token| {
+                let Token::Key { key, index } = token else {
+                    unreachable!("wildcard in a plain pointer")
+                };
+                match node {
+                    JsonValue::Object(obj) => obj.get(key),
+                    JsonValue::Array(arr) => arr.get((*index)?),
+                    _ => None,
+                }
+            });
+        }
+        let mut out = Vec::new();
+        self.walk(value, &self.start(), &mut JsonPath::new(), &mut out, true);
+        out.pop().map(|(_, found)| found)
+    }
+
+    /// Mutable counterpart of [`CompiledPath::get`].
+    pub fn get_mut<'a>(&self, value: &'a mut JsonValue) -> Option<&'a mut JsonValue> {
+        if !self.has_wildcards {
+            return self.tokens.iter().try_fold(value, |node, token| {
+                let Token::Key { key, index } = token else {
+                    unreachable!("wildcard in a plain pointer")
+                };
+                match node {
+                    JsonValue::Object(obj) => obj.get_mut(key),
+                    JsonValue::Array(arr) => arr.get_mut((*index)?),
+                    _ => None,
+                }
+            });
+        }
+        let mut out = Vec::new();
+        self.walk(value, &self.start(), &mut JsonPath::new(), &mut out, true);
+        let (path, _) = out.pop()?;
+        path.segments()
+            .iter()
+            .try_fold(value, |node, segment| match (node, segment) {
+                (JsonValue::Object(obj), PathSegment::Key(key)) => obj.get_mut(key),
+                (JsonValue::Array(arr), PathSegment::Index(index)) => arr.get_mut(*index),
+                _ => None,
+            })
+    }
+
+    /// The states before any token is consumed.
+    fn start(&self) -> Vec<usize> {
+        self.closure(vec![0])
+    }
+
+    /// Adds the states reachable by letting each `**` match zero levels,
+    /// and drops duplicates. A state is the number of tokens consumed.
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < states.len() {
+            if self.tokens.get(states[i]) == Some(&Token::Deep) {
+                states.push(states[i] + 1);
+            }
+            i += 1;
+        }
+        states.sort_unstable();
+        states.dedup();
+        states
+    }
+
+    /// The states after stepping from `states` into the child at `segment`.
+    fn step(&self, states: &[usize], segment: &PathSegment) -> Vec<usize> {
+        let next = states
+            .iter()
+            .filter_map(|&state| {
+                let token = self.tokens.get(state)?;
+                if !token.accepts(segment) {
+                    None
+                } else if *token == Token::Deep {
+                    Some(state)
+                } else {
+                    Some(state + 1)
+                }
+            })
+            .collect();
+        self.closure(next)
+    }
+
+    /// Walks `value` at `path` in `states`, collecting matches; returns
+    /// `true` once `first` is set and a match has been found.
+    fn walk<'a>(
+        &self,
+        value: &'a JsonValue,
+        states: &[usize],
+        path: &mut JsonPath,
+        out: &mut Vec<(JsonPath, &'a JsonValue)>,
+        first: bool,
+    ) -> bool {
+        if states.contains(&self.tokens.len()) {
+            out.push((path.clone(), value));
+            if first {
+                return true;
+            }
+        }
+        let mut visit = |segment: PathSegment, item: &'a JsonValue| {
+            let next = self.step(states, &segment);
+            if next.is_empty() {
+                return false;
+            }
+            path.push(segment);
+            let done = self.walk(item, &next, path, out, first);
+            path.pop();
+            done
+        };
+        match value {
+            JsonValue::Array(arr) => arr
+                .iter()
+                .enumerate()
+                .any(|(index, item)| visit(PathSegment::Index(index), item)),
+            JsonValue::Object(obj) => obj
+                .iter()
+                .any(|(key, item)| visit(PathSegment::Key(key.to_string()), item)),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_compiled_path_runs_on_many_documents() {
+        let path = CompiledPath::new("/items/*/id").unwrap();
+        assert!(path.has_wildcards());
+        assert_eq!(path.as_str(), "/items/*/id");
+        let documents = [
+            parse_json(r#"{"items": [{"id": 1}, {"id": 2}]}"#).unwrap(),
+            parse_json(r#"{"items": {"a": {"id": 3}, "b": {}}}"#).unwrap(),
+            parse_json(r#"{"items": 5}"#).unwrap(),
+        ];
+        let found: Vec<Vec<String>> = documents
+            .iter()
+            .map(|doc| {
+                path.matches(doc)
+                    .into_iter()
+                    .map(|(at, value)| format!("{at}={value}"))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                vec!["/items/0/id=1", "/items/1/id=2"],
+                vec!["/items/a/id=3"],
+                vec![]
+            ]
+        );
+        assert_eq!(path.get(&documents[0]), Some(&JsonValue::Number(1.0)));
+        assert_eq!(path.get(&documents[2]), None);
+    }
+
+    #[test]
+    fn test_plain_pointer_resolves_like_pointer() {
+        let value = parse_json(r#"{"a": [{"b": 1}, 2], "": {"0": 3}}"#).unwrap();
+        for pointer in ["", "/a/0/b", "/a/1", "//0", "/a/01", "/a/2", "/x"] {
+            let path = CompiledPath::new(pointer).unwrap();
+            assert!(!path.has_wildcards());
+            assert_eq!(path.get(&value), value.pointer(pointer), "{pointer}");
+        }
+        assert!(matches!(
+            CompiledPath::new("a/b"),
+            Err(JsonError::InvalidPointer { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut value = parse_json(r#"{"a": [{"b": 1}, {"b": 2}]}"#).unwrap();
+        *CompiledPath::new("/a/1/b")
+            .unwrap()
+            .get_mut(&mut value)
+            .unwrap() = JsonValue::Null;
+        *CompiledPath::new("/**/b")
+            .unwrap()
+            .get_mut(&mut value)
+            .unwrap() = JsonValue::Null;
+        assert_eq!(
+            value,
+            parse_json(r#"{"a": [{"b": null}, {"b": null}]}"#).unwrap()
+        );
+        assert!(
+            CompiledPath::new("/a/5")
+                .unwrap()
+                .get_mut(&mut value)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use crate::error::ExpectedToken;
+use crate::parser::check_input_size;
+use crate::path::{OpenPath, parse_index, pointer_tokens};
+use crate::{
+    Document, JsonError, JsonParser, JsonPath, JsonValue, Limits, ParserOptions, PathSegment,
+    Result, Token, Tokenizer,
+};
+use std::ops::Range;
+
+/// Looks up the value at JSON Pointer `path` (`/users/0/name`) straight
+/// from JSON text, without parsing the rest of the document.
+///
+/// The text is decoded in full up front, but tokens are read only as far
+/// as the path needs: to the end of each object on it, as a key may repeat
+/// later on, and up to the element named in each array. Values passed
+/// over on the way are skipped without being built, and only checked for
+/// balanced brackets, so a document that is malformed elsewhere may still
+/// answer. When an object repeats a key, the last member with that key is
+/// used, as [`JsonParser::parse`] keeps by default. The value found fails
+/// with [`JsonError::DepthLimitExceeded`] if it nests deeper than 128.
+/// Returns `Ok(None)` when the path does not resolve, and
+/// [`JsonError::InvalidPointer`] when `path` is not a pointer.
+///
+/// ```
+/// use rust_json_parser::{JsonValue, get_raw};
+///
+/// let json = r#"{"meta": {"huge": [1, 2, 3]}, "users": [{"name": "Ada"}]}"#;
+/// assert_eq!(
+///     get_raw(json, "/users/0/name").unwrap(),
+///     Some(JsonValue::String("Ada".into()))
+/// );
+/// assert_eq!(get_raw(json, "/users/1").unwrap(), None);
+/// ```
+pub fn get_raw(json: &str, path: &str) -> Result<Option<JsonValue>> {
+    let tokens = pointer_tokens(path).ok_or_else(|| JsonError::InvalidPointer {
+        pointer: path.to_string(),
+    })?;
+    // The value found is built recursively
+    let options = ParserOptions::new().limits(Limits::new().capped());
+    check_input_size(json, &options)?;
+    let mut reader = RawReader::new(json, &options);
+    let found = reader
+        .find(&tokens)
+        .map_err(|err| err.under(&reader.path))?;
+    if !found {
+        return Ok(None);
+    }
+    let mut value = Vec::new();
+    reader
+        .skip_value(Some(&mut value))
+        .map_err(|err| err.under(&reader.path))?;
+    let clock = reader.tokenizer.clock();
+    JsonParser::from_tokens(json, value, clock, options)
+        .parse()
+        .map(Some)
+        .map_err(|err| err.under(&reader.path))
+}
+
+/// Sets the value at JSON Pointer `pointer` in JSON text, returning the
+/// edited text with every byte outside the changed region left as it was.
+///
+/// An existing value is overwritten. Otherwise the value is added: a
+/// missing object member is appended, and `/arr/-` or an index one past the
+/// end appends an element. The new value is written compactly. Fails with
+/// [`JsonError::InvalidPointer`] when the parent does not exist or the
+/// index is further out, and with the parse error when `json` is not valid
+/// or nests deeper than 128.
+/// See [`Document`] for several edits to the same text.
+///
+/// ```
+/// use rust_json_parser::{JsonValue, set_raw};
+///
+/// let json = "{\n  \"name\": \"svc\",   \"replicas\": 2\n}";
+/// let edited = set_raw(json, "/replicas", &JsonValue::Number(3.0)).unwrap();
+/// assert_eq!(edited, "{\n  \"name\": \"svc\",   \"replicas\": 3\n}");
+/// ```
+pub fn set_raw(json: &str, pointer: &str, value: &JsonValue) -> Result<String> {
+    let mut doc = Document::parse(json)?;
+    if doc.raw(pointer).is_some() {
+        doc.replace(pointer, value)?;
+    } else {
+        doc.insert(pointer, value)?;
+    }
+    Ok(doc.into_string())
+}
+
+/// A token stream with one token of lookahead.
+struct RawReader {
+    tokenizer: Tokenizer,
+    peeked: Option<(Token, Range<usize>)>,
+    /// Where the last token read ended, for end-of-input errors.
+    end: usize,
+    /// The containers [`RawReader::find`] has entered.
+    path: JsonPath,
+}
+
+impl RawReader {
+    fn new(json: &str, options: &ParserOptions) -> Self {
+        Self {
+            tokenizer: Tokenizer::with_options(json, options.clone()),
+            peeked: None,
+            end: 0,
+            path: JsonPath::new(),
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<(Token, Range<usize>)>> {
+        let next = match self.peeked.take() {
+            Some(token) => Some(token),
+            None => self.tokenizer.next_token()?,
+        };
+        if let Some((_, span)) = &next {
+            self.end = span.end;
+        }
+        Ok(next)
+    }
+
+    fn peek(&mut self) -> Result<Option<&Token>> {
+        if self.peeked.is_none() {
+            self.peeked = self.tokenizer.next_token()?;
+        }
+        Ok(self.peeked.as_ref().map(|(token, _)| token))
+    }
+
+    /// Reads a token that must be there, failing with `expected` otherwise.
+    fn expect(&mut self, expected: &[ExpectedToken]) -> Result<(Token, Range<usize>)> {
+        self.next()?.ok_or_else(|| JsonError::UnexpectedEndOfInput {
+            expected: expected.to_vec(),
+            position: self.end,
+            path: JsonPath::new(),
+        })
+    }
+
+    /// Moves past everything before the value at `tokens`, leaving it next.
+    /// Returns `false` when the path does not resolve.
+    fn find(&mut self, tokens: &[String]) -> Result<bool> {
+        for token in tokens {
+            match self.expect(&[ExpectedToken::Value])? {
+                (Token::LeftBrace, _) => {
+                    if !self.find_member(token)? {
+                        return Ok(false);
+                    }
+                    self.path.push_key(token.as_str());
+                }
+                (Token::LeftBracket, _) => {
+                    let Some(index) = parse_index(token) else {
+                        return Ok(false);
+                    };
+                    if !self.find_element(index)? {
+                        return Ok(false);
+                    }
+                    self.path.push_index(index);
+                }
+                (
+                    token @ (Token::RightBrace | Token::RightBracket | Token::Comma | Token::Colon),
+                    span,
+                ) => {
+                    return Err(unexpected(vec![ExpectedToken::Value], &token, &span));
+                }
+                // A scalar has no children
+                _ => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Moves past the members of the object just opened, then back to the
+    /// value of the last member named `key`.
+    fn find_member(&mut self, key: &str) -> Result<bool> {
+        if self.peek()? == Some(&Token::RightBrace) {
+            self.next()?;
+            return Ok(false);
+        }
+        // Just past the colon of the last member named `key` so far
+        let mut found = None;
+        loop {
+            let expected_key = [ExpectedToken::Key];
+            let member = match self.expect(&expected_key)? {
+                (Token::String(member), _) => member,
+                (token, span) => return Err(unexpected(expected_key.to_vec(), &token, &span)),
+            };
+            match self.expect(&[ExpectedToken::Colon])? {
+                (Token::Colon, _) => {}
+                (token, span) => return Err(unexpected(vec![ExpectedToken::Colon], &token, &span)),
+            }
+            if member == key {
+                found = Some(self.end);
+            }
+            self.skip_value(None)
+                .map_err(|err| err.inside(PathSegment::Key(member)))?;
+            if !self.separator(Token::RightBrace, ExpectedToken::RightBrace)? {
+                break;
+            }
+        }
+        let Some(position) = found else {
+            return Ok(false);
+        };
+        self.peeked = None;
+        self.tokenizer.seek(position);
+        self.end = position;
+        Ok(true)
+    }
+
+    /// Moves past the elements of the array just opened up to element
+    /// `index`.
+    fn find_element(&mut self, index: usize) -> Result<bool> {
+        if self.peek()? == Some(&Token::RightBracket) {
+            self.next()?;
+            return Ok(false);
+        }
+        for i in 0..index {
+            self.skip_value(None)
+                .map_err(|err| err.inside(PathSegment::Index(i)))?;
+            if !self.separator(Token::RightBracket, ExpectedToken::RightBracket)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Reads the `,` after a member or element; `false` at the `close` of
+    /// its container instead.
+    fn separator(&mut self, close: Token, expected_close: ExpectedToken) -> Result<bool> {
+        let expected = [ExpectedToken::Comma, expected_close];
+        match self.expect(&expected)? {
+            (Token::Comma, _) => Ok(true),
+            (token, _) if token == close => Ok(false),
+            (token, span) => Err(unexpected(expected.to_vec(), &token, &span)),
+        }
+    }
+
+    /// Moves past one value, checking only that its brackets balance, and
+    /// collects its tokens into `sink` if given.
+    fn skip_value(&mut self, mut sink: Option<&mut Vec<(Token, Range<usize>)>>) -> Result<()> {
+        let mut open = Vec::new();
+        let mut path = OpenPath::default();
+        loop {
+            let (token, span) = self
+                .expect(&[ExpectedToken::Value])
+                .map_err(|err| err.under(&path.path()))?;
+            match token {
+                Token::LeftBrace => open.push(Token::RightBrace),
+                Token::LeftBracket => open.push(Token::RightBracket),
+                Token::RightBrace | Token::RightBracket if open.last() == Some(&token) => {
+                    open.pop();
+                }
+                Token::RightBrace | Token::RightBracket => {
+                    let expected = match open.last() {
+                        Some(Token::RightBrace) => ExpectedToken::RightBrace,
+                        Some(_) => ExpectedToken::RightBracket,
+                        None => ExpectedToken::Value,
+                    };
+                    return Err(unexpected(vec![expected], &token, &span).under(&path.path()));
+                }
+                _ => {}
+            }
+            path.step(&token);
+            if let Some(sink) = sink.as_deref_mut() {
+                sink.push((token, span));
+            }
+            if open.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn unexpected(expected: Vec<ExpectedToken>, token: &Token, span: &Range<usize>) -> JsonError {
+    JsonError::UnexpectedToken {
+        expected,
+        found: format!("{token:?}"),
+        position: span.start,
+        path: JsonPath::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_set_replaces_or_adds() {
+        let json = "{\n    \"a\": [1,2],\n    \"b\": {\"c\": 1.50}\n}\n";
+        let one = JsonValue::Number(1.0);
+        assert_eq!(
+            set_raw(json, "/a/0", &JsonValue::Null).unwrap(),
+            "{\n    \"a\": [null,2],\n    \"b\": {\"c\": 1.50}\n}\n"
+        );
+        assert_eq!(
+            set_raw(json, "/a/2", &one).unwrap(),
+            "{\n    \"a\": [1,2,1],\n    \"b\": {\"c\": 1.50}\n}\n"
+        );
+        assert_eq!(
+            set_raw(json, "/d", &parse_json(r#"{"e": [true]}"#).unwrap()).unwrap(),
+            "{\n    \"a\": [1,2],\n    \"b\": {\"c\": 1.50},\n    \"d\": {\"e\":[true]}\n}\n"
+        );
+        assert_eq!(set_raw(json, "", &one).unwrap(), "1\n");
+        for pointer in ["/a/3", "/x/y", "/b/c/d", "c"] {
+            assert!(
+                matches!(
+                    set_raw(json, pointer, &one),
+                    Err(JsonError::InvalidPointer { .. })
+                ),
+                "{pointer}"
+            );
+        }
+        assert!(set_raw("{", "/a", &one).is_err());
+    }
+
+    #[test]
+    fn test_finds_values_by_pointer() {
+        let json =
+            r#"{"a": {"skip": [1, {"x": [2]}], "b": [10, [20, 21], {"c~/": "deep"}]}, "n": null}"#;
+        let value = parse_json(json).unwrap();
+        for path in [
+            "",
+            "/a",
+            "/a/b",
+            "/a/b/1/1",
+            "/a/b/2/c~0~1",
+            "/n",
+            "/a/skip/1/x",
+        ] {
+            assert_eq!(
+                get_raw(json, path).unwrap().as_ref(),
+                value.pointer(path),
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_missing_paths_are_none() {
+        let json = r#"{"a": [1, 2], "s": "text", "e": {}, "z": []}"#;
+        for path in [
+            "/b", "/a/2", "/a/x", "/a/01", "/s/0", "/e/k", "/z/0", "/a/0/k",
+        ] {
+            assert_eq!(get_raw(json, path).unwrap(), None, "{path}");
+        }
+        assert!(matches!(
+            get_raw(json, "a"),
+            Err(JsonError::InvalidPointer { .. })
+        ));
+    }
+
+    #[test]
+    fn test_stops_reading_after_the_value() {
+        let json = r#"[{"id": 7}, {"rest": [garbage"#;
+        assert_eq!(
+            get_raw(json, "/0/id").unwrap(),
+            Some(JsonValue::Number(7.0))
+        );
+        assert!(get_raw(json, "/1/rest").is_err());
+        // A key may repeat, so objects are read to the end
+        assert!(get_raw(r#"{"id": 7, "rest": [garbage"#, "/id").is_err());
+    }
+
+    #[test]
+    fn test_deep_values_are_refused() {
+        let depth = 100_000;
+        let deep = "[".repeat(depth) + &"]".repeat(depth);
+        let json = format!(r#"{{"deep": {deep}, "id": 7}}"#);
+        assert!(matches!(
+            get_raw(&json, "/deep"),
+            Err(JsonError::DepthLimitExceeded { limit: 128, .. })
+        ));
+        // Skipping is not limited
+        assert_eq!(get_raw(&json, "/id").unwrap(), Some(JsonValue::Number(7.0)));
+    }
+
+    #[test]
+    fn test_repeated_keys_resolve_to_the_last() {
+        let json = r#"{"a": {"b": 1}, "c": 0, "a": {"x": [2]}, "a": {"b": [3, 4]}}"#;
+        let value = parse_json(json).unwrap();
+        for path in ["/a", "/a/b", "/a/b/1", "/a/x"] {
+            assert_eq!(
+                get_raw(json, path).unwrap().as_ref(),
+                value.pointer(path),
+                "{path}"
+            );
+        }
+        assert_eq!(
+            get_raw(r#"{"a": 1, "a": 2}"#, "/a").unwrap(),
+            Some(JsonValue::Number(2.0))
+        );
+    }
+
+    #[test]
+    fn test_errors_on_the_way_carry_their_path() {
+        let input = r#"{"a": [1, {"b": ]}], "c": 3}"#;
+        let err = get_raw(input, "/c").unwrap_err();
+        assert_eq!(
+            err.path().map(|path| path.to_string()).as_deref(),
+            Some("/a/1/b")
+        );
+        assert!(matches!(
+            err,
+            JsonError::UnexpectedToken { position: 16, .. }
+        ));
+
+        let input = r#"{"a": [1, [2 3]]}"#;
+        let err = get_raw(input, "/a/1").unwrap_err();
+        assert_eq!(
+            err.path().map(|path| path.to_string()).as_deref(),
+            Some("/a/1")
+        );
+
+        let err = get_raw(r#"{"a" 1}"#, "/a").unwrap_err();
+        assert_eq!(
+            err,
+            JsonError::UnexpectedToken {
+                expected: vec![ExpectedToken::Colon],
+                found: "Number(1.0)".into(),
+                position: 5,
+                path: JsonPath::new(),
+            }
+        );
+        assert!(matches!(
+            get_raw(r#"{"a": [1, "#, "/a/2"),
+            Err(JsonError::UnexpectedEndOfInput { .. })
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{JsonError, JsonParser, JsonPath, JsonReport, JsonValue, ParserOptions, Result};
+
+/// What [`parse_with_recovery`] does about one malformed value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recovery {
+    /// Use this value in its place.
+    Substitute(JsonValue),
+    /// Leave the value out: drop the array element or object member. A
+    /// malformed top-level value becomes null.
+    Skip,
+    /// Stop and return the error.
+    Abort,
+}
+
+/// Parses `input`, letting `hook` decide what happens to each value that
+/// fails to parse instead of failing on the first.
+///
+/// A value is recoverable when its literal is malformed (`tru`, `"bad \q"`,
+/// `1.2.3`) or missing before a `,` or closing bracket. `hook` receives the
+/// error and the path of the value, once per incident in document order.
+/// Broken structure, such as a missing `,` or `:`, cannot be recovered
+/// from and is returned as the error, as are malformed keys and comments.
+///
+/// Returns the value with the decisions applied, and a report listing each
+/// recovered error.
+///
+/// ```
+/// use rust_json_parser::{JsonValue, ParserOptions, Recovery, parse_with_recovery};
+///
+/// let mut log = Vec::new();
+/// let (value, report) = parse_with_recovery(
+///     r#"{"id": 7, "score": 9..5}"#,
+///     &ParserOptions::default(),
+///     |err, path| {
+///         assert_eq!(err.path(), Some(path));
+///         log.push(err.to_string());
+///         Recovery::Substitute(JsonValue::Null)
+///     },
+/// )
+/// .unwrap();
+/// assert_eq!(value.to_string(), r#"{"id":7,"score":null}"#);
+/// assert_eq!(report.errors.len(), 1);
+/// assert!(log[0].starts_with("at /score: Invalid number"));
+/// ```
+pub fn parse_with_recovery<F>(
+    input: &str,
+    options: &ParserOptions,
+    mut hook: F,
+) -> Result<(JsonValue, JsonReport)>
+where
+    F: FnMut(&JsonError, &JsonPath) -> Recovery,
+{
+    let mut parser = JsonParser::recovering(input, options.clone())?;
+    let mut value = parser.parse()?;
+    let mut state = parser.recovery.take().expect("set by recovering");
+    // A malformed literal the parser never reached, such as a key
+    if let Some((_, error)) = state.malformed.pop_front() {
+        return Err(error);
+    }
+
+    let mut report = JsonReport::new();
+    let mut decisions = Vec::with_capacity(state.incidents.len());
+    for incident in state.incidents {
+        let decision = hook(&incident.error, &incident.path);
+        if decision == Recovery::Abort {
+            return Err(incident.error);
+        }
+        report.errors.push(incident.error);
+        decisions.push((incident.positions, decision));
+    }
+    // Last first, so removing an element does not move later incidents
+    for (positions, decision) in decisions.into_iter().rev() {
+        apply(&mut value, &positions, decision);
+    }
+    Ok((value, report))
+}
+
+/// Applies `decision` to the value reached by taking, in each container on
+/// the way, the member or element at the next of `positions`.
+fn apply(root: &mut JsonValue, positions: &[usize], decision: Recovery) {
+    match decision {
+        Recovery::Substitute(replacement) => {
+            if let Some(slot) = locate(root, positions) {
+                *slot = replacement;
+            }
+        }
+        Recovery::Skip => {
+            let Some((&last, parent)) = positions.split_last() else {
+                return;
+            };
+            match locate(root, parent) {
+                Some(JsonValue::Array(items)) => {
+                    items.remove(last);
+                }
+                Some(JsonValue::Object(map)) => {
+                    map.remove_at(last);
+                }
+                _ => {}
+            }
+        }
+        Recovery::Abort => unreachable!("aborts return early"),
+    }
+}
+
+fn locate<'a>(mut value: &'a mut JsonValue, positions: &[usize]) -> Option<&'a mut JsonValue> {
+    for &i in positions {
+        value = match value {
+            JsonValue::Array(items) => items.get_mut(i)?,
+            JsonValue::Object(map) => map.value_at_mut(i)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse_json;
+    use crate::{DuplicateKeys, JsonErrorKind};
+
+    fn recover(
+        input: &str,
+        decide: fn(&JsonError) -> Recovery,
+    ) -> Result<(JsonValue, Vec<String>)> {
+        let mut seen = Vec::new();
+        let (value, report) =
+            parse_with_recovery(input, &ParserOptions::default(), |err, path| {
+                seen.push(path.to_string());
+                decide(err)
+            })?;
+        assert_eq!(report.errors.len(), seen.len());
+        Ok((value, seen))
+    }
+
+    #[test]
+    fn test_substitutes_malformed_values() {
+        let input = r#"{"a": tru, "b": [1, "x\q", 3, 1.2.3], "c": @, "d": true}"#;
+        let (value, seen) = recover(input, |_| Recovery::Substitute(JsonValue::Null)).unwrap();
+        assert_eq!(
+            value,
+            parse_json(r#"{"a": null, "b": [1, null, 3, null], "c": null, "d": true}"#).unwrap()
+        );
+        assert_eq!(seen, ["/a", "/b/1", "/b/3", "/c"]);
+    }
+
+    #[test]
+    fn test_skip_drops_elements_and_members() {
+        let (value, seen) = recover(r#"{"a": [nul, 1, nul, 2], "b": ?, "c": {"d": }}"#, |_| {
+            Recovery::Skip
+        })
+        .unwrap();
+        assert_eq!(value, parse_json(r#"{"a": [1, 2], "c": {}}"#).unwrap());
+        assert_eq!(seen, ["/a/0", "/a/2", "/b", "/c/d"]);
+        let (value, _) = recover("tru", |_| Recovery::Skip).unwrap();
+        assert_eq!(value, JsonValue::Null);
+    }
+
+    #[test]
+    fn test_hook_sees_the_error() {
+        let (value, _) = recover("[1, , 2.e, \"\\x\"]", |err| {
+            Recovery::Substitute(JsonValue::String(err.kind().code().into()))
+        })
+        .unwrap();
+        assert_eq!(
+            value,
+            parse_json(r#"[1, "unexpected_token", "invalid_number", "invalid_escape"]"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_abort_returns_the_error_with_its_path() {
+        let input = r#"{"a": [1, tru]}"#;
+        let err = recover(input, |_| Recovery::Abort).unwrap_err();
+        assert_eq!(
+            err.path().map(|path| path.to_string()).as_deref(),
+            Some("/a/1")
+        );
+        assert_eq!(err.kind(), JsonErrorKind::UnexpectedToken);
+    }
+
+    #[test]
+    fn test_structural_errors_are_not_recoverable() {
+        for input in [r#"{"a": 1 "b": 2}"#, "[1, 2", r#"{tru: 1}"#, "[1] // c"] {
+            assert!(
+                recover(input, |_| Recovery::Skip).is_err(),
+                "{input} should fail"
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicate_keys_keep_incidents_in_place() {
+        let input = r#"{"a": tru, "a": 1, "b": fals, "b": 2}"#;
+        let options = ParserOptions::new().duplicate_keys(DuplicateKeys::Collect);
+        let mut seen = Vec::new();
+        let (value, _) = parse_with_recovery(input, &options, |_, path| {
+            seen.push(path.to_string());
+            Recovery::Substitute(JsonValue::Boolean(false))
+        })
+        .unwrap();
+        assert_eq!(value.to_string(), r#"{"a":false,"a":1,"b":false,"b":2}"#);
+        assert_eq!(seen, ["/a", "/b"]);
+
+        let (value, _) = parse_with_recovery(input, &options, |_, _| Recovery::Skip).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":1,"b":2}"#);
+
+        let (value, seen) = recover(input, |_| Recovery::Substitute(JsonValue::Null)).unwrap();
+        assert_eq!(value, parse_json(r#"{"a": 1, "b": 2}"#).unwrap());
+        assert!(seen.is_empty());
+    }
+}
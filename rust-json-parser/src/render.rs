@@ -0,0 +1,231 @@
+//! Turning a token stream back into text without building a [`JsonValue`](crate::JsonValue).
+
+use crate::{FormatConfig, JsonWriter, Token, WriterError};
+use std::io;
+
+/// What the innermost open container accepts next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    /// Just opened: the first child or the closing bracket.
+    Start,
+    /// After a comma: another child, or the closing bracket if the comma
+    /// was a trailing one.
+    Item,
+    /// After an object key.
+    Colon,
+    /// After a colon.
+    Value,
+    /// After a child: a comma or the closing bracket.
+    Next,
+}
+
+#[derive(Debug)]
+struct Frame {
+    object: bool,
+    expect: Expect,
+}
+
+/// Renders `tokens` as text laid out by `config`.
+///
+/// The tokens are checked as they go, so out-of-place commas, colons and
+/// brackets fail with [`WriterError::InvalidCall`]. Trailing commas, which
+/// [`ParserOptions::allow_trailing_commas`](crate::ParserOptions::allow_trailing_commas)
+/// lets the tokenizer produce, are dropped.
+pub fn render_tokens<I>(tokens: I, config: &FormatConfig) -> Result<String, WriterError>
+where
+    I: IntoIterator<Item = Token>,
+{
+    let bytes = write_tokens(tokens, Vec::new(), config)?;
+    Ok(String::from_utf8(bytes).expect("JsonWriter only writes UTF-8"))
+}
+
+/// Like [`render_tokens`], writing into `writer` as the tokens arrive and
+/// returning it once the document is complete.
+pub fn write_tokens<I, W>(tokens: I, writer: W, config: &FormatConfig) -> Result<W, WriterError>
+where
+    I: IntoIterator<Item = Token>,
+    W: io::Write,
+{
+    let mut sink = TokenSink::new(writer, config);
+    for token in tokens {
+        sink.push(&token)?;
+    }
+    sink.finish()
+}
+
+/// Checks tokens against the document structure and feeds them to a
+/// [`JsonWriter`].
+pub(crate) struct TokenSink<W: io::Write> {
+    out: JsonWriter<W>,
+    stack: Vec<Frame>,
+}
+
+impl<W: io::Write> TokenSink<W> {
+    pub(crate) fn new(writer: W, config: &FormatConfig) -> Self {
+        Self {
+            out: JsonWriter::with_config(writer, config.clone()),
+            stack: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, token: &Token) -> Result<(), WriterError> {
+        let out = &mut self.out;
+        let top = self.stack.last_mut();
+        match token {
+            Token::Comma => match top {
+                Some(frame) if frame.expect == Expect::Next => frame.expect = Expect::Item,
+                _ => return Err(out_of_place()),
+            },
+            Token::Colon => match top {
+                Some(frame) if frame.expect == Expect::Colon => frame.expect = Expect::Value,
+                _ => return Err(out_of_place()),
+            },
+            Token::RightBracket | Token::RightBrace => {
+                let object = *token == Token::RightBrace;
+                match top {
+                    Some(frame)
+                        if frame.object == object
+                            && matches!(
+                                frame.expect,
+                                Expect::Start | Expect::Item | Expect::Next
+                            ) => {}
+                    _ => return Err(out_of_place()),
+                }
+                // A trailing comma after the last child is not written
+                if object {
+                    out.end_object()?;
+                } else {
+                    out.end_array()?;
+                }
+                self.stack.pop();
+                self.completed();
+            }
+            Token::String(key)
+                if top.as_ref().is_some_and(|frame| {
+                    frame.object && matches!(frame.expect, Expect::Start | Expect::Item)
+                }) =>
+            {
+                out.key(key)?;
+                self.stack.last_mut().expect("matched above").expect = Expect::Colon;
+            }
+            value => {
+                let accepts_value = match top {
+                    None => true,
+                    Some(frame) if frame.object => frame.expect == Expect::Value,
+                    Some(frame) => matches!(frame.expect, Expect::Start | Expect::Item),
+                };
+                if !accepts_value {
+                    return Err(out_of_place());
+                }
+                match value {
+                    Token::LeftBrace | Token::LeftBracket => {
+                        let object = *value == Token::LeftBrace;
+                        if object {
+                            out.begin_object()?;
+                        } else {
+                            out.begin_array()?;
+                        }
+                        self.stack.push(Frame {
+                            object,
+                            expect: Expect::Start,
+                        });
+                        return Ok(());
+                    }
+                    Token::String(s) => out.string(s)?,
+                    Token::Number(n) => out.number(*n)?,
+                    Token::Boolean(b) => out.bool(*b)?,
+                    Token::Null => out.null()?,
+                    _ => unreachable!("punctuation is handled above"),
+                }
+                self.completed();
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the document is complete and returns the sink.
+    pub(crate) fn finish(self) -> Result<W, WriterError> {
+        self.out.finish()
+    }
+
+    /// Records that the innermost container has received a whole child.
+    fn completed(&mut self) {
+        if let Some(frame) = self.stack.last_mut() {
+            frame.expect = Expect::Next;
+        }
+    }
+}
+
+fn out_of_place() -> WriterError {
+    WriterError::InvalidCall("token out of place")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonParser, ParserOptions, Tokenizer};
+
+    fn tokens(input: &str) -> Vec<Token> {
+        Tokenizer::new(input).tokenize().unwrap()
+    }
+
+    #[test]
+    fn test_renders_compact_and_pretty() {
+        let input = r#" { "a" : [1, true, null, {"b": "x\ny"}], "c": {} } "#;
+        let value = JsonParser::new(input).unwrap().parse().unwrap();
+        assert_eq!(
+            render_tokens(tokens(input), &FormatConfig::new()).unwrap(),
+            value.to_string()
+        );
+        assert_eq!(
+            render_tokens(tokens(input), &FormatConfig::pretty(2)).unwrap(),
+            value.to_string_pretty(2)
+        );
+    }
+
+    #[test]
+    fn test_write_tokens_streams_into_writer() {
+        let out = write_tokens(tokens("[1, [2]]"), Vec::new(), &FormatConfig::new()).unwrap();
+        assert_eq!(out, b"[1,[2]]");
+    }
+
+    #[test]
+    fn test_drops_trailing_commas() {
+        let options = ParserOptions::new().allow_trailing_commas(true);
+        let tokens = Tokenizer::with_options("{\"a\": [1, 2,],}", options)
+            .tokenize()
+            .unwrap();
+        assert_eq!(
+            render_tokens(tokens, &FormatConfig::new()).unwrap(),
+            r#"{"a":[1,2]}"#
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_streams() {
+        let cases = vec![
+            "[1 2]",
+            "[1,,2]",
+            "[,]",
+            r#"{"a" 1}"#,
+            r#"{"a": 1 "b": 2}"#,
+            r#"{"a":}"#,
+            "{1: 2}",
+            "[1}",
+            "]",
+            ": 1",
+            "[1",
+            "1 2",
+            "",
+        ];
+        for input in cases {
+            assert!(
+                matches!(
+                    render_tokens(tokens(input), &FormatConfig::new()),
+                    Err(WriterError::InvalidCall(_))
+                ),
+                "{input}"
+            );
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{JsonError, JsonPath};
+use std::fmt;
+
+/// The category of a [`JsonWarning`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// An object repeats a key, so all but one of the values are lost.
+    DuplicateKey,
+    /// A number that `f64` cannot hold exactly.
+    PrecisionLoss,
+    /// Containers nested deeper than the configured depth.
+    DeepNesting,
+    /// A string longer than the configured length.
+    LongString,
+    /// An escape that is not needed or has a shorter form.
+    NonCanonicalEscape,
+}
+
+/// Something questionable in a document that does not stop it from
+/// being read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonWarning {
+    pub kind: WarningKind,
+    pub message: String,
+    /// Character offset the warning points at.
+    pub position: usize,
+    /// The value the warning is about.
+    pub path: JsonPath,
+}
+
+impl fmt::Display for JsonWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_root() {
+            write!(f, "at {}: ", self.path)?;
+        }
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+/// Every finding from a pass over a document, so they can be fixed in one
+/// go instead of one rerun each.
+///
+/// Displays one finding per line, errors first, followed by a count.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JsonReport {
+    pub errors: Vec<JsonError>,
+    pub warnings: Vec<JsonWarning>,
+}
+
+impl JsonReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `true` when there are no errors; warnings are allowed.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// `true` when there are no findings at all.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+
+    /// Number of errors and warnings together.
+    pub fn len(&self) -> usize {
+        self.errors.len() + self.warnings.len()
+    }
+}
+
+impl fmt::Display for JsonReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for error in &self.errors {
+            writeln!(f, "error: {error}")?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "warning: {warning}")?;
+        }
+        write!(
+            f,
+            "{}, {}",
+            plural(self.errors.len(), "error"),
+            plural(self.warnings.len(), "warning")
+        )
+    }
+}
+
+fn plural(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {noun}")
+    } else {
+        format!("{n} {noun}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExpectedToken;
+
+    #[test]
+    fn test_display_lists_every_finding() {
+        let mut path = JsonPath::new();
+        path.push_key("a");
+        let report = JsonReport {
+            errors: vec![JsonError::UnexpectedEndOfInput {
+                expected: vec![ExpectedToken::RightBracket],
+                position: 7,
+                path: JsonPath::new(),
+            }],
+            warnings: vec![
+                JsonWarning {
+                    kind: WarningKind::DuplicateKey,
+                    message: "Duplicate key \"a\"".into(),
+                    position: 9,
+                    path: JsonPath::new(),
+                },
+                JsonWarning {
+                    kind: WarningKind::LongString,
+                    message: "String is 9000 characters long".into(),
+                    position: 3,
+                    path,
+                },
+            ],
+        };
+        assert_eq!(
+            report.to_string(),
+            "error: Unexpected end of input at position 7: expected ']'\n\
+             warning: Duplicate key \"a\" at position 9\n\
+             warning: at /a: String is 9000 characters long at position 3\n\
+             1 error, 2 warnings"
+        );
+        assert!(!report.is_ok());
+        assert_eq!(report.len(), 3);
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = JsonReport::new();
+        assert!(report.is_ok() && report.is_empty());
+        assert_eq!(report.to_string(), "0 errors, 0 warnings");
+    }
+}
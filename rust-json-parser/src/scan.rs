@@ -0,0 +1,236 @@
+//! Bulk scanning for the tokenizer: how far a run of characters that need
+//! no individual attention goes, so it can be skipped or copied at once.
+//!
+//! With the `simd` feature, runs are found several characters at a time
+//! with SSE2 or AVX2 on x86 and NEON on AArch64, whichever the CPU running
+//! the code supports; otherwise, and for the last few characters of the
+//! input, one character at a time.
+//!
+//! This only speeds up the inside of strings and runs of whitespace. It is
+//! not a structural index: the input is still decoded to `char`s first and
+//! each token is still read on its own, so documents of short tokens gain
+//! nothing. `benches/scan.rs` measures tokenizing; on one x86-64 core with
+//! AVX2, best of 30 rounds over 4 MB, across three runs:
+//!
+//! | document     | without `simd` | with `simd`  |
+//! |--------------|----------------|--------------|
+//! | long strings | 280–300 MB/s   | 370–410 MB/s |
+//! | indented     | 310–340 MB/s   | 335–375 MB/s |
+//! | short tokens | 30–31 MB/s     | 28–31 MB/s   |
+
+/// How many leading characters of `input` a string literal takes as they
+/// are: everything before the first `quote`, backslash or control
+/// character.
+pub(crate) fn string_run(input: &[char], quote: char) -> usize {
+    let quote = u32::from(quote);
+    first_stop(input, [quote, u32::from('\\'), quote, quote], 0x20, false)
+}
+
+/// How many leading characters of `input` are JSON whitespace: space, tab,
+/// line feed and carriage return.
+pub(crate) fn whitespace_run(input: &[char]) -> usize {
+    first_stop(input, [' ', '\t', '\n', '\r'].map(u32::from), 0, true)
+}
+
+/// The index of the first character in `input` that is one of `needles` or
+/// below `below`, or with `invert` the first that is neither; the length of
+/// `input` when there is none.
+fn first_stop(input: &[char], needles: [u32; 4], below: u32, invert: bool) -> usize {
+    #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU supports AVX2.
+            return unsafe { x86::first_stop_avx2(input, needles, below, invert) };
+        }
+        if is_x86_feature_detected!("sse2") {
+            // SAFETY: the CPU supports SSE2.
+            return unsafe { x86::first_stop_sse2(input, needles, below, invert) };
+        }
+    }
+    #[cfg(all(feature = "simd", target_arch = "aarch64"))]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") {
+            // SAFETY: the CPU supports NEON.
+            return unsafe { neon::first_stop(input, needles, below, invert) };
+        }
+    }
+    first_stop_from(input, 0, needles, below, invert)
+}
+
+/// [`first_stop`] one character at a time, starting at `from`.
+fn first_stop_from(
+    input: &[char],
+    from: usize,
+    needles: [u32; 4],
+    below: u32,
+    invert: bool,
+) -> usize {
+    input[from..]
+        .iter()
+        .position(|&c| {
+            let c = u32::from(c);
+            (needles.contains(&c) || c < below) != invert
+        })
+        .map_or(input.len(), |i| from + i)
+}
+
+// Every `char` is a `u32` in size and alignment, and below 0x110000, so it
+// loads into a 32-bit lane and compares the same signed or unsigned.
+
+#[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    #[target_feature(enable = "avx2")]
+    pub(super) fn first_stop_avx2(
+        input: &[char],
+        needles: [u32; 4],
+        below: u32,
+        invert: bool,
+    ) -> usize {
+        const LANES: usize = 8;
+        let splat = needles.map(|n| _mm256_set1_epi32(n as i32));
+        let below_v = _mm256_set1_epi32(below as i32);
+        let mut i = 0;
+        while i + LANES <= input.len() {
+            // SAFETY: lanes `i..i + LANES` are within `input`.
+            let v = unsafe { _mm256_loadu_si256(input.as_ptr().add(i).cast()) };
+            let mut hits = _mm256_cmpgt_epi32(below_v, v);
+            for needle in splat {
+                hits = _mm256_or_si256(hits, _mm256_cmpeq_epi32(v, needle));
+            }
+            let mut mask = _mm256_movemask_ps(_mm256_castsi256_ps(hits)) as u32;
+            if invert {
+                mask = !mask & 0xff;
+            }
+            if mask != 0 {
+                return i + mask.trailing_zeros() as usize;
+            }
+            i += LANES;
+        }
+        super::first_stop_from(input, i, needles, below, invert)
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) fn first_stop_sse2(
+        input: &[char],
+        needles: [u32; 4],
+        below: u32,
+        invert: bool,
+    ) -> usize {
+        const LANES: usize = 4;
+        let splat = needles.map(|n| _mm_set1_epi32(n as i32));
+        let below_v = _mm_set1_epi32(below as i32);
+        let mut i = 0;
+        while i + LANES <= input.len() {
+            // SAFETY: lanes `i..i + LANES` are within `input`.
+            let v = unsafe { _mm_loadu_si128(input.as_ptr().add(i).cast()) };
+            let mut hits = _mm_cmplt_epi32(v, below_v);
+            for needle in splat {
+                hits = _mm_or_si128(hits, _mm_cmpeq_epi32(v, needle));
+            }
+            let mut mask = _mm_movemask_ps(_mm_castsi128_ps(hits)) as u32;
+            if invert {
+                mask = !mask & 0xf;
+            }
+            if mask != 0 {
+                return i + mask.trailing_zeros() as usize;
+            }
+            i += LANES;
+        }
+        super::first_stop_from(input, i, needles, below, invert)
+    }
+}
+
+#[cfg(all(feature = "simd", target_arch = "aarch64"))]
+mod neon {
+    use std::arch::aarch64::*;
+
+    #[target_feature(enable = "neon")]
+    pub(super) fn first_stop(input: &[char], needles: [u32; 4], below: u32, invert: bool) -> usize {
+        const LANES: usize = 4;
+        let splat = needles.map(|n| vdupq_n_u32(n));
+        let below_v = vdupq_n_u32(below);
+        let mut i = 0;
+        while i + LANES <= input.len() {
+            // SAFETY: lanes `i..i + LANES` are within `input`.
+            let v = unsafe { vld1q_u32(input.as_ptr().add(i).cast()) };
+            let mut hits = vcltq_u32(v, below_v);
+            for needle in splat {
+                hits = vorrq_u32(hits, vceqq_u32(v, needle));
+            }
+            if invert {
+                hits = vmvnq_u32(hits);
+            }
+            if vmaxvq_u32(hits) != 0 {
+                // The stop is in this block; find which lane.
+                break;
+            }
+            i += LANES;
+        }
+        super::first_stop_from(input, i, needles, below, invert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every length up to a few blocks, with the stop at every position.
+    #[test]
+    fn test_runs_match_a_plain_scan() {
+        for len in 0..40 {
+            for stop in 0..=len {
+                let mut text: Vec<char> = "aé😀b".chars().cycle().take(len).collect();
+                if stop < len {
+                    text[stop] = '"';
+                }
+                assert_eq!(string_run(&text, '"'), stop, "{len} {stop}");
+
+                let mut spaces: Vec<char> = " \t\n\r".chars().cycle().take(len).collect();
+                if stop < len {
+                    spaces[stop] = 'x';
+                }
+                assert_eq!(whitespace_run(&spaces), stop, "{len} {stop}");
+            }
+        }
+    }
+
+    /// The kernel not picked on this CPU gets no other coverage.
+    #[cfg(all(feature = "simd", any(target_arch = "x86", target_arch = "x86_64")))]
+    #[test]
+    fn test_sse2_matches_a_plain_scan() {
+        if !is_x86_feature_detected!("sse2") {
+            return;
+        }
+        let needles = ['"', '\\', '"', '"'].map(u32::from);
+        for len in 0..12 {
+            for stop in 0..=len {
+                let mut text = vec!['a'; len];
+                if stop < len {
+                    text[stop] = '\u{0}';
+                }
+                // SAFETY: the CPU supports SSE2.
+                let found = unsafe { x86::first_stop_sse2(&text, needles, 0x20, false) };
+                assert_eq!(found, stop, "{len} {stop}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_string_run_stops() {
+        let text: Vec<char> = "abcdefgh\\ijk".chars().collect();
+        assert_eq!(string_run(&text, '"'), 8);
+        let text: Vec<char> = "abcdefghij\u{1f}".chars().collect();
+        assert_eq!(string_run(&text, '"'), 10);
+        let text: Vec<char> = "it\"s 'quoted'".chars().collect();
+        assert_eq!(string_run(&text, '\''), 5);
+        assert_eq!(string_run(&text, '"'), 2);
+        // Not JSON whitespace, however it looks.
+        let text: Vec<char> = "      \u{a0}".chars().collect();
+        assert_eq!(whitespace_run(&text), 6);
+    }
+}
@@ -0,0 +1,933 @@
+//! Validation against a subset of [JSON Schema](https://json-schema.org/).
+//!
+//! A [`Schema`] is compiled once from a schema document and then checks
+//! any number of values, reporting every [`Violation`] with the path to
+//! the offending value. The supported keywords are `type`, `enum`,
+//! `const`, `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`,
+//! `minLength`, `maxLength`, `pattern`, `items`, `minItems`, `maxItems`,
+//! `properties`, `required` and `additionalProperties`; `true` and `false`
+//! are schemas too. Other keywords, including `$ref`, are ignored.
+//! [`infer_schema`] goes the other way, describing a set of samples.
+//!
+//! ```
+//! use rust_json_parser::JsonParser;
+//! use rust_json_parser::schema::Schema;
+//!
+//! let schema = Schema::from_json(&JsonParser::new(
+//!     r#"{
+//!         "type": "object",
+//!         "required": ["name"],
+//!         "properties": {
+//!             "name": {"type": "string", "pattern": "^[a-z]+$"},
+//!             "port": {"type": "integer", "minimum": 1, "maximum": 65535}
+//!         }
+//!     }"#,
+//! )?.parse()?)?;
+//!
+//! let config = JsonParser::new(r#"{"name": "API", "port": 0}"#)?.parse()?;
+//! let violations = schema.validate(&config).unwrap_err();
+//! let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+//! assert_eq!(
+//!     messages,
+//!     [
+//!         "'/name': \"API\" does not match pattern \"^[a-z]+$\"",
+//!         "'/port': 0 is less than the minimum of 1",
+//!     ]
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::pattern::Pattern;
+use crate::{JsonAccess, JsonAccessError, JsonObject, JsonPath, JsonValue};
+use std::fmt;
+
+/// A JSON type as named by the `type` keyword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Type {
+    Null,
+    Boolean,
+    Object,
+    Array,
+    Number,
+    /// A number with no fractional part.
+    Integer,
+    String,
+}
+
+impl Type {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => Type::Null,
+            "boolean" => Type::Boolean,
+            "object" => Type::Object,
+            "array" => Type::Array,
+            "number" => Type::Number,
+            "integer" => Type::Integer,
+            "string" => Type::String,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Object => "object",
+            Type::Array => "array",
+            Type::Number => "number",
+            Type::Integer => "integer",
+            Type::String => "string",
+        }
+    }
+
+    pub(crate) fn matches(self, value: &JsonValue) -> bool {
+        match (self, value) {
+            (Type::Null, JsonValue::Null)
+            | (Type::Boolean, JsonValue::Boolean(_))
+            | (Type::Object, JsonValue::Object(_))
+            | (Type::Array, JsonValue::Array(_))
+            | (Type::Number, JsonValue::Number(_))
+            | (Type::String, JsonValue::String(_)) => true,
+            (Type::Integer, JsonValue::Number(n)) => n.is_finite() && n.fract() == 0.0,
+            _ => false,
+        }
+    }
+}
+
+/// A compiled schema, ready to validate values.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub(crate) node: Node,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Node {
+    /// `true` accepts everything, `false` nothing.
+    Bool(bool),
+    Rules(Box<Rules>),
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Rules {
+    pub(crate) types: Option<Vec<Type>>,
+    pub(crate) enumeration: Option<Vec<JsonValue>>,
+    pub(crate) constant: Option<JsonValue>,
+    pub(crate) minimum: Option<f64>,
+    pub(crate) maximum: Option<f64>,
+    pub(crate) exclusive_minimum: Option<f64>,
+    pub(crate) exclusive_maximum: Option<f64>,
+    pub(crate) min_length: Option<usize>,
+    pub(crate) max_length: Option<usize>,
+    /// The source text, for messages, and the compiled pattern.
+    pub(crate) pattern: Option<(String, Pattern)>,
+    pub(crate) items: Option<Node>,
+    pub(crate) min_items: Option<usize>,
+    pub(crate) max_items: Option<usize>,
+    pub(crate) properties: Vec<(String, Node)>,
+    pub(crate) required: Vec<String>,
+    pub(crate) additional_properties: Option<Node>,
+}
+
+/// Why a schema document could not be compiled. Paths point into the
+/// schema document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    /// A keyword has a value of the wrong type.
+    InvalidSchema(JsonAccessError),
+    /// `type` names something other than the seven JSON Schema types.
+    UnknownType { path: JsonPath, name: String },
+    /// A length or count keyword is not a non-negative integer.
+    InvalidCount { path: JsonPath, value: f64 },
+    /// `pattern` is not a regular expression this crate supports.
+    InvalidPattern {
+        path: JsonPath,
+        pattern: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::InvalidSchema(err) => write!(f, "Invalid schema: {err}"),
+            SchemaError::UnknownType { path, name } => {
+                write!(f, "Unknown type '{name}' at {}", location(path))
+            }
+            SchemaError::InvalidCount { path, value } => {
+                write!(
+                    f,
+                    "Expected a non-negative integer at {}, found {value}",
+                    location(path)
+                )
+            }
+            SchemaError::InvalidPattern {
+                path,
+                pattern,
+                reason,
+            } => {
+                write!(
+                    f,
+                    "Invalid pattern '{pattern}' at {}: {reason}",
+                    location(path)
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SchemaError::InvalidSchema(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<JsonAccessError> for SchemaError {
+    fn from(err: JsonAccessError) -> Self {
+        SchemaError::InvalidSchema(err)
+    }
+}
+
+fn location(path: &JsonPath) -> String {
+    if path.is_root() {
+        "the document root".to_string()
+    } else {
+        format!("'{path}'")
+    }
+}
+
+/// One way a value fails its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Where the failing value is, in the validated document.
+    pub path: JsonPath,
+    /// The keyword that failed, such as `"minimum"`.
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", location(&self.path), self.message)
+    }
+}
+
+impl Schema {
+    /// Compiles a schema document: an object of keywords, or a boolean.
+    pub fn from_json(schema: &JsonValue) -> Result<Self, SchemaError> {
+        Ok(Schema {
+            node: Node::compile(&schema.access())?,
+        })
+    }
+
+    /// Checks `value`, returning every violation, in document order, if
+    /// there are any.
+    pub fn validate(&self, value: &JsonValue) -> Result<(), Vec<Violation>> {
+        let mut validator = Validator {
+            path: JsonPath::new(),
+            violations: Vec::new(),
+        };
+        validator.node(&self.node, value);
+        if validator.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(validator.violations)
+        }
+    }
+
+    pub fn is_valid(&self, value: &JsonValue) -> bool {
+        self.validate(value).is_ok()
+    }
+}
+
+impl Node {
+    /// Whether `value` satisfies this schema.
+    pub(crate) fn accepts(&self, value: &JsonValue) -> bool {
+        let mut validator = Validator {
+            path: JsonPath::new(),
+            violations: Vec::new(),
+        };
+        validator.node(self, value);
+        validator.violations.is_empty()
+    }
+
+    fn compile(schema: &JsonAccess<'_>) -> Result<Self, SchemaError> {
+        if let JsonValue::Boolean(accept) = schema.value() {
+            return Ok(Node::Bool(*accept));
+        }
+        let object = schema.as_object().map_err(|err| JsonAccessError {
+            expected: "schema",
+            ..err
+        })?;
+        let mut rules = Rules::default();
+        for key in object.keys() {
+            let field = schema.field(key)?;
+            match key.as_str() {
+                "type" => rules.types = Some(compile_types(&field)?),
+                "enum" => rules.enumeration = Some(field.as_array()?.iter().cloned().collect()),
+                "const" => rules.constant = Some(field.value().clone()),
+                "minimum" => rules.minimum = Some(field.as_f64()?),
+                "maximum" => rules.maximum = Some(field.as_f64()?),
+                "exclusiveMinimum" => rules.exclusive_minimum = Some(field.as_f64()?),
+                "exclusiveMaximum" => rules.exclusive_maximum = Some(field.as_f64()?),
+                "minLength" => rules.min_length = Some(count(&field)?),
+                "maxLength" => rules.max_length = Some(count(&field)?),
+                "pattern" => {
+                    let source = field.as_str()?;
+                    let pattern =
+                        Pattern::new(source).map_err(|err| SchemaError::InvalidPattern {
+                            path: field.path().clone(),
+                            pattern: source.to_string(),
+                            reason: err.to_string(),
+                        })?;
+                    rules.pattern = Some((source.to_string(), pattern));
+                }
+                "items" => rules.items = Some(Node::compile(&field)?),
+                "minItems" => rules.min_items = Some(count(&field)?),
+                "maxItems" => rules.max_items = Some(count(&field)?),
+                "properties" => {
+                    for name in field.as_object()?.keys() {
+                        let node = Node::compile(&field.field(name)?)?;
+                        rules.properties.push((name.to_string(), node));
+                    }
+                }
+                "required" => {
+                    for index in 0..field.as_array()?.len() {
+                        let name = field.index(index)?.as_str()?;
+                        rules.required.push(name.to_string());
+                    }
+                }
+                "additionalProperties" => {
+                    rules.additional_properties = Some(Node::compile(&field)?);
+                }
+                _ => {}
+            }
+        }
+        Ok(Node::Rules(Box::new(rules)))
+    }
+}
+
+/// `type` is one type name or an array of them.
+fn compile_types(field: &JsonAccess<'_>) -> Result<Vec<Type>, SchemaError> {
+    let names = match field.value() {
+        JsonValue::Array(names) => (0..names.len())
+            .map(|index| field.index(index))
+            .collect::<Result<Vec<_>, _>>()?,
+        _ => vec![field.clone()],
+    };
+    names
+        .iter()
+        .map(|name| {
+            let text = name.as_str()?;
+            Type::from_name(text).ok_or_else(|| SchemaError::UnknownType {
+                path: name.path().clone(),
+                name: text.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn count(field: &JsonAccess<'_>) -> Result<usize, SchemaError> {
+    let value = field.as_f64()?;
+    if value >= 0.0 && value.fract() == 0.0 && value <= usize::MAX as f64 {
+        Ok(value as usize)
+    } else {
+        Err(SchemaError::InvalidCount {
+            path: field.path().clone(),
+            value,
+        })
+    }
+}
+
+struct Validator {
+    path: JsonPath,
+    violations: Vec<Violation>,
+}
+
+impl Validator {
+    fn violation(&mut self, keyword: &'static str, message: String) {
+        self.violations.push(Violation {
+            path: self.path.clone(),
+            keyword,
+            message,
+        });
+    }
+
+    fn node(&mut self, node: &Node, value: &JsonValue) {
+        match node {
+            Node::Bool(true) => {}
+            Node::Bool(false) => self.violation("false", "no value is allowed here".to_string()),
+            Node::Rules(rules) => self.rules(rules, value),
+        }
+    }
+
+    fn rules(&mut self, rules: &Rules, value: &JsonValue) {
+        if let Some(types) = &rules.types
+            && !types.iter().any(|ty| ty.matches(value))
+        {
+            let names: Vec<&str> = types.iter().map(|ty| ty.name()).collect();
+            self.violation(
+                "type",
+                format!(
+                    "expected {}, found {}",
+                    names.join(" or "),
+                    value.type_name()
+                ),
+            );
+            // The remaining keywords would only restate the mismatch.
+            return;
+        }
+        if let Some(allowed) = &rules.enumeration
+            && !allowed.contains(value)
+        {
+            let allowed: Vec<String> = allowed.iter().map(JsonValue::to_string).collect();
+            self.violation(
+                "enum",
+                format!("{value} is not one of {}", allowed.join(", ")),
+            );
+        }
+        if let Some(constant) = &rules.constant
+            && constant != value
+        {
+            self.violation("const", format!("expected {constant}, found {value}"));
+        }
+        match value {
+            JsonValue::Number(n) => self.number(rules, *n, value),
+            JsonValue::String(s) => self.string(rules, s, value),
+            JsonValue::Array(items) => {
+                self.count("minItems", rules.min_items, "items", items.len(), true);
+                self.count("maxItems", rules.max_items, "items", items.len(), false);
+                if let Some(schema) = &rules.items {
+                    for (index, item) in items.iter().enumerate() {
+                        self.path.push_index(index);
+                        self.node(schema, item);
+                        self.path.pop();
+                    }
+                }
+            }
+            JsonValue::Object(members) => {
+                for name in &rules.required {
+                    if !members.contains_key(name) {
+                        self.violation("required", format!("missing required property '{name}'"));
+                    }
+                }
+                for (name, member) in members.iter() {
+                    let property = rules
+                        .properties
+                        .iter()
+                        .find(|(property, _)| property == name.as_str())
+                        .map(|(_, schema)| schema);
+                    self.path.push_key(name.clone());
+                    match (property, &rules.additional_properties) {
+                        (Some(schema), _) => self.node(schema, member),
+                        (None, Some(Node::Bool(false))) => self.violation(
+                            "additionalProperties",
+                            format!("property '{name}' is not allowed"),
+                        ),
+                        (None, Some(schema)) => self.node(schema, member),
+                        (None, None) => {}
+                    }
+                    self.path.pop();
+                }
+            }
+            JsonValue::Null | JsonValue::Boolean(_) => {}
+        }
+    }
+
+    fn number(&mut self, rules: &Rules, n: f64, value: &JsonValue) {
+        if let Some(min) = rules.minimum
+            && n < min
+        {
+            self.violation(
+                "minimum",
+                format!(
+                    "{value} is less than the minimum of {}",
+                    JsonValue::Number(min)
+                ),
+            );
+        }
+        if let Some(max) = rules.maximum
+            && n > max
+        {
+            self.violation(
+                "maximum",
+                format!(
+                    "{value} is greater than the maximum of {}",
+                    JsonValue::Number(max)
+                ),
+            );
+        }
+        if let Some(min) = rules.exclusive_minimum
+            && n <= min
+        {
+            self.violation(
+                "exclusiveMinimum",
+                format!("{value} is not greater than {}", JsonValue::Number(min)),
+            );
+        }
+        if let Some(max) = rules.exclusive_maximum
+            && n >= max
+        {
+            self.violation(
+                "exclusiveMaximum",
+                format!("{value} is not less than {}", JsonValue::Number(max)),
+            );
+        }
+    }
+
+    fn string(&mut self, rules: &Rules, s: &str, value: &JsonValue) {
+        // JSON Schema counts lengths in code points.
+        let chars = s.chars().count();
+        self.count("minLength", rules.min_length, "characters", chars, true);
+        self.count("maxLength", rules.max_length, "characters", chars, false);
+        if let Some((source, pattern)) = &rules.pattern
+            && !pattern.is_match(s)
+        {
+            self.violation(
+                "pattern",
+                format!(
+                    "{value} does not match pattern {}",
+                    JsonValue::String(source.as_str().into())
+                ),
+            );
+        }
+    }
+
+    fn count(
+        &mut self,
+        keyword: &'static str,
+        limit: Option<usize>,
+        unit: &str,
+        actual: usize,
+        is_min: bool,
+    ) {
+        let Some(limit) = limit else { return };
+        if is_min && actual < limit {
+            self.violation(
+                keyword,
+                format!("expected at least {limit} {unit}, found {actual}"),
+            );
+        } else if !is_min && actual > limit {
+            self.violation(
+                keyword,
+                format!("expected at most {limit} {unit}, found {actual}"),
+            );
+        }
+    }
+}
+
+/// The `$schema` URI that [`infer_schema`] declares.
+const DRAFT_2020_12: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// String formats [`infer_schema`] recognizes, checked in order.
+const FORMATS: [(&str, &str); 7] = [
+    (
+        "date-time",
+        r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])[Tt]([01]\d|2[0-3]):[0-5]\d:[0-5]\d(\.\d+)?([Zz]|[+-]([01]\d|2[0-3]):[0-5]\d)$",
+    ),
+    ("date", r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])$"),
+    (
+        "time",
+        r"^([01]\d|2[0-3]):[0-5]\d:[0-5]\d(\.\d+)?([Zz]|[+-]([01]\d|2[0-3]):[0-5]\d)$",
+    ),
+    (
+        "uuid",
+        r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+    ),
+    ("uri", r"^[A-Za-z][A-Za-z0-9+.-]*://\S+$"),
+    ("email", r"^[^\s@]+@[^\s@]+\.[^\s@]+$"),
+    (
+        "ipv4",
+        r"^((25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)\.){3}(25[0-5]|2[0-4]\d|1\d\d|[1-9]?\d)$",
+    ),
+];
+
+/// Builds a draft 2020-12 schema that every one of `values` satisfies.
+///
+/// The schema records the types seen at each location, with `integer`
+/// for numbers that never had a fraction; `required` lists the members
+/// present in every object seen at that location, so the others read as
+/// optional. Strings that all share one `format` (`date-time`, `date`,
+/// `time`, `uuid`, `uri` with an authority, `email` or `ipv4`) get it.
+/// Array elements are merged into a single `items` schema. With no
+/// values, the schema accepts anything.
+///
+/// ```
+/// use rust_json_parser::JsonParser;
+/// use rust_json_parser::schema::infer_schema;
+///
+/// let samples = [
+///     JsonParser::new(r#"{"id": 1, "email": "a@example.com"}"#)?.parse()?,
+///     JsonParser::new(r#"{"id": 2, "email": null, "tags": ["x"]}"#)?.parse()?,
+/// ];
+/// let schema = infer_schema(&samples);
+/// assert_eq!(
+///     schema.get("properties").unwrap().to_string(),
+///     r#"{"id":{"type":"integer"},"email":{"type":["null","string"],"format":"email"},"tags":{"type":"array","items":{"type":"string"}}}"#
+/// );
+/// assert_eq!(schema.get("required").unwrap().to_string(), r#"["id","email"]"#);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn infer_schema(values: &[JsonValue]) -> JsonValue {
+    let formats: Vec<(&str, Pattern)> = FORMATS
+        .iter()
+        .map(|&(name, pattern)| (name, Pattern::new(pattern).expect("built-in pattern")))
+        .collect();
+    let mut shape = Shape::default();
+    for value in values {
+        shape.observe(value, &formats);
+    }
+    let mut schema = JsonObject::new();
+    schema.insert(
+        "$schema".to_string(),
+        JsonValue::String(DRAFT_2020_12.into()),
+    );
+    shape.describe(&mut schema);
+    JsonValue::Object(schema)
+}
+
+/// What has been seen at one location across all samples.
+#[derive(Debug, Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    /// Numbers with a fractional part.
+    number: bool,
+    /// The format shared by every string so far: `None` before the first
+    /// string, `Some(None)` once they disagree.
+    string: Option<Option<&'static str>>,
+    /// Shape of all elements, once an array has been seen.
+    array: Option<Box<Shape>>,
+    object: Option<ObjectShape>,
+}
+
+#[derive(Debug, Default)]
+struct ObjectShape {
+    /// Objects seen here, to tell required members from optional ones.
+    seen: usize,
+    /// Members in first-seen order, with how many objects had them.
+    members: Vec<(String, usize, Shape)>,
+}
+
+impl Shape {
+    fn observe(&mut self, value: &JsonValue, formats: &[(&'static str, Pattern)]) {
+        match value {
+            JsonValue::Null => self.null = true,
+            JsonValue::Boolean(_) => self.boolean = true,
+            JsonValue::Number(n) if n.is_finite() && n.fract() == 0.0 => self.integer = true,
+            JsonValue::Number(_) => self.number = true,
+            JsonValue::String(s) => {
+                let format = formats
+                    .iter()
+                    .find(|(_, pattern)| pattern.is_match(s))
+                    .map(|&(name, _)| name);
+                self.string = match self.string {
+                    None => Some(format),
+                    Some(seen) if seen == format => Some(seen),
+                    Some(_) => Some(None),
+                };
+            }
+            JsonValue::Array(items) => {
+                let shape = self.array.get_or_insert_with(Box::default);
+                for item in items.iter() {
+                    shape.observe(item, formats);
+                }
+            }
+            JsonValue::Object(members) => {
+                let object = self.object.get_or_insert_with(ObjectShape::default);
+                object.seen += 1;
+                for (key, member) in members.iter() {
+                    let index = match object
+                        .members
+                        .iter()
+                        .position(|(name, ..)| name == key.as_str())
+                    {
+                        Some(index) => index,
+                        None => {
+                            object.members.push((key.to_string(), 0, Shape::default()));
+                            object.members.len() - 1
+                        }
+                    };
+                    let (_, count, shape) = &mut object.members[index];
+                    *count += 1;
+                    shape.observe(member, formats);
+                }
+            }
+        }
+    }
+
+    /// Writes the keywords for this shape into `schema`; nothing at all
+    /// when nothing was seen, which accepts anything.
+    fn describe(&self, schema: &mut JsonObject) {
+        let mut types = Vec::new();
+        let flags = [
+            (self.null, "null"),
+            (self.boolean, "boolean"),
+            (self.integer && !self.number, "integer"),
+            (self.number, "number"),
+            (self.string.is_some(), "string"),
+            (self.array.is_some(), "array"),
+            (self.object.is_some(), "object"),
+        ];
+        for (seen, name) in flags {
+            if seen {
+                types.push(JsonValue::String(name.into()));
+            }
+        }
+        match types.len() {
+            0 => return,
+            1 => schema.insert("type".to_string(), types.pop().expect("one type")),
+            _ => schema.insert("type".to_string(), JsonValue::Array(types.into())),
+        };
+        if let Some(Some(format)) = self.string {
+            schema.insert("format".to_string(), JsonValue::String(format.into()));
+        }
+        if let Some(items) = &self.array {
+            let mut described = JsonObject::new();
+            items.describe(&mut described);
+            if !described.is_empty() {
+                schema.insert("items".to_string(), JsonValue::Object(described));
+            }
+        }
+        if let Some(object) = &self.object {
+            let mut properties = JsonObject::new();
+            let mut required = Vec::new();
+            for (name, count, shape) in &object.members {
+                let mut described = JsonObject::new();
+                shape.describe(&mut described);
+                properties.insert(name.clone(), JsonValue::Object(described));
+                if *count == object.seen {
+                    required.push(JsonValue::String(name.as_str().into()));
+                }
+            }
+            if !properties.is_empty() {
+                schema.insert("properties".to_string(), JsonValue::Object(properties));
+            }
+            if !required.is_empty() {
+                schema.insert("required".to_string(), JsonValue::Array(required.into()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn schema(input: &str) -> Schema {
+        Schema::from_json(&parse_json(input).unwrap()).unwrap()
+    }
+
+    fn violations(schema_json: &str, value: &str) -> Vec<(String, &'static str)> {
+        match schema(schema_json).validate(&parse_json(value).unwrap()) {
+            Ok(()) => Vec::new(),
+            Err(violations) => violations
+                .into_iter()
+                .map(|v| (v.path.to_string(), v.keyword))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_type() {
+        let cases = vec![
+            (r#""integer""#, "3", true),
+            (r#""integer""#, "3.5", false),
+            (r#""number""#, "3.5", true),
+            (r#"["string", "null"]"#, "null", true),
+            (r#"["string", "null"]"#, "1", false),
+            (r#""object""#, "[]", false),
+        ];
+        for (ty, value, valid) in cases {
+            let schema = schema(&format!(r#"{{"type": {ty}}}"#));
+            assert_eq!(
+                schema.is_valid(&parse_json(value).unwrap()),
+                valid,
+                "{ty} {value}"
+            );
+        }
+        let err = schema(r#"{"type": ["string", "null"]}"#)
+            .validate(&parse_json("1").unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err[0].to_string(),
+            "the document root: expected string or null, found number"
+        );
+    }
+
+    #[test]
+    fn test_scalar_keywords() {
+        let number = r#"{"minimum": 1, "maximum": 10, "exclusiveMaximum": 10}"#;
+        assert!(violations(number, "5").is_empty());
+        assert_eq!(violations(number, "0"), [(String::new(), "minimum")]);
+        assert_eq!(
+            violations(number, "10"),
+            [(String::new(), "exclusiveMaximum")]
+        );
+        assert_eq!(
+            violations(number, "11"),
+            [
+                (String::new(), "maximum"),
+                (String::new(), "exclusiveMaximum")
+            ]
+        );
+
+        let string = r#"{"minLength": 2, "maxLength": 3, "pattern": "^[a-z]"}"#;
+        assert!(violations(string, r#""añb""#).is_empty());
+        assert_eq!(
+            violations(string, r#""A""#),
+            [(String::new(), "minLength"), (String::new(), "pattern")]
+        );
+
+        let choices = r#"{"enum": ["red", "green", 1]}"#;
+        assert!(violations(choices, "1").is_empty());
+        assert_eq!(violations(choices, r#""blue""#), [(String::new(), "enum")]);
+        assert_eq!(violations(r#"{"const": {"a": 1}}"#, r#"{"a": 2}"#).len(), 1);
+    }
+
+    #[test]
+    fn test_reports_every_violation_with_paths() {
+        let schema_json = r#"{
+            "type": "object",
+            "required": ["id", "tags"],
+            "properties": {
+                "id": {"type": "integer"},
+                "tags": {"type": "array", "items": {"type": "string"}, "maxItems": 2},
+                "owner": {
+                    "type": "object",
+                    "properties": {"email": {"pattern": "@"}},
+                    "additionalProperties": false
+                }
+            }
+        }"#;
+        let value = r#"{
+            "tags": ["a", 2, "c"],
+            "owner": {"email": "nobody", "age": 3},
+            "extra": true
+        }"#;
+        assert_eq!(
+            violations(schema_json, value),
+            [
+                (String::new(), "required"),
+                ("/tags".to_string(), "maxItems"),
+                ("/tags/1".to_string(), "type"),
+                ("/owner/email".to_string(), "pattern"),
+                ("/owner/age".to_string(), "additionalProperties"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_boolean_schemas() {
+        assert!(schema("true").is_valid(&parse_json(r#"{"any": "thing"}"#).unwrap()));
+        assert!(!schema("false").is_valid(&JsonValue::Null));
+        assert_eq!(
+            violations(r#"{"items": false}"#, "[1]"),
+            [("/0".to_string(), "false")]
+        );
+        assert!(violations(r#"{"items": false}"#, "[]").is_empty());
+    }
+
+    #[test]
+    fn test_invalid_schemas() {
+        let err = |input: &str| Schema::from_json(&parse_json(input).unwrap()).unwrap_err();
+        assert!(matches!(err("[]"), SchemaError::InvalidSchema(_)));
+        assert!(matches!(
+            err(r#"{"properties": {"a": {"minimum": "1"}}}"#),
+            SchemaError::InvalidSchema(JsonAccessError { ref path, .. })
+                if path.to_string() == "/properties/a/minimum"
+        ));
+        assert_eq!(
+            err(r#"{"type": ["string", "text"]}"#).to_string(),
+            "Unknown type 'text' at '/type/1'"
+        );
+        assert!(matches!(
+            err(r#"{"minLength": 1.5}"#),
+            SchemaError::InvalidCount { .. }
+        ));
+        assert!(matches!(
+            err(r#"{"pattern": "(a"}"#),
+            SchemaError::InvalidPattern { .. }
+        ));
+        let deep = format!(r#"{{"pattern": "{}"}}"#, "(".repeat(100_000));
+        assert!(matches!(err(&deep), SchemaError::InvalidPattern { .. }));
+    }
+
+    #[test]
+    fn test_infer_types_and_optionality() {
+        let samples: Vec<JsonValue> = [
+            r#"{"id": 1, "score": 2, "name": "a", "meta": {"x": true}}"#,
+            r#"{"id": 2, "score": 2.5, "name": null, "list": []}"#,
+            r#"{"id": 3, "score": 1, "name": "c", "list": [1, "two"]}"#,
+        ]
+        .iter()
+        .map(|input| parse_json(input).unwrap())
+        .collect();
+        let inferred = infer_schema(&samples);
+        assert_eq!(
+            inferred.to_string(),
+            concat!(
+                r#"{"$schema":"https://json-schema.org/draft/2020-12/schema","type":"object","#,
+                r#""properties":{"id":{"type":"integer"},"score":{"type":"number"},"#,
+                r#""name":{"type":["null","string"]},"#,
+                r#""meta":{"type":"object","properties":{"x":{"type":"boolean"}},"required":["x"]},"#,
+                r#""list":{"type":"array","items":{"type":["integer","string"]}}},"#,
+                r#""required":["id","score","name"]}"#
+            )
+        );
+
+        let compiled = Schema::from_json(&inferred).unwrap();
+        for sample in &samples {
+            assert!(compiled.is_valid(sample), "{sample}");
+        }
+        assert!(!compiled.is_valid(&parse_json(r#"{"score": 1, "name": "x"}"#).unwrap()));
+    }
+
+    #[test]
+    fn test_infer_formats() {
+        let cases = vec![
+            (
+                r#"["2024-02-29T12:30:00Z", "2023-01-01t00:00:00.5+01:00"]"#,
+                Some("date-time"),
+            ),
+            (r#"["2024-02-29"]"#, Some("date")),
+            (r#"["23:59:59Z"]"#, Some("time")),
+            (r#"["123e4567-e89b-12d3-a456-426614174000"]"#, Some("uuid")),
+            (r#"["https://example.com/a@b.c"]"#, Some("uri")),
+            (r#"["ops@example.com"]"#, Some("email")),
+            (r#"["192.168.0.1"]"#, Some("ipv4")),
+            (r#"["2024-13-01"]"#, None),
+            (r#"["256.1.1.1"]"#, None),
+            (r#"["2024-02-29", "ops@example.com"]"#, None),
+            (r#"["2024-02-29", "plain"]"#, None),
+        ];
+        for (strings, expected) in cases {
+            let items = parse_json(strings)
+                .unwrap()
+                .into_elements()
+                .collect::<Vec<_>>();
+            let inferred = infer_schema(&items);
+            assert_eq!(
+                inferred.get("format").and_then(JsonValue::as_str),
+                expected,
+                "{strings}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_infer_nothing() {
+        let inferred = infer_schema(&[]);
+        assert_eq!(
+            inferred.to_string(),
+            r#"{"$schema":"https://json-schema.org/draft/2020-12/schema"}"#
+        );
+        let empty_arrays = infer_schema(&[parse_json("[]").unwrap()]);
+        assert_eq!(empty_arrays.get("items"), None);
+    }
+}
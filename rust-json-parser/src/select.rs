@@ -0,0 +1,250 @@
+use crate::path::{parse_index, pointer_tokens};
+use crate::{JsonError, Result};
+
+/// The parts of a document to build, for
+/// [`JsonParser::parse_selected`](crate::JsonParser::parse_selected).
+///
+/// A selection names object members by key and array elements by index,
+/// the way JSON Pointer reference tokens do, or every element of an array
+/// with [`Selection::elements`]. Each selected value is built as the
+/// selection given for it says; [`Selection::all`] keeps a value whole.
+/// Scalars are kept whatever the selection, so a selection that expects an
+/// object where the document has a number gets the number.
+///
+/// ```
+/// use rust_json_parser::Selection;
+///
+/// let by_tree = Selection::new().field(
+///     "users",
+///     Selection::new().field("0", Selection::new().field("name", Selection::all())),
+/// );
+/// assert_eq!(Selection::from_pointers(&["/users/0/name"]).unwrap(), by_tree);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selection {
+    /// Keep the whole value; the other fields are then empty.
+    all: bool,
+    fields: Vec<(String, Selection)>,
+    elements: Option<Box<Selection>>,
+}
+
+impl Selection {
+    /// Selects nothing inside a value: containers come back empty.
+    pub(crate) const NOTHING: Selection = Selection {
+        all: false,
+        fields: Vec::new(),
+        elements: None,
+    };
+
+    /// A selection of nothing, to add fields to.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps the value whole.
+    pub fn all() -> Self {
+        Self {
+            all: true,
+            ..Self::default()
+        }
+    }
+
+    /// Selects the object member `key`, or the array element when `key` is
+    /// an index, built as `selection` says. Selecting a key twice keeps
+    /// what either selection names.
+    pub fn field(mut self, key: impl Into<String>, selection: Selection) -> Self {
+        self.add_field(key.into(), selection);
+        self
+    }
+
+    /// Selects every element of an array, built as `selection` says.
+    pub fn elements(mut self, selection: Selection) -> Self {
+        self.add_elements(selection);
+        self
+    }
+
+    /// Keeps the value at each JSON Pointer whole, along with the
+    /// containers on the way to it. Fails with
+    /// [`JsonError::InvalidPointer`] on a malformed pointer.
+    pub fn from_pointers(pointers: &[&str]) -> Result<Self> {
+        let mut selection = Selection::new();
+        for &pointer in pointers {
+            let tokens = pointer_tokens(pointer).ok_or_else(|| JsonError::InvalidPointer {
+                pointer: pointer.to_string(),
+            })?;
+            let path = tokens
+                .into_iter()
+                .rev()
+                .fold(Selection::all(), |inner, token| {
+                    Selection::new().field(token, inner)
+                });
+            selection.merge(path);
+        }
+        Ok(selection)
+    }
+
+    pub(crate) fn is_all(&self) -> bool {
+        self.all
+    }
+
+    /// The selection for member `key`, or `None` to skip it.
+    pub(crate) fn member(&self, key: &str) -> Option<&Selection> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == key)
+            .map(|(_, selection)| selection)
+    }
+
+    /// The selection for element `index`, or `None` to skip it.
+    pub(crate) fn element(&self, index: usize) -> Option<&Selection> {
+        self.fields
+            .iter()
+            .find(|(field, _)| parse_index(field) == Some(index))
+            .map(|(_, selection)| selection)
+            .or(self.elements.as_deref())
+    }
+
+    fn add_field(&mut self, key: String, selection: Selection) {
+        if self.all {
+            return;
+        }
+        match self.fields.iter_mut().find(|(field, _)| *field == key) {
+            Some((_, existing)) => existing.merge(selection),
+            None => self.fields.push((key, selection)),
+        }
+    }
+
+    fn add_elements(&mut self, selection: Selection) {
+        if self.all {
+            return;
+        }
+        match &mut self.elements {
+            Some(existing) => existing.merge(selection),
+            None => self.elements = Some(Box::new(selection)),
+        }
+    }
+
+    /// Widens `self` to also keep what `other` keeps.
+    fn merge(&mut self, other: Selection) {
+        if other.all {
+            *self = Selection::all();
+            return;
+        }
+        for (key, selection) in other.fields {
+            self.add_field(key, selection);
+        }
+        if let Some(selection) = other.elements {
+            self.add_elements(*selection);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::parse_json;
+    use crate::{DuplicateKeys, JsonParser, JsonValue, Limits, ParserOptions};
+
+    fn select(input: &str, selection: &Selection) -> Result<JsonValue> {
+        JsonParser::new(input)?.parse_selected(selection)
+    }
+
+    #[test]
+    fn test_builds_only_selected_parts() {
+        let input = r#"{"a": {"b": [1, {"c": 2, "d": 3}], "e": "x"}, "f": [[1], [2, 3]], "g": 4}"#;
+        let selection = Selection::new()
+            .field(
+                "a",
+                Selection::new().field(
+                    "b",
+                    Selection::new().field("1", Selection::new().field("d", Selection::all())),
+                ),
+            )
+            .field(
+                "f",
+                Selection::new().elements(Selection::new().field("0", Selection::all())),
+            )
+            .field("g", Selection::new().field("nested", Selection::all()));
+        assert_eq!(
+            select(input, &selection).unwrap(),
+            parse_json(r#"{"a": {"b": [{"d": 3}]}, "f": [[1], [2]], "g": 4}"#).unwrap()
+        );
+        assert_eq!(
+            select(input, &Selection::new()).unwrap(),
+            JsonValue::new_object()
+        );
+        assert_eq!(
+            select(input, &Selection::all()).unwrap(),
+            parse_json(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_from_pointers_merges_paths() {
+        let selection = Selection::from_pointers(&["/a/b", "/a/c/0", "/a/c", "/x"]).unwrap();
+        let expected = Selection::new()
+            .field(
+                "a",
+                Selection::new()
+                    .field("b", Selection::all())
+                    .field("c", Selection::all()),
+            )
+            .field("x", Selection::all());
+        assert_eq!(selection, expected);
+        assert_eq!(
+            Selection::from_pointers(&["/a", ""]).unwrap(),
+            Selection::all()
+        );
+        assert!(matches!(
+            Selection::from_pointers(&["a"]),
+            Err(JsonError::InvalidPointer { .. })
+        ));
+    }
+
+    #[test]
+    fn test_skipped_parts_are_still_checked() {
+        let selection = Selection::from_pointers(&["/keep"]).unwrap();
+        for input in [
+            r#"{"keep": 1, "skip": [1 2]}"#,
+            r#"{"keep": 1, "skip": {"a" 1}}"#,
+            r#"{"keep": 1, "skip": tru}"#,
+            r#"{"keep": 1, "skip": [1,]}"#,
+        ] {
+            assert!(select(input, &selection).is_err(), "{input} should fail");
+        }
+        let input = r#"{"keep": 1, "skip": [1, {"b": ]}]}"#;
+        let err = select(input, &selection).unwrap_err();
+        assert_eq!(
+            err.path().map(|path| path.to_string()).as_deref(),
+            Some("/skip/1/b")
+        );
+
+        let options = ParserOptions::new().limits(Limits::new().max_depth(2));
+        let deep = r#"{"keep": 1, "skip": [[[]]]}"#;
+        let result =
+            JsonParser::with_options(deep, options).and_then(|mut p| p.parse_selected(&selection));
+        assert!(matches!(result, Err(JsonError::DepthLimitExceeded { .. })));
+    }
+
+    #[test]
+    fn test_duplicate_keys_in_selected_members() {
+        let input = r#"{"a": 1, "a": 2, "b": {"x": 1, "x": 2}}"#;
+        let selection = Selection::from_pointers(&["/a"]).unwrap();
+        let parse = |duplicate_keys| {
+            JsonParser::with_options(input, ParserOptions::new().duplicate_keys(duplicate_keys))
+                .and_then(|mut parser| parser.parse_selected(&selection))
+        };
+        assert_eq!(
+            parse(DuplicateKeys::LastWins).unwrap(),
+            parse_json(r#"{"a": 2}"#).unwrap()
+        );
+        assert_eq!(
+            parse(DuplicateKeys::Collect).unwrap().to_string(),
+            r#"{"a":1,"a":2}"#
+        );
+        assert!(matches!(
+            parse(DuplicateKeys::Error),
+            Err(JsonError::DuplicateKey { .. })
+        ));
+    }
+}
@@ -0,0 +1,936 @@
+use crate::canonical::es_number;
+use crate::value::{Escaping, escape_json_string_with};
+use crate::{Comments, JsonError, JsonObject, JsonPath, JsonString, JsonValue, Result};
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+use std::io;
+
+/// What to write for NaN and the infinities, which JSON has no syntax for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinite {
+    /// Fail with [`JsonError::NonFiniteNumber`]. Methods that cannot fail,
+    /// such as `to_string()`, write `null` instead.
+    #[default]
+    Error,
+    /// Write `null`, as JavaScript's `JSON.stringify` does.
+    Null,
+    /// Write `NaN`, `Infinity` or `-Infinity`, which JSON5 and Python's
+    /// `json` module accept. The output is not valid JSON.
+    Literal,
+}
+
+/// Layout choices for [`JsonValue::serialize_with`].
+///
+/// The default is the compact form `to_string()` produces. Setting an indent
+/// puts every element and member on its own line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatConfig {
+    indent: Option<String>,
+    item_separator: Cow<'static, str>,
+    pub(crate) key_separator: Cow<'static, str>,
+    sort_keys: bool,
+    pub(crate) trailing_newline: bool,
+    escaping: Escaping,
+    float_precision: Option<usize>,
+    integers_without_fraction: bool,
+    canonical_numbers: bool,
+    pub(crate) non_finite: NonFinite,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            indent: None,
+            item_separator: Cow::Borrowed(","),
+            key_separator: Cow::Borrowed(":"),
+            sort_keys: false,
+            trailing_newline: false,
+            escaping: Escaping::default(),
+            float_precision: None,
+            integers_without_fraction: false,
+            canonical_numbers: false,
+            non_finite: NonFinite::Error,
+        }
+    }
+}
+
+impl FormatConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The layout of [`JsonValue::to_string_pretty`]: `indent` spaces per
+    /// level and `": "` between keys and values.
+    pub fn pretty(indent: usize) -> Self {
+        Self::new().indent(" ".repeat(indent)).key_separator(": ")
+    }
+
+    /// Output that depends only on the value, for files kept under version
+    /// control: two-space indentation, keys sorted, numbers in canonical form
+    /// and a trailing newline. The same value always gives the same bytes, on
+    /// any machine.
+    pub fn deterministic() -> Self {
+        Self::pretty(2)
+            .sort_keys(true)
+            .canonical_numbers(true)
+            .trailing_newline(true)
+    }
+
+    /// Text repeated once per nesting level at the start of each line, such
+    /// as `"\t"`. An empty indent still breaks lines.
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = Some(indent.into());
+        self
+    }
+
+    /// Written between elements and members. When indenting, the line break
+    /// follows it.
+    pub fn item_separator(mut self, separator: impl Into<Cow<'static, str>>) -> Self {
+        self.item_separator = separator.into();
+        self
+    }
+
+    /// Written between a key and its value.
+    pub fn key_separator(mut self, separator: impl Into<Cow<'static, str>>) -> Self {
+        self.key_separator = separator.into();
+        self
+    }
+
+    /// Writes object members in key order instead of document order.
+    pub fn sort_keys(mut self, enabled: bool) -> Self {
+        self.sort_keys = enabled;
+        self
+    }
+
+    /// Ends the output with a newline, as text files conventionally do.
+    pub fn trailing_newline(mut self, enabled: bool) -> Self {
+        self.trailing_newline = enabled;
+        self
+    }
+
+    /// Writes numbers rounded to at most `digits` decimal places, without
+    /// trailing zeros (`12.34567` becomes `12.346` and `1.5` stays `1.5` for
+    /// 3). One zero is kept after the point of a number that rounds to a
+    /// whole one (`2.0`) unless [`FormatConfig::integers_without_fraction`]
+    /// is set. By default numbers use the shortest form that reads back to
+    /// the same value.
+    pub fn float_precision(mut self, digits: usize) -> Self {
+        self.float_precision = Some(digits);
+        self
+    }
+
+    /// Writes numbers without a fractional part as plain integers (`2`), even
+    /// when [`FormatConfig::float_precision`] is set and rounds them to one.
+    pub fn integers_without_fraction(mut self, enabled: bool) -> Self {
+        self.integers_without_fraction = enabled;
+        self
+    }
+
+    /// Writes numbers as ECMAScript and [`JsonValue::to_canonical_string`]
+    /// do: `-0` as `0`, and exponents only outside `1e-6..1e21` (`1e+21`,
+    /// `1e-7`). Has no effect when [`FormatConfig::float_precision`] is set.
+    pub fn canonical_numbers(mut self, enabled: bool) -> Self {
+        self.canonical_numbers = enabled;
+        self
+    }
+
+    /// How to write NaN and the infinities.
+    pub fn non_finite(mut self, policy: NonFinite) -> Self {
+        self.non_finite = policy;
+        self
+    }
+
+    /// Escapes every non-ASCII character as `\uXXXX`, using a surrogate pair
+    /// for characters above U+FFFF, so the output is pure ASCII.
+    pub fn ensure_ascii(mut self, enabled: bool) -> Self {
+        self.escaping.ascii = enabled;
+        self
+    }
+
+    /// Escapes `<`, `>`, `&`, U+2028 and U+2029 as `\uXXXX`, so the output
+    /// can be embedded in an HTML `<script>` element without closing it or
+    /// breaking older JavaScript parsers.
+    pub fn escape_html(mut self, enabled: bool) -> Self {
+        self.escaping.html = enabled;
+        self
+    }
+
+    /// Writes every `/` as `\/`, so `</` never appears literally.
+    pub fn escape_slash(mut self, enabled: bool) -> Self {
+        self.escaping.slash = enabled;
+        self
+    }
+}
+
+/// A container whose children are still being written.
+struct OpenContainer<'v> {
+    children: Children<'v>,
+    written: usize,
+    /// Key of the child most recently returned by `next`.
+    key: Option<&'v JsonString>,
+    close: char,
+}
+
+enum Children<'v> {
+    Array(std::slice::Iter<'v, JsonValue>),
+    Object(std::vec::IntoIter<(&'v JsonString, &'v JsonValue)>),
+}
+
+impl<'v> OpenContainer<'v> {
+    /// The next child, with its key when the container is an object.
+    fn next(&mut self) -> Option<(Option<&'v JsonString>, &'v JsonValue)> {
+        let next = match &mut self.children {
+            Children::Array(items) => items.next().map(|item| (None, item)),
+            Children::Object(members) => members.next().map(|(key, item)| (Some(key), item)),
+        };
+        if let Some((key, _)) = next {
+            self.written += 1;
+            self.key = key;
+        }
+        next
+    }
+}
+
+/// Writes values into `out` following a [`FormatConfig`].
+pub(crate) struct Serializer<'a, W> {
+    pub(crate) out: &'a mut W,
+    pub(crate) config: &'a FormatConfig,
+    /// Whether [`NonFinite::Error`] may fail; callers that cannot report
+    /// the failure get `null`.
+    can_fail: bool,
+    /// Pointer to the number refused under [`NonFinite::Error`].
+    pub(crate) refused: Option<String>,
+    /// Comments to write before the nodes they belong to.
+    comments: Option<&'a Comments>,
+}
+
+impl<'a, W: Write> Serializer<'a, W> {
+    pub(crate) fn new(out: &'a mut W, config: &'a FormatConfig) -> Self {
+        Serializer {
+            out,
+            config,
+            can_fail: true,
+            refused: None,
+            comments: None,
+        }
+    }
+
+    fn document(&mut self, value: &JsonValue) -> fmt::Result {
+        if let Some(comments) = self.comments {
+            self.comments(comments.get(&JsonPath::new()), 0)?;
+        }
+        self.value(value, 0)?;
+        if self.config.trailing_newline {
+            self.out.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    /// Writes `value`, whose first line sits at nesting level `depth`.
+    ///
+    /// Open containers are tracked on an explicit stack rather than by
+    /// recursion, so nesting depth is limited only by memory.
+    pub(crate) fn value(&mut self, value: &JsonValue, depth: usize) -> fmt::Result {
+        let mut open: Vec<OpenContainer<'_>> = Vec::new();
+        // Only kept up to date when there are comments to look up
+        let mut path = JsonPath::new();
+        self.begin(value, &mut open)?;
+        while let Some(container) = open.last_mut() {
+            let index = container.written;
+            match container.next() {
+                Some((key, child)) => {
+                    self.separator(index, depth + open.len())?;
+                    if let Some(comments) = self.comments {
+                        match key {
+                            Some(key) => path.push_key(key.as_str()),
+                            None => path.push_index(index),
+                        }
+                        self.comments(comments.get(&path), depth + open.len())?;
+                    }
+                    if let Some(key) = key {
+                        self.string(key)?;
+                        self.out.write_str(&self.config.key_separator)?;
+                    }
+                    let opened = open.len();
+                    self.begin(child, &mut open)?;
+                    if self.comments.is_some() && open.len() == opened {
+                        path.pop();
+                    }
+                }
+                None => {
+                    let close = container.close;
+                    open.pop();
+                    if self.comments.is_some() && !open.is_empty() {
+                        path.pop();
+                    }
+                    if index > 0 {
+                        self.line_break(depth + open.len())?;
+                    }
+                    self.out.write_char(close)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes a scalar or empty container whole, or opens a container and
+    /// pushes it so its children are written next.
+    fn begin<'v>(
+        &mut self,
+        value: &'v JsonValue,
+        open: &mut Vec<OpenContainer<'v>>,
+    ) -> fmt::Result {
+        match value {
+            JsonValue::Null => self.out.write_str("null"),
+            JsonValue::Boolean(b) => write!(self.out, "{b}"),
+            JsonValue::Number(n) if !n.is_finite() => self.non_finite(*n, open),
+            JsonValue::Number(n) => self.number(*n),
+            JsonValue::String(s) => self.string(s),
+            JsonValue::Array(arr) if arr.is_empty() => self.out.write_str("[]"),
+            JsonValue::Object(obj) if obj.is_empty() => self.out.write_str("{}"),
+            JsonValue::Array(arr) => {
+                open.push(OpenContainer {
+                    children: Children::Array(arr.iter()),
+                    written: 0,
+                    key: None,
+                    close: ']',
+                });
+                self.out.write_char('[')
+            }
+            JsonValue::Object(obj) => {
+                open.push(OpenContainer {
+                    children: Children::Object(self.members(obj).into_iter()),
+                    written: 0,
+                    key: None,
+                    close: '}',
+                });
+                self.out.write_char('{')
+            }
+        }
+    }
+
+    fn number(&mut self, n: f64) -> fmt::Result {
+        match self.config.float_precision {
+            Some(digits) => {
+                let mut text = format!("{n:.digits$}");
+                if text.contains('.') {
+                    text.truncate(text.trim_end_matches('0').len());
+                    if text.ends_with('.') {
+                        if self.config.integers_without_fraction {
+                            text.pop();
+                        } else {
+                            text.push('0');
+                        }
+                    }
+                }
+                self.out.write_str(&text)
+            }
+            None if self.config.canonical_numbers => self.out.write_str(&es_number(n)),
+            None => write!(self.out, "{n}"),
+        }
+    }
+
+    /// Writes NaN or an infinity as [`FormatConfig::non_finite`] says, with
+    /// `open` locating it for the error.
+    fn non_finite(&mut self, n: f64, open: &[OpenContainer<'_>]) -> fmt::Result {
+        match self.config.non_finite {
+            NonFinite::Error if self.can_fail => {
+                let mut path = JsonPath::new();
+                for container in open {
+                    match container.key {
+                        Some(key) => path.push_key(key.as_str()),
+                        None => path.push_index(container.written - 1),
+                    }
+                }
+                self.refused = Some(path.to_pointer());
+                Err(fmt::Error)
+            }
+            NonFinite::Error | NonFinite::Null => self.out.write_str("null"),
+            NonFinite::Literal if n.is_nan() => self.out.write_str("NaN"),
+            NonFinite::Literal if n > 0.0 => self.out.write_str("Infinity"),
+            NonFinite::Literal => self.out.write_str("-Infinity"),
+        }
+    }
+
+    /// Writes each comment followed by a break back to `depth`. Without an
+    /// indent, block comments share the line and line comments end it.
+    fn comments(&mut self, comments: &[String], depth: usize) -> fmt::Result {
+        for comment in comments {
+            self.out.write_str(comment)?;
+            if self.config.indent.is_some() {
+                self.line_break(depth)?;
+            } else if comment.starts_with("//") {
+                self.out.write_char('\n')?;
+            } else {
+                self.out.write_char(' ')?;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn string(&mut self, s: &str) -> fmt::Result {
+        self.out
+            .write_str(&escape_json_string_with(s, self.config.escaping))
+    }
+
+    fn members<'v>(&self, obj: &'v JsonObject) -> Vec<(&'v JsonString, &'v JsonValue)> {
+        let mut members: Vec<_> = obj.iter().collect();
+        if self.config.sort_keys {
+            members.sort_by(|a, b| a.0.cmp(b.0));
+        }
+        members
+    }
+
+    /// Goes before the `i`-th child of a container.
+    pub(crate) fn separator(&mut self, i: usize, depth: usize) -> fmt::Result {
+        if i > 0 {
+            self.out.write_str(&self.config.item_separator)?;
+        }
+        self.line_break(depth)
+    }
+
+    pub(crate) fn line_break(&mut self, depth: usize) -> fmt::Result {
+        if let Some(indent) = &self.config.indent {
+            self.out.write_char('\n')?;
+            if !indent.is_empty() {
+                for _ in 0..depth {
+                    self.out.write_str(indent)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adapts an `io::Write` to `fmt::Write`, keeping the I/O error that
+/// `fmt::Error` cannot carry.
+#[derive(Debug)]
+pub(crate) struct IoWriter<W> {
+    pub(crate) inner: W,
+    pub(crate) error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|err| {
+            self.error = Some(err);
+            fmt::Error
+        })
+    }
+}
+
+/// Appends to a byte buffer, skipping the intermediate `String`.
+struct VecWriter<'a>(&'a mut Vec<u8>);
+
+impl Write for VecWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Fills a caller's slice, failing once it is full.
+struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        let dest = self.buf.get_mut(self.len..end).ok_or(fmt::Error)?;
+        dest.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// A `fmt::Write` sink that refuses to grow past a byte budget.
+struct BudgetWriter {
+    out: String,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl BudgetWriter {
+    fn new(limit: usize) -> Self {
+        Self {
+            out: String::new(),
+            limit,
+            exceeded: false,
+        }
+    }
+
+    fn finish(self, result: fmt::Result) -> Result<String> {
+        match result {
+            Ok(()) => Ok(self.out),
+            Err(_) if self.exceeded => Err(JsonError::OutputLimitExceeded { limit: self.limit }),
+            // Only the budget check makes writing into a String fail
+            Err(_) => unreachable!("formatting into a String failed"),
+        }
+    }
+}
+
+impl Write for BudgetWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.out.len() + s.len() > self.limit {
+            self.exceeded = true;
+            return Err(fmt::Error);
+        }
+        self.out.push_str(s);
+        Ok(())
+    }
+}
+
+/// Indentation used by [`to_string_pretty`].
+const DEFAULT_INDENT: usize = 2;
+
+/// Serializes `value` with two-space indentation.
+pub fn to_string_pretty(value: &JsonValue) -> String {
+    value.to_string_pretty(DEFAULT_INDENT)
+}
+
+impl JsonValue {
+    /// Serializes with one member or element per line, nested `indent`
+    /// spaces deeper per level. Use `to_string()` for the compact form.
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        self.serialize_with(&FormatConfig::pretty(indent))
+    }
+
+    /// Serializes following `config`.
+    ///
+    /// This cannot fail, so under [`NonFinite::Error`] non-finite numbers
+    /// are written as `null`; [`JsonValue::try_serialize_with`] reports them.
+    pub fn serialize_with(&self, config: &FormatConfig) -> String {
+        let mut out = String::new();
+        // Writing into a String cannot fail
+        let _ = self.write_with(&mut out, config);
+        out
+    }
+
+    /// Like [`JsonValue::serialize_with`], writing each of `comments` on the
+    /// line before the node it belongs to. The output is JSONC: read it back
+    /// with [`ParserOptions::allow_comments`](crate::ParserOptions::allow_comments).
+    pub fn serialize_with_comments(&self, config: &FormatConfig, comments: &Comments) -> String {
+        let mut out = String::new();
+        let mut ser = Serializer::new(&mut out, config);
+        ser.can_fail = false;
+        ser.comments = Some(comments);
+        // Writing into a String cannot fail
+        let _ = ser.document(self);
+        out
+    }
+
+    /// Like [`JsonValue::serialize_with`], but fails with
+    /// [`JsonError::NonFiniteNumber`] under [`NonFinite::Error`].
+    pub fn try_serialize_with(&self, config: &FormatConfig) -> Result<String> {
+        let mut out = String::new();
+        // Writing into a String cannot fail
+        let _ = self.try_write_with(&mut out, config)?;
+        Ok(out)
+    }
+
+    /// Writes the compact form straight into `writer`, without building the
+    /// whole string first.
+    ///
+    /// Output goes out in many small writes, so wrap unbuffered sinks such as
+    /// files and sockets in an [`io::BufWriter`].
+    pub fn to_writer<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        self.to_writer_with(writer, &FormatConfig::new())
+    }
+
+    /// Like [`JsonValue::to_writer`], indented as by [`JsonValue::to_string_pretty`].
+    pub fn to_writer_pretty<W: io::Write>(&self, writer: W, indent: usize) -> io::Result<()> {
+        self.to_writer_with(writer, &FormatConfig::pretty(indent))
+    }
+
+    /// Like [`JsonValue::to_writer`], following `config`. A number refused
+    /// under [`NonFinite::Error`] fails with [`io::ErrorKind::InvalidData`],
+    /// wrapping the [`JsonError::NonFiniteNumber`].
+    pub fn to_writer_with<W: io::Write>(&self, writer: W, config: &FormatConfig) -> io::Result<()> {
+        let mut out = IoWriter {
+            inner: writer,
+            error: None,
+        };
+        let result = self
+            .try_write_with(&mut out, config)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        match result {
+            Ok(()) => Ok(()),
+            Err(_) => Err(out
+                .error
+                .unwrap_or_else(|| io::Error::other("formatting failed"))),
+        }
+    }
+
+    /// Writes without failing on non-finite numbers, for callers such as
+    /// `Display` that cannot report them.
+    pub(crate) fn write_with<W: Write>(&self, out: &mut W, config: &FormatConfig) -> fmt::Result {
+        let mut ser = Serializer::new(out, config);
+        ser.can_fail = false;
+        ser.document(self)
+    }
+
+    /// The outer error is a number refused under [`NonFinite::Error`]; the
+    /// inner result is the sink's.
+    fn try_write_with<W: Write>(&self, out: &mut W, config: &FormatConfig) -> Result<fmt::Result> {
+        let mut ser = Serializer::new(out, config);
+        let result = ser.document(self);
+        match ser.refused {
+            Some(pointer) => Err(JsonError::NonFiniteNumber { pointer }),
+            None => Ok(result),
+        }
+    }
+
+    /// Compact serialization that fails with [`JsonError::OutputLimitExceeded`]
+    /// as soon as the output would grow past `max_output_bytes`.
+    pub fn to_string_limited(&self, max_output_bytes: usize) -> Result<String> {
+        self.serialize_with_limited(&FormatConfig::new(), max_output_bytes)
+    }
+
+    /// Pretty serialization with the same output budget as [`JsonValue::to_string_limited`].
+    pub fn pretty_print_limited(&self, indent: usize, max_output_bytes: usize) -> Result<String> {
+        self.serialize_with_limited(&FormatConfig::pretty(indent), max_output_bytes)
+    }
+
+    /// The compact form as UTF-8 bytes.
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.serialize_into(&mut buf);
+        buf
+    }
+
+    /// Appends the compact form to `buf`. Clearing and reusing one buffer
+    /// across many values avoids allocating for each of them.
+    pub fn serialize_into(&self, buf: &mut Vec<u8>) {
+        // Writing into a Vec cannot fail
+        let _ = self.write_with(&mut VecWriter(buf), &FormatConfig::new());
+    }
+
+    /// Writes the compact form to the start of `buf` and returns how many
+    /// bytes it took.
+    ///
+    /// Fails with [`JsonError::OutputLimitExceeded`] when `buf` is too small,
+    /// leaving its contents unspecified, and with
+    /// [`JsonError::NonFiniteNumber`] as [`JsonValue::try_serialize_with`] does.
+    pub fn serialize_into_slice(&self, buf: &mut [u8]) -> Result<usize> {
+        let limit = buf.len();
+        let mut writer = SliceWriter { buf, len: 0 };
+        match self.try_write_with(&mut writer, &FormatConfig::new())? {
+            Ok(()) => Ok(writer.len),
+            Err(_) => Err(JsonError::OutputLimitExceeded { limit }),
+        }
+    }
+
+    /// [`JsonValue::try_serialize_with`] with the same output budget as
+    /// [`JsonValue::to_string_limited`].
+    pub fn serialize_with_limited(
+        &self,
+        config: &FormatConfig,
+        max_output_bytes: usize,
+    ) -> Result<String> {
+        let mut writer = BudgetWriter::new(max_output_bytes);
+        let result = self.try_write_with(&mut writer, config)?;
+        writer.finish(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_compact_within_budget() {
+        let value = parse_json("[1, 2, 3]").unwrap();
+        assert_eq!(value.to_string_limited(7).unwrap(), "[1,2,3]");
+    }
+
+    #[test]
+    fn test_byte_outputs_match_to_string() {
+        let value = parse_json(r#"{"a": [1, "é"], "b": null}"#).unwrap();
+        let expected = value.to_string();
+        assert_eq!(value.to_vec(), expected.as_bytes());
+
+        let mut buf = b"prefix:".to_vec();
+        value.serialize_into(&mut buf);
+        assert_eq!(buf, format!("prefix:{expected}").as_bytes());
+
+        let mut slice = [0u8; 64];
+        let len = value.serialize_into_slice(&mut slice).unwrap();
+        assert_eq!(&slice[..len], expected.as_bytes());
+    }
+
+    #[test]
+    fn test_serialize_into_small_slice() {
+        let value = parse_json("[1, 2, 3]").unwrap();
+        let mut exact = [0u8; 7];
+        assert_eq!(value.serialize_into_slice(&mut exact), Ok(7));
+        let mut short = [0u8; 6];
+        assert_eq!(
+            value.serialize_into_slice(&mut short),
+            Err(JsonError::OutputLimitExceeded { limit: 6 })
+        );
+        assert!(matches!(
+            JsonValue::Number(f64::NAN).serialize_into_slice(&mut exact),
+            Err(JsonError::NonFiniteNumber { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compact_over_budget() {
+        let value = parse_json("[1, 2, 3]").unwrap();
+        assert_eq!(
+            value.to_string_limited(6),
+            Err(JsonError::OutputLimitExceeded { limit: 6 })
+        );
+    }
+
+    #[test]
+    fn test_pretty_matches_unlimited_output() {
+        let value = parse_json(r#"{"a": [1, {"b": null}], "c": []}"#).unwrap();
+        let expected = value.pretty_print(2);
+        assert_eq!(
+            value.pretty_print_limited(2, expected.len()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_pretty_amplification_is_stopped() {
+        // A small document whose indented form is far larger than its input
+        let input = format!("{}{}", "[".repeat(50), "]".repeat(50));
+        let value = parse_json(&input).unwrap();
+        assert!(matches!(
+            value.pretty_print_limited(4, input.len() * 2),
+            Err(JsonError::OutputLimitExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pretty_print_layout() {
+        let value = parse_json("[1, [true], {}]").unwrap();
+        assert_eq!(
+            value.pretty_print(2),
+            "[\n  1,\n  [\n    true\n  ],\n  {}\n]"
+        );
+    }
+
+    #[test]
+    fn test_to_string_pretty() {
+        let value = parse_json(r#"{"a": [1], "b": {}}"#).unwrap();
+        let expected = "{\n  \"a\": [\n    1\n  ],\n  \"b\": {}\n}";
+        assert_eq!(value.to_string_pretty(2), expected);
+        assert_eq!(to_string_pretty(&value), expected);
+        assert_eq!(value.to_string_pretty(2), value.pretty_print(2));
+    }
+
+    #[test]
+    fn test_pretty_output_escapes_strings() {
+        let value = parse_json(r#"{"say \"hi\"": "line\nbreak \\ done"}"#).unwrap();
+        let pretty = value.to_string_pretty(0);
+        assert_eq!(
+            pretty,
+            "{\n\"say \\\"hi\\\"\": \"line\\nbreak \\\\ done\"\n}"
+        );
+        assert_eq!(parse_json(&pretty).unwrap(), value);
+    }
+
+    #[test]
+    fn test_default_config_is_compact() {
+        let value = parse_json(r#"{"b": [1, "x"], "a": {}}"#).unwrap();
+        assert_eq!(
+            value.serialize_with(&FormatConfig::new()),
+            value.to_string()
+        );
+    }
+
+    #[test]
+    fn test_tabs_and_sorted_keys() {
+        let value = parse_json(r#"{"b": [1, 2], "a": {"z": null, "y": true}}"#).unwrap();
+        let config = FormatConfig::pretty(0)
+            .indent("\t")
+            .sort_keys(true)
+            .trailing_newline(true);
+        assert_eq!(
+            value.serialize_with(&config),
+            "{\n\t\"a\": {\n\t\t\"y\": true,\n\t\t\"z\": null\n\t},\n\t\"b\": [\n\t\t1,\n\t\t2\n\t]\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_custom_separators_on_one_line() {
+        let value = parse_json(r#"{"a": [1, 2], "b": 3}"#).unwrap();
+        let config = FormatConfig::new().item_separator(", ").key_separator(": ");
+        assert_eq!(value.serialize_with(&config), r#"{"a": [1, 2], "b": 3}"#);
+    }
+
+    #[test]
+    fn test_alternate_display_is_pretty() {
+        let value = parse_json(r#"{"a": [1, "x"]}"#).unwrap();
+        assert_eq!(format!("{value:#}"), value.to_string_pretty(2));
+        assert_eq!(format!("{value}"), r#"{"a":[1,"x"]}"#);
+    }
+
+    #[test]
+    fn test_ensure_ascii() {
+        let value = parse_json(r#"{"café": "😀 ok"}"#).unwrap();
+        let ascii = value.serialize_with(&FormatConfig::new().ensure_ascii(true));
+        assert_eq!(ascii, r#"{"caf\u00e9":"\ud83d\ude00 ok"}"#);
+        assert!(ascii.is_ascii());
+        assert_eq!(parse_json(&ascii).unwrap(), value);
+        assert_eq!(value.to_string(), r#"{"café":"😀 ok"}"#);
+    }
+
+    #[test]
+    fn test_escape_html() {
+        let value = JsonValue::String("</script><b>&\u{2028}\u{2029}".into());
+        let safe = value.serialize_with(&FormatConfig::new().escape_html(true));
+        assert_eq!(
+            safe,
+            r#""\u003c/script\u003e\u003cb\u003e\u0026\u2028\u2029""#
+        );
+        assert_eq!(parse_json(&safe).unwrap(), value);
+        assert!(value.to_string().contains("</script>"));
+    }
+
+    #[test]
+    fn test_escape_slash() {
+        let value = parse_json(r#"{"url": "https://x.io/a</b>"}"#).unwrap();
+        let escaped = value.serialize_with(&FormatConfig::new().escape_slash(true));
+        assert_eq!(escaped, r#"{"url":"https:\/\/x.io\/a<\/b>"}"#);
+        assert!(!escaped.contains("</"));
+        assert_eq!(parse_json(&escaped).unwrap(), value);
+    }
+
+    #[test]
+    fn test_to_writer_matches_string_output() {
+        let value = parse_json(r#"{"a": [1, "x\n"], "b": null}"#).unwrap();
+        let mut compact = Vec::new();
+        value.to_writer(&mut compact).unwrap();
+        assert_eq!(String::from_utf8(compact).unwrap(), value.to_string());
+
+        let mut pretty = Vec::new();
+        value.to_writer_pretty(&mut pretty, 4).unwrap();
+        assert_eq!(
+            String::from_utf8(pretty).unwrap(),
+            value.to_string_pretty(4)
+        );
+    }
+
+    #[test]
+    fn test_to_writer_reports_io_errors() {
+        struct Full;
+        impl io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let err = JsonValue::Null.to_writer(Full).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::StorageFull);
+    }
+
+    #[test]
+    fn test_number_formatting() {
+        let value = parse_json("[2, 0.5, 12.34567, -0.0004, 1.9996]").unwrap();
+        let cases = vec![
+            (FormatConfig::new(), "[2,0.5,12.34567,-0.0004,1.9996]"),
+            (
+                FormatConfig::new().float_precision(3),
+                "[2.0,0.5,12.346,-0.0,2.0]",
+            ),
+            (
+                FormatConfig::new()
+                    .float_precision(3)
+                    .integers_without_fraction(true),
+                "[2,0.5,12.346,-0,2]",
+            ),
+            (FormatConfig::new().float_precision(0), "[2,0,12,-0,2]"),
+        ];
+        for (config, expected) in cases {
+            assert_eq!(value.serialize_with(&config), expected, "{config:?}");
+        }
+    }
+
+    #[test]
+    fn test_non_finite_policy() {
+        let value = JsonValue::Array(
+            vec![
+                JsonValue::Number(f64::NAN),
+                JsonValue::Number(f64::INFINITY),
+                JsonValue::Number(f64::NEG_INFINITY),
+            ]
+            .into(),
+        );
+        let cases = vec![
+            (NonFinite::Null, "[null,null,null]"),
+            (NonFinite::Literal, "[NaN,Infinity,-Infinity]"),
+        ];
+        for (policy, expected) in cases {
+            let config = FormatConfig::new().non_finite(policy);
+            assert_eq!(value.try_serialize_with(&config).unwrap(), expected);
+            assert_eq!(value.serialize_with(&config), expected);
+        }
+
+        // The strict default fails where it can and writes null where it cannot
+        let err = JsonError::NonFiniteNumber {
+            pointer: "/0".to_string(),
+        };
+        assert_eq!(
+            value.try_serialize_with(&FormatConfig::new()),
+            Err(err.clone())
+        );
+        assert_eq!(value.to_string_limited(100), Err(err));
+        assert_eq!(value.to_string(), "[null,null,null]");
+        let io_err = value.to_writer(Vec::new()).unwrap_err();
+        assert_eq!(io_err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_non_finite_error_points_at_the_number() {
+        let mut value = parse_json(r#"{"a": [1, {"b~c": 2}]}"#).unwrap();
+        *value.pointer_mut("/a/1/b~0c").unwrap() = JsonValue::Number(f64::NAN);
+        assert_eq!(
+            value.try_serialize_with(&FormatConfig::pretty(2)),
+            Err(JsonError::NonFiniteNumber {
+                pointer: "/a/1/b~0c".to_string()
+            })
+        );
+        assert_eq!(
+            JsonValue::Number(f64::NAN).try_serialize_with(&FormatConfig::new()),
+            Err(JsonError::NonFiniteNumber {
+                pointer: String::new()
+            })
+        );
+    }
+
+    #[test]
+    fn test_deterministic_preset() {
+        let a = parse_json(r#"{"b": [1e21, -0, 1e-7], "a": {"y": 0.5, "x": "é"}}"#).unwrap();
+        let b = parse_json(r#"{"a": {"x": "é", "y": 0.5}, "b": [1e21, 0, 1e-7]}"#).unwrap();
+        let config = FormatConfig::deterministic();
+        assert_eq!(
+            a.serialize_with(&config),
+            "{\n  \"a\": {\n    \"x\": \"é\",\n    \"y\": 0.5\n  },\n  \"b\": [\n    1e+21,\n    0,\n    1e-7\n  ]\n}\n"
+        );
+        assert_eq!(a.serialize_with(&config), b.serialize_with(&config));
+        assert_eq!(parse_json(&a.serialize_with(&config)).unwrap(), a);
+    }
+
+    #[test]
+    fn test_deep_nesting_does_not_recurse() {
+        let depth = 100_000;
+        let mut value = JsonValue::Null;
+        for _ in 0..depth {
+            value = JsonValue::Array(vec![value].into());
+        }
+        let compact = value.to_string();
+        assert_eq!(compact.len(), 2 * depth + 4);
+        assert_eq!(&compact[depth - 1..depth + 5], "[null]");
+        assert_eq!(value.to_string_pretty(0).lines().count(), 2 * depth + 1);
+    }
+}
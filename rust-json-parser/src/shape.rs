@@ -0,0 +1,365 @@
+use crate::{JsonPath, JsonValue};
+use std::fmt;
+
+/// The expected structure of a value, built in code and checked with
+/// [`Shape::check`]: a lighter alternative to a JSON Schema for asserting
+/// what an API returned.
+///
+/// Object shapes allow members they do not mention unless made
+/// [`exact`](Shape::exact).
+///
+/// ```
+/// use rust_json_parser::{JsonParser, Shape};
+///
+/// let shape = Shape::object()
+///     .key("id", Shape::int())
+///     .key("tags", Shape::array_of(Shape::string()))
+///     .optional_key("owner", Shape::string().nullable());
+///
+/// let response = JsonParser::new(r#"{"id": 1.5, "tags": ["a", 2]}"#)?.parse()?;
+/// let mismatches = shape.check(&response).unwrap_err();
+/// let messages: Vec<String> = mismatches.iter().map(|m| m.to_string()).collect();
+/// assert_eq!(
+///     messages,
+///     [
+///         "Expected integer at '/id', found number",
+///         "Expected string at '/tags/1', found number",
+///     ]
+/// );
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shape {
+    pub(crate) kind: Kind,
+    pub(crate) nullable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Kind {
+    Any,
+    Null,
+    Bool,
+    /// A number with no fractional part.
+    Int,
+    Number,
+    String,
+    ArrayOf(Box<Shape>),
+    Object {
+        members: Vec<Member>,
+        exact: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Member {
+    pub(crate) key: String,
+    pub(crate) shape: Shape,
+    pub(crate) required: bool,
+}
+
+/// One place where a value does not fit its [`Shape`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShapeMismatch {
+    pub path: JsonPath,
+    /// What the shape wanted here, such as `"integer or null"`; `None` for
+    /// a member an exact object does not allow.
+    pub expected: Option<String>,
+    /// The JSON type present, or `None` when a required member is missing.
+    pub found: Option<&'static str>,
+}
+
+impl fmt::Display for ShapeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = if self.path.is_root() {
+            "the document root".to_string()
+        } else {
+            format!("'{}'", self.path)
+        };
+        match (&self.expected, self.found) {
+            (Some(expected), Some(found)) => {
+                write!(f, "Expected {expected} at {location}, found {found}")
+            }
+            (Some(expected), None) => write!(f, "Missing {expected} at {location}"),
+            (None, found) => write!(f, "Unexpected {} at {location}", found.unwrap_or("value")),
+        }
+    }
+}
+
+impl std::error::Error for ShapeMismatch {}
+
+impl Shape {
+    fn new(kind: Kind) -> Self {
+        Shape {
+            kind,
+            nullable: false,
+        }
+    }
+
+    /// Any value at all.
+    pub fn any() -> Self {
+        Self::new(Kind::Any)
+    }
+
+    pub fn null() -> Self {
+        Self::new(Kind::Null)
+    }
+
+    pub fn bool() -> Self {
+        Self::new(Kind::Bool)
+    }
+
+    /// A number with no fractional part.
+    pub fn int() -> Self {
+        Self::new(Kind::Int)
+    }
+
+    pub fn number() -> Self {
+        Self::new(Kind::Number)
+    }
+
+    pub fn string() -> Self {
+        Self::new(Kind::String)
+    }
+
+    /// An array whose every element fits `items`.
+    pub fn array_of(items: Shape) -> Self {
+        Self::new(Kind::ArrayOf(Box::new(items)))
+    }
+
+    /// An object; add its members with [`key`](Shape::key) and
+    /// [`optional_key`](Shape::optional_key).
+    pub fn object() -> Self {
+        Self::new(Kind::Object {
+            members: Vec::new(),
+            exact: false,
+        })
+    }
+
+    /// Requires the member `key`, fitting `shape`. Panics if this is not
+    /// an object shape.
+    pub fn key(self, key: impl Into<String>, shape: Shape) -> Self {
+        self.member(key.into(), shape, true)
+    }
+
+    /// Allows the member `key`, which must fit `shape` when present.
+    /// Panics if this is not an object shape.
+    pub fn optional_key(self, key: impl Into<String>, shape: Shape) -> Self {
+        self.member(key.into(), shape, false)
+    }
+
+    fn member(mut self, key: String, shape: Shape, required: bool) -> Self {
+        let Kind::Object { members, .. } = &mut self.kind else {
+            panic!("Shape::key on a non-object shape");
+        };
+        members.retain(|member| member.key != key);
+        members.push(Member {
+            key,
+            shape,
+            required,
+        });
+        self
+    }
+
+    /// Rejects object members not named by [`key`](Shape::key) or
+    /// [`optional_key`](Shape::optional_key). No effect on other shapes.
+    pub fn exact(mut self) -> Self {
+        if let Kind::Object { exact, .. } = &mut self.kind {
+            *exact = true;
+        }
+        self
+    }
+
+    /// Also accepts `null`.
+    pub fn nullable(mut self) -> Self {
+        self.nullable = true;
+        self
+    }
+
+    /// Checks `value` against the shape, returning every mismatch, in
+    /// document order, if there are any.
+    pub fn check(&self, value: &JsonValue) -> Result<(), Vec<ShapeMismatch>> {
+        let mut mismatches = Vec::new();
+        self.check_at(value, &mut JsonPath::new(), &mut mismatches);
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatches)
+        }
+    }
+
+    pub fn matches(&self, value: &JsonValue) -> bool {
+        self.check(value).is_ok()
+    }
+
+    /// What the shape accepts, as used in mismatch messages.
+    fn expected(&self) -> String {
+        let name = match self.kind {
+            Kind::Any => "any value",
+            Kind::Null => "null",
+            Kind::Bool => "boolean",
+            Kind::Int => "integer",
+            Kind::Number => "number",
+            Kind::String => "string",
+            Kind::ArrayOf(_) => "array",
+            Kind::Object { .. } => "object",
+        };
+        if self.nullable && self.kind != Kind::Null {
+            format!("{name} or null")
+        } else {
+            name.to_string()
+        }
+    }
+
+    fn check_at(&self, value: &JsonValue, path: &mut JsonPath, out: &mut Vec<ShapeMismatch>) {
+        if self.nullable && value.is_null() {
+            return;
+        }
+        let fits = match (&self.kind, value) {
+            (Kind::Any, _)
+            | (Kind::Null, JsonValue::Null)
+            | (Kind::Bool, JsonValue::Boolean(_))
+            | (Kind::Number, JsonValue::Number(_))
+            | (Kind::String, JsonValue::String(_)) => true,
+            (Kind::Int, JsonValue::Number(n)) => n.is_finite() && n.fract() == 0.0,
+            (Kind::ArrayOf(items), JsonValue::Array(elements)) => {
+                for (index, element) in elements.iter().enumerate() {
+                    path.push_index(index);
+                    items.check_at(element, path, out);
+                    path.pop();
+                }
+                true
+            }
+            (Kind::Object { members, exact }, JsonValue::Object(object)) => {
+                for member in members {
+                    path.push_key(member.key.clone());
+                    match object.get(&member.key) {
+                        Some(value) => member.shape.check_at(value, path, out),
+                        None if member.required => out.push(ShapeMismatch {
+                            path: path.clone(),
+                            expected: Some(member.shape.expected()),
+                            found: None,
+                        }),
+                        None => {}
+                    }
+                    path.pop();
+                }
+                if *exact {
+                    for (key, value) in object.iter() {
+                        if !members.iter().any(|member| member.key == key.as_str()) {
+                            path.push_key(key.clone());
+                            out.push(ShapeMismatch {
+                                path: path.clone(),
+                                expected: None,
+                                found: Some(value.type_name()),
+                            });
+                            path.pop();
+                        }
+                    }
+                }
+                true
+            }
+            _ => false,
+        };
+        if !fits {
+            out.push(ShapeMismatch {
+                path: path.clone(),
+                expected: Some(self.expected()),
+                found: Some(value.type_name()),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn mismatches(shape: &Shape, input: &str) -> Vec<String> {
+        match shape.check(&parse_json(input).unwrap()) {
+            Ok(()) => Vec::new(),
+            Err(mismatches) => mismatches.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_scalars() {
+        let cases = vec![
+            (Shape::int(), "3", true),
+            (Shape::int(), "3.5", false),
+            (Shape::number(), "3.5", true),
+            (Shape::string(), r#""x""#, true),
+            (Shape::string(), "null", false),
+            (Shape::string().nullable(), "null", true),
+            (Shape::bool(), "false", true),
+            (Shape::null(), "0", false),
+            (Shape::any(), "[1]", true),
+        ];
+        for (shape, input, fits) in cases {
+            assert_eq!(
+                shape.matches(&parse_json(input).unwrap()),
+                fits,
+                "{shape:?} {input}"
+            );
+        }
+        assert_eq!(
+            mismatches(&Shape::int().nullable(), r#""1""#),
+            ["Expected integer or null at the document root, found string"]
+        );
+    }
+
+    #[test]
+    fn test_reports_every_mismatch_with_paths() {
+        let shape = Shape::object()
+            .key("id", Shape::int())
+            .key("name", Shape::string())
+            .key(
+                "items",
+                Shape::array_of(Shape::object().key("sku", Shape::string())),
+            )
+            .optional_key("note", Shape::string());
+        assert_eq!(
+            mismatches(
+                &shape,
+                r#"{"id": "7", "items": [{"sku": "a"}, {"sku": 2}, {}], "note": null}"#
+            ),
+            [
+                "Expected integer at '/id', found string",
+                "Missing string at '/name'",
+                "Expected string at '/items/1/sku', found number",
+                "Missing string at '/items/2/sku'",
+                "Expected string at '/note', found null",
+            ]
+        );
+        assert!(mismatches(&shape, r#"{"id": 7, "name": "n", "items": [], "x": 1}"#).is_empty());
+    }
+
+    #[test]
+    fn test_exact_objects_reject_unknown_keys() {
+        let shape = Shape::object()
+            .key("a", Shape::int())
+            .optional_key("b", Shape::int())
+            .exact();
+        assert!(shape.matches(&parse_json(r#"{"a": 1}"#).unwrap()));
+        assert_eq!(
+            mismatches(&shape, r#"{"a": 1, "c": "x"}"#),
+            ["Unexpected string at '/c'"]
+        );
+    }
+
+    #[test]
+    fn test_key_replaces_earlier_definition() {
+        let shape = Shape::object()
+            .key("a", Shape::int())
+            .optional_key("a", Shape::string());
+        assert!(shape.matches(&parse_json("{}").unwrap()));
+        assert!(!shape.matches(&parse_json(r#"{"a": 1}"#).unwrap()));
+    }
+
+    #[test]
+    #[should_panic(expected = "non-object shape")]
+    fn test_key_on_non_object_panics() {
+        let _ = Shape::int().key("a", Shape::int());
+    }
+}
@@ -0,0 +1,64 @@
+use crate::JsonValue;
+
+impl JsonValue {
+    /// Approximate memory held by this value, in bytes: the value itself plus
+    /// everything it owns on the heap (string buffers, spare array capacity,
+    /// object members and their key index).
+    ///
+    /// Allocator bookkeeping is not counted, so the real footprint is
+    /// somewhat larger. Good enough for budgeting a cache, not for exact
+    /// accounting.
+    pub fn deep_size_of(&self) -> usize {
+        size_of::<JsonValue>() + self.heap_size_of()
+    }
+
+    /// Heap bytes owned by this value, excluding its own inline size.
+    fn heap_size_of(&self) -> usize {
+        match self {
+            JsonValue::Null | JsonValue::Boolean(_) | JsonValue::Number(_) => 0,
+            JsonValue::String(s) => s.heap_bytes(),
+            JsonValue::Array(arr) => {
+                arr.capacity() * size_of::<JsonValue>()
+                    + arr.iter().map(JsonValue::heap_size_of).sum::<usize>()
+            }
+            JsonValue::Object(obj) => {
+                obj.heap_bytes() + obj.values().map(JsonValue::heap_size_of).sum::<usize>()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonArray;
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_scalars_take_inline_size() {
+        let inline = size_of::<JsonValue>();
+        assert_eq!(JsonValue::Null.deep_size_of(), inline);
+        assert_eq!(JsonValue::Number(1.0).deep_size_of(), inline);
+        let s = "x".repeat(100);
+        assert_eq!(JsonValue::String(s.into()).deep_size_of(), inline + 100);
+    }
+
+    #[test]
+    fn test_arrays_count_capacity_and_children() {
+        let inline = size_of::<JsonValue>();
+        let mut arr = JsonArray::with_capacity(8);
+        let text = "too long to store inline";
+        arr.push(JsonValue::String(text.into()));
+        let cap = arr.capacity();
+        let value = JsonValue::Array(arr);
+        assert_eq!(value.deep_size_of(), inline + cap * inline + text.len());
+    }
+
+    #[test]
+    fn test_size_grows_with_content() {
+        let small = parse_json(r#"{"a": 1}"#).unwrap();
+        let large = parse_json(r#"{"a": 1, "b": ["some text", {"c": [1, 2, 3]}]}"#).unwrap();
+        assert!(small.deep_size_of() > size_of::<JsonValue>());
+        assert!(large.deep_size_of() > small.deep_size_of());
+    }
+}
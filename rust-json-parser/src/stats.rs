@@ -0,0 +1,414 @@
+use crate::{CompiledPath, JsonError, JsonParser, JsonValue, Limits, ParserOptions};
+use std::io::{self, BufRead};
+
+/// Default number of samples kept per field for percentile estimates.
+const DEFAULT_RESERVOIR: usize = 4096;
+
+/// Which statistics to collect for the numbers found at one JSON Pointer,
+/// or at every location a pointer pattern matches.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSpec {
+    pointer: String,
+    histogram_bounds: Option<Vec<f64>>,
+    top_k: usize,
+    reservoir: usize,
+}
+
+impl FieldSpec {
+    /// Statistics for the values at `pointer`, which may use the `*` and
+    /// `**` wildcards of [`JsonValue::pointer_matches`] to pool the values
+    /// of many locations, such as `/items/*/price`. A malformed pointer
+    /// matches nothing.
+    pub fn new(pointer: impl Into<String>) -> Self {
+        FieldSpec {
+            pointer: pointer.into(),
+            histogram_bounds: None,
+            top_k: 0,
+            reservoir: DEFAULT_RESERVOIR,
+        }
+    }
+
+    /// Counts values into buckets split at `bounds`, which must be ascending.
+    pub fn histogram(mut self, bounds: Vec<f64>) -> Self {
+        self.histogram_bounds = Some(bounds);
+        self
+    }
+
+    /// Keeps the `k` largest values seen.
+    pub fn top_k(mut self, k: usize) -> Self {
+        self.top_k = k;
+        self
+    }
+
+    /// Number of samples kept for percentiles. Percentiles are exact until
+    /// more values than this have been seen, and estimated afterwards.
+    pub fn reservoir(mut self, samples: usize) -> Self {
+        self.reservoir = samples.max(1);
+        self
+    }
+}
+
+/// Value counts per bucket. Bucket `i` holds values below `bounds[i]` and at
+/// or above the previous bound; the last bucket holds everything else.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+}
+
+impl Histogram {
+    fn new(bounds: Vec<f64>) -> Self {
+        Histogram {
+            counts: vec![0; bounds.len() + 1],
+            bounds,
+        }
+    }
+
+    pub fn bounds(&self) -> &[f64] {
+        &self.bounds
+    }
+
+    /// One count per bucket: `bounds().len() + 1` entries.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    fn record(&mut self, value: f64) {
+        let bucket = self.bounds.partition_point(|&bound| bound <= value);
+        self.counts[bucket] += 1;
+    }
+}
+
+/// Statistics gathered so far for one field.
+#[derive(Debug, Clone)]
+pub struct FieldStats {
+    spec: FieldSpec,
+    path: Option<CompiledPath>,
+    count: u64,
+    non_numeric: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    samples: Vec<f64>,
+    histogram: Option<Histogram>,
+    top: Vec<f64>,
+    rng: u64,
+}
+
+impl FieldStats {
+    fn new(spec: FieldSpec) -> Self {
+        FieldStats {
+            histogram: spec.histogram_bounds.clone().map(Histogram::new),
+            path: CompiledPath::new(&spec.pointer).ok(),
+            spec,
+            count: 0,
+            non_numeric: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            samples: Vec::new(),
+            top: Vec::new(),
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    pub fn pointer(&self) -> &str {
+        &self.spec.pointer
+    }
+
+    /// Number of numeric values seen, counting each match of a pattern.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Number of times the pointer matched something other than a number.
+    pub fn non_numeric(&self) -> u64 {
+        self.non_numeric
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    /// The nearest-rank `q`-quantile (`0.0..=1.0`) of the sampled values.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted = self.samples.clone();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (q.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+        Some(sorted[rank.saturating_sub(1)])
+    }
+
+    pub fn p50(&self) -> Option<f64> {
+        self.quantile(0.5)
+    }
+
+    pub fn p99(&self) -> Option<f64> {
+        self.quantile(0.99)
+    }
+
+    pub fn histogram(&self) -> Option<&Histogram> {
+        self.histogram.as_ref()
+    }
+
+    /// The largest values seen, in descending order.
+    pub fn top_k(&self) -> &[f64] {
+        &self.top
+    }
+
+    fn observe(&mut self, value: &JsonValue) {
+        let Some(n) = value.as_f64() else {
+            self.non_numeric += 1;
+            return;
+        };
+        self.count += 1;
+        self.sum += n;
+        self.min = self.min.min(n);
+        self.max = self.max.max(n);
+
+        if let Some(histogram) = &mut self.histogram {
+            histogram.record(n);
+        }
+        self.record_top(n);
+        self.record_sample(n);
+    }
+
+    fn record_top(&mut self, n: f64) {
+        if self.spec.top_k == 0 {
+            return;
+        }
+        let at = self.top.partition_point(|&kept| kept >= n);
+        if at < self.spec.top_k {
+            self.top.insert(at, n);
+            self.top.truncate(self.spec.top_k);
+        }
+    }
+
+    /// Reservoir sampling (algorithm R), so memory stays bounded on long streams.
+    fn record_sample(&mut self, n: f64) {
+        if self.samples.len() < self.spec.reservoir {
+            self.samples.push(n);
+            return;
+        }
+        // xorshift64: a fixed seed keeps results reproducible between runs
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let slot = self.rng % self.count;
+        if let Ok(slot) = usize::try_from(slot)
+            && slot < self.samples.len()
+        {
+            self.samples[slot] = n;
+        }
+    }
+}
+
+/// Totals from [`StreamStats::scan_ndjson`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ScanSummary {
+    /// Non-blank lines read.
+    pub records: usize,
+    /// Lines that failed to parse; they are skipped.
+    pub invalid: usize,
+    /// The first parse failure, with its 1-based line number.
+    pub first_error: Option<(usize, JsonError)>,
+}
+
+/// Collects numeric statistics for a set of pointers across many documents.
+#[derive(Debug, Clone, Default)]
+pub struct StreamStats {
+    fields: Vec<FieldStats>,
+}
+
+impl StreamStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts collecting statistics for `spec`.
+    pub fn field(mut self, spec: FieldSpec) -> Self {
+        self.fields.push(FieldStats::new(spec));
+        self
+    }
+
+    /// Records the values `document` holds at every tracked pointer.
+    pub fn observe(&mut self, document: &JsonValue) {
+        for field in &mut self.fields {
+            let Some(path) = &field.path else {
+                continue;
+            };
+            if path.has_wildcards() {
+                for (_, value) in path.matches(document) {
+                    field.observe(value);
+                }
+            } else if let Some(value) = path.get(document) {
+                field.observe(value);
+            }
+        }
+    }
+
+    /// Reads newline-delimited JSON, observing each record. Blank lines are
+    /// ignored and malformed lines are counted and skipped, as are lines
+    /// nested deeper than 128 levels.
+    pub fn scan_ndjson<R: BufRead>(&mut self, reader: R) -> io::Result<ScanSummary> {
+        // Each record is built recursively
+        let options = ParserOptions::new().limits(Limits::new().capped());
+        let mut summary = ScanSummary::default();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            summary.records += 1;
+            match JsonParser::with_options(&line, options.clone()).and_then(|mut p| p.parse()) {
+                Ok(document) => self.observe(&document),
+                Err(err) => {
+                    summary.invalid += 1;
+                    summary.first_error.get_or_insert((number + 1, err));
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    /// The statistics for `pointer`, if it is tracked.
+    pub fn get(&self, pointer: &str) -> Option<&FieldStats> {
+        self.fields
+            .iter()
+            .find(|field| field.spec.pointer == pointer)
+    }
+
+    pub fn fields(&self) -> &[FieldStats] {
+        &self.fields
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENTS: &str = r#"{"route": "/a", "latency": {"ms": 12}}
+{"route": "/b", "latency": {"ms": 250}}
+
+{"route": "/a", "latency": {"ms": 40}}
+{"route": "/c", "latency": {"ms": "timeout"}}
+not json
+{"route": "/b"}
+{"route": "/a", "latency": {"ms": 8}}
+"#;
+
+    fn scanned() -> (StreamStats, ScanSummary) {
+        let mut stats = StreamStats::new().field(
+            FieldSpec::new("/latency/ms")
+                .histogram(vec![10.0, 50.0, 100.0])
+                .top_k(2),
+        );
+        let summary = stats.scan_ndjson(EVENTS.as_bytes()).unwrap();
+        (stats, summary)
+    }
+
+    #[test]
+    fn test_scan_summary() {
+        let (_, summary) = scanned();
+        assert_eq!(summary.records, 7);
+        assert_eq!(summary.invalid, 1);
+        assert_eq!(summary.first_error.map(|(line, _)| line), Some(6));
+    }
+
+    #[test]
+    fn test_summary_statistics() {
+        let (stats, _) = scanned();
+        let latency = stats.get("/latency/ms").unwrap();
+        assert_eq!(latency.count(), 4);
+        assert_eq!(latency.non_numeric(), 1);
+        assert_eq!(latency.min(), Some(8.0));
+        assert_eq!(latency.max(), Some(250.0));
+        assert_eq!(latency.mean(), Some(77.5));
+        assert_eq!(latency.p50(), Some(12.0));
+        assert_eq!(latency.p99(), Some(250.0));
+        assert_eq!(latency.top_k(), &[250.0, 40.0]);
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let (stats, _) = scanned();
+        let histogram = stats.get("/latency/ms").unwrap().histogram().unwrap();
+        assert_eq!(histogram.bounds(), &[10.0, 50.0, 100.0]);
+        assert_eq!(histogram.counts(), &[1, 2, 0, 1]);
+    }
+
+    #[test]
+    fn test_empty_field() {
+        let stats = StreamStats::new().field(FieldSpec::new("/missing"));
+        let field = stats.get("/missing").unwrap();
+        assert_eq!(field.count(), 0);
+        assert_eq!(field.mean(), None);
+        assert_eq!(field.p50(), None);
+        assert!(stats.get("/untracked").is_none());
+    }
+
+    #[test]
+    fn test_wildcard_pointer_pools_matches() {
+        let orders = r#"{"items": [{"price": 5}, {"price": 20}]}
+{"items": [{"price": 12, "extra": {"price": 1}}]}
+{"items": []}
+"#;
+        let mut stats = StreamStats::new()
+            .field(FieldSpec::new("/items/*/price").top_k(3))
+            .field(FieldSpec::new("/**/price"))
+            .field(FieldSpec::new("items/*"));
+        let summary = stats.scan_ndjson(orders.as_bytes()).unwrap();
+        assert_eq!(summary.invalid, 0);
+
+        let prices = stats.get("/items/*/price").unwrap();
+        assert_eq!(prices.count(), 3);
+        assert_eq!(prices.top_k(), &[20.0, 12.0, 5.0]);
+        assert_eq!(prices.mean(), Some(37.0 / 3.0));
+        assert_eq!(stats.get("/**/price").unwrap().count(), 4);
+        assert_eq!(stats.get("items/*").unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_scan_skips_deeply_nested_lines() {
+        let deep = format!(
+            "{}{}\n{{\"n\": 1}}\n",
+            "[".repeat(100_000),
+            "]".repeat(100_000)
+        );
+        let mut stats = StreamStats::new().field(FieldSpec::new("/n"));
+        let summary = stats.scan_ndjson(deep.as_bytes()).unwrap();
+        assert_eq!(summary.records, 2);
+        assert_eq!(summary.invalid, 1);
+        let (line, err) = summary.first_error.unwrap();
+        assert_eq!(line, 1);
+        assert!(matches!(
+            err,
+            JsonError::DepthLimitExceeded { limit: 128, .. }
+        ));
+        assert_eq!(stats.get("/n").unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_reservoir_bounds_memory() {
+        let mut stats = StreamStats::new().field(FieldSpec::new("").reservoir(100));
+        for n in 0..10_000 {
+            stats.observe(&JsonValue::Number(n as f64));
+        }
+        let field = stats.get("").unwrap();
+        assert_eq!(field.count(), 10_000);
+        assert_eq!(field.samples.len(), 100);
+        assert_eq!(field.max(), Some(9999.0));
+        let median = field.p50().unwrap();
+        assert!((2000.0..8000.0).contains(&median), "Estimate was {median}");
+    }
+}
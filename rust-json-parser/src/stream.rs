@@ -0,0 +1,708 @@
+//! Tokenizing and reformatting input that is read incrementally, so memory
+//! use does not grow with the size of the document.
+
+use crate::options::Clock;
+use crate::path::{parse_index, pointer_tokens};
+use crate::render::TokenSink;
+use crate::{
+    ExpectedToken, FormatConfig, JsonError, JsonParser, JsonPath, JsonValue, Limits, ParserOptions,
+    PathSegment, Token, Tokenizer, WriterError,
+};
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+/// Errors produced while reading a stream of JSON.
+#[derive(Debug)]
+pub enum StreamError {
+    /// Reading the input or writing the output failed, or the input was not
+    /// valid UTF-8.
+    Io(io::Error),
+    Json(JsonError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(f, "I/O error: {err}"),
+            StreamError::Json(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Io(err) => Some(err),
+            StreamError::Json(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(err: io::Error) -> Self {
+        StreamError::Io(err)
+    }
+}
+
+impl From<JsonError> for StreamError {
+    fn from(err: JsonError) -> Self {
+        StreamError::Json(err)
+    }
+}
+
+/// Reads tokens from a [`BufRead`] one at a time.
+///
+/// Only the token being read is held in memory. Each literal is checked by
+/// [`Tokenizer`], so strings, numbers and keywords follow the same strict
+/// rules and errors carry the same character positions.
+pub struct TokenReader<R> {
+    reader: R,
+    /// Characters consumed so far.
+    position: usize,
+    /// The literal being read.
+    lexeme: Vec<u8>,
+    /// Where the last token started.
+    token_start: usize,
+    failed: bool,
+}
+
+impl<R: BufRead> TokenReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            position: 0,
+            lexeme: Vec::new(),
+            token_start: 0,
+            failed: false,
+        }
+    }
+
+    /// Character offset of the next unread character.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn bump(&mut self, byte: u8) {
+        self.reader.consume(1);
+        // Count characters, not bytes: skip UTF-8 continuation bytes
+        if byte & 0xC0 != 0x80 {
+            self.position += 1;
+        }
+    }
+
+    fn read_token(&mut self) -> Result<Option<Token>, StreamError> {
+        let byte = loop {
+            match self.peek()? {
+                None => return Ok(None),
+                Some(b @ (b' ' | b'\t' | b'\n' | b'\r')) => self.bump(b),
+                Some(b) => break b,
+            }
+        };
+        let start = self.position;
+        self.token_start = start;
+        let punctuation = match byte {
+            b'{' => Some(Token::LeftBrace),
+            b'}' => Some(Token::RightBrace),
+            b'[' => Some(Token::LeftBracket),
+            b']' => Some(Token::RightBracket),
+            b',' => Some(Token::Comma),
+            b':' => Some(Token::Colon),
+            _ => None,
+        };
+        if let Some(token) = punctuation {
+            self.bump(byte);
+            return Ok(Some(token));
+        }
+
+        self.lexeme.clear();
+        match byte {
+            b'"' => self.read_string()?,
+            b'-' | b'0'..=b'9' => {
+                self.read_while(|b| {
+                    b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E')
+                })?;
+            }
+            b'a'..=b'z' | b'A'..=b'Z' => self.read_while(|b| b.is_ascii_alphabetic())?,
+            _ => {
+                // Take the whole character, so the error can name it
+                let len = match byte {
+                    0xF0.. => 4,
+                    0xE0.. => 3,
+                    0xC0.. => 2,
+                    _ => 1,
+                };
+                for _ in 0..len {
+                    if let Some(b) = self.peek()? {
+                        self.lexeme.push(b);
+                        self.bump(b);
+                    }
+                }
+                return Err(JsonError::UnexpectedToken {
+                    expected: vec![ExpectedToken::Value],
+                    found: String::from_utf8_lossy(&self.lexeme).into_owned(),
+                    position: start,
+                    path: JsonPath::new(),
+                }
+                .into());
+            }
+        }
+
+        let more = self.peek()?.is_some();
+        let text = std::str::from_utf8(&self.lexeme)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let mut tokens = Tokenizer::new(text)
+            .more_follows(more)
+            .tokenize()
+            .map_err(|err| err.shifted(start))?;
+        match (tokens.pop(), tokens.is_empty()) {
+            (Some(token), true) => Ok(Some(token)),
+            _ => Err(JsonError::InvalidNumber {
+                value: text.to_string(),
+                position: start,
+                path: JsonPath::new(),
+            }
+            .into()),
+        }
+    }
+
+    /// Reads a string literal, quotes and raw escapes included.
+    fn read_string(&mut self) -> io::Result<()> {
+        let mut escaped = false;
+        let mut first = true;
+        while let Some(b) = self.peek()? {
+            self.lexeme.push(b);
+            self.bump(b);
+            match b {
+                b'"' if !escaped && !first => break,
+                b'\\' => escaped = !escaped,
+                _ => escaped = false,
+            }
+            first = false;
+        }
+        // An unterminated literal is reported by the tokenizer
+        Ok(())
+    }
+
+    fn read_while(&mut self, accept: impl Fn(u8) -> bool) -> io::Result<()> {
+        while let Some(b) = self.peek()? {
+            if !accept(b) {
+                break;
+            }
+            self.lexeme.push(b);
+            self.bump(b);
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for TokenReader<R> {
+    type Item = Result<Token, StreamError>;
+
+    /// Yields tokens until the input ends or the first error, which is
+    /// returned once.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.read_token().transpose();
+        if matches!(result, Some(Err(_))) {
+            self.failed = true;
+        }
+        result
+    }
+}
+
+/// Reformats the JSON document in `reader` into `writer` following
+/// `config`, without building a [`JsonValue`](crate::JsonValue), and returns
+/// the writer.
+///
+/// Memory use depends on nesting depth and the longest literal, not on the
+/// size of the input, so multi-gigabyte files can be pretty-printed or
+/// minified. Numbers are rewritten in the form `config` chooses. Wrap
+/// unbuffered sinks in an [`io::BufWriter`].
+pub fn transcode<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    config: &FormatConfig,
+) -> Result<W, StreamError> {
+    let mut tokens = TokenReader::new(reader);
+    let mut sink = TokenSink::new(writer, config);
+    while let Some(token) = tokens.next() {
+        let token = token?;
+        sink.push(&token).map_err(|err| match err {
+            WriterError::Io(err) => StreamError::Io(err),
+            WriterError::InvalidCall(_) => StreamError::Json(JsonError::UnexpectedToken {
+                expected: vec![ExpectedToken::Continuation],
+                found: format!("{token:?}"),
+                position: tokens.token_start,
+                path: JsonPath::new(),
+            }),
+        })?;
+    }
+    sink.finish().map_err(|err| match err {
+        WriterError::Io(err) => StreamError::Io(err),
+        WriterError::InvalidCall(_) => StreamError::Json(JsonError::UnexpectedEndOfInput {
+            expected: vec![ExpectedToken::Continuation],
+            position: tokens.position(),
+            path: JsonPath::new(),
+        }),
+    })
+}
+
+/// Reads the JSON document in `reader` once and calls
+/// `on_match(pointer, value)` for each value found at one of `pointers`,
+/// without building the rest of the document.
+///
+/// Only matched values are built; everything else is passed over with just
+/// its brackets checked, so memory use depends on the size of the matches
+/// and not on the document. Matches are reported in document order, and
+/// every member of a repeated key matches. When pointers lie inside the
+/// value of another, their matches follow the outer one, in the order of
+/// `pointers`. The whole input is read, so a malformed literal or
+/// unbalanced bracket anywhere in it is returned as an error, after the
+/// matches before it were reported, as is a match nested deeper than 128.
+/// Fails up front with [`JsonError::InvalidPointer`] when a pointer is
+/// malformed.
+///
+/// ```
+/// use rust_json_parser::{JsonValue, project};
+///
+/// let json = r#"{"id": 7, "blob": [1, 2, 3], "user": {"name": "Ada", "tags": []}}"#;
+/// let mut found = Vec::new();
+/// project(json.as_bytes(), &["/user/name", "/id"], |pointer, value| {
+///     found.push((pointer.to_string(), value));
+/// })
+/// .unwrap();
+/// assert_eq!(
+///     found,
+///     [
+///         ("/id".to_string(), JsonValue::Number(7.0)),
+///         ("/user/name".to_string(), JsonValue::String("Ada".into())),
+///     ]
+/// );
+/// ```
+pub fn project<R, F>(reader: R, pointers: &[&str], mut on_match: F) -> Result<(), StreamError>
+where
+    R: BufRead,
+    F: FnMut(&str, JsonValue),
+{
+    let targets = pointers
+        .iter()
+        .map(|&pointer| {
+            pointer_tokens(pointer)
+                .map(|tokens| (pointer, tokens))
+                .ok_or_else(|| JsonError::InvalidPointer {
+                    pointer: pointer.to_string(),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut projection = Projection {
+        tokens: TokenReader::new(reader),
+        targets,
+        path: Vec::new(),
+        // Matches are built recursively
+        options: ParserOptions::new().limits(Limits::new().capped()),
+    };
+    let first = projection.expect(&[ExpectedToken::Value])?;
+    // An error leaves the path where it was found
+    projection
+        .value(first, &mut on_match)
+        .map_err(|err| match err {
+            StreamError::Json(err) => err.under(&projection.path.clone().into()).into(),
+            err => err,
+        })?;
+    match projection.tokens.next().transpose()? {
+        Some(token) => Err(unexpected(
+            vec![ExpectedToken::EndOfInput],
+            &token,
+            projection.tokens.token_start,
+        )),
+        None => Ok(()),
+    }
+}
+
+/// State for [`project`].
+struct Projection<'a, R> {
+    tokens: TokenReader<R>,
+    /// Each pointer with its reference tokens.
+    targets: Vec<(&'a str, Vec<String>)>,
+    /// Where the value being read is.
+    path: Vec<PathSegment>,
+    options: ParserOptions,
+}
+
+impl<R: BufRead> Projection<'_, R> {
+    /// Reads a token that must be there, with where it starts.
+    fn expect(&mut self, expected: &[ExpectedToken]) -> Result<(Token, usize), StreamError> {
+        match self.tokens.next().transpose()? {
+            Some(token) => Ok((token, self.tokens.token_start)),
+            None => Err(JsonError::UnexpectedEndOfInput {
+                expected: expected.to_vec(),
+                position: self.tokens.position(),
+                path: JsonPath::new(),
+            }
+            .into()),
+        }
+    }
+
+    /// Reads the value that starts with `first`, at `self.path`.
+    fn value<F>(&mut self, first: (Token, usize), on_match: &mut F) -> Result<(), StreamError>
+    where
+        F: FnMut(&str, JsonValue),
+    {
+        let depth = self.path.len();
+        let matched = self
+            .targets
+            .iter()
+            .any(|(_, tokens)| tokens.len() == depth && leads_to(&self.path, tokens));
+        let below = self
+            .targets
+            .iter()
+            .any(|(_, tokens)| tokens.len() > depth && leads_to(&self.path, tokens));
+        if matched {
+            let mut value_tokens = Vec::new();
+            self.skip(first, Some(&mut value_tokens))?;
+            return self.report(value_tokens, on_match);
+        }
+        match first {
+            (Token::LeftBrace, _) if below => self.members(on_match),
+            (Token::LeftBracket, _) if below => self.elements(on_match),
+            first => self.skip(first, None),
+        }
+    }
+
+    /// Builds a matched value and reports it under every pointer that
+    /// names it or a value inside it.
+    fn report<F>(
+        &mut self,
+        tokens: Vec<(Token, Range<usize>)>,
+        on_match: &mut F,
+    ) -> Result<(), StreamError>
+    where
+        F: FnMut(&str, JsonValue),
+    {
+        // The brackets of `tokens` balance, so the parser never runs past
+        // them to the end of the input it is given
+        let clock = Clock::start(&self.options.limits);
+        let value = JsonParser::from_tokens("", tokens, clock, self.options.clone()).parse()?;
+        let depth = self.path.len();
+        let (exact, inner): (Vec<_>, Vec<_>) = self
+            .targets
+            .iter()
+            .filter(|(_, tokens)| leads_to(&self.path, tokens))
+            .partition(|(_, tokens)| tokens.len() == depth);
+        for (pointer, tokens) in exact.into_iter().chain(inner) {
+            let inner: JsonPath = tokens[depth..]
+                .iter()
+                .map(|token| PathSegment::Key(token.clone()))
+                .collect::<Vec<_>>()
+                .into();
+            if let Some(found) = value.pointer(&inner.to_pointer()) {
+                on_match(pointer, found.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the members of an object whose `{` was just read.
+    fn members<F>(&mut self, on_match: &mut F) -> Result<(), StreamError>
+    where
+        F: FnMut(&str, JsonValue),
+    {
+        let mut expected_key = vec![ExpectedToken::Key, ExpectedToken::RightBrace];
+        loop {
+            let key = match self.expect(&expected_key)? {
+                (Token::String(key), _) => key,
+                (Token::RightBrace, _) if expected_key.len() == 2 => return Ok(()),
+                (token, start) => return Err(unexpected(expected_key, &token, start)),
+            };
+            match self.expect(&[ExpectedToken::Colon])? {
+                (Token::Colon, _) => {}
+                (token, start) => {
+                    return Err(unexpected(vec![ExpectedToken::Colon], &token, start));
+                }
+            }
+            self.path.push(PathSegment::Key(key));
+            let first = self.expect(&[ExpectedToken::Value])?;
+            self.value(first, on_match)?;
+            self.path.pop();
+            let expected = [ExpectedToken::Comma, ExpectedToken::RightBrace];
+            match self.expect(&expected)? {
+                (Token::Comma, _) => expected_key = vec![ExpectedToken::Key],
+                (Token::RightBrace, _) => return Ok(()),
+                (token, start) => return Err(unexpected(expected.to_vec(), &token, start)),
+            }
+        }
+    }
+
+    /// Reads the elements of an array whose `[` was just read.
+    fn elements<F>(&mut self, on_match: &mut F) -> Result<(), StreamError>
+    where
+        F: FnMut(&str, JsonValue),
+    {
+        let first = self.expect(&[ExpectedToken::Value, ExpectedToken::RightBracket])?;
+        if first.0 == Token::RightBracket {
+            return Ok(());
+        }
+        let mut next = first;
+        for index in 0.. {
+            self.path.push(PathSegment::Index(index));
+            self.value(next, on_match)?;
+            self.path.pop();
+            let expected = [ExpectedToken::Comma, ExpectedToken::RightBracket];
+            match self.expect(&expected)? {
+                (Token::Comma, _) => next = self.expect(&[ExpectedToken::Value])?,
+                (Token::RightBracket, _) => break,
+                (token, start) => return Err(unexpected(expected.to_vec(), &token, start)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads past the value that starts with `first`, checking only that
+    /// its brackets balance, and collects its tokens into `sink` if given.
+    fn skip(
+        &mut self,
+        first: (Token, usize),
+        mut sink: Option<&mut Vec<(Token, Range<usize>)>>,
+    ) -> Result<(), StreamError> {
+        if let (token @ (Token::Comma | Token::Colon), start) = first {
+            return Err(unexpected(vec![ExpectedToken::Value], &token, start));
+        }
+        let mut open = Vec::new();
+        let mut next = first;
+        loop {
+            let (token, start) = next;
+            match token {
+                Token::LeftBrace => open.push(Token::RightBrace),
+                Token::LeftBracket => open.push(Token::RightBracket),
+                Token::RightBrace | Token::RightBracket if open.last() == Some(&token) => {
+                    open.pop();
+                }
+                Token::RightBrace | Token::RightBracket => {
+                    let expected = match open.last() {
+                        Some(Token::RightBrace) => ExpectedToken::RightBrace,
+                        Some(_) => ExpectedToken::RightBracket,
+                        None => ExpectedToken::Value,
+                    };
+                    return Err(unexpected(vec![expected], &token, start));
+                }
+                _ => {}
+            }
+            if let Some(sink) = sink.as_deref_mut() {
+                sink.push((token, start..start));
+            }
+            if open.is_empty() {
+                return Ok(());
+            }
+            next = self.expect(&[ExpectedToken::Continuation])?;
+        }
+    }
+}
+
+/// Whether `path` is, or is inside of, the location `tokens` point to.
+fn leads_to(path: &[PathSegment], tokens: &[String]) -> bool {
+    path.len() <= tokens.len()
+        && path
+            .iter()
+            .zip(tokens)
+            .all(|(segment, token)| match segment {
+                PathSegment::Key(key) => key == token,
+                PathSegment::Index(index) => parse_index(token) == Some(*index),
+            })
+}
+
+fn unexpected(expected: Vec<ExpectedToken>, token: &Token, position: usize) -> StreamError {
+    JsonError::UnexpectedToken {
+        expected,
+        found: format!("{token:?}"),
+        position,
+        path: JsonPath::new(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JsonErrorKind, JsonParser};
+
+    fn read_all(input: &str) -> Result<Vec<Token>, StreamError> {
+        TokenReader::new(input.as_bytes()).collect()
+    }
+
+    #[test]
+    fn test_reader_matches_tokenizer() {
+        let input = "{\"é\": [1.5e3, -0, true, null, \"a\\\"b\\ud83d\\ude00\"], \"k\" : false}";
+        assert_eq!(
+            read_all(input).unwrap(),
+            Tokenizer::new(input).tokenize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reader_handles_tiny_buffers() {
+        let input = "[\"caf\u{e9} \u{1F600}\", 12.5]";
+        let reader = io::BufReader::with_capacity(1, input.as_bytes());
+        let tokens: Vec<Token> = TokenReader::new(reader).map(Result::unwrap).collect();
+        assert_eq!(tokens, Tokenizer::new(input).tokenize().unwrap());
+    }
+
+    #[test]
+    fn test_reader_errors_carry_character_positions() {
+        let cases = vec![
+            ("[\"é\", tru]", 6),
+            ("[1, --2]", 4),
+            ("[\"é\\x\"]", 3),
+            ("  @", 2),
+            ("[\"open", 1),
+        ];
+        for (input, position) in cases {
+            let err = read_all(input).unwrap_err();
+            let StreamError::Json(err) = err else {
+                panic!("{input}: {err}")
+            };
+            let expected = Tokenizer::new(input).tokenize().unwrap_err();
+            assert_eq!(err, expected, "{input}");
+            assert!(err.to_string().contains(&position.to_string()), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_transcode_reformats() {
+        let input = "{\"a\": [1, {\"b\": null}], \"c\": \"x\"}";
+        let value = JsonParser::new(input).unwrap().parse().unwrap();
+        let pretty = transcode(input.as_bytes(), Vec::new(), &FormatConfig::pretty(2)).unwrap();
+        assert_eq!(
+            String::from_utf8(pretty).unwrap(),
+            value.to_string_pretty(2)
+        );
+        let input = "{\n  \"x\": [\n    1,\n    2\n  ]\n}\n";
+        let compact = transcode(input.as_bytes(), Vec::new(), &FormatConfig::new()).unwrap();
+        assert_eq!(compact, br#"{"x":[1,2]}"#);
+    }
+
+    #[test]
+    fn test_transcode_reports_structure_errors() {
+        let cases = vec![("[1 2]", 3), ("{\"a\" 1}", 5), ("[1]  2", 5)];
+        for (input, expected) in cases {
+            assert!(
+                matches!(
+                    transcode(input.as_bytes(), Vec::new(), &FormatConfig::new()),
+                    Err(StreamError::Json(JsonError::UnexpectedToken { position, .. }))
+                        if position == expected
+                ),
+                "{input}"
+            );
+        }
+        assert!(matches!(
+            transcode("[1, [".as_bytes(), Vec::new(), &FormatConfig::new()),
+            Err(StreamError::Json(JsonError::UnexpectedEndOfInput {
+                position: 5,
+                ..
+            }))
+        ));
+    }
+
+    fn projected(input: &str, pointers: &[&str]) -> Result<Vec<(String, JsonValue)>, StreamError> {
+        let mut found = Vec::new();
+        let reader = io::BufReader::with_capacity(3, input.as_bytes());
+        project(reader, pointers, |pointer, value| {
+            found.push((pointer.to_string(), value))
+        })?;
+        Ok(found)
+    }
+
+    #[test]
+    fn test_project_reports_matches_in_document_order() {
+        let input = r#"{"skip": {"x": [1, [2]]}, "a": [{"b": 1}, {"b": [2, 3]}], "c~/d": null}"#;
+        let value = JsonParser::new(input).unwrap().parse().unwrap();
+        let pointers = ["/c~0~1d", "/a/1/b", "/a/0", "/a/0/b", "/a/7", "/nope", ""];
+        let found = projected(input, &pointers).unwrap();
+        let order: Vec<_> = found.iter().map(|(pointer, _)| pointer.as_str()).collect();
+        assert_eq!(order, ["", "/c~0~1d", "/a/1/b", "/a/0", "/a/0/b"]);
+        for (pointer, matched) in &found {
+            assert_eq!(value.pointer(pointer), Some(matched), "{pointer}");
+        }
+    }
+
+    #[test]
+    fn test_project_matches_every_repeated_key() {
+        let found = projected(r#"[{"k": 1, "k": "two"}, {"k": 3}]"#, &["/0/k", "/1/k"]).unwrap();
+        let values: Vec<_> = found.into_iter().map(|(_, value)| value).collect();
+        assert_eq!(
+            values,
+            [
+                JsonValue::Number(1.0),
+                JsonValue::String("two".into()),
+                JsonValue::Number(3.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_project_reads_the_whole_input() {
+        let cases = vec![
+            (r#"{"a": 1, "b": {"c": ]}}"#, 20),
+            (r#"{"a": 1, "b": ,}"#, 14),
+            (r#"{"a": 1} 2"#, 9),
+            (r#"{"a": {"x" 1}}"#, 11),
+        ];
+        for (input, expected) in cases {
+            let err = projected(input, &["/a"]).unwrap_err();
+            assert!(
+                matches!(
+                    &err,
+                    StreamError::Json(JsonError::UnexpectedToken { position, .. })
+                        if *position == expected
+                ),
+                "{input}: {err}"
+            );
+        }
+        let input = r#"[0, {"a": {"x" 1}}]"#;
+        let Err(StreamError::Json(err)) = projected(input, &["/1/a"]) else {
+            panic!("malformed match accepted");
+        };
+        assert_eq!(
+            err.path().map(|path| path.to_string()).as_deref(),
+            Some("/1/a/x")
+        );
+        assert!(matches!(
+            projected(r#"{"a": [1, "#, &["/a/0"]),
+            Err(StreamError::Json(JsonError::UnexpectedEndOfInput {
+                position: 10,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            projected("[]", &["a"]),
+            Err(StreamError::Json(JsonError::InvalidPointer { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_project_rejects_a_deep_match() {
+        let deep = format!("{{\"a\": {}{}}}", "[".repeat(200_000), "]".repeat(200_000));
+        let Err(StreamError::Json(err)) = project(deep.as_bytes(), &["/a"], |_, _| {}) else {
+            panic!("deep match accepted");
+        };
+        assert_eq!(err.kind(), JsonErrorKind::DepthLimitExceeded);
+        let mut found = 0;
+        project(deep.as_bytes(), &["/b"], |_, _| found += 1).unwrap();
+        assert_eq!(found, 0);
+    }
+
+    #[test]
+    fn test_transcode_rejects_invalid_utf8() {
+        let input: &[u8] = b"[\"\xff\"]";
+        assert!(matches!(
+            transcode(input, Vec::new(), &FormatConfig::new()),
+            Err(StreamError::Io(err)) if err.kind() == io::ErrorKind::InvalidData
+        ));
+    }
+}
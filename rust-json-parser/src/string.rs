@@ -0,0 +1,279 @@
+use std::borrow::{Borrow, Cow};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// The text of a JSON string value or object key.
+///
+/// Dereferences to `str`, so it reads like one; build it from a `String` or
+/// `&str` with `into()`. Text of up to [`JsonString::INLINE_LEN`] bytes,
+/// which covers most keys and identifiers, is stored in the value itself
+/// without allocating. Strings handed out by an
+/// [`Interner`](crate::Interner) share one allocation with every other
+/// document holding the same text, and clone by bumping a reference count.
+#[derive(Clone, Default)]
+pub struct JsonString {
+    repr: Repr,
+}
+
+#[derive(Clone)]
+enum Repr {
+    /// Always valid UTF-8 in `bytes[..len]`.
+    Inline {
+        len: u8,
+        bytes: [u8; JsonString::INLINE_LEN],
+    },
+    Owned(Box<str>),
+    Shared(Arc<str>),
+}
+
+impl Default for Repr {
+    fn default() -> Self {
+        Repr::Inline {
+            len: 0,
+            bytes: [0; JsonString::INLINE_LEN],
+        }
+    }
+}
+
+impl JsonString {
+    /// The longest text, in bytes, stored without allocating.
+    pub const INLINE_LEN: usize = 22;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.repr {
+            Repr::Inline { len, bytes } => {
+                // SAFETY: `inline` only copies whole `str`s in.
+                unsafe { std::str::from_utf8_unchecked(&bytes[..usize::from(*len)]) }
+            }
+            Repr::Owned(s) => s,
+            Repr::Shared(s) => s,
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        match self.repr {
+            Repr::Owned(s) => s.into_string(),
+            _ => self.as_str().to_string(),
+        }
+    }
+
+    /// Whether the text is stored in the value itself, without allocating.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline { .. })
+    }
+
+    /// Whether the text is shared through an [`Interner`](crate::Interner).
+    pub fn is_shared(&self) -> bool {
+        matches!(self.repr, Repr::Shared(_))
+    }
+
+    pub(crate) fn shared(text: Arc<str>) -> Self {
+        Self {
+            repr: Repr::Shared(text),
+        }
+    }
+
+    /// `text` stored in place, if it is short enough.
+    pub(crate) fn inline(text: &str) -> Option<Self> {
+        let mut bytes = [0; Self::INLINE_LEN];
+        bytes
+            .get_mut(..text.len())?
+            .copy_from_slice(text.as_bytes());
+        Some(Self {
+            repr: Repr::Inline {
+                len: text.len() as u8,
+                bytes,
+            },
+        })
+    }
+
+    /// Heap bytes this string owns. Shared text belongs to the interner
+    /// that handed it out, so it counts for nothing here.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        match &self.repr {
+            Repr::Owned(s) => s.len(),
+            Repr::Inline { .. } | Repr::Shared(_) => 0,
+        }
+    }
+}
+
+impl Deref for JsonString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for JsonString {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl Borrow<str> for JsonString {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl PartialEq for JsonString {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for JsonString {}
+
+impl PartialEq<str> for JsonString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for JsonString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for JsonString {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialOrd for JsonString {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JsonString {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+// Hashes as `str` does, as `Borrow<str>` requires.
+impl Hash for JsonString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl fmt::Debug for JsonString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for JsonString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self)
+    }
+}
+
+impl From<String> for JsonString {
+    fn from(s: String) -> Self {
+        Self::inline(&s).unwrap_or_else(|| Self {
+            repr: Repr::Owned(s.into_boxed_str()),
+        })
+    }
+}
+
+impl From<&str> for JsonString {
+    fn from(s: &str) -> Self {
+        Self::inline(s).unwrap_or_else(|| Self {
+            repr: Repr::Owned(s.into()),
+        })
+    }
+}
+
+impl From<&String> for JsonString {
+    fn from(s: &String) -> Self {
+        s.as_str().into()
+    }
+}
+
+impl From<Box<str>> for JsonString {
+    fn from(s: Box<str>) -> Self {
+        Self::inline(&s).unwrap_or(Self {
+            repr: Repr::Owned(s),
+        })
+    }
+}
+
+impl From<Cow<'_, str>> for JsonString {
+    fn from(s: Cow<'_, str>) -> Self {
+        s.into_owned().into()
+    }
+}
+
+impl From<char> for JsonString {
+    fn from(c: char) -> Self {
+        String::from(c).into()
+    }
+}
+
+impl From<JsonString> for String {
+    fn from(s: JsonString) -> Self {
+        s.into_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_reads_like_str() {
+        let s = JsonString::from("héllo");
+        assert_eq!(s, "héllo");
+        assert_eq!(s.len(), 6);
+        assert_eq!(s.to_uppercase(), "HÉLLO");
+        assert_eq!(format!("{s} {s:?}"), r#"héllo "héllo""#);
+        assert_eq!(String::from(s), "héllo");
+    }
+
+    #[test]
+    fn test_short_text_is_inline() {
+        let limit = "é".repeat(JsonString::INLINE_LEN / 2);
+        for text in ["", "id", limit.as_str()] {
+            let s = JsonString::from(text);
+            assert!(s.is_inline(), "{text}");
+            assert_eq!(s, text);
+            assert_eq!(s.heap_bytes(), 0);
+            assert_eq!(s.clone().into_string(), text);
+        }
+        let long = format!("{limit}x");
+        let s = JsonString::from(long.clone());
+        assert!(!s.is_inline());
+        assert_eq!(s, long);
+        assert_eq!(s.heap_bytes(), long.len());
+        assert_eq!(size_of::<JsonString>(), 24);
+    }
+
+    #[test]
+    fn test_representations_compare_by_text() {
+        let text = "a key longer than inline";
+        let shared = JsonString::shared(Arc::from(text));
+        let owned = JsonString::from(text.to_string());
+        assert!(shared.is_shared() && !owned.is_shared());
+        assert_eq!(shared, owned);
+        assert_eq!(
+            JsonString::shared(Arc::from("key")),
+            JsonString::from("key")
+        );
+        let mut map = HashMap::new();
+        map.insert(shared, 1);
+        assert_eq!(map.get(text), Some(&1));
+    }
+}
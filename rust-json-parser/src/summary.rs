@@ -0,0 +1,143 @@
+use crate::JsonValue;
+use crate::value::escape_json_string;
+use std::fmt;
+
+/// Lazily writes a shortened form of a value, for log lines.
+///
+/// Created by [`JsonValue::summary`]. Containers below the depth limit are
+/// elided to `[ …N items… ]` or `{…}`, and only the first few children of
+/// each container are shown. The output is meant for people and is not
+/// valid JSON.
+#[derive(Debug, Clone, Copy)]
+pub struct Summary<'a> {
+    value: &'a JsonValue,
+    max_depth: usize,
+    max_items: usize,
+}
+
+impl JsonValue {
+    /// Returns a [`fmt::Display`] adapter that writes at most `max_depth`
+    /// levels of containers and `max_items` children of each:
+    /// `{"users": [ …1523 items… ], "meta": {…}}` at depth 1.
+    pub fn summary(&self, max_depth: usize, max_items: usize) -> Summary<'_> {
+        Summary {
+            value: self,
+            max_depth,
+            max_items,
+        }
+    }
+}
+
+impl fmt::Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(self.value, 0, f)
+    }
+}
+
+impl Summary<'_> {
+    /// Recursion is bounded by `max_depth`, so deep values are safe.
+    fn write(&self, value: &JsonValue, depth: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match value {
+            JsonValue::Array(arr) if arr.is_empty() => f.write_str("[]"),
+            JsonValue::Object(obj) if obj.is_empty() => f.write_str("{}"),
+            JsonValue::Array(arr) if depth >= self.max_depth => {
+                write!(f, "[ …{}… ]", items(arr.len()))
+            }
+            JsonValue::Object(_) if depth >= self.max_depth => f.write_str("{…}"),
+            JsonValue::Array(arr) => {
+                f.write_str("[")?;
+                for (i, item) in arr.iter().take(self.max_items).enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    self.write(item, depth + 1, f)?;
+                }
+                self.write_rest(arr.len(), f)?;
+                f.write_str("]")
+            }
+            JsonValue::Object(obj) => {
+                f.write_str("{")?;
+                for (i, (key, item)) in obj.iter().take(self.max_items).enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: ", escape_json_string(key))?;
+                    self.write(item, depth + 1, f)?;
+                }
+                self.write_rest(obj.len(), f)?;
+                f.write_str("}")
+            }
+            scalar => write!(f, "{scalar}"),
+        }
+    }
+
+    /// Notes how many children of a container of `len` were left out.
+    fn write_rest(&self, len: usize, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hidden = len.saturating_sub(self.max_items);
+        match hidden {
+            0 => Ok(()),
+            _ if self.max_items == 0 => write!(f, "…{}…", items(hidden)),
+            _ => write!(f, ", …{hidden} more…"),
+        }
+    }
+}
+
+fn items(n: usize) -> String {
+    if n == 1 {
+        "1 item".to_string()
+    } else {
+        format!("{n} items")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_elides_below_max_depth() {
+        let users = format!("[{}]", vec!["{\"id\": 1}"; 1523].join(","));
+        let value = parse_json(&format!(r#"{{"users": {users}, "meta": {{"v": 2}}}}"#)).unwrap();
+        assert_eq!(
+            value.summary(1, 10).to_string(),
+            r#"{"users": [ …1523 items… ], "meta": {…}}"#
+        );
+        assert_eq!(value.summary(0, 10).to_string(), "{…}");
+    }
+
+    #[test]
+    fn test_limits_items_per_container() {
+        let value =
+            parse_json(r#"{"a": [1, 2, 3, 4], "b": {"x": "s", "y": null}, "c": []}"#).unwrap();
+        let cases = vec![
+            (
+                value.summary(5, 10),
+                r#"{"a": [1, 2, 3, 4], "b": {"x": "s", "y": null}, "c": []}"#,
+            ),
+            (
+                value.summary(5, 2),
+                r#"{"a": [1, 2, …2 more…], "b": {"x": "s", "y": null}, …1 more…}"#,
+            ),
+            (value.summary(5, 0), "{…3 items…}"),
+            (
+                value.summary(1, 3),
+                r#"{"a": [ …4 items… ], "b": {…}, "c": []}"#,
+            ),
+        ];
+        for (summary, expected) in cases {
+            assert_eq!(summary.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_scalars_print_whole() {
+        assert_eq!(
+            JsonValue::String("x\"y".into()).summary(0, 0).to_string(),
+            r#""x\"y""#
+        );
+        let one = parse_json("[[7]]").unwrap();
+        assert_eq!(one.summary(1, 5).to_string(), "[[ …1 item… ]]");
+    }
+}
@@ -0,0 +1,845 @@
+use crate::path::{parse_index, pointer_tokens};
+use crate::{
+    ExpectedToken, JsonArray, JsonError, JsonObject, JsonPath, JsonValue, Result, Tokenizer,
+    unescape_json_string,
+};
+use std::borrow::Cow;
+
+/// Parses `json` into a [`Tape`]: a flat index of where each value sits in
+/// the text, read without building a [`JsonValue`] tree.
+///
+/// Parsing allocates nothing per value, and strings and numbers are only
+/// decoded when read, so for read-only access to a few parts of a large
+/// document this is much cheaper than [`JsonParser::parse`](crate::JsonParser::parse).
+/// The tape borrows `json`, which must follow the grammar of
+/// [`ParserOptions::strict_rfc8259`](crate::ParserOptions::strict_rfc8259),
+/// except that keys may repeat. A mistake is reported with the error that
+/// parser gives for it.
+///
+/// ```
+/// use rust_json_parser::parse_to_tape;
+///
+/// let tape = parse_to_tape(r#"{"users": [{"name": "Ada", "age": 36}, {"name": "Alé"}]}"#)?;
+/// let users = tape.root().get("users").unwrap();
+/// let names: Vec<_> = users.elements().filter_map(|u| u.get("name")?.as_str()).collect();
+/// assert_eq!(names, ["Ada", "Alé"]);
+/// assert_eq!(tape.root().pointer("/users/0/age").and_then(|v| v.as_f64()), Some(36.0));
+/// # Ok::<(), rust_json_parser::JsonError>(())
+/// ```
+pub fn parse_to_tape(json: &str) -> Result<Tape<'_>> {
+    let mut builder = Builder {
+        scan: Scanner::new(json, 0),
+        entries: Vec::new(),
+        open: Vec::new(),
+    };
+    builder.document()?;
+    Ok(Tape {
+        text: json,
+        entries: builder.entries,
+    })
+}
+
+/// A parsed document as a flat list of entries over its text, returned by
+/// [`parse_to_tape`]. Navigate it from [`Tape::root`].
+#[derive(Debug, Clone)]
+pub struct Tape<'a> {
+    pub(crate) text: &'a str,
+    /// In document order, each object key before its value.
+    pub(crate) entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Entry {
+    pub(crate) kind: Kind,
+    /// Byte offset of the first character: a quote, bracket, digit, sign
+    /// or keyword letter.
+    pub(crate) start: usize,
+    /// Byte offset just past the last character.
+    pub(crate) end: usize,
+    /// Index of the entry after this one and everything inside it.
+    pub(crate) next: usize,
+    /// Elements of an array or members of an object; 0 for scalars.
+    pub(crate) len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Null,
+    Boolean(bool),
+    Number,
+    String { escaped: bool },
+    Array,
+    Object,
+}
+
+impl<'a> Tape<'a> {
+    /// The value the document consists of.
+    pub fn root(&self) -> TapeValue<'_> {
+        TapeValue {
+            tape: self,
+            index: 0,
+        }
+    }
+
+    /// The text the tape indexes.
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+}
+
+/// One value in a [`Tape`]. Cheap to copy; reading it decodes only what is
+/// asked for.
+#[derive(Debug, Clone, Copy)]
+pub struct TapeValue<'t> {
+    tape: &'t Tape<'t>,
+    index: usize,
+}
+
+impl<'t> TapeValue<'t> {
+    fn entry(&self) -> &'t Entry {
+        &self.tape.entries[self.index]
+    }
+
+    fn at(&self, index: usize) -> TapeValue<'t> {
+        TapeValue {
+            tape: self.tape,
+            index,
+        }
+    }
+
+    /// The value's text exactly as it appears in the document.
+    pub fn raw(&self) -> &'t str {
+        let entry = self.entry();
+        &self.tape.text[entry.start..entry.end]
+    }
+
+    /// `"null"`, `"boolean"`, `"number"`, `"string"`, `"array"` or
+    /// `"object"`, as [`JsonValue::type_name`] says.
+    pub fn type_name(&self) -> &'static str {
+        match self.entry().kind {
+            Kind::Null => "null",
+            Kind::Boolean(_) => "boolean",
+            Kind::Number => "number",
+            Kind::String { .. } => "string",
+            Kind::Array => "array",
+            Kind::Object => "object",
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.entry().kind == Kind::Null
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.entry().kind == Kind::Array
+    }
+
+    pub fn is_object(&self) -> bool {
+        self.entry().kind == Kind::Object
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.entry().kind {
+            Kind::Boolean(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// The number, parsed from the text on each call.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.entry().kind {
+            Kind::Number => self.raw().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// The string's text, borrowed from the document unless it has
+    /// escapes to decode.
+    pub fn as_str(&self) -> Option<Cow<'t, str>> {
+        match self.entry().kind {
+            Kind::String { escaped } => Some(self.string(escaped)),
+            _ => None,
+        }
+    }
+
+    fn string(&self, escaped: bool) -> Cow<'t, str> {
+        let raw = self.raw();
+        if escaped {
+            Cow::Owned(
+                unescape_json_string(raw).expect("strings are checked when the tape is built"),
+            )
+        } else {
+            Cow::Borrowed(&raw[1..raw.len() - 1])
+        }
+    }
+
+    /// Elements of an array or members of an object; 0 for anything else.
+    pub fn len(&self) -> usize {
+        self.entry().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The elements of an array, in order; nothing for anything else.
+    pub fn elements(&self) -> TapeElements<'t> {
+        let (next, end) = match self.entry().kind {
+            Kind::Array => (self.index + 1, self.entry().next),
+            _ => (0, 0),
+        };
+        TapeElements {
+            value: *self,
+            next,
+            end,
+        }
+    }
+
+    /// The members of an object, in document order, repeated keys
+    /// included; nothing for anything else.
+    pub fn members(&self) -> TapeMembers<'t> {
+        let (next, end) = match self.entry().kind {
+            Kind::Object => (self.index + 1, self.entry().next),
+            _ => (0, 0),
+        };
+        TapeMembers {
+            value: *self,
+            next,
+            end,
+        }
+    }
+
+    /// The member named `key`; the last one when the key repeats, as
+    /// [`JsonParser::parse`](crate::JsonParser::parse) keeps by default.
+    pub fn get(&self, key: &str) -> Option<TapeValue<'t>> {
+        self.members()
+            .filter(|(name, _)| name.as_ref() == key)
+            .last()
+            .map(|(_, value)| value)
+    }
+
+    /// The array element at `index`.
+    pub fn get_index(&self, index: usize) -> Option<TapeValue<'t>> {
+        self.elements().nth(index)
+    }
+
+    /// Looks up a value by RFC 6901 JSON Pointer, as
+    /// [`JsonValue::pointer`] does.
+    pub fn pointer(&self, pointer: &str) -> Option<TapeValue<'t>> {
+        pointer_tokens(pointer)?
+            .iter()
+            .try_fold(*self, |node, token| match node.entry().kind {
+                Kind::Object => node.get(token),
+                Kind::Array => node.get_index(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Builds the value as a [`JsonValue`], as parsing its text would.
+    /// Containers are filled from an explicit stack, so any depth of
+    /// nesting is fine.
+    pub fn to_value(&self) -> JsonValue {
+        // The containers being built, each with the key its parent holds
+        // it under and the children left to build
+        let mut open: Vec<(Option<Cow<'t, str>>, JsonValue, Children<'t>)> = Vec::new();
+        let mut node = (None, *self);
+        loop {
+            let (key, value) = node;
+            let mut done = match value.entry().kind {
+                Kind::Null => Some((key, JsonValue::Null)),
+                Kind::Boolean(b) => Some((key, JsonValue::Boolean(b))),
+                Kind::Number => Some((key, JsonValue::Number(value.as_f64().unwrap_or(f64::NAN)))),
+                Kind::String { escaped } => {
+                    Some((key, JsonValue::String(value.string(escaped).into())))
+                }
+                Kind::Array => {
+                    let array = JsonValue::Array(JsonArray::new());
+                    open.push((key, array, Children::Items(value.elements())));
+                    None
+                }
+                Kind::Object => {
+                    let object = JsonValue::Object(JsonObject::new());
+                    open.push((key, object, Children::Members(value.members())));
+                    None
+                }
+            };
+            // Add what was built to its parent, closing every container
+            // that completes, until one has a child left to build
+            node = loop {
+                let Some((_, parent, children)) = open.last_mut() else {
+                    let (_, value) = done.expect("the outermost value was built");
+                    return value;
+                };
+                match (parent, done.take()) {
+                    (JsonValue::Array(array), Some((_, value))) => array.push(value),
+                    (JsonValue::Object(object), Some((Some(key), value))) => {
+                        object.insert(key.into_owned(), value);
+                    }
+                    _ => {}
+                }
+                match children.next() {
+                    Some(child) => break child,
+                    None => {
+                        let (key, value, _) = open.pop().expect("checked above");
+                        done = Some((key, value));
+                    }
+                }
+            };
+        }
+    }
+}
+
+/// The children of a container [`TapeValue::to_value`] is building.
+enum Children<'t> {
+    Items(TapeElements<'t>),
+    Members(TapeMembers<'t>),
+}
+
+impl<'t> Iterator for Children<'t> {
+    type Item = (Option<Cow<'t, str>>, TapeValue<'t>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Children::Items(items) => items.next().map(|item| (None, item)),
+            Children::Members(members) => members.next().map(|(key, value)| (Some(key), value)),
+        }
+    }
+}
+
+/// Iterator returned by [`TapeValue::elements`].
+#[derive(Debug, Clone)]
+pub struct TapeElements<'t> {
+    value: TapeValue<'t>,
+    next: usize,
+    end: usize,
+}
+
+impl<'t> Iterator for TapeElements<'t> {
+    type Item = TapeValue<'t>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let item = self.value.at(self.next);
+        self.next = item.entry().next;
+        Some(item)
+    }
+}
+
+/// Iterator returned by [`TapeValue::members`].
+#[derive(Debug, Clone)]
+pub struct TapeMembers<'t> {
+    value: TapeValue<'t>,
+    next: usize,
+    end: usize,
+}
+
+impl<'t> Iterator for TapeMembers<'t> {
+    type Item = (Cow<'t, str>, TapeValue<'t>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let key = self.value.at(self.next);
+        let value = self.value.at(key.entry().next);
+        self.next = value.entry().next;
+        let Kind::String { escaped } = key.entry().kind else {
+            unreachable!("object keys are strings")
+        };
+        Some((key.string(escaped), value))
+    }
+}
+
+/// Builds the entries of a tape in one pass over the bytes, keeping the
+/// open containers on a stack rather than recursing.
+struct Builder<'a> {
+    scan: Scanner<'a>,
+    entries: Vec<Entry>,
+    /// Open containers: the index of each entry and the values read in it.
+    open: Vec<(usize, usize)>,
+}
+
+impl Builder<'_> {
+    fn document(&mut self) -> Result<()> {
+        'values: loop {
+            if self.value()? {
+                continue;
+            }
+            // Close containers until one continues with another value.
+            while let Some(&(index, _)) = self.open.last() {
+                let is_object = self.entries[index].kind == Kind::Object;
+                let (close, closing) = if is_object {
+                    (b'}', ExpectedToken::RightBrace)
+                } else {
+                    (b']', ExpectedToken::RightBracket)
+                };
+                self.scan.skip_whitespace();
+                match self.scan.peek() {
+                    Some(b',') => {
+                        let comma = self.scan.pos;
+                        self.scan.pos += 1;
+                        self.scan.skip_whitespace();
+                        if self.scan.peek() == Some(close) {
+                            return Err(disabled(
+                                "trailing commas",
+                                "allow_trailing_commas",
+                                self.scan.char_offset(comma),
+                            ));
+                        }
+                        if is_object {
+                            self.key()?;
+                        }
+                        continue 'values;
+                    }
+                    Some(c) if c == close => self.close(),
+                    _ => return Err(self.scan.unexpected(vec![ExpectedToken::Comma, closing])),
+                }
+            }
+            self.scan.skip_whitespace();
+            if self.scan.pos < self.scan.bytes.len() {
+                return Err(self.scan.unexpected(vec![ExpectedToken::EndOfInput]));
+            }
+            return Ok(());
+        }
+    }
+
+    /// Reads one value. Returns `true` when that opened a container whose
+    /// first value is to be read next; an empty one is closed at once.
+    fn value(&mut self) -> Result<bool> {
+        self.scan.skip_whitespace();
+        if let Some((_, count)) = self.open.last_mut() {
+            *count += 1;
+        }
+        let start = self.scan.pos;
+        let kind = match self.scan.bytes.get(start) {
+            Some(&open @ (b'{' | b'[')) => {
+                let (kind, close) = if open == b'{' {
+                    (Kind::Object, b'}')
+                } else {
+                    (Kind::Array, b']')
+                };
+                self.open.push((self.entries.len(), 0));
+                self.push(kind, start, start + 1);
+                self.scan.pos += 1;
+                self.scan.skip_whitespace();
+                if self.scan.peek() == Some(close) {
+                    self.close();
+                    return Ok(false);
+                }
+                if kind == Kind::Object {
+                    self.key()?;
+                }
+                return Ok(true);
+            }
+            Some(b'"') => {
+                self.string()?;
+                return Ok(false);
+            }
+            Some(b'-' | b'0'..=b'9') => {
+                while self.scan.peek().is_some_and(|b| {
+                    b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E')
+                }) {
+                    self.scan.pos += 1;
+                }
+                let raw = &self.scan.text[start..self.scan.pos];
+                if !Tokenizer::is_rfc8259_number(raw) {
+                    if self.scan.pos == self.scan.bytes.len()
+                        && Tokenizer::is_rfc8259_number(&format!("{raw}0"))
+                    {
+                        return Err(self.scan.unexpected(vec![ExpectedToken::Digit]));
+                    }
+                    return Err(JsonError::InvalidNumber {
+                        value: raw.to_string(),
+                        position: self.scan.char_offset(start),
+                        path: JsonPath::new(),
+                    });
+                }
+                Kind::Number
+            }
+            Some(b) if b.is_ascii_alphabetic() => {
+                while self.scan.peek().is_some_and(|b| b.is_ascii_alphabetic()) {
+                    self.scan.pos += 1;
+                }
+                match &self.scan.text[start..self.scan.pos] {
+                    "true" => Kind::Boolean(true),
+                    "false" => Kind::Boolean(false),
+                    "null" => Kind::Null,
+                    word if self.scan.pos == self.scan.bytes.len()
+                        && ["true", "false", "null"]
+                            .iter()
+                            .any(|keyword| keyword.starts_with(word)) =>
+                    {
+                        return Err(self.scan.unexpected(vec![ExpectedToken::Keyword]));
+                    }
+                    word => {
+                        return Err(JsonError::UnexpectedToken {
+                            expected: vec![ExpectedToken::Keyword],
+                            found: word.to_string(),
+                            position: self.scan.char_offset(start),
+                            path: JsonPath::new(),
+                        });
+                    }
+                }
+            }
+            _ => return Err(self.scan.unexpected(vec![ExpectedToken::Value])),
+        };
+        self.push(kind, start, self.scan.pos);
+        Ok(false)
+    }
+
+    /// Reads an object key and its colon, leaving the value to be read.
+    fn key(&mut self) -> Result<()> {
+        self.scan.skip_whitespace();
+        if self.scan.peek() != Some(b'"') {
+            return Err(self.scan.unexpected(vec![ExpectedToken::Key]));
+        }
+        self.string()?;
+        self.scan.skip_whitespace();
+        if self.scan.peek() != Some(b':') {
+            return Err(self.scan.unexpected(vec![ExpectedToken::Colon]));
+        }
+        self.scan.pos += 1;
+        Ok(())
+    }
+
+    /// Reads a string literal, checking its escapes without decoding them.
+    fn string(&mut self) -> Result<()> {
+        let start = self.scan.pos;
+        let escaped = self.scan.string()?;
+        self.push(Kind::String { escaped }, start, self.scan.pos);
+        Ok(())
+    }
+
+    fn push(&mut self, kind: Kind, start: usize, end: usize) {
+        let next = self.entries.len() + 1;
+        self.entries.push(Entry {
+            kind,
+            start,
+            end,
+            next,
+            len: 0,
+        });
+    }
+
+    /// Closes the innermost container at the bracket under `pos`.
+    fn close(&mut self) {
+        let (index, count) = self.open.pop().expect("a container is open");
+        self.scan.pos += 1;
+        let next = self.entries.len();
+        let entry = &mut self.entries[index];
+        entry.end = self.scan.pos;
+        entry.next = next;
+        entry.len = count;
+    }
+}
+
+/// Reads JSON text byte by byte: the whitespace, string literals and
+/// error positions shared by the tape builder and the skimmer behind
+/// [`LazyValue`](crate::LazyValue).
+#[derive(Debug, Clone)]
+pub(crate) struct Scanner<'a> {
+    pub(crate) text: &'a str,
+    pub(crate) bytes: &'a [u8],
+    /// Byte offset of the next byte to read.
+    pub(crate) pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub(crate) fn new(text: &'a str, pos: usize) -> Self {
+        Self {
+            text,
+            bytes: text.as_bytes(),
+            pos,
+        }
+    }
+
+    pub(crate) fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    /// Moves past the string literal at `pos`, checking its escapes
+    /// without decoding them, and returns whether it has any.
+    pub(crate) fn string(&mut self) -> Result<bool> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut escaped = false;
+        loop {
+            match self.peek() {
+                None => {
+                    return Err(JsonError::UnexpectedEndOfInput {
+                        expected: vec![ExpectedToken::Quote('"')],
+                        position: self.char_offset(start),
+                        path: JsonPath::new(),
+                    });
+                }
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    escaped = true;
+                    self.escape()?;
+                }
+                Some(b) if b < 0x20 => {
+                    return Err(JsonError::UnexpectedToken {
+                        expected: vec![ExpectedToken::EscapedControlCharacter],
+                        found: format!("U+{b:04X}"),
+                        position: self.char_offset(self.pos),
+                        path: JsonPath::new(),
+                    });
+                }
+                // Bytes inside a multi-byte character are never a quote,
+                // backslash or control character.
+                Some(_) => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        Ok(escaped)
+    }
+
+    /// Checks the escape at the backslash under `pos` and moves past it.
+    /// A `\u` escape of a UTF-16 surrogate must be one half of a pair.
+    fn escape(&mut self) -> Result<()> {
+        let slash = self.pos;
+        let Some(&c) = self.bytes.get(slash + 1) else {
+            return Err(JsonError::UnexpectedEndOfInput {
+                expected: vec![ExpectedToken::EscapeCharacter],
+                position: self.char_offset(slash + 1),
+                path: JsonPath::new(),
+            });
+        };
+        match c {
+            b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't' => {
+                self.pos += 2;
+                Ok(())
+            }
+            b'u' => {
+                let high = self.code_unit(slash)?;
+                if !(0xD800..0xE000).contains(&high) {
+                    return Ok(());
+                }
+                let paired = high < 0xDC00
+                    && self.bytes[self.pos..].starts_with(b"\\u")
+                    && (0xDC00..0xE000).contains(&self.code_unit(slash)?);
+                if paired {
+                    Ok(())
+                } else {
+                    Err(JsonError::InvalidUnicode {
+                        sequence: self.text[slash + 2..self.pos].to_string(),
+                        position: self.char_offset(slash),
+                        path: JsonPath::new(),
+                    })
+                }
+            }
+            _ => Err(JsonError::InvalidEscape {
+                character: self.text[slash + 1..].chars().next().unwrap_or('\\'),
+                position: self.char_offset(slash),
+                path: JsonPath::new(),
+            }),
+        }
+    }
+
+    /// Reads the `\uXXXX` at `pos` and moves past it; `slash` is where the
+    /// whole escape started, for errors.
+    fn code_unit(&mut self, slash: usize) -> Result<u32> {
+        let digits = self.pos + 2..self.pos + 6;
+        let unit = self
+            .text
+            .get(digits.clone())
+            .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+        match unit {
+            Some(unit) => {
+                self.pos = digits.end;
+                Ok(unit)
+            }
+            None => {
+                let end = digits.end.min(self.text.len());
+                let sequence: String = self.text[digits.start.min(end)..].chars().take(4).collect();
+                Err(JsonError::InvalidUnicode {
+                    sequence,
+                    position: self.char_offset(slash),
+                    path: JsonPath::new(),
+                })
+            }
+        }
+    }
+
+    pub(crate) fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// The error for whatever is at `pos` when one of `expected` was due.
+    pub(crate) fn unexpected(&self, expected: Vec<ExpectedToken>) -> JsonError {
+        let position = self.char_offset(self.pos);
+        let rest = &self.bytes[self.pos..];
+        if rest.starts_with(b"'") {
+            return disabled("single-quoted strings", "allow_single_quotes", position);
+        }
+        if rest.starts_with(b"//") || rest.starts_with(b"/*") {
+            return disabled("comments", "allow_comments", position);
+        }
+        match self.text[self.pos..].chars().next() {
+            Some(c) => JsonError::UnexpectedToken {
+                expected,
+                found: c.to_string(),
+                position,
+                path: JsonPath::new(),
+            },
+            None => JsonError::UnexpectedEndOfInput {
+                expected,
+                position,
+                path: JsonPath::new(),
+            },
+        }
+    }
+
+    /// Errors report character offsets, like the parser's.
+    pub(crate) fn char_offset(&self, byte: usize) -> usize {
+        char_offset(self.text, byte)
+    }
+}
+
+/// Errors report character offsets, like the parser's.
+pub(crate) fn char_offset(text: &str, byte: usize) -> usize {
+    text[..byte].chars().count()
+}
+
+/// The error for syntax the strict parser has switched off.
+fn disabled(feature: &str, option: &str, position: usize) -> JsonError {
+    JsonError::DisabledFeature {
+        feature: feature.to_string(),
+        option: option.to_string(),
+        position,
+        path: JsonPath::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DuplicateKeys, JsonParser, ParserOptions};
+
+    fn parse_strict(input: &str) -> Result<JsonValue> {
+        let options = ParserOptions::strict_rfc8259().duplicate_keys(DuplicateKeys::LastWins);
+        JsonParser::with_options(input, options)?.parse()
+    }
+
+    #[test]
+    fn test_to_value_matches_parser() {
+        let inputs = [
+            "null",
+            " true ",
+            "-0.5e3",
+            r#""tab\t é 😀""#,
+            "[]",
+            "{}",
+            r#"{"a": [1, {"b": null}, []], "c": {"d": "e"}, "a": false}"#,
+            "[[[1], 2], 3, {\"\": [\"\"]}]",
+        ];
+        for input in inputs {
+            let tape = parse_to_tape(input).unwrap();
+            assert_eq!(
+                tape.root().to_value(),
+                parse_strict(input).unwrap(),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_navigation() {
+        let tape =
+            parse_to_tape(r#"{"a": [10, {"b": "x\ny"}, []], "c": "plain", "a": [20]}"#).unwrap();
+        let root = tape.root();
+        assert_eq!(root.type_name(), "object");
+        assert_eq!(root.len(), 3);
+        let keys: Vec<_> = root.members().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["a", "c", "a"]);
+        // The last of a repeated key, as the parser keeps.
+        assert_eq!(root.get("a").unwrap().raw(), "[20]");
+
+        let first = root.members().next().unwrap().1;
+        assert_eq!(first.len(), 3);
+        assert_eq!(first.get_index(0).and_then(|v| v.as_f64()), Some(10.0));
+        assert!(first.get_index(2).unwrap().is_empty());
+        assert!(first.get_index(3).is_none());
+        let b = first.get_index(1).unwrap().get("b").unwrap();
+        assert!(matches!(b.as_str(), Some(Cow::Owned(s)) if s == "x\ny"));
+        assert!(matches!(
+            root.get("c").unwrap().as_str(),
+            Some(Cow::Borrowed("plain"))
+        ));
+
+        assert_eq!(root.pointer("/a/0").unwrap().raw(), "20");
+        assert!(root.pointer("/c/0").is_none());
+        assert_eq!(root.pointer("").unwrap().raw(), tape.as_str());
+        assert_eq!(root.get("c").unwrap().elements().count(), 0);
+    }
+
+    #[test]
+    fn test_deep_nesting_does_not_recurse() {
+        let depth = 100_000;
+        let input = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        let tape = parse_to_tape(&input).unwrap();
+        let mut node = tape.root();
+        for _ in 1..depth {
+            node = node.get_index(0).unwrap();
+        }
+        assert!(node.is_empty());
+        let value = tape.root().to_value();
+        let mut node = &value;
+        for _ in 1..depth {
+            node = node.get_index(0).unwrap();
+        }
+        assert_eq!(node, &JsonValue::Array(JsonArray::new()));
+    }
+
+    #[test]
+    fn test_errors_match_parser() {
+        let inputs = [
+            "",
+            "[1,]",
+            "[1 2]",
+            r#"{"a" 1}"#,
+            "{1: 2}",
+            "01",
+            "-",
+            "tru",
+            r#""abc"#,
+            r#""\x""#,
+            r#""\u12""#,
+            r#""\ud800""#,
+            r#""\ud800A""#,
+            "[1] 2",
+            "{\"a\": 1",
+            "{\"a\": 1, }",
+            "['a']",
+            "[1, /* c */ 2]",
+            "[1, / 2]",
+        ];
+        for input in inputs {
+            let expected = parse_strict(input).unwrap_err();
+            let found = parse_to_tape(input).unwrap_err();
+            assert_eq!(found.kind(), expected.kind(), "{input}: {found}");
+            assert_eq!(found.position(), expected.position(), "{input}: {found}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_extensions() {
+        for input in [
+            "// c\n1",
+            "'a'",
+            "NaN",
+            "\"\u{1}\"",
+            "0x10",
+            "1_000",
+            "\u{a0}1",
+        ] {
+            assert!(parse_to_tape(input).is_err(), "{input}");
+            assert!(parse_strict(input).is_err(), "{input}");
+        }
+    }
+}
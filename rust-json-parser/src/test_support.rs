@@ -0,0 +1,38 @@
+//! Helpers shared by the unit tests of every module.
+
+use crate::{JsonParser, JsonValue, Result};
+use std::fs;
+use std::path::PathBuf;
+
+/// Parses `input` with the default options.
+pub(crate) fn parse_json(input: &str) -> Result<JsonValue> {
+    JsonParser::new(input)?.parse()
+}
+
+/// A scratch directory removed when dropped.
+pub(crate) struct TempDir(pub(crate) PathBuf);
+
+impl TempDir {
+    /// Creates an empty directory under the system temp directory, unique
+    /// to `name` and this process.
+    pub(crate) fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("rust-json-parser-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    /// Writes the file at `relative`, creating any directories it is in.
+    pub(crate) fn write(&self, relative: &str, contents: &[u8]) {
+        let path = self.0.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
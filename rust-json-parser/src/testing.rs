@@ -0,0 +1,248 @@
+//! [`quickcheck`] support: random [`JsonValue`]s for property tests, with
+//! shrinking to a minimal failing input.
+//!
+//! `JsonValue` implements [`Arbitrary`], sized by the [`Gen`]: a value
+//! holds at most `g.size()` values in all and nests at most four deep. For
+//! other limits, call [`arbitrary_with`] from an `Arbitrary` impl of your
+//! own.
+//!
+//! ```
+//! use quickcheck::{QuickCheck, TestResult};
+//! use rust_json_parser::{JsonParser, JsonValue};
+//!
+//! fn round_trips(value: JsonValue) -> TestResult {
+//!     let text = value.to_string();
+//!     TestResult::from_bool(JsonParser::new(&text).unwrap().parse().unwrap() == value)
+//! }
+//! QuickCheck::new().tests(50).quickcheck(round_trips as fn(JsonValue) -> TestResult);
+//! ```
+
+use crate::{JsonArray, JsonObject, JsonValue};
+use quickcheck::{Arbitrary, Gen};
+
+/// Limits for [`arbitrary_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArbitraryOptions {
+    pub(crate) max_depth: usize,
+    pub(crate) max_values: Option<usize>,
+}
+
+impl Default for ArbitraryOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_values: None,
+        }
+    }
+}
+
+impl ArbitraryOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nesting past which only scalars are generated; a top-level
+    /// container is depth 1, so `0` means scalars only. Defaults to 4.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Most values in the whole document, containers included. Defaults
+    /// to the size of the [`Gen`].
+    pub fn max_values(mut self, values: usize) -> Self {
+        self.max_values = Some(values);
+        self
+    }
+}
+
+/// A random value within `options`. Numbers are always finite, since JSON
+/// has no way to write the others.
+pub fn arbitrary_with(g: &mut Gen, options: &ArbitraryOptions) -> JsonValue {
+    let mut budget = options.max_values.unwrap_or(g.size()).max(1);
+    value(g, options, 0, &mut budget)
+}
+
+/// `budget` is how many more values may be generated, this one included.
+fn value(g: &mut Gen, options: &ArbitraryOptions, depth: usize, budget: &mut usize) -> JsonValue {
+    *budget -= 1;
+    let kinds: &[u8] = if depth < options.max_depth && *budget > 0 {
+        &[0, 1, 2, 3, 4, 5]
+    } else {
+        &[0, 1, 2, 3]
+    };
+    match g.choose(kinds).expect("kinds is not empty") {
+        0 => JsonValue::Null,
+        1 => JsonValue::Boolean(bool::arbitrary(g)),
+        2 => JsonValue::Number(number(g)),
+        3 => JsonValue::String(String::arbitrary(g).into()),
+        4 => {
+            let len = usize::arbitrary(g) % (*budget + 1);
+            let mut items = JsonArray::new();
+            while items.len() < len && *budget > 0 {
+                items.push(value(g, options, depth + 1, budget));
+            }
+            JsonValue::Array(items)
+        }
+        _ => {
+            let len = usize::arbitrary(g) % (*budget + 1);
+            let mut object = JsonObject::new();
+            for _ in 0..len {
+                if *budget == 0 {
+                    break;
+                }
+                let key = String::arbitrary(g);
+                let member = value(g, options, depth + 1, budget);
+                object.insert(key, member);
+            }
+            JsonValue::Object(object)
+        }
+    }
+}
+
+/// Mostly small integers, which is what JSON mostly holds, and otherwise
+/// any finite `f64`.
+fn number(g: &mut Gen) -> f64 {
+    if bool::arbitrary(g) {
+        return f64::from(i32::arbitrary(g) % 1000);
+    }
+    loop {
+        let n = f64::arbitrary(g);
+        if n.is_finite() {
+            return n;
+        }
+    }
+}
+
+impl Arbitrary for JsonValue {
+    fn arbitrary(g: &mut Gen) -> Self {
+        arbitrary_with(g, &ArbitraryOptions::default())
+    }
+
+    /// Tries, in order: each child of a container on its own, then the
+    /// container with members removed, then with one member shrunk.
+    /// Scalars shrink within their type.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        match self {
+            JsonValue::Null => quickcheck::empty_shrinker(),
+            JsonValue::Boolean(b) => Box::new(b.shrink().map(JsonValue::Boolean)),
+            JsonValue::Number(n) => {
+                Box::new(n.shrink().filter(|n| n.is_finite()).map(JsonValue::Number))
+            }
+            JsonValue::String(s) => {
+                Box::new(s.to_string().shrink().map(|s| JsonValue::String(s.into())))
+            }
+            JsonValue::Array(items) => {
+                let items = items.as_slice().to_vec();
+                let children = items.clone().into_iter();
+                let smaller = items
+                    .shrink()
+                    .map(|items| JsonValue::Array(JsonArray::from(items)));
+                Box::new(children.chain(smaller))
+            }
+            JsonValue::Object(object) => {
+                let members: Vec<(String, JsonValue)> = object
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.clone()))
+                    .collect();
+                let children: Vec<JsonValue> =
+                    members.iter().map(|(_, value)| value.clone()).collect();
+                let without = {
+                    let members = members.clone();
+                    (0..members.len()).map(move |skip| {
+                        let kept = members.iter().enumerate().filter(|&(i, _)| i != skip);
+                        JsonValue::Object(kept.map(|(_, member)| member.clone()).collect())
+                    })
+                };
+                let shrunk = (0..members.len()).flat_map(move |at| {
+                    let members = members.clone();
+                    members[at].1.shrink().map(move |value| {
+                        let mut object: JsonObject = members.iter().cloned().collect();
+                        object.insert(members[at].0.clone(), value);
+                        JsonValue::Object(object)
+                    })
+                });
+                Box::new(children.into_iter().chain(without).chain(shrunk))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JsonParser;
+    use crate::test_support::parse_json;
+    use quickcheck::{QuickCheck, TestResult};
+
+    #[test]
+    fn test_generated_values_round_trip() {
+        fn round_trips(value: JsonValue) -> TestResult {
+            let text = value.to_string();
+            TestResult::from_bool(JsonParser::new(&text).unwrap().parse().unwrap() == value)
+        }
+        QuickCheck::new()
+            .tests(200)
+            .quickcheck(round_trips as fn(JsonValue) -> TestResult);
+    }
+
+    fn depth(value: &JsonValue) -> usize {
+        match value {
+            JsonValue::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+            JsonValue::Object(object) => 1 + object.values().map(depth).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    fn count(value: &JsonValue) -> usize {
+        match value {
+            JsonValue::Array(items) => 1 + items.iter().map(count).sum::<usize>(),
+            JsonValue::Object(object) => 1 + object.values().map(count).sum::<usize>(),
+            _ => 1,
+        }
+    }
+
+    #[test]
+    fn test_options_bound_the_value() {
+        let mut g = Gen::new(50);
+        let options = ArbitraryOptions::new().max_depth(2).max_values(5);
+        for _ in 0..200 {
+            let value = arbitrary_with(&mut g, &options);
+            assert!(depth(&value) <= 2, "{value}");
+            assert!(count(&value) <= 5, "{value}");
+        }
+        let scalars = ArbitraryOptions::new().max_depth(0);
+        assert!((0..50).all(|_| depth(&arbitrary_with(&mut g, &scalars)) == 0));
+    }
+
+    #[test]
+    fn test_shrink_tries_children_first() {
+        let value = parse_json(r#"{"a": [1, 2], "b": true}"#).unwrap();
+        let shrunk: Vec<String> = value.shrink().map(|v| v.to_string()).collect();
+        assert_eq!(
+            shrunk[..4],
+            ["[1,2]", "true", r#"{"b":true}"#, r#"{"a":[1,2]}"#]
+        );
+        assert!(shrunk.contains(&r#"{"a":[1,2],"b":false}"#.to_string()));
+        assert!(JsonValue::Null.shrink().next().is_none());
+    }
+
+    #[test]
+    fn test_shrinking_finds_a_minimal_counterexample() {
+        // Fails for any value holding the string "x" somewhere.
+        fn has_x(value: &JsonValue) -> bool {
+            match value {
+                JsonValue::String(s) => s == "x",
+                JsonValue::Array(items) => items.iter().any(has_x),
+                JsonValue::Object(object) => object.values().any(has_x),
+                _ => false,
+            }
+        }
+        let mut value =
+            parse_json(r#"{"k": [null, {"x": 1, "y": ["x", 3]}], "z": false}"#).unwrap();
+        while let Some(smaller) = value.shrink().find(has_x) {
+            value = smaller;
+        }
+        assert_eq!(value, JsonValue::String("x".into()));
+    }
+}
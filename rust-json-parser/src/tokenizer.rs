@@ -1,5 +1,9 @@
-use crate::{JsonError, Result};
+use crate::options::Clock;
+use crate::path::open_path;
+use crate::scan;
+use crate::{ExpectedToken, JsonError, JsonPath, ParserOptions, Result};
 use std::char::from_u32;
+use std::ops::Range;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
@@ -15,74 +19,306 @@ pub enum Token {
     Null,
 }
 
+/// Tokens with their spans, and each skipped malformed literal with the
+/// index of the token that follows it.
+pub(crate) type Recovered = (Vec<(Token, Range<usize>)>, Vec<(usize, JsonError)>);
+
 pub struct Tokenizer {
     input: Vec<char>,
     position: usize,
+    /// Where the token being read started.
+    lexeme_start: usize,
+    /// Tokens returned so far, for [`Limits::max_tokens`](crate::Limits::max_tokens).
+    tokens_read: usize,
+    clock: Clock,
+    options: ParserOptions,
+    /// The input is part of a longer text, so running out of it does not
+    /// end the document.
+    more_follows: bool,
 }
 
 impl Tokenizer {
     const UNICODE_HEX_LEN: usize = 4;
     pub fn new(input: &str) -> Self {
-        Self {
-            input: input.chars().collect(),
+        Self::with_options(input, ParserOptions::default())
+    }
+
+    pub fn with_options(input: &str, options: ParserOptions) -> Self {
+        Self::reusing(Vec::new(), input, options)
+    }
+
+    /// A tokenizer for `input` that decodes it into `buffer`, whose
+    /// allocation is kept.
+    pub(crate) fn reusing(buffer: Vec<char>, input: &str, options: ParserOptions) -> Self {
+        let mut tokenizer = Self {
+            input: buffer,
             position: 0,
-        }
+            lexeme_start: 0,
+            tokens_read: 0,
+            clock: Clock::default(),
+            options,
+            more_follows: false,
+        };
+        tokenizer.reset(input);
+        tokenizer
+    }
+
+    /// Starts over on `input` with the same options, reusing the buffer the
+    /// previous input was decoded into. Limits apply to each input afresh.
+    pub fn reset(&mut self, input: &str) {
+        self.input.clear();
+        self.input.extend(input.chars());
+        self.position = 0;
+        self.lexeme_start = 0;
+        self.tokens_read = 0;
+        self.clock = Clock::start(&self.options.limits);
+    }
+
+    /// Marks the input as a slice of a longer text that goes on after it,
+    /// so a literal cut short at its end is malformed, not incomplete.
+    pub(crate) fn more_follows(mut self, more: bool) -> Self {
+        self.more_follows = more;
+        self
+    }
+
+    /// Goes back to character offset `position`, where a token or the
+    /// whitespace before one starts, to read on from there again.
+    pub(crate) fn seek(&mut self, position: usize) {
+        self.position = position;
+    }
+
+    /// The decode buffer and options, for the next [`Tokenizer::reusing`].
+    pub(crate) fn into_parts(self) -> (Vec<char>, ParserOptions) {
+        (self.input, self.options)
     }
 
     pub fn tokenize(&mut self) -> Result<Vec<Token>> {
+        let spanned = self.tokenize_spanned()?;
+        Ok(spanned.into_iter().map(|(token, _)| token).collect())
+    }
+
+    /// Like [`Tokenizer::tokenize`], pairing each token with the range of
+    /// character offsets it was read from.
+    pub fn tokenize_spanned(&mut self) -> Result<Vec<(Token, Range<usize>)>> {
         let mut tokens = Vec::new();
+        while let Some(token) = self.next_token()? {
+            tokens.push(token);
+        }
+        Ok(tokens)
+    }
 
-        while !self.is_at_end() {
-            let start_pos = self.position;
+    /// Like [`Tokenizer::tokenize_spanned`], but an error carries the
+    /// [path](JsonError::path) the parser reports for it: that of the
+    /// element or member the tokens before it leave open.
+    pub(crate) fn tokenize_with_paths(&mut self) -> Result<Vec<(Token, Range<usize>)>> {
+        let mut tokens = Vec::new();
+        loop {
+            match self.next_token() {
+                Ok(Some(token)) => tokens.push(token),
+                Ok(None) => return Ok(tokens),
+                Err(err) => return Err(err.under(&open_path(tokens.iter().map(|(t, _)| t)))),
+            }
+        }
+    }
 
-            let c = match self.peek() {
-                Some(character) => character,
-                None => break, // Safety valve: exit if peek fails unexpectedly
-            };
+    /// Like [`Tokenizer::tokenize_with_paths`], but clears `tokens` and
+    /// `starts` and fills them with each token and the character offset it
+    /// starts at.
+    pub(crate) fn tokenize_into(
+        &mut self,
+        tokens: &mut Vec<Token>,
+        starts: &mut Vec<usize>,
+    ) -> Result<()> {
+        tokens.clear();
+        starts.clear();
+        loop {
+            match self.next_token() {
+                Ok(Some((token, span))) => {
+                    tokens.push(token);
+                    starts.push(span.start);
+                }
+                Ok(None) => return Ok(()),
+                Err(err) => return Err(err.under(&open_path(tokens.iter()))),
+            }
+        }
+    }
 
-            match c {
-                c if c.is_whitespace() => {
-                    self.advance();
+    /// Length of the input, in characters.
+    pub(crate) fn char_count(&self) -> usize {
+        self.input.len()
+    }
+
+    /// Like [`Tokenizer::tokenize_with_paths`], but a malformed literal is
+    /// skipped instead of ending the scan. Malformed comments and exceeded
+    /// limits still fail.
+    pub(crate) fn tokenize_recovering(&mut self) -> Result<Recovered> {
+        let mut tokens = Vec::new();
+        let mut malformed = Vec::new();
+        loop {
+            match self.next_token() {
+                Ok(Some(token)) => tokens.push(token),
+                Ok(None) => return Ok((tokens, malformed)),
+                Err(err) if err.is_limit() || self.input.get(self.lexeme_start) == Some(&'/') => {
+                    return Err(err.under(&open_path(tokens.iter().map(|(t, _)| t))));
+                }
+                Err(err) => {
+                    malformed.push((tokens.len(), err));
+                    self.skip_malformed();
+                }
+            }
+        }
+    }
+
+    /// Reads the next token, skipping whitespace and comments. Errors are
+    /// redacted as [`ParserOptions::redact_errors`] says.
+    pub(crate) fn next_token(&mut self) -> Result<Option<(Token, Range<usize>)>> {
+        self.read_token()
+            .map_err(|err| err.redacted(self.options.redaction))
+    }
+
+    fn read_token(&mut self) -> Result<Option<(Token, Range<usize>)>> {
+        while let Some(c) = self.peek() {
+            let start_pos = self.position;
+            self.lexeme_start = start_pos;
+            if !self.is_whitespace(c) && c != '/' {
+                self.count_token(start_pos)?;
+            }
+            let token = match c {
+                c if self.is_whitespace(c) => {
+                    // At least the one, which may be whitespace beyond ASCII.
+                    let run = scan::whitespace_run(&self.input[self.position..]);
+                    self.position += run.max(1);
+                    continue;
                 }
                 '{' => {
                     self.advance();
-                    tokens.push(Token::LeftBrace);
+                    Token::LeftBrace
                 }
                 '}' => {
                     self.advance();
-                    tokens.push(Token::RightBrace);
+                    Token::RightBrace
                 }
                 '[' => {
                     self.advance();
-                    tokens.push(Token::LeftBracket);
+                    Token::LeftBracket
                 }
                 ']' => {
                     self.advance();
-                    tokens.push(Token::RightBracket);
+                    Token::RightBracket
                 }
                 ',' => {
                     self.advance();
-                    tokens.push(Token::Comma);
+                    Token::Comma
                 }
                 ':' => {
                     self.advance();
-                    tokens.push(Token::Colon);
+                    Token::Colon
                 }
 
-                '"' => tokens.push(self.string(start_pos)?),
-                '-' | '0'..='9' => tokens.push(self.number(start_pos)?),
-                't' | 'f' | 'n' => tokens.push(self.keyword(start_pos)?),
+                '"' => self.string(start_pos, '"')?,
+                '\'' if self.options.allow_single_quotes => self.string(start_pos, '\'')?,
+                '\'' => {
+                    return Err(JsonError::DisabledFeature {
+                        feature: "single-quoted strings".to_string(),
+                        option: "allow_single_quotes".to_string(),
+                        position: start_pos,
+                        path: JsonPath::new(),
+                    });
+                }
+                '/' => {
+                    self.comment(start_pos)?;
+                    continue;
+                }
+                '-' | '0'..='9' => self.number(start_pos)?,
+                c if c.is_ascii_alphabetic() => self.keyword(start_pos)?,
 
                 _ => {
                     return Err(JsonError::UnexpectedToken {
-                        expected: "valid JSON value".to_string(),
+                        expected: vec![ExpectedToken::Value],
                         found: c.to_string(),
                         position: start_pos,
+                        path: JsonPath::new(),
                     });
                 }
+            };
+            return Ok(Some((token, start_pos..self.position)));
+        }
+        Ok(None)
+    }
+
+    fn count_token(&mut self, start_pos: usize) -> Result<()> {
+        self.clock.tick(start_pos)?;
+        self.tokens_read += 1;
+        match self.options.limits.max_tokens {
+            Some(limit) if self.tokens_read > limit => Err(JsonError::TokenLimitExceeded {
+                limit,
+                position: start_pos,
+                path: JsonPath::new(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Moves past the literal that failed to lex: to the end of a quoted
+    /// string, or else up to the next delimiter.
+    fn skip_malformed(&mut self) {
+        self.position = self.lexeme_start;
+        let Some(first) = self.advance() else {
+            return;
+        };
+        if matches!(first, '"' | '\'') {
+            while let Some(c) = self.advance() {
+                match c {
+                    '\\' => {
+                        self.advance();
+                    }
+                    c if c == first => return,
+                    _ => {}
+                }
             }
+            return;
+        }
+        while let Some(c) = self.peek() {
+            if self.is_whitespace(c) || matches!(c, ',' | ':' | '[' | ']' | '{' | '}' | '"') {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    /// The time budget, to hand on to the parser of the tokens read.
+    pub(crate) fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// The source text of `span`, as returned by [`Tokenizer::tokenize_spanned`].
+    pub(crate) fn text(&self, span: Range<usize>) -> impl Iterator<Item = char> + '_ {
+        self.input[span].iter().copied()
+    }
+
+    /// Reads the whole input as one double-quoted string literal and returns
+    /// its decoded contents.
+    pub(crate) fn string_literal(&mut self) -> Result<String> {
+        if self.peek() != Some('"') {
+            return Err(JsonError::UnexpectedToken {
+                expected: vec![ExpectedToken::String],
+                found: self.peek().map_or("end of input".to_string(), String::from),
+                position: self.position,
+                path: JsonPath::new(),
+            });
+        }
+        let Token::String(s) = self.string(self.position, '"')? else {
+            unreachable!("string() only returns string tokens")
+        };
+        match self.peek() {
+            None => Ok(s),
+            Some(c) => Err(JsonError::UnexpectedToken {
+                expected: vec![ExpectedToken::EndOfInput],
+                found: c.to_string(),
+                position: self.position,
+                path: JsonPath::new(),
+            }),
         }
-        Ok(tokens)
     }
 
     // --- Private Helper Methods ---
@@ -98,31 +334,113 @@ impl Tokenizer {
         self.input.get(self.position).copied()
     }
 
-    fn is_at_end(&self) -> bool {
-        self.position >= self.input.len()
+    fn is_whitespace(&self, c: char) -> bool {
+        if self.options.strict_whitespace {
+            matches!(c, ' ' | '\t' | '\n' | '\r')
+        } else {
+            c.is_whitespace()
+        }
     }
 
     // --- Specialized Token Parsers ---
 
-    fn string(&mut self, start_pos: usize) -> Result<Token> {
+    fn string(&mut self, start_pos: usize, quote: char) -> Result<Token> {
         self.advance(); // Consume the opening quote
         let mut extracted = String::new();
-
-        while let Some(c) = self.advance() {
-            match c {
-                '"' => return Ok(Token::String(extracted)),
-                '\\' => {
-                    let escaped_char = self.parse_escape()?;
-                    extracted.push(escaped_char);
-                }
-                _ => extracted.push(c),
+        let mut chars = 0;
+
+        loop {
+            // Copy the plain characters before the next quote, escape or
+            // control character in one go.
+            let run = scan::string_run(&self.input[self.position..], quote);
+            if run > 0 {
+                chars += run;
+                self.check_string_chars(chars, start_pos)?;
+                extracted.extend(&self.input[self.position..self.position + run]);
+                self.position += run;
             }
+            let Some(c) = self.advance() else {
+                break;
+            };
+            let decoded = match c {
+                c if c == quote => return Ok(Token::String(extracted)),
+                '\\' => self.parse_escape(quote)?,
+                '\u{0}'..='\u{1f}' if self.options.strict_strings => {
+                    return Err(JsonError::UnexpectedToken {
+                        expected: vec![ExpectedToken::EscapedControlCharacter],
+                        found: format!("U+{:04X}", c as u32),
+                        position: self.position - 1,
+                        path: JsonPath::new(),
+                    });
+                }
+                c => c,
+            };
+            chars += 1;
+            self.check_string_chars(chars, start_pos)?;
+            extracted.push(decoded);
         }
 
         // If we hit None before a closing quote
         Err(JsonError::UnexpectedEndOfInput {
-            expected: "\"".to_string(),
+            expected: vec![ExpectedToken::Quote(quote)],
             position: start_pos,
+            path: JsonPath::new(),
+        })
+    }
+
+    /// Enforces [`Limits::max_string_chars`](crate::Limits::max_string_chars)
+    /// on a string that started at `start_pos` and has `chars` so far.
+    fn check_string_chars(&self, chars: usize, start_pos: usize) -> Result<()> {
+        match self.options.limits.max_string_chars {
+            Some(limit) if chars > limit => Err(JsonError::StringLimitExceeded {
+                limit,
+                position: start_pos,
+                path: JsonPath::new(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Skips a `// line` or `/* block */` comment.
+    fn comment(&mut self, start_pos: usize) -> Result<()> {
+        self.advance(); // Consume the first '/'
+        let kind = self.peek();
+        if !matches!(kind, Some('/' | '*')) {
+            return Err(JsonError::UnexpectedToken {
+                expected: vec![ExpectedToken::Value],
+                found: "/".to_string(),
+                position: start_pos,
+                path: JsonPath::new(),
+            });
+        }
+        if !self.options.allow_comments {
+            return Err(JsonError::DisabledFeature {
+                feature: "comments".to_string(),
+                option: "allow_comments".to_string(),
+                position: start_pos,
+                path: JsonPath::new(),
+            });
+        }
+        self.advance();
+
+        if kind == Some('/') {
+            while let Some(c) = self.advance() {
+                if c == '\n' {
+                    break;
+                }
+            }
+            return Ok(());
+        }
+        while let Some(c) = self.advance() {
+            if c == '*' && self.peek() == Some('/') {
+                self.advance();
+                return Ok(());
+            }
+        }
+        Err(JsonError::UnexpectedEndOfInput {
+            expected: vec![ExpectedToken::CommentEnd],
+            position: start_pos,
+            path: JsonPath::new(),
         })
     }
 
@@ -131,23 +449,145 @@ impl Tokenizer {
 
         // Consume characters as long as they belong to a JSON number
         while let Some(c) = self.peek() {
-            if c.is_ascii_digit() || c == '.' || c == '-' || c == 'e' || c == 'E' || c == '+' {
+            if c.is_ascii_digit()
+                || c == '.'
+                || c == '-'
+                || c == 'e'
+                || c == 'E'
+                || c == '+'
+                || (c == '_' && self.options.allow_digit_separators)
+            {
                 num_str.push(self.advance().unwrap_or(c));
             } else {
                 break;
             }
         }
 
-        let val = num_str
+        // Input that ends partway through a number, as `-` or `1.5e+`, may
+        // just not have arrived yet
+        let rejected = if self.options.strict_numbers {
+            !Self::is_rfc8259_number(&num_str)
+        } else {
+            num_str.parse::<f64>().is_err()
+        };
+        if rejected && self.at_document_end() && Self::is_rfc8259_number(&format!("{num_str}0")) {
+            return Err(JsonError::UnexpectedEndOfInput {
+                expected: vec![ExpectedToken::Digit],
+                position: self.position,
+                path: JsonPath::new(),
+            });
+        }
+
+        let digits = if num_str.contains('_') {
+            Self::strip_digit_separators(&num_str).ok_or_else(|| JsonError::InvalidNumber {
+                value: num_str.clone(),
+                position: start_pos,
+                path: JsonPath::new(),
+            })?
+        } else {
+            num_str.clone()
+        };
+
+        if self.options.strict_numbers && !Self::is_rfc8259_number(&digits) {
+            return Err(JsonError::InvalidNumber {
+                value: num_str,
+                position: start_pos,
+                path: JsonPath::new(),
+            });
+        }
+        let val = digits
             .parse::<f64>()
             .map_err(|_| JsonError::InvalidNumber {
-                value: num_str,
+                value: num_str.clone(),
                 position: start_pos,
+                path: JsonPath::new(),
             })?;
 
+        if !self.options.suffix_handlers.is_empty()
+            && self.peek().is_some_and(|c| c.is_alphabetic())
+        {
+            return self.number_suffix(val, num_str, start_pos);
+        }
+
         Ok(Token::Number(val))
     }
 
+    /// Matches `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`.
+    pub(crate) fn is_rfc8259_number(s: &str) -> bool {
+        fn digits(s: &str) -> usize {
+            s.bytes().take_while(u8::is_ascii_digit).count()
+        }
+        let s = s.strip_prefix('-').unwrap_or(s);
+        let int = digits(s);
+        if int == 0 || (int > 1 && s.starts_with('0')) {
+            return false;
+        }
+        let mut rest = &s[int..];
+        if let Some(frac) = rest.strip_prefix('.') {
+            let n = digits(frac);
+            if n == 0 {
+                return false;
+            }
+            rest = &frac[n..];
+        }
+        if let Some(exp) = rest.strip_prefix(['e', 'E']) {
+            let exp = exp.strip_prefix(['+', '-']).unwrap_or(exp);
+            let n = digits(exp);
+            if n == 0 {
+                return false;
+            }
+            rest = &exp[n..];
+        }
+        rest.is_empty()
+    }
+
+    /// Removes `_` separators, which are only valid between two digits.
+    fn strip_digit_separators(raw: &str) -> Option<String> {
+        let chars: Vec<char> = raw.chars().collect();
+        for (i, c) in chars.iter().enumerate() {
+            if *c == '_' {
+                let before = i.checked_sub(1).and_then(|j| chars.get(j));
+                let after = chars.get(i + 1);
+                if !before.is_some_and(char::is_ascii_digit)
+                    || !after.is_some_and(char::is_ascii_digit)
+                {
+                    return None;
+                }
+            }
+        }
+        Some(raw.replace('_', ""))
+    }
+
+    /// Applies the first suffix handler that recognises the unit after a number.
+    fn number_suffix(&mut self, value: f64, mut raw: String, start_pos: usize) -> Result<Token> {
+        let mut suffix = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_alphabetic() {
+                suffix.push(self.advance().unwrap_or(c));
+            } else {
+                break;
+            }
+        }
+
+        let handled = self
+            .options
+            .suffix_handlers
+            .iter()
+            .find_map(|handler| handler(value, &suffix));
+
+        match handled {
+            Some(n) => Ok(Token::Number(n)),
+            None => {
+                raw.push_str(&suffix);
+                Err(JsonError::InvalidNumber {
+                    value: raw,
+                    position: start_pos,
+                    path: JsonPath::new(),
+                })
+            }
+        }
+    }
+
     fn keyword(&mut self, start_pos: usize) -> Result<Token> {
         let mut word = String::new();
 
@@ -159,21 +599,32 @@ impl Tokenizer {
             }
         }
 
+        let cut_short = |keyword: &str| keyword.len() > word.len() && keyword.starts_with(&word);
+        if self.at_document_end() && ["true", "false", "null"].into_iter().any(cut_short) {
+            return Err(JsonError::UnexpectedEndOfInput {
+                expected: vec![ExpectedToken::Keyword],
+                position: self.position,
+                path: JsonPath::new(),
+            });
+        }
+
         match word.as_str() {
             "true" => Ok(Token::Boolean(true)),
             "false" => Ok(Token::Boolean(false)),
             "null" => Ok(Token::Null),
             _ => Err(JsonError::UnexpectedToken {
-                expected: "keyword".to_string(),
+                expected: vec![ExpectedToken::Keyword],
                 found: word,
                 position: start_pos,
+                path: JsonPath::new(),
             }),
         }
     }
 
-    fn parse_escape(&mut self) -> Result<char> {
+    fn parse_escape(&mut self, quote: char) -> Result<char> {
         let slash_pos = self.position - 1; // Position of the '\'
         match self.advance() {
+            Some('\'') if quote == '\'' => Ok('\''),
             Some('n') => Ok('\n'),
             Some('r') => Ok('\r'),
             Some('t') => Ok('\t'),
@@ -188,48 +639,153 @@ impl Tokenizer {
                 Err(JsonError::InvalidEscape {
                     character: other,
                     position: slash_pos,
+                    path: JsonPath::new(),
                 })
             }
             None => Err(JsonError::UnexpectedEndOfInput {
-                expected: "escape character".to_string(),
+                expected: vec![ExpectedToken::EscapeCharacter],
                 position: self.position,
+                path: JsonPath::new(),
             }),
         }
     }
 
     fn unicode_escape(&mut self, start_pos: usize) -> Result<char> {
+        let (hex_string, code_point) = self.hex_code_unit(start_pos)?;
+
+        // Characters above U+FFFF arrive as a UTF-16 surrogate pair: \uD83D\uDE00
+        if (0xD800..0xDC00).contains(&code_point)
+            && self.peek() == Some('\\')
+            && self.input.get(self.position + 1) == Some(&'u')
+        {
+            self.advance();
+            self.advance();
+            let (low_hex, low) = self.hex_code_unit(start_pos)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(JsonError::InvalidUnicode {
+                    sequence: format!("{hex_string}\\u{low_hex}"),
+                    position: start_pos,
+                    path: JsonPath::new(),
+                });
+            }
+            let combined = 0x10000 + ((code_point - 0xD800) << 10) + (low - 0xDC00);
+            return from_u32(combined).ok_or(JsonError::InvalidUnicode {
+                sequence: format!("{hex_string}\\u{low_hex}"),
+                position: start_pos,
+                path: JsonPath::new(),
+            });
+        }
+
+        // The low half of a pair may not have arrived yet
+        let rest = &self.input[self.position..];
+        if (0xD800..0xDC00).contains(&code_point)
+            && (rest.is_empty() || rest == ['\\'])
+            && !self.more_follows
+        {
+            return Err(JsonError::UnexpectedEndOfInput {
+                expected: vec![ExpectedToken::EscapeCharacter],
+                position: self.input.len(),
+                path: JsonPath::new(),
+            });
+        }
+
+        from_u32(code_point).ok_or(JsonError::InvalidUnicode {
+            sequence: hex_string,
+            position: start_pos,
+            path: JsonPath::new(),
+        })
+    }
+
+    /// Reads the four hex digits of a `\u` escape.
+    fn hex_code_unit(&mut self, start_pos: usize) -> Result<(String, u32)> {
         let mut hex_string = String::with_capacity(Self::UNICODE_HEX_LEN);
 
         // Collect exactly 4 characters
         for _ in 0..Self::UNICODE_HEX_LEN {
             match self.advance() {
                 Some(c) => hex_string.push(c),
+                None if self.at_document_end()
+                    && hex_string.chars().all(|c| c.is_ascii_hexdigit()) =>
+                {
+                    return Err(JsonError::UnexpectedEndOfInput {
+                        expected: vec![ExpectedToken::Digit],
+                        position: self.position,
+                        path: JsonPath::new(),
+                    });
+                }
                 None => {
                     return Err(JsonError::InvalidUnicode {
                         sequence: hex_string,
                         position: start_pos,
+                        path: JsonPath::new(),
                     });
                 }
             }
         }
 
-        // Convert Hex to u32, then to char
+        // Convert Hex to u32
         let code_point =
             u32::from_str_radix(&hex_string, 16).map_err(|_| JsonError::InvalidUnicode {
                 sequence: hex_string.clone(),
                 position: start_pos,
+                path: JsonPath::new(),
             })?;
-
-        from_u32(code_point).ok_or(JsonError::InvalidUnicode {
-            sequence: hex_string,
-            position: start_pos,
-        })
+        Ok((hex_string, code_point))
     }
 
     #[cfg(test)]
     fn position(&self) -> usize {
         self.position
     }
+
+    fn is_at_end(&self) -> bool {
+        self.position >= self.input.len()
+    }
+
+    /// Whether the input, and the document with it, ends here, so a
+    /// literal cut short may be completed by input still to come.
+    fn at_document_end(&self) -> bool {
+        self.is_at_end() && !self.more_follows
+    }
+}
+
+/// The keyword a hand-edited `word` was most likely meant to be: one typo
+/// away, a different case, or another language's spelling of null.
+pub(crate) fn suggest_keyword(word: &str) -> Option<&'static str> {
+    const KEYWORDS: [&str; 3] = ["true", "false", "null"];
+    let lower = word.to_lowercase();
+    if matches!(lower.as_str(), "none" | "nil" | "undefined") {
+        return Some("null");
+    }
+    KEYWORDS
+        .into_iter()
+        .find(|keyword| edit_distance(&lower, keyword) <= 1)
+}
+
+/// Edits needed to turn `a` into `b`, counting insertions, deletions,
+/// substitutions and swaps of adjacent characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
 }
 
 #[cfg(test)]
@@ -326,6 +882,33 @@ mod tests {
             assert_eq!(t1.advance(), t2.advance());
             assert_eq!(t1.advance(), t2.advance());
         }
+
+        #[test]
+        fn test_spans_cover_token_text() {
+            let mut tokenizer = Tokenizer::new(r#" {"é": -1.5e2 }"#);
+            let spans: Vec<_> = tokenizer
+                .tokenize_spanned()
+                .unwrap()
+                .into_iter()
+                .map(|(_, span)| span)
+                .collect();
+            assert_eq!(spans, vec![1..2, 2..5, 5..6, 7..13, 14..15]);
+            let text: String = tokenizer.text(7..13).collect();
+            assert_eq!(text, "-1.5e2");
+        }
+
+        #[test]
+        fn test_reset_starts_over_on_new_input() {
+            let mut tokenizer = Tokenizer::new("[true, null]");
+            tokenizer.advance();
+            tokenizer.reset("false");
+            assert_eq!(tokenizer.tokenize().unwrap(), vec![Token::Boolean(false)]);
+            assert!(tokenizer.is_at_end());
+            let capacity = tokenizer.input.capacity();
+            tokenizer.reset("1");
+            assert_eq!(tokenizer.tokenize().unwrap(), vec![Token::Number(1.0)]);
+            assert_eq!(tokenizer.input.capacity(), capacity);
+        }
     }
 
     // --- Basic Tokens Happy Path Tests ---
@@ -522,7 +1105,9 @@ mod tests {
             // "1.2.3" is not a valid f64
             let result = tokenize("1.2.3");
             match result {
-                Err(JsonError::InvalidNumber { value, position }) => {
+                Err(JsonError::InvalidNumber {
+                    value, position, ..
+                }) => {
                     assert_eq!(value, "1.2.3");
                     assert_eq!(position, 0);
                 }
@@ -549,12 +1134,42 @@ mod tests {
                     found, expected, ..
                 }) => {
                     assert_eq!(found, "truthy");
-                    assert!(expected.contains("keyword"));
+                    assert_eq!(expected, [ExpectedToken::Keyword]);
                 }
                 _ => panic!("Should have failed with UnexpectedToken error for 'truthy'"),
             }
         }
 
+        #[test]
+        fn test_keyword_typos_get_suggestions() {
+            let cases = vec![
+                ("ture", Some("true")),
+                ("flase", Some("false")),
+                ("nul", Some("null")),
+                ("True", Some("true")),
+                ("None", Some("null")),
+                ("undefined", Some("null")),
+                ("truthy", None),
+                ("yes", None),
+            ];
+            for (word, suggestion) in cases {
+                // Followed by a space, as a word at the very end may be cut short
+                let err = tokenize(&format!("{word} ")).unwrap_err();
+                let JsonError::UnexpectedToken { found, .. } = &err else {
+                    panic!("{word} should be an unexpected token");
+                };
+                assert_eq!(found, word);
+                assert_eq!(
+                    err.help(),
+                    suggestion.map(|keyword| format!("did you mean \"{keyword}\"?"))
+                );
+            }
+            assert_eq!(
+                tokenize("[ture]").unwrap_err().to_string(),
+                "Unexpected token at position 1: expected keyword, found ture — did you mean \"true\"?"
+            );
+        }
+
         #[test]
         fn test_error_illegal_character() {
             let result = tokenize("{ @ }");
@@ -640,6 +1255,79 @@ mod tests {
         }
     }
 
+    mod lenient_numbers {
+        use super::*;
+        use crate::options::{byte_size_suffix, duration_suffix};
+
+        fn tokenize_with(input: &str, options: ParserOptions) -> Result<Vec<Token>> {
+            Tokenizer::with_options(input, options).tokenize()
+        }
+
+        #[test]
+        fn test_digit_separators() {
+            let options = ParserOptions::new().allow_digit_separators(true);
+            let tokens = tokenize_with("[5_000, 1_000.5, -2_0]", options).unwrap();
+            assert_eq!(tokens[1], Token::Number(5000.0));
+            assert_eq!(tokens[3], Token::Number(1000.5));
+            assert_eq!(tokens[5], Token::Number(-20.0));
+        }
+
+        #[test]
+        fn test_misplaced_digit_separators() {
+            for input in ["5__000", "5_", "1_.5", "1._5"] {
+                let options = ParserOptions::new().allow_digit_separators(true);
+                let result = tokenize_with(input, options);
+                assert!(
+                    matches!(result, Err(JsonError::InvalidNumber { .. })),
+                    "Should reject separator placement in: {input}"
+                );
+            }
+        }
+
+        #[test]
+        fn test_digit_separators_disabled_by_default() {
+            let result = tokenize("5_000");
+            assert!(matches!(
+                result,
+                Err(JsonError::UnexpectedToken { position: 1, .. })
+            ));
+        }
+
+        #[test]
+        fn test_unit_suffixes() {
+            let options = ParserOptions::new()
+                .with_suffix_handler(byte_size_suffix)
+                .with_suffix_handler(duration_suffix);
+            let tokens = tokenize_with(r#"{"size": 10KB, "ttl": 2h}"#, options).unwrap();
+            assert_eq!(tokens[3], Token::Number(10_000.0));
+            assert_eq!(tokens[7], Token::Number(7200.0));
+        }
+
+        #[test]
+        fn test_unknown_suffix_is_invalid_number() {
+            let options = ParserOptions::new().with_suffix_handler(byte_size_suffix);
+            match tokenize_with("[10parsecs]", options) {
+                Err(JsonError::InvalidNumber {
+                    value, position, ..
+                }) => {
+                    assert_eq!(value, "10parsecs");
+                    assert_eq!(position, 1);
+                }
+                other => panic!("Expected InvalidNumber, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn test_custom_suffix_handler() {
+            fn percent(value: f64, suffix: &str) -> Option<f64> {
+                (suffix == "pct").then_some(value / 100.0)
+            }
+            let options = ParserOptions::new().with_suffix_handler(percent);
+            let tokens = tokenize_with("25pct", options).unwrap();
+            assert_eq!(tokens, vec![Token::Number(0.25)]);
+        }
+    }
+
     mod unicode_escapes {
         use super::*;
 
@@ -673,8 +1361,32 @@ mod tests {
             assert_eq!(tokens, vec![Token::String("J".to_string())]);
         }
 
+        #[test]
+        fn test_unicode_escape_surrogate_pair() {
+            // \ud83d\ude00 is U+1F600
+            let mut tokenizer = Tokenizer::new(r#""a\ud83d\ude00b""#);
+            let tokens = tokenizer.tokenize().unwrap();
+            assert_eq!(tokens, vec![Token::String("a\u{1F600}b".to_string())]);
+        }
+
         // unicode escapes error cases
         #[test]
+        fn test_invalid_unicode_lone_surrogates() {
+            for input in [
+                r#""\ud83d""#,
+                r#""\ud83dx""#,
+                r#""\ud83d\u0041""#,
+                r#""\ude00""#,
+            ] {
+                let mut tokenizer = Tokenizer::new(input);
+                let result = tokenizer.tokenize();
+                assert!(
+                    matches!(result, Err(JsonError::InvalidUnicode { .. })),
+                    "Input: {input}"
+                );
+            }
+        }
+        #[test]
         fn test_invalid_unicode_too_short() {
             let mut tokenizer = Tokenizer::new(r#""\u004""#);
             let result = tokenizer.tokenize();
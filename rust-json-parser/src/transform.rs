@@ -0,0 +1,263 @@
+use crate::{JsonPath, JsonValue};
+
+impl JsonValue {
+    /// Applies `f` to every direct member of an array or object.
+    ///
+    /// Scalars are returned unchanged.
+    pub fn map_values<F>(self, mut f: F) -> JsonValue
+    where
+        F: FnMut(JsonValue) -> JsonValue,
+    {
+        match self {
+            JsonValue::Array(arr) => JsonValue::Array(arr.into_iter().map(f).collect()),
+            JsonValue::Object(obj) => {
+                JsonValue::Object(obj.into_iter().map(|(k, v)| (k, f(v))).collect())
+            }
+            other => other,
+        }
+    }
+
+    /// Keeps only the top-level object members whose key satisfies `keep`.
+    ///
+    /// Non-object values are returned unchanged.
+    pub fn filter_keys<F>(self, mut keep: F) -> JsonValue
+    where
+        F: FnMut(&str) -> bool,
+    {
+        match self {
+            JsonValue::Object(obj) => {
+                JsonValue::Object(obj.into_iter().filter(|(k, _)| keep(k)).collect())
+            }
+            other => other,
+        }
+    }
+
+    /// Removes, at any depth, every array element and object member for which
+    /// `keep` returns `false`.
+    ///
+    /// Containers are filtered before their surviving children are visited, so
+    /// a removed subtree is never descended into. The root itself is always kept.
+    pub fn retain_recursive<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&JsonPath, &JsonValue) -> bool,
+    {
+        retain_at(self, &mut JsonPath::new(), &mut keep);
+    }
+
+    /// Reorders the members of every object, at any depth, by key.
+    ///
+    /// Objects keep their insertion order otherwise, so this gives a stable
+    /// layout for snapshots and content hashing regardless of input order.
+    pub fn sort_keys(&mut self) {
+        match self {
+            JsonValue::Array(arr) => arr.iter_mut().for_each(JsonValue::sort_keys),
+            JsonValue::Object(obj) => {
+                obj.sort_keys();
+                obj.values_mut().for_each(JsonValue::sort_keys);
+            }
+            _ => {}
+        }
+    }
+
+    /// Rebuilds the document bottom-up, passing every node and its path to `f`.
+    ///
+    /// Children are transformed before their parent. Returning `None` removes
+    /// the node from its parent; returning `None` for the root yields `None`.
+    pub fn transform<F>(self, mut f: F) -> Option<JsonValue>
+    where
+        F: FnMut(&JsonPath, JsonValue) -> Option<JsonValue>,
+    {
+        transform_at(self, &mut JsonPath::new(), &mut f)
+    }
+}
+
+fn retain_at<F>(value: &mut JsonValue, path: &mut JsonPath, keep: &mut F)
+where
+    F: FnMut(&JsonPath, &JsonValue) -> bool,
+{
+    match value {
+        JsonValue::Array(arr) => {
+            // Paths use the index the element had before anything was removed
+            let mut index = 0;
+            let mut kept_indices = Vec::with_capacity(arr.len());
+            arr.retain(|item| {
+                path.push_index(index);
+                let kept = keep(path, item);
+                path.pop();
+                if kept {
+                    kept_indices.push(index);
+                }
+                index += 1;
+                kept
+            });
+            for (item, index) in arr.iter_mut().zip(kept_indices) {
+                path.push_index(index);
+                retain_at(item, path, keep);
+                path.pop();
+            }
+        }
+        JsonValue::Object(obj) => {
+            obj.retain(|key, val| {
+                path.push_key(key.as_str());
+                let kept = keep(path, val);
+                path.pop();
+                kept
+            });
+            for (key, val) in obj.iter_mut() {
+                path.push_key(key.as_str());
+                retain_at(val, path, keep);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn transform_at<F>(value: JsonValue, path: &mut JsonPath, f: &mut F) -> Option<JsonValue>
+where
+    F: FnMut(&JsonPath, JsonValue) -> Option<JsonValue>,
+{
+    let value = match value {
+        JsonValue::Array(arr) => JsonValue::Array(
+            arr.into_iter()
+                .enumerate()
+                .filter_map(|(i, item)| {
+                    path.push_index(i);
+                    let result = transform_at(item, path, f);
+                    path.pop();
+                    result
+                })
+                .collect(),
+        ),
+        JsonValue::Object(obj) => JsonValue::Object(
+            obj.into_iter()
+                .filter_map(|(key, val)| {
+                    path.push_key(key.as_str());
+                    let result = transform_at(val, path, f);
+                    path.pop();
+                    result.map(|v| (key, v))
+                })
+                .collect(),
+        ),
+        other => other,
+    };
+    f(path, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_map_values_on_containers() {
+        let double = |v: JsonValue| match v {
+            JsonValue::Number(n) => JsonValue::Number(n * 2.0),
+            other => other,
+        };
+        let arr = parse_json("[1, 2, [3]]").unwrap().map_values(double);
+        assert_eq!(arr, parse_json("[2, 4, [3]]").unwrap());
+
+        let obj = parse_json(r#"{"a": 1, "b": "x"}"#)
+            .unwrap()
+            .map_values(double);
+        assert_eq!(obj, parse_json(r#"{"a": 2, "b": "x"}"#).unwrap());
+    }
+
+    #[test]
+    fn test_map_values_on_scalar_is_identity() {
+        let value = JsonValue::Number(1.0).map_values(|_| JsonValue::Null);
+        assert_eq!(value, JsonValue::Number(1.0));
+    }
+
+    #[test]
+    fn test_filter_keys() {
+        let value = parse_json(r#"{"id": 1, "_internal": 2, "name": "x"}"#)
+            .unwrap()
+            .filter_keys(|k| !k.starts_with('_'));
+        assert_eq!(value, parse_json(r#"{"id": 1, "name": "x"}"#).unwrap());
+    }
+
+    #[test]
+    fn test_retain_recursive_removes_at_any_depth() {
+        let mut value = parse_json(r#"{"a": null, "b": [1, null, {"c": null, "d": 2}]}"#).unwrap();
+        value.retain_recursive(|_, v| !v.is_null());
+        assert_eq!(value, parse_json(r#"{"b": [1, {"d": 2}]}"#).unwrap());
+    }
+
+    #[test]
+    fn test_retain_recursive_reports_paths() {
+        let mut value = parse_json(r#"{"keep": [0, 1, 2], "drop": {"x": 1}}"#).unwrap();
+        let mut seen = Vec::new();
+        value.retain_recursive(|path, _| {
+            seen.push(path.to_pointer());
+            path.to_pointer() != "/drop" && path.to_pointer() != "/keep/1"
+        });
+        seen.sort();
+        // "/drop/x" is never visited because its parent was removed
+        assert_eq!(
+            seen,
+            vec!["/drop", "/keep", "/keep/0", "/keep/1", "/keep/2"]
+        );
+        assert_eq!(value, parse_json(r#"{"keep": [0, 2]}"#).unwrap());
+    }
+
+    #[test]
+    fn test_sort_keys_is_recursive() {
+        let mut value = parse_json(r#"{"b": [{"z": 1, "y": 2}], "a": {"d": 0, "c": 0}}"#).unwrap();
+        value.sort_keys();
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":{"c":0,"d":0},"b":[{"y":2,"z":1}]}"#
+        );
+    }
+
+    #[test]
+    fn test_sort_keys_normalizes_input_order() {
+        let mut left = parse_json(r#"{"x": 1, "y": {"q": 1, "p": 2}}"#).unwrap();
+        let mut right = parse_json(r#"{"y": {"p": 2, "q": 1}, "x": 1}"#).unwrap();
+        assert_ne!(left.to_string(), right.to_string());
+
+        left.sort_keys();
+        right.sort_keys();
+        assert_eq!(left.to_string(), right.to_string());
+    }
+
+    #[test]
+    fn test_transform_converts_and_removes() {
+        let value =
+            parse_json(r#"{"timeout_ms": 1500, "debug": null, "nested": {"delay_ms": 250}}"#)
+                .unwrap();
+        let result = value
+            .transform(|path, v| match v {
+                JsonValue::Null => None,
+                JsonValue::Number(n) if path.to_pointer().ends_with("_ms") => {
+                    Some(JsonValue::Number(n / 1000.0))
+                }
+                other => Some(other),
+            })
+            .unwrap();
+        assert_eq!(
+            result,
+            parse_json(r#"{"timeout_ms": 1.5, "nested": {"delay_ms": 0.25}}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_transform_is_bottom_up() {
+        let value = parse_json("[[1, 2], [3]]").unwrap();
+        let mut order = Vec::new();
+        value.transform(|path, v| {
+            order.push(path.to_pointer());
+            Some(v)
+        });
+        assert_eq!(order, vec!["/0/0", "/0/1", "/0", "/1/0", "/1", ""]);
+    }
+
+    #[test]
+    fn test_transform_can_remove_root() {
+        let result = JsonValue::Null.transform(|_, _| None);
+        assert!(result.is_none());
+    }
+}
@@ -0,0 +1,314 @@
+//! TypeScript declarations (`.d.ts`) for JSON payloads, generated from a
+//! JSON Schema with [`from_schema`] or straight from sample values with
+//! [`from_samples`], which infers the schema first.
+//!
+//! Objects with known members become interfaces, named after the member
+//! that holds them (`User` → `UserAddress`); members that are not
+//! `required` are optional (`?:`).
+//!
+//! ```
+//! use rust_json_parser::{JsonParser, typescript};
+//!
+//! let samples = [
+//!     JsonParser::new(r#"{"id": 1, "address": {"city": "Oslo"}}"#)?.parse()?,
+//!     JsonParser::new(r#"{"id": 2, "nickname": null}"#)?.parse()?,
+//! ];
+//! assert_eq!(
+//!     typescript::from_samples("User", &samples),
+//!     "export interface User {
+//!   id: number;
+//!   address?: UserAddress;
+//!   nickname?: null;
+//! }
+//!
+//! export interface UserAddress {
+//!   city: string;
+//! }
+//! "
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::schema::infer_schema;
+use crate::{JsonObject, JsonValue, escape_json_string};
+use std::collections::HashSet;
+
+/// Declarations for values matching `schema`, the root one named `name`.
+///
+/// Understands `type`, `enum`, `const`, `items`, `properties`, `required`
+/// and `additionalProperties`, which covers what
+/// [`infer_schema`](crate::schema::infer_schema) produces; other keywords
+/// are ignored, and anything unconstrained is `unknown`. A root that is not
+/// an object interface becomes a type alias.
+pub fn from_schema(name: &str, schema: &JsonValue) -> String {
+    let mut generator = Generator {
+        declarations: Vec::new(),
+        names: HashSet::new(),
+    };
+    let root = type_name(name);
+    let is_interface = schema.get("type").and_then(JsonValue::as_str) == Some("object")
+        && matches!(schema.get("properties"), Some(JsonValue::Object(_)))
+        && schema.get("const").is_none()
+        && schema.get("enum").is_none();
+    if is_interface {
+        generator.ty(schema, &root);
+    } else {
+        generator.reserve(&root);
+        let ty = generator.ty(schema, &root);
+        generator
+            .declarations
+            .insert(0, format!("export type {root} = {ty};\n"));
+    }
+    generator.declarations.join("\n")
+}
+
+/// Declarations for values like `samples`, via
+/// [`infer_schema`](crate::schema::infer_schema).
+pub fn from_samples(name: &str, samples: &[JsonValue]) -> String {
+    from_schema(name, &infer_schema(samples))
+}
+
+struct Generator {
+    /// In order of first reference, so the root comes first.
+    declarations: Vec<String>,
+    names: HashSet<String>,
+}
+
+impl Generator {
+    /// Claims an unused declaration name based on `name`.
+    fn reserve(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        while self.names.contains(&candidate) {
+            candidate = format!("{name}{n}");
+            n += 1;
+        }
+        self.names.insert(candidate.clone());
+        candidate
+    }
+
+    /// The TypeScript type for `schema`; `name` is used for any interface
+    /// it needs.
+    fn ty(&mut self, schema: &JsonValue, name: &str) -> String {
+        let schema = match schema {
+            JsonValue::Boolean(false) => return "never".to_string(),
+            JsonValue::Object(schema) => schema,
+            _ => return "unknown".to_string(),
+        };
+        if let Some(value) = schema.get("const") {
+            return literal(value);
+        }
+        if let Some(JsonValue::Array(values)) = schema.get("enum") {
+            return union(values.iter().map(literal).collect());
+        }
+        let types: Vec<&str> = match schema.get("type") {
+            Some(JsonValue::String(ty)) => vec![ty.as_str()],
+            Some(JsonValue::Array(types)) => types.iter().filter_map(JsonValue::as_str).collect(),
+            _ => return "unknown".to_string(),
+        };
+        let mut members = Vec::new();
+        for ty in types {
+            let member = match ty {
+                "null" => "null".to_string(),
+                "boolean" => "boolean".to_string(),
+                "integer" | "number" => "number".to_string(),
+                "string" => "string".to_string(),
+                "array" => {
+                    let items = match schema.get("items") {
+                        Some(items) => self.ty(items, &format!("{name}Item")),
+                        None => "unknown".to_string(),
+                    };
+                    if items.contains(' ') {
+                        format!("({items})[]")
+                    } else {
+                        format!("{items}[]")
+                    }
+                }
+                "object" => self.object(schema, name),
+                _ => "unknown".to_string(),
+            };
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+        union(members)
+    }
+
+    /// An interface when `schema` lists properties, otherwise an index
+    /// signature.
+    fn object(&mut self, schema: &JsonObject, name: &str) -> String {
+        let Some(JsonValue::Object(properties)) = schema.get("properties") else {
+            let values = match schema.get("additionalProperties") {
+                Some(values) => self.ty(values, &format!("{name}Value")),
+                None => "unknown".to_string(),
+            };
+            return format!("{{ [key: string]: {values} }}");
+        };
+        let name = self.reserve(name);
+        // Claim the slot now so this interface precedes the ones it uses.
+        let slot = self.declarations.len();
+        self.declarations.push(String::new());
+
+        let required: Vec<&str> = match schema.get("required") {
+            Some(JsonValue::Array(keys)) => keys.iter().filter_map(JsonValue::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let mut body = format!("export interface {name} {{\n");
+        for (key, property) in properties.iter() {
+            let ty = self.ty(property, &format!("{name}{}", pascal_case(key)));
+            let optional = if required.contains(&key.as_str()) {
+                ""
+            } else {
+                "?"
+            };
+            body.push_str(&format!("  {}{optional}: {ty};\n", property_name(key)));
+        }
+        if let Some(values) = schema.get("additionalProperties")
+            && values != &JsonValue::Boolean(false)
+        {
+            let ty = self.ty(values, &format!("{name}Value"));
+            body.push_str(&format!("  [key: string]: {ty};\n"));
+        }
+        body.push_str("}\n");
+        self.declarations[slot] = body;
+        name
+    }
+}
+
+fn union(members: Vec<String>) -> String {
+    if members.is_empty() {
+        "never".to_string()
+    } else {
+        members.join(" | ")
+    }
+}
+
+/// A JSON scalar as a TypeScript literal type; JSON syntax is valid here.
+/// Arrays and objects have no literal type and widen to `unknown`.
+fn literal(value: &JsonValue) -> String {
+    match value {
+        JsonValue::Array(_) | JsonValue::Object(_) => "unknown".to_string(),
+        scalar => scalar.to_string(),
+    }
+}
+
+/// `key` as written in an interface: bare when it is an identifier.
+fn property_name(key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        key.to_string()
+    } else {
+        escape_json_string(key)
+    }
+}
+
+/// `user_id`, `user-id` and `userId` all become `UserId`.
+fn pascal_case(text: &str) -> String {
+    let mut out = String::new();
+    for word in text.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            out.push(first.to_ascii_uppercase());
+            out.extend(chars);
+        }
+    }
+    out
+}
+
+/// `name` in PascalCase, with an underscore before a leading digit so it
+/// is an identifier.
+fn type_name(name: &str) -> String {
+    let name = pascal_case(name);
+    match name.chars().next() {
+        None => "Type".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("_{name}"),
+        Some(_) => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[test]
+    fn test_from_schema() {
+        let schema = parse_json(
+            r#"{
+                "type": "object",
+                "required": ["id", "kind"],
+                "properties": {
+                    "id": {"type": "integer"},
+                    "kind": {"enum": ["a", "b", 3]},
+                    "tags": {"type": "array", "items": {"type": ["string", "null"]}},
+                    "line-items": {
+                        "type": ["array"],
+                        "items": {"type": "object", "properties": {"sku": {"const": "x"}}}
+                    },
+                    "extra": {"type": "object", "additionalProperties": {"type": "boolean"}},
+                    "any": {}
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            from_schema("order", &schema),
+            r#"export interface Order {
+  id: number;
+  kind: "a" | "b" | 3;
+  tags?: (string | null)[];
+  "line-items"?: OrderLineItemsItem[];
+  extra?: { [key: string]: boolean };
+  any?: unknown;
+}
+
+export interface OrderLineItemsItem {
+  sku?: "x";
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_non_object_root_is_an_alias() {
+        let schema = parse_json(r#"{"type": "array", "items": {"type": "number"}}"#).unwrap();
+        assert_eq!(
+            from_schema("scores", &schema),
+            "export type Scores = number[];\n"
+        );
+        assert_eq!(
+            from_schema("x", &JsonValue::Boolean(true)),
+            "export type X = unknown;\n"
+        );
+        let samples = [parse_json("[]").unwrap()];
+        assert_eq!(
+            from_samples("empty", &samples),
+            "export type Empty = unknown[];\n"
+        );
+    }
+
+    #[test]
+    fn test_interface_names_do_not_collide() {
+        let samples =
+            [parse_json(r#"{"a_b": {"x": 1}, "aB": {"y": 2}, "9": {"z": true}}"#).unwrap()];
+        let output = from_samples("Root", &samples);
+        assert!(output.contains("  a_b: RootAB;\n"), "{output}");
+        assert!(output.contains("  aB: RootAB2;\n"), "{output}");
+        assert!(output.contains("  \"9\": Root9;\n"), "{output}");
+        assert!(output.contains("export interface RootAB2 {\n  y: number;\n}"));
+    }
+
+    #[test]
+    fn test_pascal_case() {
+        assert_eq!(pascal_case("user_id"), "UserId");
+        assert_eq!(pascal_case("user-id"), "UserId");
+        assert_eq!(pascal_case("userId"), "UserId");
+        assert_eq!(type_name("2fa"), "_2fa");
+        assert_eq!(type_name("--"), "Type");
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{JsonError, JsonParser, Limits, ParserOptions};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// The outcome of validating one file.
+#[derive(Debug)]
+pub struct FileReport {
+    pub path: PathBuf,
+    /// `None` when the file parsed within the limits.
+    pub error: Option<FileError>,
+}
+
+impl FileReport {
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Why a file failed validation.
+#[derive(Debug)]
+pub enum FileError {
+    /// The file could not be read, or was not valid UTF-8.
+    Io(io::Error),
+    Json(JsonError),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Io(err) => write!(f, "I/O error: {err}"),
+            FileError::Json(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for FileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FileError::Io(err) => Some(err),
+            FileError::Json(err) => Some(err),
+        }
+    }
+}
+
+/// Validates every `*.json` file under `dir`, recursively, on `threads` worker threads.
+///
+/// `threads == 0` uses the available parallelism. Reports are returned sorted
+/// by path, one per file, whether it passed or not. Only failing to list a
+/// directory is an error; unreadable files are reported per file. Limits
+/// without a depth cap get one of 128.
+pub fn validate_dir(
+    dir: impl AsRef<Path>,
+    limits: &Limits,
+    threads: usize,
+) -> io::Result<Vec<FileReport>> {
+    // No file may exhaust a worker's stack
+    let limits = &limits.capped();
+    let mut files = Vec::new();
+    collect_json_files(dir.as_ref(), &mut files)?;
+    files.sort();
+
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, NonZeroUsize::get),
+        n => n,
+    }
+    .min(files.len().max(1));
+
+    let next = AtomicUsize::new(0);
+    let reports = Mutex::new(Vec::with_capacity(files.len()));
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = files.get(i) else { break };
+                    let report = FileReport {
+                        path: path.clone(),
+                        error: validate_file(path, limits).err(),
+                    };
+                    reports.lock().unwrap().push((i, report));
+                }
+            });
+        }
+    });
+
+    let mut reports = reports.into_inner().unwrap();
+    reports.sort_by_key(|(i, _)| *i);
+    Ok(reports.into_iter().map(|(_, report)| report).collect())
+}
+
+fn validate_file(path: &Path, limits: &Limits) -> Result<(), FileError> {
+    if let Some(limit) = limits.max_input_bytes {
+        // Check the size before reading so oversized files are never loaded
+        let size = fs::metadata(path).map_err(FileError::Io)?.len();
+        if size > limit as u64 {
+            return Err(FileError::Json(JsonError::InputTooLarge {
+                size: usize::try_from(size).unwrap_or(usize::MAX),
+                limit,
+            }));
+        }
+    }
+    let input = fs::read_to_string(path).map_err(FileError::Io)?;
+    let options = ParserOptions::new().limits(*limits);
+    JsonParser::with_options(&input, options)
+        .and_then(|mut parser| parser.parse())
+        .map(drop)
+        .map_err(FileError::Json)
+}
+
+fn collect_json_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect_json_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn names(dir: &TempDir, reports: &[FileReport]) -> Vec<String> {
+        reports
+            .iter()
+            .map(|r| {
+                r.path
+                    .strip_prefix(&dir.0)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reports_every_json_file_in_order() {
+        let dir = TempDir::new("validate-order");
+        dir.write("b.json", b"[1, 2]");
+        dir.write("a.json", b"{\"ok\": true}");
+        dir.write("nested/deeper/c.json", b"{oops}");
+        dir.write("notes.txt", b"not json");
+
+        for threads in [0, 1, 4] {
+            let reports = validate_dir(&dir.0, &Limits::new(), threads).unwrap();
+            let expected = ["a.json", "b.json", "nested/deeper/c.json"]
+                .map(|p| Path::new(p).to_string_lossy().into_owned());
+            assert_eq!(names(&dir, &reports), expected);
+            assert!(reports[0].is_ok() && reports[1].is_ok());
+            assert!(matches!(reports[2].error, Some(FileError::Json(_))));
+        }
+    }
+
+    #[test]
+    fn test_limits_are_applied() {
+        let dir = TempDir::new("validate-limits");
+        dir.write("deep.json", b"[[[1]]]");
+        dir.write("big.json", b"\"0123456789\"");
+
+        let limits = Limits::new().max_depth(2).max_input_bytes(8);
+        let reports = validate_dir(&dir.0, &limits, 2).unwrap();
+        assert!(matches!(
+            reports[0].error,
+            Some(FileError::Json(JsonError::InputTooLarge {
+                size: 12,
+                limit: 8
+            }))
+        ));
+        assert!(matches!(
+            reports[1].error,
+            Some(FileError::Json(JsonError::DepthLimitExceeded {
+                limit: 2,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_depth_is_capped_by_default() {
+        let dir = TempDir::new("validate-deep");
+        let deep = "[".repeat(200_000) + &"]".repeat(200_000);
+        dir.write("deep.json", deep.as_bytes());
+        let reports = validate_dir(&dir.0, &Limits::new(), 1).unwrap();
+        assert!(matches!(
+            reports[0].error,
+            Some(FileError::Json(JsonError::DepthLimitExceeded {
+                limit: 128,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_unreadable_file_is_reported() {
+        let dir = TempDir::new("validate-utf8");
+        dir.write("bad.json", &[0xff, 0xfe]);
+        let reports = validate_dir(&dir.0, &Limits::new(), 1).unwrap();
+        assert!(matches!(reports[0].error, Some(FileError::Io(_))));
+    }
+
+    #[test]
+    fn test_missing_directory_is_an_error() {
+        let result = validate_dir("/definitely/not/here", &Limits::new(), 1);
+        assert!(result.is_err());
+    }
+}
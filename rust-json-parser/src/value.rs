@@ -1,19 +1,52 @@
-use std::{collections::HashMap, fmt};
+use crate::{FormatConfig, JsonArray, JsonObject, JsonString, Result, Tokenizer};
+use std::fmt::{self, Write};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum JsonValue {
+    #[default]
     Null,
     Boolean(bool),
     Number(f64),
-    String(String),
-    Array(Vec<JsonValue>),
-    Object(HashMap<String, JsonValue>),
+    String(JsonString),
+    Array(JsonArray),
+    Object(JsonObject),
 }
 
 impl JsonValue {
+    /// An empty object.
+    pub fn new_object() -> Self {
+        JsonValue::Object(JsonObject::new())
+    }
+
+    /// An empty array.
+    pub fn new_array() -> Self {
+        JsonValue::Array(JsonArray::new())
+    }
+
+    /// An empty array with room for `capacity` elements before reallocating.
+    pub fn array_with_capacity(capacity: usize) -> Self {
+        JsonValue::Array(JsonArray::with_capacity(capacity))
+    }
+
+    /// An empty object with room for `capacity` members before reallocating.
+    pub fn object_with_capacity(capacity: usize) -> Self {
+        JsonValue::Object(JsonObject::with_capacity(capacity))
+    }
+
     pub fn is_null(&self) -> bool {
         matches!(self, JsonValue::Null)
     }
+    /// The JSON type of this value, as used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Boolean(_) => "boolean",
+            JsonValue::Number(_) => "number",
+            JsonValue::String(_) => "string",
+            JsonValue::Array(_) => "array",
+            JsonValue::Object(_) => "object",
+        }
+    }
     pub fn as_str(&self) -> Option<&str> {
         match self {
             JsonValue::String(s) => Some(s.as_str()),
@@ -33,13 +66,25 @@ impl JsonValue {
         }
     }
 
-    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+    pub fn as_array(&self) -> Option<&JsonArray> {
+        match self {
+            JsonValue::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+    pub fn as_array_mut(&mut self) -> Option<&mut JsonArray> {
         match self {
             JsonValue::Array(arr) => Some(arr),
             _ => None,
         }
     }
-    pub fn as_object(&self) -> Option<&HashMap<String, JsonValue>> {
+    pub fn as_object(&self) -> Option<&JsonObject> {
+        match self {
+            JsonValue::Object(obj) => Some(obj),
+            _ => None,
+        }
+    }
+    pub fn as_object_mut(&mut self) -> Option<&mut JsonObject> {
         match self {
             JsonValue::Object(obj) => Some(obj),
             _ => None,
@@ -51,6 +96,47 @@ impl JsonValue {
             _ => None,
         }
     }
+    /// Like [`JsonValue::get`], but ignoring letter case; see
+    /// [`JsonObject::get_ignore_case`].
+    pub fn get_ignore_case(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(obj) => obj.get_ignore_case(key),
+            _ => None,
+        }
+    }
+    /// Consumes an object, yielding its owned keys and values.
+    ///
+    /// Any other variant yields nothing.
+    pub fn into_entries(self) -> impl Iterator<Item = (JsonString, JsonValue)> {
+        match self {
+            JsonValue::Object(obj) => Some(obj.into_iter()),
+            _ => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    /// Consumes an array, yielding its owned elements in order.
+    ///
+    /// Any other variant yields nothing.
+    pub fn into_elements(self) -> impl Iterator<Item = JsonValue> {
+        match self {
+            JsonValue::Array(arr) => Some(arr.into_iter()),
+            _ => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    /// Every value for `key` in this object, in document order: more than
+    /// one only where [`DuplicateKeys::Collect`](crate::DuplicateKeys::Collect)
+    /// kept a repeated key. Yields nothing for a missing key or a
+    /// non-object.
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a JsonValue> + 'a {
+        self.as_object()
+            .into_iter()
+            .flat_map(move |obj| obj.get_all(key))
+    }
     pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
         match self {
             JsonValue::Array(arr) => arr.get(index),
@@ -58,118 +144,121 @@ impl JsonValue {
         }
     }
 
-    /// Public entry point for pretty printing
-    pub fn pretty_print(&self, indent: usize) -> String {
-        self.format_json_pretty(indent, 0)
+    /// Recursively removes `null` members, empty arrays and empty objects.
+    ///
+    /// Same as [`JsonValue::prune_with`] using the default [`PruneOptions`].
+    pub fn prune(&mut self) {
+        self.prune_with(&PruneOptions::default());
     }
 
-    /// Helper function to handle recursive indentation
-    fn format_json_pretty(&self, indent_size: usize, depth: usize) -> String {
-        let current_indent = " ".repeat(depth * indent_size);
-        let next_indent = " ".repeat((depth + 1) * indent_size);
-
+    /// Recursively removes the members selected by `options`.
+    ///
+    /// Children are pruned first, so a container that only held removable
+    /// members is removed as well. The root value itself is never removed.
+    pub fn prune_with(&mut self, options: &PruneOptions) {
         match self {
-            JsonValue::Null => "null".to_string(),
-            JsonValue::Boolean(b) => b.to_string(),
-            JsonValue::Number(n) => n.to_string(),
-            JsonValue::String(s) => format!("\"{s}\""),
-
             JsonValue::Array(arr) => {
-                if arr.is_empty() {
-                    return "[]".to_string();
+                for item in arr.iter_mut() {
+                    item.prune_with(options);
                 }
-                let mut parts = Vec::new();
-                for item in arr {
-                    parts.push(format!(
-                        "{}{}",
-                        next_indent,
-                        item.format_json_pretty(indent_size, depth + 1)
-                    ));
-                }
-                format!("[\n{}\n{}]", parts.join(",\n"), current_indent)
+                arr.retain(|item| !options.removes(item));
             }
-
             JsonValue::Object(obj) => {
-                if obj.is_empty() {
-                    return "{}".to_string();
+                for val in obj.values_mut() {
+                    val.prune_with(options);
                 }
-                let mut parts = Vec::new();
-                for (key, value) in obj {
-                    let formatted_val = value.format_json_pretty(indent_size, depth + 1);
-                    parts.push(format!("{next_indent}\"{key}\": {formatted_val}"));
-                }
-                format!("{{\n{}\n{}}}", parts.join(",\n"), current_indent)
+                obj.retain(|_, val| !options.removes(val));
             }
+            _ => {}
         }
     }
+
+    /// Same as [`JsonValue::to_string_pretty`].
+    pub fn pretty_print(&self, indent: usize) -> String {
+        self.to_string_pretty(indent)
+    }
 }
 
-trait JsonFormat {
-    fn write_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+/// Selects what [`JsonValue::prune_with`] removes. Everything is removed by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneOptions {
+    nulls: bool,
+    empty_arrays: bool,
+    empty_objects: bool,
 }
 
-impl JsonFormat for String {
-    fn write_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", escape_json_string(self))
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self {
+            nulls: true,
+            empty_arrays: true,
+            empty_objects: true,
+        }
     }
 }
 
-impl JsonFormat for f64 {
-    fn write_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{self}")
+impl PruneOptions {
+    pub fn new() -> Self {
+        Self::default()
     }
-}
 
-impl JsonFormat for bool {
-    fn write_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{self}")
+    pub fn nulls(mut self, remove: bool) -> Self {
+        self.nulls = remove;
+        self
     }
-}
 
-// Arrays
-impl JsonFormat for [JsonValue] {
-    fn write_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "[")?;
-        for (i, val) in self.iter().enumerate() {
-            if i > 0 {
-                write!(f, ",")?;
-            }
+    pub fn empty_arrays(mut self, remove: bool) -> Self {
+        self.empty_arrays = remove;
+        self
+    }
 
-            write!(f, "{val}")?;
-        }
-        write!(f, "]")
+    pub fn empty_objects(mut self, remove: bool) -> Self {
+        self.empty_objects = remove;
+        self
     }
-}
 
-// Objects
-impl JsonFormat for HashMap<String, JsonValue> {
-    fn write_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{{")?;
-        for (i, (key, val)) in self.iter().enumerate() {
-            if i > 0 {
-                write!(f, ",")?;
-            }
-            // Escape key and then format value
-            write!(f, "{}:{}", escape_json_string(key), val)?;
+    pub(crate) fn removes(&self, value: &JsonValue) -> bool {
+        match value {
+            JsonValue::Null => self.nulls,
+            JsonValue::Array(arr) => self.empty_arrays && arr.is_empty(),
+            JsonValue::Object(obj) => self.empty_objects && obj.is_empty(),
+            _ => false,
         }
-        write!(f, "}}")
     }
 }
 
+/// Compact JSON with `{}`; indented by two spaces with `{:#}`.
+///
+/// Both forms are written without recursion, so any value that could be
+/// built can be printed.
 impl fmt::Display for JsonValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            JsonValue::Null => write!(f, "null"),
-            JsonValue::Boolean(b) => b.write_json(f),
-            JsonValue::Number(n) => n.write_json(f),
-            JsonValue::String(s) => s.write_json(f),
-            JsonValue::Array(arr) => arr.as_slice().write_json(f),
-            JsonValue::Object(obj) => obj.write_json(f),
+        if f.alternate() {
+            self.write_with(f, &FormatConfig::pretty(2))
+        } else {
+            self.write_with(f, &FormatConfig::new())
         }
     }
 }
 
-fn escape_json_string(s: &str) -> String {
+/// Extra characters to escape beyond what JSON requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Escaping {
+    /// Everything outside ASCII, as `\uXXXX` (surrogate pairs above U+FFFF).
+    pub(crate) ascii: bool,
+    /// `<`, `>`, `&`, U+2028 and U+2029, which are unsafe inside `<script>`.
+    pub(crate) html: bool,
+    /// `/` as `\/`.
+    pub(crate) slash: bool,
+}
+
+/// Quotes `s` as a JSON string literal, escaping `"`, `\` and control
+/// characters: `a"b` becomes `"a\"b"`.
+pub fn escape_json_string(s: &str) -> String {
+    escape_json_string_with(s, Escaping::default())
+}
+
+pub(crate) fn escape_json_string_with(s: &str, escaping: Escaping) -> String {
     let mut escaped = String::with_capacity(s.len() + 2);
     escaped.push('"');
     for c in s.chars() {
@@ -179,6 +268,15 @@ fn escape_json_string(s: &str) -> String {
             '\n' => escaped.push_str("\\n"),
             '\r' => escaped.push_str("\\r"),
             '\t' => escaped.push_str("\\t"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            // JSON forbids raw control characters inside strings
+            c if c < '\u{20}' => push_unicode_escape(&mut escaped, c),
+            c if escaping.ascii && !c.is_ascii() => push_unicode_escape(&mut escaped, c),
+            '/' if escaping.slash => escaped.push_str("\\/"),
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if escaping.html => {
+                push_unicode_escape(&mut escaped, c);
+            }
             _ => escaped.push(c),
         }
     }
@@ -186,23 +284,60 @@ fn escape_json_string(s: &str) -> String {
     escaped
 }
 
+/// Decodes a JSON string literal, quotes included, back into its text. The
+/// inverse of [`escape_json_string`]: every escape is understood, `\uXXXX`
+/// surrogate pairs included.
+///
+/// Fails if `literal` is not exactly one well-formed string literal.
+pub fn unescape_json_string(literal: &str) -> Result<String> {
+    Tokenizer::new(literal).string_literal()
+}
+
+/// Writes `c` as one `\uXXXX` escape, or two for a UTF-16 surrogate pair.
+fn push_unicode_escape(out: &mut String, c: char) {
+    let mut units = [0; 2];
+    for unit in c.encode_utf16(&mut units) {
+        let _ = write!(out, "\\u{unit:04x}");
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ExpectedToken;
     #[test]
     fn test_json_value_creation() {
         let null_val = JsonValue::Null;
         let bool_val = JsonValue::Boolean(true);
         let num_val = JsonValue::Number(42.5);
-        let str_val = JsonValue::String("hello".to_string());
+        let str_val = JsonValue::String("hello".into());
         assert!(null_val.is_null());
         assert_eq!(bool_val.as_bool(), Some(true));
         assert_eq!(num_val.as_f64(), Some(42.5));
         assert_eq!(str_val.as_str(), Some("hello"));
     }
     #[test]
+    fn test_default_and_empty_constructors() {
+        assert_eq!(JsonValue::default(), JsonValue::Null);
+        assert_eq!(
+            JsonValue::new_object().as_object().map(JsonObject::len),
+            Some(0)
+        );
+        assert_eq!(JsonValue::new_array().as_array().map(|a| a.len()), Some(0));
+
+        let arr = JsonValue::array_with_capacity(16);
+        assert!(arr.as_array().unwrap().is_empty());
+        assert_eq!(arr, JsonValue::new_array());
+        assert_eq!(JsonValue::object_with_capacity(16), JsonValue::new_object());
+    }
+    #[test]
+    fn test_value_fits_in_three_words() {
+        assert_eq!(size_of::<JsonValue>(), 24);
+        assert_eq!(size_of::<Option<JsonValue>>(), 24);
+    }
+    #[test]
     fn test_json_value_accessors() {
-        let value = JsonValue::String("test".to_string());
+        let value = JsonValue::String("test".into());
         assert_eq!(value.as_str(), Some("test"));
         assert_eq!(value.as_f64(), None);
         assert_eq!(value.as_bool(), None);
@@ -221,8 +356,8 @@ mod tests {
         assert_eq!(JsonValue::Boolean(true), JsonValue::Boolean(true));
         assert_eq!(JsonValue::Number(42.0), JsonValue::Number(42.0));
         assert_eq!(
-            JsonValue::String("test".to_string()),
-            JsonValue::String("test".to_string())
+            JsonValue::String("test".into()),
+            JsonValue::String("test".into())
         );
         assert_ne!(JsonValue::Null, JsonValue::Boolean(false));
         assert_ne!(JsonValue::Number(1.0), JsonValue::Number(2.0));
@@ -244,10 +379,10 @@ mod tests {
         ];
 
         for (val, expected_str, expected_f64, expected_bool, is_null) in cases {
-            assert_eq!(val.as_str(), expected_str, "Failed as_str on {:?}", val);
-            assert_eq!(val.as_f64(), expected_f64, "Failed as_f64 on {:?}", val);
-            assert_eq!(val.as_bool(), expected_bool, "Failed as_bool on {:?}", val);
-            assert_eq!(val.is_null(), is_null, "Failed is_null on {:?}", val);
+            assert_eq!(val.as_str(), expected_str, "Failed as_str on {val:?}");
+            assert_eq!(val.as_f64(), expected_f64, "Failed as_f64 on {val:?}");
+            assert_eq!(val.as_bool(), expected_bool, "Failed as_bool on {val:?}");
+            assert_eq!(val.is_null(), is_null, "Failed is_null on {val:?}");
         }
     }
 
@@ -264,7 +399,8 @@ mod tests {
     #[test]
     fn test_array_accessor() {
         // Create: [true, 42.0]
-        let array_val = JsonValue::Array(vec![JsonValue::Boolean(true), JsonValue::Number(42.0)]);
+        let array_val =
+            JsonValue::Array(vec![JsonValue::Boolean(true), JsonValue::Number(42.0)].into());
 
         // Success case
         assert!(array_val.as_array().is_some());
@@ -277,7 +413,7 @@ mod tests {
 
     #[test]
     fn test_object_accessor() {
-        let mut map = HashMap::new();
+        let mut map = JsonObject::new();
         map.insert("id".to_string(), JsonValue::Number(1.0));
         let obj_val = JsonValue::Object(map);
 
@@ -290,12 +426,31 @@ mod tests {
         assert!(null_val.as_object().is_none());
     }
 
+    #[test]
+    fn test_mutable_container_accessors() {
+        let mut value = JsonValue::Array(JsonArray::new());
+        value.as_array_mut().unwrap().push(JsonValue::Null);
+        assert_eq!(value.as_array().unwrap().len(), 1);
+        assert!(value.as_object_mut().is_none());
+
+        let mut value = JsonValue::Object(JsonObject::new());
+        value
+            .as_object_mut()
+            .unwrap()
+            .insert("k".to_string(), JsonValue::Null);
+        assert!(value.get("k").is_some());
+        assert!(value.as_array_mut().is_none());
+    }
+
     #[test]
     fn test_array_get_index() {
-        let array_val = JsonValue::Array(vec![
-            JsonValue::String("first".to_string()),
-            JsonValue::String("second".to_string()),
-        ]);
+        let array_val = JsonValue::Array(
+            vec![
+                JsonValue::String("first".into()),
+                JsonValue::String("second".into()),
+            ]
+            .into(),
+        );
 
         // Valid indexes
         assert_eq!(
@@ -317,8 +472,8 @@ mod tests {
 
     #[test]
     fn test_object_get() {
-        let mut map = HashMap::new();
-        map.insert("name".to_string(), JsonValue::String("Mike".to_string()));
+        let mut map = JsonObject::new();
+        map.insert("name".to_string(), JsonValue::String("Mike".into()));
         let obj_val = JsonValue::Object(map);
 
         // Valid key
@@ -330,51 +485,152 @@ mod tests {
         assert!(obj_val.get("age").is_none());
 
         // Type mismatch: calling get on an Array
-        let arr_val = JsonValue::Array(vec![]);
+        let arr_val = JsonValue::Array(JsonArray::new());
         assert!(arr_val.get("name").is_none());
     }
 
+    #[test]
+    fn test_into_entries_moves_members_out() {
+        let mut map = JsonObject::new();
+        map.insert("a".to_string(), JsonValue::Number(1.0));
+        map.insert("b".to_string(), JsonValue::String("two".into()));
+
+        let mut entries: Vec<_> = JsonValue::Object(map).into_entries().collect();
+        entries.sort_by(|x, y| x.0.cmp(&y.0));
+        assert_eq!(
+            entries,
+            vec![
+                ("a".into(), JsonValue::Number(1.0)),
+                ("b".into(), JsonValue::String("two".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_elements_preserves_order() {
+        let arr = JsonValue::Array(vec![JsonValue::Null, JsonValue::Boolean(false)].into());
+        let elements: Vec<_> = arr.into_elements().collect();
+        assert_eq!(elements, vec![JsonValue::Null, JsonValue::Boolean(false)]);
+    }
+
+    #[test]
+    fn test_into_iterators_on_wrong_variant_are_empty() {
+        assert_eq!(JsonValue::Number(1.0).into_entries().count(), 0);
+        assert_eq!(
+            JsonValue::Object(JsonObject::new()).into_elements().count(),
+            0
+        );
+        assert_eq!(
+            JsonValue::Array(vec![JsonValue::Null].into())
+                .into_entries()
+                .count(),
+            0
+        );
+    }
+
+    mod prune_tests {
+        use super::*;
+
+        use crate::test_support::parse_json;
+
+        #[test]
+        fn test_prune_removes_noise() {
+            let mut value =
+                parse_json(r#"{"a": null, "b": [], "c": {}, "d": [null, 1, {}], "e": 0}"#).unwrap();
+            value.prune();
+            assert_eq!(value, parse_json(r#"{"d": [1], "e": 0}"#).unwrap());
+        }
+
+        #[test]
+        fn test_prune_cascades_to_emptied_containers() {
+            let mut value =
+                parse_json(r#"{"outer": {"inner": [null, {}]}, "keep": false}"#).unwrap();
+            value.prune();
+            assert_eq!(value, parse_json(r#"{"keep": false}"#).unwrap());
+        }
+
+        #[test]
+        fn test_prune_keeps_root() {
+            let mut value = parse_json(r#"[null, []]"#).unwrap();
+            value.prune();
+            assert_eq!(value, JsonValue::Array(JsonArray::new()));
+
+            let mut null = JsonValue::Null;
+            null.prune();
+            assert!(null.is_null());
+        }
+
+        #[test]
+        fn test_prune_with_options() {
+            let input = r#"{"a": null, "b": [], "c": {}}"#;
+
+            let mut value = parse_json(input).unwrap();
+            value.prune_with(&PruneOptions::new().nulls(false));
+            assert_eq!(value, parse_json(r#"{"a": null}"#).unwrap());
+
+            let mut value = parse_json(input).unwrap();
+            value.prune_with(&PruneOptions::new().empty_arrays(false).empty_objects(false));
+            assert_eq!(value, parse_json(r#"{"b": [], "c": {}}"#).unwrap());
+        }
+    }
+
     mod display_tests {
         use super::*;
-        use crate::JsonParser;
+
+        use crate::test_support::parse_json;
 
         // Helper
-        fn parse_json(input: &str) -> crate::Result<JsonValue> {
-            JsonParser::new(input)?.parse()
-        }
         #[test]
         fn test_display_primitives() {
             assert_eq!(JsonValue::Null.to_string(), "null");
             assert_eq!(JsonValue::Boolean(true).to_string(), "true");
             assert_eq!(JsonValue::Boolean(false).to_string(), "false");
             assert_eq!(JsonValue::Number(42.0).to_string(), "42");
-            assert_eq!(JsonValue::Number(3.14).to_string(), "3.14");
-            assert_eq!(
-                JsonValue::String("hello".to_string()).to_string(),
-                "\"hello\""
-            );
+            assert_eq!(JsonValue::Number(2.5).to_string(), "2.5");
+            assert_eq!(JsonValue::String("hello".into()).to_string(), "\"hello\"");
         }
         #[test]
         fn test_display_array() {
-            let value = JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)]);
+            let value =
+                JsonValue::Array(vec![JsonValue::Number(1.0), JsonValue::Number(2.0)].into());
             assert_eq!(value.to_string(), "[1,2]");
         }
         #[test]
         fn test_display_empty_containers() {
-            assert_eq!(JsonValue::Array(vec![]).to_string(), "[]");
-            assert_eq!(JsonValue::Object(HashMap::new()).to_string(), "{}");
+            assert_eq!(JsonValue::Array(JsonArray::new()).to_string(), "[]");
+            assert_eq!(JsonValue::Object(JsonObject::new()).to_string(), "{}");
         }
         #[test]
         fn test_display_escape_string() {
-            let value = JsonValue::String("hello\nworld".to_string());
+            let value = JsonValue::String("hello\nworld".into());
             assert_eq!(value.to_string(), "\"hello\\nworld\"");
         }
         #[test]
+        fn test_display_escapes_every_control_character() {
+            let cases = vec![
+                ("\u{1}", "\"\\u0001\""),
+                ("a\u{b}b", "\"a\\u000bb\""),
+                ("\u{1f}", "\"\\u001f\""),
+                ("\u{8}\u{c}", "\"\\b\\f\""),
+                ("\u{7f}", "\"\u{7f}\""),
+            ];
+            for (input, expected) in cases {
+                let value = JsonValue::String(input.into());
+                assert_eq!(value.to_string(), expected, "Input: {input:?}");
+                assert_eq!(parse_json(expected).unwrap(), value);
+            }
+        }
+        #[test]
         fn test_display_escape_quotes() {
-            let value = JsonValue::String("say \"hi\"".to_string());
+            let value = JsonValue::String("say \"hi\"".into());
             assert_eq!(value.to_string(), "\"say \\\"hi\\\"\"");
         }
         #[test]
+        fn test_display_keeps_key_order() {
+            let value = parse_json(r#"{"b": 1, "a": 2, "c": 3}"#).unwrap();
+            assert_eq!(value.to_string(), r#"{"b":1,"a":2,"c":3}"#);
+        }
+        #[test]
         fn test_display_nested() {
             let value = parse_json(r#"{"arr": [1, 2]}"#).unwrap();
             let output = value.to_string();
@@ -383,4 +639,68 @@ mod tests {
             assert!(output.contains("[1,2]"));
         }
     }
+    mod escaping {
+        use super::*;
+        use crate::{JsonError, JsonPath};
+
+        #[test]
+        fn test_escape_round_trips() {
+            let cases = vec![
+                "",
+                "plain",
+                "quote \" and backslash \\",
+                "\n\r\t\u{8}\u{c}\u{0}\u{1f}",
+                "/ < > & \u{2028}",
+                "café 😀",
+            ];
+            for text in cases {
+                let literal = escape_json_string(text);
+                assert_eq!(unescape_json_string(&literal).unwrap(), text, "{literal}");
+            }
+            assert_eq!(escape_json_string("a\"b\u{1}"), r#""a\"b\u0001""#);
+        }
+
+        #[test]
+        fn test_unescape_decodes_every_escape() {
+            let cases = vec![
+                (r#""\"\\\/\b\f\n\r\t""#, "\"\\/\u{8}\u{c}\n\r\t"),
+                (r#""\u0041\u00e9\u20AC""#, "Aé€"),
+                (r#""\ud83d\ude00!""#, "😀!"),
+            ];
+            for (literal, expected) in cases {
+                assert_eq!(
+                    unescape_json_string(literal).unwrap(),
+                    expected,
+                    "{literal}"
+                );
+            }
+        }
+
+        #[test]
+        fn test_unescape_rejects_malformed_literals() {
+            let cases = vec![
+                "unquoted",
+                r#""unterminated"#,
+                r#""a" trailing"#,
+                r#""\x""#,
+                r#""\u12""#,
+                r#""\ud83d""#,
+                r#""\ud83d\u0041""#,
+                r#""\ude00""#,
+                "",
+            ];
+            for literal in cases {
+                assert!(unescape_json_string(literal).is_err(), "{literal}");
+            }
+            assert_eq!(
+                unescape_json_string(r#""a"b"#),
+                Err(JsonError::UnexpectedToken {
+                    expected: vec![ExpectedToken::EndOfInput],
+                    found: "b".to_string(),
+                    position: 3,
+                    path: JsonPath::new(),
+                })
+            );
+        }
+    }
 }
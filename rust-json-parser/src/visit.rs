@@ -0,0 +1,256 @@
+use crate::{JsonArray, JsonObject, JsonString, JsonValue};
+
+/// Read-only traversal hooks, called in document order by [`JsonValue::accept`].
+///
+/// Every hook has an empty default, so implementors only override what they need.
+pub trait JsonVisitor {
+    fn visit_null(&mut self) {}
+    fn visit_bool(&mut self, _value: bool) {}
+    fn visit_number(&mut self, _value: f64) {}
+    fn visit_string(&mut self, _value: &str) {}
+
+    fn enter_array(&mut self, _array: &[JsonValue]) {}
+    fn leave_array(&mut self, _array: &[JsonValue]) {}
+
+    fn enter_object(&mut self, _object: &JsonObject) {}
+    /// Called before the value stored under `key` is visited.
+    fn visit_key(&mut self, _key: &str) {}
+    fn leave_object(&mut self, _object: &JsonObject) {}
+}
+
+/// Mutating traversal hooks, called in document order by [`JsonValue::accept_mut`].
+///
+/// Hooks may edit a node in place, or return `Some(replacement)` to swap the
+/// whole node out. A replacement is not visited itself, and returning one from
+/// an `enter_*` hook skips the container's children and its `leave_*` hook.
+pub trait JsonVisitorMut {
+    fn visit_null(&mut self) -> Option<JsonValue> {
+        None
+    }
+    fn visit_bool(&mut self, _value: &mut bool) -> Option<JsonValue> {
+        None
+    }
+    fn visit_number(&mut self, _value: &mut f64) -> Option<JsonValue> {
+        None
+    }
+    fn visit_string(&mut self, _value: &mut JsonString) -> Option<JsonValue> {
+        None
+    }
+
+    fn enter_array(&mut self, _array: &mut JsonArray) -> Option<JsonValue> {
+        None
+    }
+    fn leave_array(&mut self, _array: &mut JsonArray) -> Option<JsonValue> {
+        None
+    }
+
+    fn enter_object(&mut self, _object: &mut JsonObject) -> Option<JsonValue> {
+        None
+    }
+    /// Called before the value stored under `key` is visited.
+    fn visit_key(&mut self, _key: &str) {}
+    fn leave_object(&mut self, _object: &mut JsonObject) -> Option<JsonValue> {
+        None
+    }
+}
+
+impl JsonValue {
+    /// Drives `visitor` over this value and all of its descendants.
+    pub fn accept<V: JsonVisitor + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            JsonValue::Null => visitor.visit_null(),
+            JsonValue::Boolean(b) => visitor.visit_bool(*b),
+            JsonValue::Number(n) => visitor.visit_number(*n),
+            JsonValue::String(s) => visitor.visit_string(s),
+            JsonValue::Array(arr) => {
+                visitor.enter_array(arr);
+                for item in arr {
+                    item.accept(visitor);
+                }
+                visitor.leave_array(arr);
+            }
+            JsonValue::Object(obj) => {
+                visitor.enter_object(obj);
+                for (key, val) in obj {
+                    visitor.visit_key(key);
+                    val.accept(visitor);
+                }
+                visitor.leave_object(obj);
+            }
+        }
+    }
+
+    /// Drives a mutating `visitor` over this value, applying any replacements it returns.
+    pub fn accept_mut<V: JsonVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        let replacement = match self {
+            JsonValue::Null => visitor.visit_null(),
+            JsonValue::Boolean(b) => visitor.visit_bool(b),
+            JsonValue::Number(n) => visitor.visit_number(n),
+            JsonValue::String(s) => visitor.visit_string(s),
+            JsonValue::Array(arr) => visitor.enter_array(arr).or_else(|| {
+                for item in arr.iter_mut() {
+                    item.accept_mut(visitor);
+                }
+                visitor.leave_array(arr)
+            }),
+            JsonValue::Object(obj) => visitor.enter_object(obj).or_else(|| {
+                for (key, val) in obj.iter_mut() {
+                    visitor.visit_key(key);
+                    val.accept_mut(visitor);
+                }
+                visitor.leave_object(obj)
+            }),
+        };
+
+        if let Some(node) = replacement {
+            *self = node;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    #[derive(Default)]
+    struct Counter {
+        scalars: usize,
+        arrays: usize,
+        objects: usize,
+        keys: Vec<String>,
+        depth: usize,
+        max_depth: usize,
+    }
+
+    impl JsonVisitor for Counter {
+        fn visit_null(&mut self) {
+            self.scalars += 1;
+        }
+        fn visit_bool(&mut self, _value: bool) {
+            self.scalars += 1;
+        }
+        fn visit_number(&mut self, _value: f64) {
+            self.scalars += 1;
+        }
+        fn visit_string(&mut self, _value: &str) {
+            self.scalars += 1;
+        }
+        fn enter_array(&mut self, _array: &[JsonValue]) {
+            self.arrays += 1;
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+        fn leave_array(&mut self, _array: &[JsonValue]) {
+            self.depth -= 1;
+        }
+        fn enter_object(&mut self, _object: &JsonObject) {
+            self.objects += 1;
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+        fn visit_key(&mut self, key: &str) {
+            self.keys.push(key.to_string());
+        }
+        fn leave_object(&mut self, _object: &JsonObject) {
+            self.depth -= 1;
+        }
+    }
+
+    #[test]
+    fn test_visitor_sees_every_node() {
+        let value = parse_json(r#"{"a": [1, true, null, "x"], "b": {"c": []}}"#).unwrap();
+        let mut counter = Counter::default();
+        value.accept(&mut counter);
+
+        assert_eq!(counter.scalars, 4);
+        assert_eq!(counter.arrays, 2);
+        assert_eq!(counter.objects, 2);
+        assert_eq!(counter.max_depth, 3);
+        assert_eq!(
+            counter.depth, 0,
+            "Every enter should be paired with a leave"
+        );
+
+        counter.keys.sort();
+        assert_eq!(counter.keys, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_visitor_on_scalar_root() {
+        let mut counter = Counter::default();
+        JsonValue::Number(1.0).accept(&mut counter);
+        assert_eq!(counter.scalars, 1);
+        assert_eq!(counter.arrays + counter.objects, 0);
+    }
+
+    struct Doubler;
+
+    impl JsonVisitorMut for Doubler {
+        fn visit_number(&mut self, value: &mut f64) -> Option<JsonValue> {
+            *value *= 2.0;
+            None
+        }
+    }
+
+    #[test]
+    fn test_mut_visitor_edits_in_place() {
+        let mut value = parse_json(r#"[1, {"n": 2}, [3]]"#).unwrap();
+        value.accept_mut(&mut Doubler);
+        assert_eq!(value, parse_json(r#"[2, {"n": 4}, [6]]"#).unwrap());
+    }
+
+    struct NullsToStrings;
+
+    impl JsonVisitorMut for NullsToStrings {
+        fn visit_null(&mut self) -> Option<JsonValue> {
+            Some(JsonValue::String("none".into()))
+        }
+    }
+
+    #[test]
+    fn test_mut_visitor_replaces_nodes() {
+        let mut value = parse_json(r#"{"a": null, "b": [null, 1]}"#).unwrap();
+        value.accept_mut(&mut NullsToStrings);
+        assert_eq!(
+            value,
+            parse_json(r#"{"a": "none", "b": ["none", 1]}"#).unwrap()
+        );
+
+        let mut root = JsonValue::Null;
+        root.accept_mut(&mut NullsToStrings);
+        assert_eq!(root.as_str(), Some("none"));
+    }
+
+    #[derive(Default)]
+    struct CollapseArrays {
+        leaves: usize,
+        numbers_seen: usize,
+    }
+
+    impl JsonVisitorMut for CollapseArrays {
+        fn visit_number(&mut self, _value: &mut f64) -> Option<JsonValue> {
+            self.numbers_seen += 1;
+            None
+        }
+        fn enter_array(&mut self, array: &mut JsonArray) -> Option<JsonValue> {
+            Some(JsonValue::Number(array.len() as f64))
+        }
+        fn leave_array(&mut self, _array: &mut JsonArray) -> Option<JsonValue> {
+            self.leaves += 1;
+            None
+        }
+    }
+
+    #[test]
+    fn test_enter_replacement_skips_children() {
+        let mut value = parse_json(r#"{"items": [1, 2, 3]}"#).unwrap();
+        let mut visitor = CollapseArrays::default();
+        value.accept_mut(&mut visitor);
+
+        assert_eq!(value.get("items"), Some(&JsonValue::Number(3.0)));
+        assert_eq!(visitor.numbers_seen, 0, "Children should not be visited");
+        assert_eq!(visitor.leaves, 0, "leave_array should not run");
+    }
+}
@@ -0,0 +1,375 @@
+use crate::ser::{IoWriter, Serializer};
+use crate::{FormatConfig, JsonValue, NonFinite};
+use std::fmt::{self, Write as _};
+use std::io::{self, Write};
+
+/// Errors produced by [`JsonWriter`].
+#[derive(Debug)]
+pub enum WriterError {
+    Io(io::Error),
+    /// A call that would make the document invalid, such as a value where an
+    /// object key belongs or closing an array that is not open.
+    InvalidCall(&'static str),
+}
+
+impl fmt::Display for WriterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriterError::Io(err) => write!(f, "I/O error: {err}"),
+            WriterError::InvalidCall(reason) => write!(f, "Invalid JSON writer call: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for WriterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WriterError::Io(err) => Some(err),
+            WriterError::InvalidCall(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for WriterError {
+    fn from(err: io::Error) -> Self {
+        WriterError::Io(err)
+    }
+}
+
+type Result<T> = std::result::Result<T, WriterError>;
+
+const NON_FINITE: &str = "non-finite number refused by NonFinite::Error";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Container {
+    Array,
+    Object,
+}
+
+/// A container that is still open.
+#[derive(Debug)]
+struct Frame {
+    container: Container,
+    /// Elements, or keys, written so far.
+    len: usize,
+    /// An object key has been written and its value has not.
+    awaiting_value: bool,
+}
+
+/// Writes one JSON document piece by piece, without building a [`JsonValue`].
+///
+/// Every call is checked against the document structure, so output that
+/// [`JsonWriter::finish`] accepts is always valid JSON. Layout and escaping
+/// follow a [`FormatConfig`], except `sort_keys`, which cannot apply to
+/// members already written.
+///
+/// ```
+/// use rust_json_parser::JsonWriter;
+///
+/// let mut w = JsonWriter::new(Vec::new());
+/// w.begin_object()?;
+/// w.key("ids")?;
+/// w.begin_array()?;
+/// for id in 1..=3 {
+///     w.number(id as f64)?;
+/// }
+/// w.end_array()?;
+/// w.end_object()?;
+/// assert_eq!(w.finish()?, br#"{"ids":[1,2,3]}"#);
+/// # Ok::<(), rust_json_parser::WriterError>(())
+/// ```
+#[derive(Debug)]
+pub struct JsonWriter<W: Write> {
+    out: IoWriter<W>,
+    config: FormatConfig,
+    stack: Vec<Frame>,
+    complete: bool,
+}
+
+impl<W: Write> JsonWriter<W> {
+    /// A writer producing compact output.
+    pub fn new(writer: W) -> Self {
+        Self::with_config(writer, FormatConfig::new())
+    }
+
+    pub fn with_config(writer: W, config: FormatConfig) -> Self {
+        JsonWriter {
+            out: IoWriter {
+                inner: writer,
+                error: None,
+            },
+            config,
+            stack: Vec::new(),
+            complete: false,
+        }
+    }
+
+    pub fn begin_object(&mut self) -> Result<()> {
+        self.open(Container::Object, '{')
+    }
+
+    pub fn end_object(&mut self) -> Result<()> {
+        self.close(Container::Object, '}')
+    }
+
+    pub fn begin_array(&mut self) -> Result<()> {
+        self.open(Container::Array, '[')
+    }
+
+    pub fn end_array(&mut self) -> Result<()> {
+        self.close(Container::Array, ']')
+    }
+
+    /// Starts the next member of the open object; its value comes next.
+    pub fn key(&mut self, key: &str) -> Result<()> {
+        let depth = self.stack.len();
+        let frame = match self.stack.last_mut() {
+            Some(frame) if frame.container == Container::Object && !frame.awaiting_value => frame,
+            Some(frame) if frame.container == Container::Object => {
+                return Err(WriterError::InvalidCall(
+                    "key written where a value belongs",
+                ));
+            }
+            _ => return Err(WriterError::InvalidCall("key written outside an object")),
+        };
+        let index = frame.len;
+        frame.len += 1;
+        frame.awaiting_value = true;
+        let config = &self.config;
+        let mut ser = Serializer::new(&mut self.out, config);
+        let result = ser
+            .separator(index, depth)
+            .and_then(|()| ser.string(key))
+            .and_then(|()| ser.out.write_str(&config.key_separator));
+        self.check(result)
+    }
+
+    /// Writes a complete value, containers included.
+    pub fn value(&mut self, value: &JsonValue) -> Result<()> {
+        self.before_value()?;
+        let depth = self.stack.len();
+        let mut ser = Serializer::new(&mut self.out, &self.config);
+        let result = ser.value(value, depth);
+        if ser.refused.is_some() {
+            return Err(WriterError::InvalidCall(NON_FINITE));
+        }
+        self.check(result)?;
+        self.after_value();
+        Ok(())
+    }
+
+    pub fn string(&mut self, s: &str) -> Result<()> {
+        self.before_value()?;
+        let result = Serializer::new(&mut self.out, &self.config).string(s);
+        self.check(result)?;
+        self.after_value();
+        Ok(())
+    }
+
+    pub fn number(&mut self, n: f64) -> Result<()> {
+        // Refuse before the separator goes out, so the writer stays usable
+        if !n.is_finite() && self.config.non_finite == NonFinite::Error {
+            return Err(WriterError::InvalidCall(NON_FINITE));
+        }
+        self.value(&JsonValue::Number(n))
+    }
+
+    pub fn bool(&mut self, b: bool) -> Result<()> {
+        self.value(&JsonValue::Boolean(b))
+    }
+
+    pub fn null(&mut self) -> Result<()> {
+        self.value(&JsonValue::Null)
+    }
+
+    /// Checks that the document is complete, flushes, and returns the sink.
+    pub fn finish(mut self) -> Result<W> {
+        if !self.complete {
+            return Err(WriterError::InvalidCall("document is incomplete"));
+        }
+        if self.config.trailing_newline {
+            let result = self.out.write_char('\n');
+            self.check(result)?;
+        }
+        self.out.inner.flush()?;
+        Ok(self.out.inner)
+    }
+
+    fn open(&mut self, container: Container, bracket: char) -> Result<()> {
+        self.before_value()?;
+        let result = self.out.write_char(bracket);
+        self.check(result)?;
+        self.stack.push(Frame {
+            container,
+            len: 0,
+            awaiting_value: false,
+        });
+        Ok(())
+    }
+
+    fn close(&mut self, container: Container, bracket: char) -> Result<()> {
+        match self.stack.last() {
+            Some(frame) if frame.container == container && !frame.awaiting_value => {}
+            Some(frame) if frame.awaiting_value => {
+                return Err(WriterError::InvalidCall(
+                    "object closed after a key with no value",
+                ));
+            }
+            _ => {
+                return Err(WriterError::InvalidCall(
+                    "closed a container that is not open",
+                ));
+            }
+        }
+        let frame = self.stack.pop().expect("checked above");
+        let depth = self.stack.len();
+        let mut ser = Serializer::new(&mut self.out, &self.config);
+        let result = if frame.len > 0 {
+            ser.line_break(depth)
+        } else {
+            Ok(())
+        };
+        let result = result.and_then(|()| ser.out.write_char(bracket));
+        self.check(result)?;
+        self.after_value();
+        Ok(())
+    }
+
+    /// Validates that a value may come next and writes what precedes it.
+    fn before_value(&mut self) -> Result<()> {
+        let depth = self.stack.len();
+        let Some(frame) = self.stack.last_mut() else {
+            if self.complete {
+                return Err(WriterError::InvalidCall(
+                    "document already has a root value",
+                ));
+            }
+            return Ok(());
+        };
+        match frame.container {
+            Container::Object if !frame.awaiting_value => Err(WriterError::InvalidCall(
+                "value written where an object key belongs",
+            )),
+            Container::Object => {
+                frame.awaiting_value = false;
+                Ok(())
+            }
+            Container::Array => {
+                let index = frame.len;
+                frame.len += 1;
+                let result = Serializer::new(&mut self.out, &self.config).separator(index, depth);
+                self.check(result)
+            }
+        }
+    }
+
+    fn after_value(&mut self) {
+        if self.stack.is_empty() {
+            self.complete = true;
+        }
+    }
+
+    /// Turns a formatting failure back into the I/O error behind it.
+    fn check(&mut self, result: fmt::Result) -> Result<()> {
+        result.map_err(|_| match self.out.error.take() {
+            Some(err) => WriterError::Io(err),
+            None => WriterError::Io(io::Error::other("formatting failed")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_support::parse_json;
+
+    fn written(w: JsonWriter<Vec<u8>>) -> String {
+        String::from_utf8(w.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_builds_nested_document() {
+        let mut w = JsonWriter::new(Vec::new());
+        w.begin_object().unwrap();
+        w.key("name").unwrap();
+        w.string("row \"1\"").unwrap();
+        w.key("tags").unwrap();
+        w.begin_array().unwrap();
+        w.bool(true).unwrap();
+        w.null().unwrap();
+        w.value(&parse_json(r#"{"x": [1]}"#).unwrap()).unwrap();
+        w.end_array().unwrap();
+        w.key("empty").unwrap();
+        w.begin_object().unwrap();
+        w.end_object().unwrap();
+        w.end_object().unwrap();
+        assert_eq!(
+            written(w),
+            r#"{"name":"row \"1\"","tags":[true,null,{"x":[1]}],"empty":{}}"#
+        );
+    }
+
+    #[test]
+    fn test_pretty_output_matches_serializer() {
+        let value = parse_json(r#"{"a": [1, {"b": []}], "c": "d"}"#).unwrap();
+        let mut w = JsonWriter::with_config(Vec::new(), FormatConfig::pretty(2));
+        w.begin_object().unwrap();
+        w.key("a").unwrap();
+        w.begin_array().unwrap();
+        w.number(1.0).unwrap();
+        w.value(&parse_json(r#"{"b": []}"#).unwrap()).unwrap();
+        w.end_array().unwrap();
+        w.key("c").unwrap();
+        w.string("d").unwrap();
+        w.end_object().unwrap();
+        assert_eq!(written(w), value.to_string_pretty(2));
+    }
+
+    #[test]
+    fn test_rejects_invalid_calls() {
+        let mut w = JsonWriter::new(Vec::new());
+        assert!(matches!(w.key("a"), Err(WriterError::InvalidCall(_))));
+        assert!(matches!(w.end_array(), Err(WriterError::InvalidCall(_))));
+
+        w.begin_object().unwrap();
+        assert!(matches!(w.number(1.0), Err(WriterError::InvalidCall(_))));
+        w.key("a").unwrap();
+        assert!(matches!(w.key("b"), Err(WriterError::InvalidCall(_))));
+        assert!(matches!(w.end_object(), Err(WriterError::InvalidCall(_))));
+        w.number(1.0).unwrap();
+        assert!(matches!(w.end_array(), Err(WriterError::InvalidCall(_))));
+        w.end_object().unwrap();
+        assert!(matches!(w.null(), Err(WriterError::InvalidCall(_))));
+        assert_eq!(written(w), r#"{"a":1}"#);
+    }
+
+    #[test]
+    fn test_finish_requires_complete_document() {
+        let mut w = JsonWriter::new(Vec::new());
+        assert!(matches!(
+            JsonWriter::new(Vec::new()).finish(),
+            Err(WriterError::InvalidCall(_))
+        ));
+        w.begin_array().unwrap();
+        assert!(matches!(w.finish(), Err(WriterError::InvalidCall(_))));
+    }
+
+    #[test]
+    fn test_non_finite_numbers_follow_policy() {
+        let mut w = JsonWriter::new(Vec::new());
+        w.begin_array().unwrap();
+        assert!(matches!(
+            w.number(f64::NAN),
+            Err(WriterError::InvalidCall(_))
+        ));
+        w.number(1.0).unwrap();
+        w.end_array().unwrap();
+        assert_eq!(written(w), "[1]");
+
+        let config = FormatConfig::new().non_finite(NonFinite::Null);
+        let mut w = JsonWriter::with_config(Vec::new(), config);
+        w.number(f64::INFINITY).unwrap();
+        assert_eq!(written(w), "null");
+    }
+}
@@ -1,5 +1,5 @@
 import pytest
-from rust_json_parser import parse_json, parse_json_file, dumps
+from rust_json_parser import parse_json, parse_json_file, dumps, features
 
 
 class TestBasicParsing:
@@ -102,8 +102,32 @@ class TestSerialization:
 
         assert parsed["text"] == original_str
 
+    def test_dumps_max_output_bytes(self):
+        assert dumps([1, 2, 3], max_output_bytes=7) == "[1,2,3]"
+        with pytest.raises(ValueError, match="exceeds the limit"):
+            dumps([1, 2, 3], max_output_bytes=6)
+        with pytest.raises(ValueError):
+            dumps([[[[]]]], indent=4, max_output_bytes=10)
+
+    def test_dumps_rejects_nan(self):
+        with pytest.raises(ValueError, match="Non-finite number"):
+            dumps({"x": float("nan")})
+        with pytest.raises(ValueError):
+            dumps([float("inf")], max_output_bytes=100)
+
+    def test_dumps_ensure_ascii(self):
+        result = dumps({"café": "😀"}, ensure_ascii=True)
+        assert result == '{"caf\\u00e9":"\\ud83d\\ude00"}'
+        assert parse_json(result) == {"café": "😀"}
+        assert dumps("café") == '"café"'
+
     def test_dumps_escaped_keys(self):
         # Dictionary keys must also be escaped
         data = {"key\nwith\nnewline": "value"}
         result = dumps(data)
         assert r"key\nwith\nnewline" in result
+
+
+class TestFeatures:
+    def test_python_feature_is_reported(self):
+        assert "python" in features()